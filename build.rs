@@ -1,7 +1,23 @@
 use std::path::PathBuf;
 use std::{env, fs};
 
+include!("src/peripherals/map/available.rs");
+
 fn main() {
+	println!("cargo:rustc-check-cfg=cfg(cortex_m, armv6m, armv7m, armv8m, has_fpu)");
+
+	// Peripherals of the selected device
+	for (name, features) in AVAILABLE {
+		println!("cargo:rustc-check-cfg=cfg(has_{})", name);
+
+		let selected = features.iter()
+			.any(|f| env::var_os(format!("CARGO_FEATURE_{}", f.to_uppercase())).is_some());
+
+		if selected {
+			println!("cargo:rustc-cfg=has_{}", name);
+		}
+	}
+
 	let target = env::var("TARGET").unwrap();
 	let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
 	let name = env::var("CARGO_PKG_NAME").unwrap();
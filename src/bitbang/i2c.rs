@@ -0,0 +1,341 @@
+//! Bit-banged I2C master
+//!
+//! SCL and SDA are open-drain outputs that can be read back (e.g.
+//! `Pin<Output<OpenDrain>>`), with pull-ups on the bus. The slaves can stretch
+//! the clock, SCL is waited for up to a number of ticks before failing with `Timeout`.
+//! When a transfer fails both lines are released, so the bus is left idle.
+
+#[cfg(feature = "std")]
+use std::convert::Infallible;
+
+#[cfg(not(feature = "std"))]
+use core::convert::Infallible;
+
+use crate::common::{ Frequency, I2CError };
+
+use embedded_hal::blocking::i2c::{ Read, Write, WriteRead };
+use embedded_hal::digital::v2::{ InputPin, OutputPin };
+
+use super::BitTimer;
+
+/// Ticks SCL can be stretched by default
+const STRETCH: u32 = 1000;
+
+/// I2C master over two open-drain pins
+pub struct I2c<SCL, SDA, T> {
+	scl: SCL,
+	sda: SDA,
+	timer: T,
+	stretch: u32,
+}
+
+impl<SCL, SDA, T> I2c<SCL, SDA, T>
+	where SCL: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		SDA: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		T: BitTimer
+{
+	/// Releases the bus and sets the clock to `freq`
+	/// The timer ticks twice per bit
+	pub fn new(scl: SCL, sda: SDA, mut timer: T, freq: Frequency) -> Self {
+		timer.set_rate(freq * 2);
+
+		let mut i2c = I2c { scl, sda, timer, stretch: STRETCH };
+		i2c.release_scl();
+		i2c.release_sda();
+		i2c
+	}
+
+	/// Returns the pins and the timer
+	pub fn free(self) -> (SCL, SDA, T) {
+		(self.scl, self.sda, self.timer)
+	}
+
+	/// Sets the ticks a slave can hold SCL low, 0 does not wait
+	pub fn stretch(&mut self, ticks: u32) -> &mut Self {
+		self.stretch = ticks;
+		self
+	}
+
+	fn release_scl(&mut self) {
+		let _ = self.scl.set_high();
+	}
+
+	fn release_sda(&mut self) {
+		let _ = self.sda.set_high();
+	}
+
+	fn drive_scl(&mut self) {
+		let _ = self.scl.set_low();
+	}
+
+	fn drive_sda(&mut self) {
+		let _ = self.sda.set_low();
+	}
+
+	fn sda(&self) -> bool {
+		self.sda.is_high().unwrap_or(true)
+	}
+
+	/// Releases both lines, so a failed transfer does not hold the bus
+	fn abort(&mut self, e: I2CError) -> I2CError {
+		self.release_sda();
+		self.release_scl();
+		e
+	}
+
+	/// Releases SCL and waits while a slave stretches it
+	fn raise_scl(&mut self) -> Result<(), I2CError> {
+		self.release_scl();
+
+		let mut ticks = 0;
+
+		while self.scl.is_low().unwrap_or(false) {
+			if ticks == self.stretch { return Err(self.abort(I2CError::Timeout)); }
+
+			self.timer.wait();
+			ticks += 1;
+		}
+
+		Ok(())
+	}
+
+	/// Sends a start or repeated start condition
+	fn start(&mut self) -> Result<(), I2CError> {
+		self.timer.start();
+
+		self.release_sda();
+		self.timer.wait();
+		self.raise_scl()?;
+
+		// Another master holds the bus
+		if !self.sda() { return Err(self.abort(I2CError::Bus)); }
+
+		self.timer.wait();
+		self.drive_sda();
+		self.timer.wait();
+		self.drive_scl();
+
+		Ok(())
+	}
+
+	/// Sends a stop condition
+	fn stop(&mut self) -> Result<(), I2CError> {
+		self.drive_sda();
+		self.timer.wait();
+		self.raise_scl()?;
+		self.timer.wait();
+		self.release_sda();
+		self.timer.wait();
+
+		Ok(())
+	}
+
+	fn write_bit(&mut self, bit: bool) -> Result<(), I2CError> {
+		match bit {
+			true => self.release_sda(),
+			_ => self.drive_sda(),
+		}
+
+		self.timer.wait();
+		self.raise_scl()?;
+
+		// A released SDA pulled low by someone else
+		if bit && !self.sda() { return Err(self.abort(I2CError::Arbitration)); }
+
+		self.timer.wait();
+		self.drive_scl();
+
+		Ok(())
+	}
+
+	fn read_bit(&mut self) -> Result<bool, I2CError> {
+		self.release_sda();
+		self.timer.wait();
+		self.raise_scl()?;
+
+		let bit = self.sda();
+
+		self.timer.wait();
+		self.drive_scl();
+
+		Ok( bit )
+	}
+
+	/// Sends a byte and returns `true` if the slave acknowledged it
+	fn write_byte(&mut self, byte: u8) -> Result<bool, I2CError> {
+		for i in (0..8).rev() {
+			self.write_bit(byte & (1 << i) != 0)?;
+		}
+
+		Ok( !self.read_bit()? )
+	}
+
+	/// Receives a byte and acknowledges it if `ack`
+	fn read_byte(&mut self, ack: bool) -> Result<u8, I2CError> {
+		let mut byte = 0;
+
+		for _ in 0..8 {
+			byte = (byte << 1) | self.read_bit()? as u8;
+		}
+
+		self.write_bit(!ack)?;
+
+		Ok( byte )
+	}
+
+	/// Sends the address, the stop condition is sent if it is not acknowledged
+	fn address(&mut self, addr: u8, read: bool) -> Result<(), I2CError> {
+		self.start()?;
+
+		match self.write_byte((addr << 1) | read as u8)? {
+			true => Ok(()),
+			_ => {
+				self.stop()?;
+				Err(I2CError::NACK)
+			},
+		}
+	}
+
+	fn write_bytes(&mut self, addr: u8, bytes: &[u8]) -> Result<(), I2CError> {
+		self.address(addr, false)?;
+
+		for &b in bytes {
+			if !self.write_byte(b)? {
+				self.stop()?;
+				return Err(I2CError::NACK);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn read_bytes(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), I2CError> {
+		self.address(addr, true)?;
+
+		let last = buffer.len().saturating_sub(1);
+
+		for (i, b) in buffer.iter_mut().enumerate() {
+			*b = self.read_byte(i != last)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl<SCL, SDA, T> Write for I2c<SCL, SDA, T>
+	where SCL: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		SDA: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		T: BitTimer
+{
+	type Error = I2CError;
+
+	fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), I2CError> {
+		self.write_bytes(addr, bytes)?;
+		self.stop()
+	}
+}
+
+impl<SCL, SDA, T> Read for I2c<SCL, SDA, T>
+	where SCL: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		SDA: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		T: BitTimer
+{
+	type Error = I2CError;
+
+	/// The last byte is not acknowledged, so the slave stops sending
+	fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), I2CError> {
+		self.read_bytes(addr, buffer)?;
+		self.stop()
+	}
+}
+
+impl<SCL, SDA, T> WriteRead for I2c<SCL, SDA, T>
+	where SCL: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		SDA: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		T: BitTimer
+{
+	type Error = I2CError;
+
+	/// Writes and reads with a repeated start in between
+	fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), I2CError> {
+		self.write_bytes(addr, bytes)?;
+		self.read_bytes(addr, buffer)?;
+		self.stop()
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+
+	use super::*;
+	use crate::bitbang::tests::{ port, Ticks };
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::common::structs::{ Pin, Output, OpenDrain };
+	use crate::peripherals::extended::gpio::{ SIZE, IDR, ODR };
+
+	const SCL: u32 = 1 << 0;
+	const SDA: u32 = 1 << 1;
+
+	type Bus = I2c<Pin<Output<OpenDrain>>, Pin<Output<OpenDrain>>, Ticks>;
+
+	/// Master on P0 (SCL) and P1 (SDA), `pull` returns the lines the slaves hold low given the ODR
+	fn i2c<F>(pull: F) -> (SimBlock<SIZE>, Bus) where F: FnMut(u32) -> u32 + 'static {
+		let (block, mut gpio) = port();
+		let mut pull = pull;
+		block.on_read(IDR::INDEX, move |idr| idr & !pull(idr));
+
+		let scl = gpio.pin(0).unwrap().into_open_drain_output();
+		let sda = gpio.pin(1).unwrap().into_open_drain_output();
+
+		let mut i2c = I2c::new(scl, sda, Ticks(Rc::new(Cell::new(0))), Frequency::KHz(100));
+		i2c.stretch(10);
+		(block, i2c)
+	}
+
+	fn released(block: &SimBlock<SIZE>) -> bool {
+		block.peek(ODR::INDEX) & (SCL | SDA) == SCL | SDA
+	}
+
+	#[test]
+	fn nack_sends_a_stop() {
+		let (block, mut i2c) = i2c(|_| 0);
+
+		assert_eq!(i2c.write(0x50, &[1]), Err(I2CError::NACK));
+		assert!(released(&block));
+	}
+
+	#[test]
+	fn stretch_timeout_releases_the_bus() {
+		// The slave holds SCL once SDA is driven low on a data bit
+		let (block, mut i2c) = i2c(|odr| if odr & (SCL | SDA) == SCL { SCL } else { 0 });
+
+		assert_eq!(i2c.write(0x50, &[0]), Err(I2CError::Timeout));
+		assert!(released(&block));
+	}
+
+	#[test]
+	fn busy_bus_releases_the_bus() {
+		// Another master holds SDA
+		let (block, mut i2c) = i2c(|_| SDA);
+
+		assert_eq!(i2c.write(0x50, &[0]), Err(I2CError::Bus));
+		assert!(released(&block));
+	}
+
+	#[test]
+	fn arbitration_loss_releases_the_bus() {
+		// Another master drives SDA low right after the start condition was checked
+		let mut reads = 0;
+		let (block, mut i2c) = i2c(move |_| {
+			reads += 1;
+			if reads > 2 { SDA } else { 0 }
+		});
+
+		// 0x50 starts with a 1 bit, which reads back low
+		assert_eq!(i2c.write(0x50, &[0]), Err(I2CError::Arbitration));
+		assert!(released(&block));
+	}
+}
@@ -0,0 +1,150 @@
+//! Bit-banged buses
+//!
+//! Masters of I2C, SPI, UART and 1-Wire driven by software over GPIO pins, for
+//! devices on pins with no free peripheral. They implement the same `embedded-hal`
+//! blocking traits as the hardware drivers, so they can be swapped in.
+//! 1-Wire needs varying slot times, so it runs on a microsecond delay instead.
+//!
+//! The bit timing comes from a `BitTimer`: `CycleTimer` waits on the DWT cycle
+//! counter and keeps the cadence across the bits, `SpinTimer` spins a fixed number
+//! of cycles per tick and is slower than the set rate by the time spent between ticks.
+//!
+//! ```ignore
+//! let timer = CycleTimer::new(&*p.dwt, &clocks);
+//! let scl = gpiob.pin(8)?.into_open_drain_output();
+//! let sda = gpiob.pin(9)?.into_open_drain_output();
+//!
+//! let mut i2c = bitbang::I2c::new(scl, sda, timer, Frequency::KHz(100));
+//! i2c.write_read(0x68, &[0x75], &mut id)?;
+//! ```
+
+pub mod i2c;
+pub mod onewire;
+pub mod serial;
+pub mod spi;
+
+pub use self::i2c::I2c;
+pub use self::onewire::OneWire;
+pub use self::serial::Serial;
+pub use self::spi::Spi;
+
+use crate::common::{ asm, Clocks, Frequency };
+
+#[cfg(not(armv6m))]
+use crate::peripherals::core::dwt::Dwt;
+
+/// Timing source of the bit-banged buses
+/// The buses set the rate of the ticks they need and wait for each of them
+pub trait BitTimer {
+	/// Sets the rate of the ticks
+	fn set_rate(&mut self, rate: Frequency);
+
+	/// Restarts the ticks from now
+	fn start(&mut self);
+
+	/// Waits for the next tick
+	fn wait(&mut self);
+}
+
+/// Ticks counted on the DWT cycle counter
+/// The counter must be running, see `DwtDelay::new` or `Monotonic::dwt`
+#[cfg(not(armv6m))]
+pub struct CycleTimer {
+	dwt: &'static Dwt,
+	clock: Frequency,
+	period: u32,
+	last: u32,
+}
+
+#[cfg(not(armv6m))]
+impl CycleTimer {
+	/// Ticks at the core clock, until a rate is set by a bus
+	pub fn new(dwt: &'static Dwt, clocks: &Clocks) -> Self {
+		CycleTimer { dwt, clock: clocks.ahb1f, period: 1, last: 0 }
+	}
+}
+
+#[cfg(not(armv6m))]
+impl BitTimer for CycleTimer {
+	fn set_rate(&mut self, rate: Frequency) {
+		self.period = (self.clock / rate).max(1);
+	}
+
+	fn start(&mut self) {
+		self.last = self.dwt.get_cycle_count();
+	}
+
+	/// The ticks are counted from the previous one, not from the call, so the
+	/// time spent between two calls does not add up
+	fn wait(&mut self) {
+		self.last = self.last.wrapping_add(self.period);
+		while (self.dwt.get_cycle_count().wrapping_sub(self.last) as i32) < 0 {}
+	}
+}
+
+/// Ticks spun with `asm::delay`
+pub struct SpinTimer {
+	clock: Frequency,
+	cycles: u32,
+}
+
+impl SpinTimer {
+	/// Ticks at the core clock, until a rate is set by a bus
+	pub fn new(clocks: &Clocks) -> Self {
+		SpinTimer { clock: clocks.ahb1f, cycles: 1 }
+	}
+}
+
+impl BitTimer for SpinTimer {
+	fn set_rate(&mut self, rate: Frequency) {
+		self.cycles = (self.clock / rate).max(1);
+	}
+
+	fn start(&mut self) {}
+
+	fn wait(&mut self) {
+		asm::delay(self.cycles);
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+pub(crate) mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+
+	use super::BitTimer;
+	use crate::common::Frequency;
+	use crate::common::register::{ sim::{ self, SimBlock }, RegisterSpec };
+	use crate::peripherals::extended::gpio::{ Gpio, SIZE, BSRR, IDR, ODR };
+
+	/// Port of open-drain lines: ODR follows the BSRR writes and IDR reads ODR back
+	/// The tests pull lines low by adding read hooks to IDR
+	pub(crate) fn port() -> (SimBlock<SIZE>, Gpio) {
+		let block = SimBlock::<SIZE>::new();
+		let odr = block.addr(ODR::INDEX);
+
+		block.poke(ODR::INDEX, 0xFFFF)
+			.on_write(BSRR::INDEX, move |_, bsrr| {
+				sim::poke(odr, (sim::peek(odr) | (bsrr & 0xFFFF)) & !(bsrr >> 16));
+				0
+			})
+			.on_read(IDR::INDEX, move |_| sim::peek(odr));
+
+		let gpio = Gpio::from_block( unsafe { block.regs() } );
+		(block, gpio)
+	}
+
+	/// Timer counting its ticks
+	pub(crate) struct Ticks(pub Rc<Cell<u32>>);
+
+	impl BitTimer for Ticks {
+		fn set_rate(&mut self, _: Frequency) {}
+
+		fn start(&mut self) {}
+
+		fn wait(&mut self) {
+			self.0.set(self.0.get() + 1);
+		}
+	}
+}
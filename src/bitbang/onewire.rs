@@ -0,0 +1,543 @@
+//! 1-Wire bus master
+//!
+//! Runs on an open-drain pin that can be read back, with a pull-up on the bus
+//! (the internal one is enough for short buses). The slots use the standard speed
+//! timings of Maxim's AN126, and each one is timed in a critical section so an
+//! interrupt cannot stretch it. Use a precise delay, such as `DwtDelay`.
+//!
+//! ```ignore
+//! let pin = gpioa.pin(1)?.into_open_drain_output();
+//! pin.pull_up_state(true);
+//!
+//! let mut bus = OneWire::new(pin, DwtDelay::new(p.dwt, p.dcb, &clocks));
+//! let mut search = Search::new();
+//!
+//! while let Some(rom) = bus.search(&mut search)? {
+//! 	bus.match_rom(&rom)?;
+//! 	bus.write_byte(0x44); // DS18B20 Convert T
+//! }
+//! ```
+
+#[cfg(feature = "std")]
+use std::{ cmp::Ordering, convert::Infallible };
+
+#[cfg(not(feature = "std"))]
+use core::{ cmp::Ordering, convert::Infallible };
+
+use crate::common::OneWireError;
+use crate::interrupt;
+
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::{ InputPin, OutputPin };
+
+/// ROM commands
+const SEARCH_ROM: u8 = 0xF0;
+const ALARM_SEARCH: u8 = 0xEC;
+const READ_ROM: u8 = 0x33;
+const MATCH_ROM: u8 = 0x55;
+const SKIP_ROM: u8 = 0xCC;
+
+/// 64-bit ROM code of a device: family code, 48-bit serial number and CRC
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rom(pub [u8; 8]);
+
+impl Rom {
+	/// Returns the family code (e.g. 0x28 for the DS18B20)
+	pub fn family(&self) -> u8 {
+		self.0[0]
+	}
+
+	/// Checks the CRC of the code
+	pub fn is_valid(&self) -> bool {
+		crc8(&self.0) == 0
+	}
+}
+
+/// State of a ROM search, kept between the calls to `search`
+#[derive(Debug, Copy, Clone)]
+pub struct Search {
+	rom: [u8; 8],
+	/// Bit of the last branch that took the 0 path, 0 if none
+	last_discrepancy: u8,
+	done: bool,
+}
+
+impl Default for Search {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Search {
+	/// Starts a new search
+	pub const fn new() -> Self {
+		Search { rom: [0; 8], last_discrepancy: 0, done: false }
+	}
+
+	fn bit(&self, n: u8) -> bool {
+		self.rom[n as usize / 8] & (1 << (n % 8)) != 0
+	}
+
+	fn set_bit(&mut self, n: u8, bit: bool) {
+		match bit {
+			true => self.rom[n as usize / 8] |= 1 << (n % 8),
+			_ => self.rom[n as usize / 8] &= !(1 << (n % 8)),
+		}
+	}
+}
+
+/// Dallas/Maxim CRC8 (x^8 + x^5 + x^4 + 1), 0 over data followed by its CRC
+pub fn crc8(data: &[u8]) -> u8 {
+	data.iter().fold(0, |crc, &byte| {
+		(0..8).fold((crc, byte), |(crc, byte), _| {
+			let mix = (crc ^ byte) & 1;
+			let crc = crc >> 1;
+
+			(if mix != 0 { crc ^ 0x8C } else { crc }, byte >> 1)
+		}).0
+	})
+}
+
+/// 1-Wire master over an open-drain pin
+pub struct OneWire<P, D> {
+	pin: P,
+	delay: D,
+}
+
+impl<P, D> OneWire<P, D>
+	where P: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+		D: DelayUs<u32>
+{
+	/// Releases the bus
+	pub fn new(mut pin: P, delay: D) -> Self {
+		let _ = pin.set_high();
+		OneWire { pin, delay }
+	}
+
+	/// Returns the pin and the delay
+	pub fn free(self) -> (P, D) {
+		(self.pin, self.delay)
+	}
+
+	fn line(&self) -> bool {
+		self.pin.is_high().unwrap_or(false)
+	}
+
+	/// Sends a reset pulse and returns `true` if a device answered with a presence pulse
+	pub fn reset(&mut self) -> Result<bool, OneWireError> {
+		if !self.line() { return Err(OneWireError::BusShorted); }
+
+		let _ = self.pin.set_low();
+		self.delay.delay_us(480);
+
+		let presence = interrupt::free(|_| {
+			let _ = self.pin.set_high();
+			self.delay.delay_us(70);
+			!self.line()
+		});
+
+		self.delay.delay_us(410);
+
+		Ok( presence )
+	}
+
+	/// Writes a bit slot
+	pub fn write_bit(&mut self, bit: bool) {
+		let (low, high) = match bit {
+			true => (6, 64),
+			_ => (60, 10),
+		};
+
+		interrupt::free(|_| {
+			let _ = self.pin.set_low();
+			self.delay.delay_us(low);
+			let _ = self.pin.set_high();
+		});
+
+		self.delay.delay_us(high);
+	}
+
+	/// Reads a bit slot
+	pub fn read_bit(&mut self) -> bool {
+		let bit = interrupt::free(|_| {
+			let _ = self.pin.set_low();
+			self.delay.delay_us(6);
+			let _ = self.pin.set_high();
+			self.delay.delay_us(9);
+			self.line()
+		});
+
+		self.delay.delay_us(55);
+
+		bit
+	}
+
+	/// Writes a byte, least significant bit first
+	pub fn write_byte(&mut self, byte: u8) {
+		for i in 0..8 {
+			self.write_bit(byte & (1 << i) != 0);
+		}
+	}
+
+	/// Reads a byte, least significant bit first
+	pub fn read_byte(&mut self) -> u8 {
+		(0..8).fold(0, |byte, i| byte | ((self.read_bit() as u8) << i))
+	}
+
+	/// Writes all the bytes
+	pub fn write_bytes(&mut self, bytes: &[u8]) {
+		for &b in bytes {
+			self.write_byte(b);
+		}
+	}
+
+	/// Fills `buffer` with the bytes read
+	pub fn read_bytes(&mut self, buffer: &mut [u8]) {
+		for b in buffer.iter_mut() {
+			*b = self.read_byte();
+		}
+	}
+
+	/// Resets the bus and fails if no device is present
+	fn reset_present(&mut self) -> Result<(), OneWireError> {
+		match self.reset()? {
+			true => Ok(()),
+			_ => Err(OneWireError::NoPresence),
+		}
+	}
+
+	/// Resets the bus and addresses every device
+	pub fn skip_rom(&mut self) -> Result<(), OneWireError> {
+		self.reset_present()?;
+		self.write_byte(SKIP_ROM);
+		Ok(())
+	}
+
+	/// Resets the bus and addresses the device `rom`
+	pub fn match_rom(&mut self, rom: &Rom) -> Result<(), OneWireError> {
+		self.reset_present()?;
+		self.write_byte(MATCH_ROM);
+		self.write_bytes(&rom.0);
+		Ok(())
+	}
+
+	/// Reads the ROM code of the only device of the bus
+	pub fn read_rom(&mut self) -> Result<Rom, OneWireError> {
+		self.reset_present()?;
+		self.write_byte(READ_ROM);
+
+		let mut rom = Rom([0; 8]);
+		self.read_bytes(&mut rom.0);
+
+		match rom.is_valid() {
+			true => Ok( rom ),
+			_ => Err(OneWireError::Crc),
+		}
+	}
+
+	/// Finds the next device of the bus, `None` once all have been found
+	pub fn search(&mut self, search: &mut Search) -> Result<Option<Rom>, OneWireError> {
+		self.search_command(search, SEARCH_ROM)
+	}
+
+	/// Finds the next device with an alarm set, `None` once all have been found
+	pub fn alarm_search(&mut self, search: &mut Search) -> Result<Option<Rom>, OneWireError> {
+		self.search_command(search, ALARM_SEARCH)
+	}
+
+	/// Walks the ROM tree with the branch algorithm of Maxim's AN187
+	/// Each call follows the 1 path at the last branch it took the 0 path
+	fn search_command(&mut self, search: &mut Search, command: u8) -> Result<Option<Rom>, OneWireError> {
+		if search.done { return Ok(None); }
+
+		if !self.reset()? {
+			*search = Search::new();
+			return Ok(None);
+		}
+
+		self.write_byte(command);
+
+		let mut last_zero = 0;
+
+		for n in 1..=64u8 {
+			let bit = self.read_bit();
+			let complement = self.read_bit();
+
+			let direction = match (bit, complement) {
+				// No device answered
+				(true, true) => {
+					*search = Search::new();
+					return Ok(None);
+				},
+
+				// Devices with both values, a branch
+				(false, false) => {
+					let direction = match n.cmp(&search.last_discrepancy) {
+						Ordering::Less => search.bit(n - 1),
+						Ordering::Equal => true,
+						Ordering::Greater => false,
+					};
+
+					if !direction { last_zero = n; }
+
+					direction
+				},
+
+				// All the remaining devices have the same value
+				(bit, _) => bit,
+			};
+
+			search.set_bit(n - 1, direction);
+			self.write_bit(direction);
+		}
+
+		search.last_discrepancy = last_zero;
+		search.done = last_zero == 0;
+
+		let rom = Rom(search.rom);
+
+		match rom.is_valid() {
+			true => Ok( Some(rom) ),
+			_ => Err(OneWireError::Crc),
+		}
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	use super::*;
+	use crate::bitbang::tests::port;
+	use crate::common::register::{ sim, RegisterSpec };
+	use crate::common::structs::{ Pin, Output, OpenDrain };
+	use crate::peripherals::extended::gpio::{ IDR, ODR };
+
+	/// Step of a device in a transaction
+	#[derive(Copy, Clone)]
+	enum Step {
+		Idle,
+		/// Command byte received so far and its next bit
+		Command(u8, u8),
+		/// ROM bit searched and slot: bit, complement, direction
+		Search(u8, u8),
+		/// ROM bit sent
+		Send(u8),
+	}
+
+	/// Slave answering the ROM commands
+	struct Device {
+		rom: Rom,
+		step: Step,
+		commands: Vec<u8>,
+	}
+
+	impl Device {
+		fn new(rom: Rom) -> Self {
+			Device { rom, step: Step::Idle, commands: Vec::new() }
+		}
+
+		fn bit(&self, n: u8) -> bool {
+			self.rom.0[n as usize / 8] & (1 << (n % 8)) != 0
+		}
+
+		/// Bit the device sends in the next slot, if any
+		fn sends(&self) -> Option<bool> {
+			match self.step {
+				Step::Search(n, 0) | Step::Send(n) => Some(self.bit(n)),
+				Step::Search(n, 1) => Some(!self.bit(n)),
+				_ => None,
+			}
+		}
+
+		fn receive(&mut self, bit: bool) {
+			self.step = match self.step {
+				Step::Command(byte, 7) => {
+					let byte = byte | (bit as u8) << 7;
+					self.commands.push(byte);
+
+					match byte {
+						SEARCH_ROM => Step::Search(0, 0),
+						READ_ROM => Step::Send(0),
+						_ => Step::Idle,
+					}
+				},
+				Step::Command(byte, i) => Step::Command(byte | (bit as u8) << i, i + 1),
+				Step::Search(n, 2) if bit != self.bit(n) => Step::Idle,
+				Step::Search(63, 2) | Step::Send(63) => Step::Idle,
+				Step::Search(n, 2) => Step::Search(n + 1, 0),
+				Step::Search(n, slot) => Step::Search(n, slot + 1),
+				Step::Send(n) => Step::Send(n + 1),
+				Step::Idle => Step::Idle,
+			}
+		}
+	}
+
+	/// Bus line, low while the master or any device pulls it
+	#[derive(Default)]
+	struct Wire {
+		now: u32,
+		/// Level driven by the master and the time it was set
+		high: bool,
+		since: u32,
+		/// Interval during which a device holds the line low
+		pull: (u32, u32),
+		/// Low time of every slot the master drove
+		slots: Vec<u32>,
+		devices: Vec<Device>,
+	}
+
+	impl Wire {
+		fn drive(&mut self, high: bool) {
+			if high == self.high { return; }
+
+			match high {
+				// The devices sample the falling edge and hold the line to send a 0
+				false => if self.devices.iter().any(|d| d.sends() == Some(false)) {
+					self.pull = (self.now, self.now + 30);
+				},
+
+				true => match self.now - self.since {
+					low if low >= 480 => {
+						for d in self.devices.iter_mut() { d.step = Step::Command(0, 0); }
+						if !self.devices.is_empty() { self.pull = (self.now + 15, self.now + 135); }
+					},
+					low => {
+						self.slots.push(low);
+						for d in self.devices.iter_mut() { d.receive(low < 15); }
+					},
+				},
+			}
+
+			self.high = high;
+			self.since = self.now;
+		}
+
+		fn pulled(&self) -> bool {
+			(self.pull.0..self.pull.1).contains(&self.now)
+		}
+	}
+
+	/// Delay moving the time of the wire, the master changes the line just before each delay
+	struct Clock {
+		wire: Rc<RefCell<Wire>>,
+		odr: usize,
+	}
+
+	impl DelayUs<u32> for Clock {
+		fn delay_us(&mut self, us: u32) {
+			let mut wire = self.wire.borrow_mut();
+			wire.drive(sim::peek(self.odr) & 1 != 0);
+			wire.now += us;
+		}
+	}
+
+	type Master = OneWire<Pin<Output<OpenDrain>>, Clock>;
+
+	/// Master on P0 with `devices` on the bus
+	fn master(devices: Vec<Device>) -> (Rc<RefCell<Wire>>, Master) {
+		let (block, mut gpio) = port();
+		let wire = Rc::new(RefCell::new( Wire { high: true, devices, ..Wire::default() } ));
+
+		let w = wire.clone();
+		block.on_read(IDR::INDEX, move |idr| if w.borrow().pulled() { idr & !1 } else { idr });
+
+		let pin = gpio.pin(0).unwrap().into_open_drain_output();
+		let clock = Clock { wire: wire.clone(), odr: block.addr(ODR::INDEX) };
+
+		(wire, OneWire::new(pin, clock))
+	}
+
+	/// ROM code with its CRC
+	fn rom(family: u8, serial: u64) -> Rom {
+		let mut code = [0; 8];
+		code[0] = family;
+		code[1..7].copy_from_slice(&serial.to_le_bytes()[..6]);
+		code[7] = crc8(&code[..7]);
+		Rom(code)
+	}
+
+	#[test]
+	fn crc_of_a_known_rom() {
+		assert_eq!(crc8(&[0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00]), 0xA2);
+		assert!(Rom([0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00, 0xA2]).is_valid());
+	}
+
+	#[test]
+	fn reset_detects_presence() {
+		let (_, mut bus) = master(vec![Device::new(rom(0x28, 1))]);
+		assert_eq!(bus.reset(), Ok(true));
+
+		let (_, mut bus) = master(Vec::new());
+		assert_eq!(bus.reset(), Ok(false));
+		assert_eq!(bus.skip_rom(), Err(OneWireError::NoPresence));
+	}
+
+	#[test]
+	fn reset_fails_on_a_shorted_bus() {
+		let (wire, mut bus) = master(Vec::new());
+		wire.borrow_mut().pull = (0, u32::MAX);
+
+		assert_eq!(bus.reset(), Err(OneWireError::BusShorted));
+	}
+
+	#[test]
+	fn write_slots() {
+		let (wire, mut bus) = master(vec![Device::new(rom(0x28, 1))]);
+
+		bus.skip_rom().unwrap();
+		bus.write_byte(0x01);
+
+		let wire = wire.borrow();
+		// 0xCC then 0x01, least significant bit first
+		assert_eq!(wire.slots, [60, 60, 6, 6, 60, 60, 6, 6, 6, 60, 60, 60, 60, 60, 60, 60]);
+		assert_eq!(wire.devices[0].commands, [SKIP_ROM]);
+	}
+
+	#[test]
+	fn read_slots() {
+		let code = rom(0x28, 0x0000_1234_5678);
+		let (wire, mut bus) = master(vec![Device::new(code)]);
+
+		assert_eq!(bus.read_rom(), Ok(code));
+		// A read slot is a short low pulse, the device holds the line to send a 0
+		assert!(wire.borrow().slots[8..].iter().all(|&low| low == 6));
+	}
+
+	#[test]
+	fn read_rom_checks_the_crc() {
+		let mut code = rom(0x28, 7);
+		code.0[7] ^= 1;
+		let (_, mut bus) = master(vec![Device::new(code)]);
+
+		assert_eq!(bus.read_rom(), Err(OneWireError::Crc));
+	}
+
+	#[test]
+	fn search_finds_every_device() {
+		let mut roms = vec![rom(0x28, 0x0A), rom(0x28, 0x0B), rom(0x10, 0x0A), rom(0x28, 0x8000_0000_000A)];
+		let (_, mut bus) = master(roms.iter().map(|&r| Device::new(r)).collect());
+
+		let mut search = Search::new();
+		let mut found = Vec::new();
+
+		while let Some(rom) = bus.search(&mut search).unwrap() {
+			found.push(rom);
+			assert!(found.len() <= roms.len());
+		}
+
+		assert_eq!(bus.search(&mut search), Ok(None));
+
+		found.sort_by_key(|r| r.0);
+		roms.sort_by_key(|r| r.0);
+		assert_eq!(found, roms);
+	}
+
+	#[test]
+	fn search_of_an_empty_bus() {
+		let (_, mut bus) = master(Vec::new());
+		assert_eq!(bus.search(&mut Search::new()), Ok(None));
+	}
+}
@@ -0,0 +1,256 @@
+//! Bit-banged UART
+//!
+//! 8 data bits, no parity and one stop bit. The transmission blocks for the
+//! whole frame. The reception is polled: `read` returns `WouldBlock` while the
+//! line is idle and receives the whole frame once a start bit is seen, so it must
+//! be polled (or called from an EXTI interrupt on RX) faster than half a bit.
+
+#[cfg(feature = "std")]
+use std::convert::Infallible;
+
+#[cfg(not(feature = "std"))]
+use core::convert::Infallible;
+
+use crate::common::{ Frequency, SerialError };
+
+use embedded_hal::blocking::serial::write;
+use embedded_hal::digital::v2::{ InputPin, OutputPin };
+use embedded_hal::serial::{ Read, Write };
+
+use super::BitTimer;
+
+/// UART over a TX and a RX pin
+pub struct Serial<TX, RX, T> {
+	tx: TX,
+	rx: RX,
+	timer: T,
+}
+
+impl<TX, RX, T> Serial<TX, RX, T>
+	where TX: OutputPin<Error = Infallible>,
+		RX: InputPin<Error = Infallible>,
+		T: BitTimer
+{
+	/// Idles TX high and sets the baud rate
+	/// The timer ticks twice per bit, so the bits are sampled in their middle
+	pub fn new(tx: TX, rx: RX, mut timer: T, baud: Frequency) -> Self {
+		timer.set_rate(baud * 2);
+
+		let mut serial = Serial { tx, rx, timer };
+		serial.set_tx(true);
+		serial
+	}
+
+	/// Returns the pins and the timer
+	pub fn free(self) -> (TX, RX, T) {
+		(self.tx, self.rx, self.timer)
+	}
+
+	fn set_tx(&mut self, bit: bool) {
+		let _ = match bit {
+			true => self.tx.set_high(),
+			_ => self.tx.set_low(),
+		};
+	}
+
+	fn rx(&self) -> bool {
+		self.rx.is_high().unwrap_or(true)
+	}
+
+	/// Waits for a whole bit
+	fn bit_wait(&mut self) {
+		self.timer.wait();
+		self.timer.wait();
+	}
+
+	/// Sends a frame
+	fn send(&mut self, byte: u8) {
+		self.timer.start();
+
+		self.set_tx(false);
+		self.bit_wait();
+
+		for i in 0..8 {
+			self.set_tx(byte & (1 << i) != 0);
+			self.bit_wait();
+		}
+
+		self.set_tx(true);
+		self.bit_wait();
+	}
+
+	/// Receives a frame whose start bit has just been seen
+	fn recv(&mut self) -> Result<u8, SerialError> {
+		self.timer.start();
+
+		// Middle of the start bit
+		self.timer.wait();
+		if self.rx() { return Err(SerialError::Noise); }
+
+		let mut byte = 0;
+
+		for i in 0..8 {
+			self.bit_wait();
+			byte |= (self.rx() as u8) << i;
+		}
+
+		self.bit_wait();
+		if !self.rx() { return Err(SerialError::Framing); }
+
+		Ok( byte )
+	}
+}
+
+impl<TX, RX, T> Read<u8> for Serial<TX, RX, T>
+	where TX: OutputPin<Error = Infallible>,
+		RX: InputPin<Error = Infallible>,
+		T: BitTimer
+{
+	type Error = SerialError;
+
+	fn read(&mut self) -> nb::Result<u8, SerialError> {
+		match self.rx() {
+			true => Err(nb::Error::WouldBlock),
+			_ => self.recv().map_err(nb::Error::Other),
+		}
+	}
+}
+
+impl<TX, RX, T> Write<u8> for Serial<TX, RX, T>
+	where TX: OutputPin<Error = Infallible>,
+		RX: InputPin<Error = Infallible>,
+		T: BitTimer
+{
+	type Error = Infallible;
+
+	fn write(&mut self, byte: u8) -> nb::Result<(), Infallible> {
+		self.send(byte);
+		Ok(())
+	}
+
+	/// The frames are sent in full by `write`
+	fn flush(&mut self) -> nb::Result<(), Infallible> {
+		Ok(())
+	}
+}
+
+impl<TX, RX, T> write::Default<u8> for Serial<TX, RX, T>
+	where TX: OutputPin<Error = Infallible>,
+		RX: InputPin<Error = Infallible>,
+		T: BitTimer
+{}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use std::cell::{ Cell, RefCell };
+	use std::rc::Rc;
+
+	use super::*;
+	use crate::bitbang::tests::{ port, Ticks };
+	use crate::common::register::{ sim::{ self, SimBlock }, RegisterSpec };
+	use crate::common::structs::{ Pin, Input, Floating, Output, PushPull };
+	use crate::peripherals::extended::gpio::{ SIZE, IDR, ODR };
+
+	const TX: u32 = 1 << 0;
+	const RX: u32 = 1 << 1;
+
+	type Uart<T> = Serial<Pin<Output<PushPull>>, Pin<Input<Floating>>, T>;
+
+	/// Timer recording the port output at each tick
+	struct Samples {
+		odr: usize,
+		levels: Rc<RefCell<Vec<u32>>>,
+	}
+
+	impl BitTimer for Samples {
+		fn set_rate(&mut self, _: Frequency) {}
+
+		fn start(&mut self) {}
+
+		fn wait(&mut self) {
+			self.levels.borrow_mut().push(sim::peek(self.odr));
+		}
+	}
+
+	/// Frame of `byte` as the line levels of its bits: start, data LSB first, stop
+	fn frame(byte: u8) -> Vec<bool> {
+		let mut bits = vec![false];
+		bits.extend((0..8).map(|i| byte & (1 << i) != 0));
+		bits.push(true);
+		bits
+	}
+
+	/// UART on P0 (TX) and P1 (RX), whose timer is made by `timer` from the ODR address
+	fn serial<T: BitTimer>(timer: impl FnOnce(usize) -> T) -> (SimBlock<SIZE>, Uart<T>) {
+		let (block, mut gpio) = port();
+
+		let tx = gpio.pin(0).unwrap().into_push_pull_output();
+		let rx = gpio.pin(1).unwrap().into_floating_input();
+
+		let serial = Serial::new(tx, rx, timer(block.addr(ODR::INDEX)), Frequency::Hz(9600));
+		(block, serial)
+	}
+
+	/// Line sending `bits`, one bit every two ticks, then idling high
+	fn sending(bits: Vec<bool>) -> impl Fn(u32) -> bool {
+		move |tick| bits.get(tick as usize / 2).cloned().unwrap_or(true)
+	}
+
+	/// UART whose RX is at `line(tick)`, counting the ticks from the first read
+	fn receiving<F>(line: F) -> Uart<Ticks> where F: Fn(u32) -> bool + 'static {
+		let ticks = Rc::new(Cell::new(0));
+		let (block, serial) = serial(|_| Ticks(ticks.clone()));
+
+		block.on_read(IDR::INDEX, move |idr| if line(ticks.get()) { idr | RX } else { idr & !RX });
+
+		serial
+	}
+
+	#[test]
+	fn write_sends_a_frame() {
+		let levels = Rc::new(RefCell::new(Vec::new()));
+		let (block, mut serial) = serial(|odr| Samples { odr, levels: levels.clone() });
+
+		assert_eq!(block.peek(ODR::INDEX) & TX, TX, "idles high");
+
+		serial.write(0x35).unwrap();
+
+		// Two ticks per bit, TX holds each bit for both
+		let levels = levels.borrow();
+		let sent: Vec<bool> = levels.chunks(2)
+			.map(|bit| {
+				assert_eq!(bit[0] & TX, bit[1] & TX);
+				bit[0] & TX != 0
+			})
+			.collect();
+
+		assert_eq!(sent, frame(0x35));
+		assert_eq!(block.peek(ODR::INDEX) & TX, TX, "back to idle");
+	}
+
+	#[test]
+	fn read_receives_a_frame() {
+		let mut serial = receiving(sending(frame(0xA7)));
+		assert_eq!(serial.read(), Ok(0xA7));
+	}
+
+	#[test]
+	fn read_waits_for_a_start_bit() {
+		let mut serial = receiving(|_| true);
+		assert_eq!(serial.read(), Err(nb::Error::WouldBlock));
+	}
+
+	#[test]
+	fn read_checks_the_start_and_stop_bits() {
+		// A glitch, the line is back high in the middle of the start bit
+		let mut serial = receiving(|tick| tick != 0);
+		assert_eq!(serial.read(), Err(nb::Error::Other(SerialError::Noise)));
+
+		let mut bits = frame(0xA7);
+		bits[9] = false;
+
+		let mut serial = receiving(sending(bits));
+		assert_eq!(serial.read(), Err(nb::Error::Other(SerialError::Framing)));
+	}
+}
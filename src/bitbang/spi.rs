@@ -0,0 +1,320 @@
+//! Bit-banged SPI master
+//!
+//! Supports the four clock polarity and phase modes, MSB or LSB first.
+//! The chip select is left to the user, as with the hardware driver.
+
+#[cfg(feature = "std")]
+use std::convert::Infallible;
+
+#[cfg(not(feature = "std"))]
+use core::convert::Infallible;
+
+use crate::common::Frequency;
+
+use embedded_hal::blocking::spi::{ Transfer, Write };
+use embedded_hal::digital::v2::{ InputPin, OutputPin };
+use embedded_hal::spi::{ Mode, Phase, Polarity };
+
+use super::BitTimer;
+
+/// SPI master over three pins
+pub struct Spi<SCK, MISO, MOSI, T> {
+	sck: SCK,
+	miso: MISO,
+	mosi: MOSI,
+	timer: T,
+	mode: Mode,
+	lsb: bool,
+}
+
+impl<SCK, MISO, MOSI, T> Spi<SCK, MISO, MOSI, T>
+	where SCK: OutputPin<Error = Infallible>,
+		MISO: InputPin<Error = Infallible>,
+		MOSI: OutputPin<Error = Infallible>,
+		T: BitTimer
+{
+	/// Idles the clock in `mode` and sets it to `freq`
+	/// The timer ticks twice per bit
+	pub fn new(sck: SCK, miso: MISO, mosi: MOSI, mut timer: T, mode: Mode, freq: Frequency) -> Self {
+		timer.set_rate(freq * 2);
+
+		let mut spi = Spi { sck, miso, mosi, timer, mode, lsb: false };
+		spi.set_sck(false);
+		spi
+	}
+
+	/// Returns the pins and the timer
+	pub fn free(self) -> (SCK, MISO, MOSI, T) {
+		(self.sck, self.miso, self.mosi, self.timer)
+	}
+
+	/// Sends the least significant bit first (`true`) or the most significant bit first (`false`)
+	pub fn lsb_state(&mut self, s: bool) -> &mut Self {
+		self.lsb = s;
+		self
+	}
+
+	/// Changes the polarity and phase, idling the clock in the new polarity
+	pub fn set_mode(&mut self, mode: Mode) -> &mut Self {
+		self.mode = mode;
+		self.set_sck(false);
+		self
+	}
+
+	/// Drives the clock to its active (`true`) or idle (`false`) level
+	fn set_sck(&mut self, active: bool) {
+		let high = active != (self.mode.polarity == Polarity::IdleHigh);
+
+		let _ = match high {
+			true => self.sck.set_high(),
+			_ => self.sck.set_low(),
+		};
+	}
+
+	fn set_mosi(&mut self, bit: bool) {
+		let _ = match bit {
+			true => self.mosi.set_high(),
+			_ => self.mosi.set_low(),
+		};
+	}
+
+	fn miso(&self) -> bool {
+		self.miso.is_high().unwrap_or(false)
+	}
+
+	/// Sends and receives a bit
+	fn bit(&mut self, out: bool) -> bool {
+		match self.mode.phase {
+			// Data set before the first edge, sampled on it
+			Phase::CaptureOnFirstTransition => {
+				self.set_mosi(out);
+				self.timer.wait();
+				self.set_sck(true);
+				let bit = self.miso();
+				self.timer.wait();
+				self.set_sck(false);
+				bit
+			},
+
+			// Data set on the first edge, sampled on the second
+			Phase::CaptureOnSecondTransition => {
+				self.set_sck(true);
+				self.set_mosi(out);
+				self.timer.wait();
+				self.set_sck(false);
+				let bit = self.miso();
+				self.timer.wait();
+				bit
+			},
+		}
+	}
+
+	/// Sends `byte` and returns the received byte
+	pub fn exchange(&mut self, byte: u8) -> u8 {
+		let mut read = 0;
+
+		for i in 0..8 {
+			let n = if self.lsb { i } else { 7 - i };
+			read |= (self.bit(byte & (1 << n) != 0) as u8) << n;
+		}
+
+		read
+	}
+}
+
+impl<SCK, MISO, MOSI, T> Transfer<u8> for Spi<SCK, MISO, MOSI, T>
+	where SCK: OutputPin<Error = Infallible>,
+		MISO: InputPin<Error = Infallible>,
+		MOSI: OutputPin<Error = Infallible>,
+		T: BitTimer
+{
+	type Error = Infallible;
+
+	fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Infallible> {
+		self.timer.start();
+
+		for w in words.iter_mut() {
+			*w = self.exchange(*w);
+		}
+
+		Ok( words )
+	}
+}
+
+impl<SCK, MISO, MOSI, T> Write<u8> for Spi<SCK, MISO, MOSI, T>
+	where SCK: OutputPin<Error = Infallible>,
+		MISO: InputPin<Error = Infallible>,
+		MOSI: OutputPin<Error = Infallible>,
+		T: BitTimer
+{
+	type Error = Infallible;
+
+	fn write(&mut self, words: &[u8]) -> Result<(), Infallible> {
+		self.timer.start();
+
+		for &w in words {
+			self.exchange(w);
+		}
+
+		Ok(())
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use std::cell::{ Cell, RefCell };
+	use std::rc::Rc;
+
+	use super::*;
+	use crate::bitbang::tests::{ port, Ticks };
+	use crate::common::register::{ sim::{ self, SimBlock }, RegisterSpec };
+	use crate::common::structs::{ Pin, Input, Floating, Output, PushPull };
+	use crate::peripherals::extended::gpio::{ SIZE, BSRR, IDR, ODR };
+
+	use embedded_hal::spi::{ MODE_0, MODE_1, MODE_2, MODE_3 };
+
+	const MODES: [Mode; 4] = [MODE_0, MODE_1, MODE_2, MODE_3];
+
+	const SCK: u32 = 1 << 0;
+	const MISO: u32 = 1 << 1;
+	const MOSI: u32 = 1 << 2;
+
+	type Bus = Spi<Pin<Output<PushPull>>, Pin<Input<Floating>>, Pin<Output<PushPull>>, Ticks>;
+
+	/// Slave sampling MOSI and shifting MISO on the edges of its mode
+	struct Slave {
+		mode: Mode,
+		lsb: bool,
+		sck: bool,
+		/// Byte sent and its bits shifted out so far
+		tx: u8,
+		shifted: u32,
+		rx: u8,
+		sampled: u32,
+		miso: bool,
+	}
+
+	impl Slave {
+		fn bit(&self, n: u32) -> u32 {
+			if self.lsb { n } else { 7 - n }
+		}
+
+		/// Drives the next bit on MISO
+		fn shift(&mut self) {
+			if self.shifted < 8 {
+				self.miso = self.tx & (1 << self.bit(self.shifted)) != 0;
+				self.shifted += 1;
+			}
+		}
+
+		fn edge(&mut self, odr: u32) {
+			let sck = odr & SCK != 0;
+			if sck == self.sck { return; }
+			self.sck = sck;
+
+			let leading = sck != (self.mode.polarity == Polarity::IdleHigh);
+
+			if leading == (self.mode.phase == Phase::CaptureOnFirstTransition) {
+				self.rx |= ((odr & MOSI != 0) as u8) << self.bit(self.sampled);
+				self.sampled += 1;
+			} else {
+				self.shift();
+			}
+		}
+	}
+
+	/// Master on P0 (SCK), P1 (MISO) and P2 (MOSI) with a slave sending `tx`
+	fn spi(mode: Mode, lsb: bool, tx: u8) -> (SimBlock<SIZE>, Bus, Rc<RefCell<Slave>>, Rc<Cell<u32>>) {
+		let (block, mut gpio) = port();
+		let ticks = Rc::new(Cell::new(0));
+
+		let sck = gpio.pin(0).unwrap().into_push_pull_output();
+		let miso = gpio.pin(1).unwrap().into_floating_input();
+		let mosi = gpio.pin(2).unwrap().into_push_pull_output();
+
+		let mut spi = Spi::new(sck, miso, mosi, Ticks(ticks.clone()), mode, Frequency::MHz(1));
+		spi.lsb_state(lsb);
+
+		let idle = block.peek(ODR::INDEX) & SCK != 0;
+		let slave = Rc::new(RefCell::new(Slave { mode, lsb, sck: idle, tx, shifted: 0, rx: 0, sampled: 0, miso: false }));
+
+		// Selected: with the first phase the first bit is out before the first edge
+		if mode.phase == Phase::CaptureOnFirstTransition {
+			slave.borrow_mut().shift();
+		}
+
+		let odr = block.addr(ODR::INDEX);
+		let s = slave.clone();
+		block.on_write(BSRR::INDEX, move |_, bsrr| {
+			s.borrow_mut().edge(sim::peek(odr));
+			bsrr
+		});
+
+		let s = slave.clone();
+		block.on_read(IDR::INDEX, move |idr| if s.borrow().miso { idr | MISO } else { idr & !MISO });
+
+		(block, spi, slave, ticks)
+	}
+
+	#[test]
+	fn the_clock_idles_at_its_polarity() {
+		for (mode, idle) in [(MODE_0, 0), (MODE_1, 0), (MODE_2, SCK), (MODE_3, SCK)] {
+			let (block, mut spi, _, _) = spi(mode, false, 0);
+			assert_eq!(block.peek(ODR::INDEX) & SCK, idle);
+
+			spi.exchange(0xFF);
+			assert_eq!(block.peek(ODR::INDEX) & SCK, idle);
+		}
+
+		let (block, mut spi, _, _) = spi(MODE_0, false, 0);
+		spi.set_mode(MODE_3);
+		assert_eq!(block.peek(ODR::INDEX) & SCK, SCK);
+	}
+
+	#[test]
+	fn each_mode_exchanges_with_its_slave() {
+		for (n, mode) in MODES.iter().cloned().enumerate() {
+			let (_, mut spi, slave, ticks) = spi(mode, false, 0x3C);
+
+			assert_eq!(spi.exchange(0xA5), 0x3C, "mode {}", n);
+
+			let slave = slave.borrow();
+			assert_eq!(slave.rx, 0xA5, "mode {}", n);
+			assert_eq!(slave.sampled, 8, "mode {}", n);
+
+			// Two ticks per bit
+			assert_eq!(ticks.get(), 16);
+		}
+	}
+
+	#[test]
+	fn lsb_first_exchange() {
+		for (n, mode) in MODES.iter().cloned().enumerate() {
+			let (_, mut spi, slave, _) = spi(mode, true, 0x01);
+
+			assert_eq!(spi.transfer(&mut [0x80]).unwrap(), [0x01], "mode {}", n);
+			assert_eq!(slave.borrow().rx, 0x80, "mode {}", n);
+		}
+	}
+
+	#[test]
+	fn msb_is_sent_first() {
+		let (block, mut spi, _, _) = spi(MODE_0, false, 0);
+		let odr = block.addr(ODR::INDEX);
+
+		// MOSI on each rising edge of SCK
+		let bits = Rc::new(RefCell::new(Vec::new()));
+		let b = bits.clone();
+		let mut sck = false;
+		block.on_write(BSRR::INDEX, move |_, bsrr| {
+			let level = sim::peek(odr);
+			if level & SCK != 0 && !sck { b.borrow_mut().push((level & MOSI != 0) as u8); }
+			sck = level & SCK != 0;
+			bsrr
+		});
+
+		spi.write(&[0b1000_0110]).unwrap();
+		assert_eq!(*bits.borrow(), [1, 0, 0, 0, 0, 1, 1, 0]);
+	}
+}
@@ -53,9 +53,18 @@ pub fn delay(n: u32) {
 		}
 	}
 
-	#[cfg(not(cortex_m))]
+	// The simulated backend has no timing, so it does nothing
+	#[cfg(all(not(cortex_m), feature = "sim"))]
 	{
-		unimplemented!();
+		let _ = n;
+	}
+
+	// Other hosts spin `n` times, the time is not that of `n` cycles
+	#[cfg(all(not(cortex_m), not(feature = "sim")))]
+	{
+		for _ in 0..n {
+			core::hint::spin_loop();
+		}
 	}
 }
 
@@ -80,7 +89,10 @@ pub fn nop() {
 		}
 	}
 
-	#[cfg(not(cortex_m))]
+	#[cfg(all(not(cortex_m), feature = "sim"))]
+	{}
+
+	#[cfg(all(not(cortex_m), not(feature = "sim")))]
 	{
 		unimplemented!();
 	}
@@ -190,7 +202,10 @@ pub fn isb() {
 		}
 	}
 
-	#[cfg(not(cortex_m))]
+	#[cfg(all(not(cortex_m), feature = "sim"))]
+	{}
+
+	#[cfg(all(not(cortex_m), not(feature = "sim")))]
 	{
 		unimplemented!()
 	}
@@ -222,7 +237,10 @@ pub fn dsb() {
 		}
 	}
 
-	#[cfg(not(cortex_m))]
+	#[cfg(all(not(cortex_m), feature = "sim"))]
+	{}
+
+	#[cfg(all(not(cortex_m), not(feature = "sim")))]
 	{
 		unimplemented!()
 	}
@@ -252,7 +270,10 @@ pub fn dmb() {
 		}
 	}
 
-	#[cfg(not(cortex_m))]
+	#[cfg(all(not(cortex_m), feature = "sim"))]
+	{}
+
+	#[cfg(all(not(cortex_m), not(feature = "sim")))]
 	{
 		unimplemented!()
 	}
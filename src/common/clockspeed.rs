@@ -1,6 +1,16 @@
 
 use super::Frequency;
 
+// `freeze` sizes the flash wait states and the voltage scale from these limits,
+// a family feature alone (e.g. `stm32f4`) does not tell them
+#[cfg(not(any(
+	feature = "stm32f1",   feature = "stm32f401", feature = "stm32f410", feature = "stm32f411",
+	feature = "stm32f412", feature = "stm32f4x3", feature = "stm32f4x5", feature = "stm32f4x7",
+	feature = "stm32f4x9", feature = "stm32f446", feature = "stm32f72x", feature = "stm32f73x"
+)))]
+compile_error!("select a device feature: `stm32f100`, `stm32f1`, `stm32f401`, `stm32f410`, `stm32f411`, \
+	`stm32f412`, `stm32f4x3`, `stm32f4x5`, `stm32f4x7`, `stm32f4x9`, `stm32f446`, `stm32f72x` or `stm32f73x`");
+
 #[cfg(any(
 	feature = "stm32f4disco", feature = "stm32f410", feature = "stm32f411",
 	feature = "stm32f412",    feature = "stm32f4x3"
@@ -19,6 +29,25 @@ pub mod clockspeed {
 	pub const APB1MAX: Frequency = Frequency::MHz(50);
 	pub const APB2MAX: Frequency = Frequency::MHz(100);
 	pub const AHBMAX: Frequency = Frequency::MHz(100);
+
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(2);
+	pub const VCOMIN: Frequency = Frequency::MHz(100);
+	pub const VCOMAX: Frequency = Frequency::MHz(432);
+
+	/// HCLK per flash wait state, by `VoltageRange`
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(16), Frequency::MHz(18), Frequency::MHz(24), Frequency::MHz(30),
+	];
+
+	/// Highest HCLK of each regulator voltage scale and its VOS value, from the lowest power
+	pub const VOSCALE: &[(Frequency, u32)] = &[
+		(Frequency::MHz(64), 0b01),
+		(Frequency::MHz(84), 0b10),
+		(Frequency::MHz(100), 0b11),
+	];
+	/// HCLK above which the regulator needs the over-drive
+	pub const OVERDRIVE: Option<Frequency> = None;
 }
 
 #[cfg(feature = "stm32f401")]
@@ -33,12 +62,60 @@ pub mod clockspeed {
 	pub const APB1MAX: Frequency = Frequency::MHz(42);
 	pub const APB2MAX: Frequency = Frequency::MHz(84);
 	pub const AHBMAX: Frequency = Frequency::MHz(84);
+
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(2);
+	pub const VCOMIN: Frequency = Frequency::MHz(192);
+	pub const VCOMAX: Frequency = Frequency::MHz(432);
+
+	/// HCLK per flash wait state, by `VoltageRange`
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(16), Frequency::MHz(18), Frequency::MHz(24), Frequency::MHz(30),
+	];
+
+	/// Highest HCLK of each regulator voltage scale and its VOS value, from the lowest power
+	pub const VOSCALE: &[(Frequency, u32)] = &[
+		(Frequency::MHz(60), 0b01),
+		(Frequency::MHz(84), 0b10),
+	];
+	/// HCLK above which the regulator needs the over-drive
+	pub const OVERDRIVE: Option<Frequency> = None;
 }
 
-#[cfg(any(
-	feature = "stm32f446", feature = "stm32f4x9",
-	feature = "stm32f4x7", feature = "stm32f4x5"
-))]
+#[cfg(any(feature = "stm32f4x5", feature = "stm32f4x7"))]
+pub mod clockspeed {
+	use super::Frequency;
+
+	pub const HSI: Frequency = Frequency::MHz(16);
+	pub const LSI: Frequency = Frequency::KHz(32);
+	pub const LSE: Frequency = Frequency::Hz(32768);
+
+	pub const SYSCLKMAX: Frequency = Frequency::MHz(168);
+	pub const APB1MAX: Frequency = Frequency::MHz(42);
+	pub const APB2MAX: Frequency = Frequency::MHz(84);
+	pub const AHBMAX: Frequency = Frequency::MHz(168);
+
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(2);
+	pub const VCOMIN: Frequency = Frequency::MHz(100);
+	pub const VCOMAX: Frequency = Frequency::MHz(432);
+
+	/// HCLK per flash wait state, by `VoltageRange`
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(20), Frequency::MHz(22), Frequency::MHz(24), Frequency::MHz(30),
+	];
+
+	/// Highest HCLK of each regulator voltage scale and its VOS value, from the lowest power
+	/// VOS is a single bit on these parts
+	pub const VOSCALE: &[(Frequency, u32)] = &[
+		(Frequency::MHz(144), 0b0),
+		(Frequency::MHz(168), 0b1),
+	];
+	/// HCLK above which the regulator needs the over-drive
+	pub const OVERDRIVE: Option<Frequency> = None;
+}
+
+#[cfg(any(feature = "stm32f446", feature = "stm32f4x9"))]
 pub mod clockspeed {
 	use super::Frequency;
 
@@ -50,6 +127,64 @@ pub mod clockspeed {
 	pub const APB1MAX: Frequency = Frequency::MHz(45);
 	pub const APB2MAX: Frequency = Frequency::MHz(90);
 	pub const AHBMAX: Frequency = Frequency::MHz(180);
+
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(2);
+	pub const VCOMIN: Frequency = Frequency::MHz(100);
+	pub const VCOMAX: Frequency = Frequency::MHz(432);
+
+	/// HCLK per flash wait state, by `VoltageRange`
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(20), Frequency::MHz(22), Frequency::MHz(24), Frequency::MHz(30),
+	];
+
+	/// Highest HCLK of each regulator voltage scale and its VOS value, from the lowest power
+	pub const VOSCALE: &[(Frequency, u32)] = &[
+		(Frequency::MHz(120), 0b01),
+		(Frequency::MHz(144), 0b10),
+		(Frequency::MHz(168), 0b11),
+	];
+	/// HCLK above which the regulator needs the over-drive
+	pub const OVERDRIVE: Option<Frequency> = Some(Frequency::MHz(168));
+}
+
+/// The STM32F100 (value line) runs at 24 MHz, the others at 72 MHz
+#[cfg(feature = "stm32f1")]
+pub mod clockspeed {
+	use super::Frequency;
+
+	pub const HSI: Frequency = Frequency::MHz(8);
+	pub const LSI: Frequency = Frequency::KHz(40);
+	pub const LSE: Frequency = Frequency::Hz(32768);
+
+	#[cfg(not(feature = "stm32f100"))]
+	pub const SYSCLKMAX: Frequency = Frequency::MHz(72);
+	#[cfg(not(feature = "stm32f100"))]
+	pub const APB1MAX: Frequency = Frequency::MHz(36);
+	#[cfg(not(feature = "stm32f100"))]
+	pub const APB2MAX: Frequency = Frequency::MHz(72);
+	#[cfg(not(feature = "stm32f100"))]
+	pub const AHBMAX: Frequency = Frequency::MHz(72);
+
+	#[cfg(feature = "stm32f100")]
+	pub const SYSCLKMAX: Frequency = Frequency::MHz(24);
+	#[cfg(feature = "stm32f100")]
+	pub const APB1MAX: Frequency = Frequency::MHz(24);
+	#[cfg(feature = "stm32f100")]
+	pub const APB2MAX: Frequency = Frequency::MHz(24);
+	#[cfg(feature = "stm32f100")]
+	pub const AHBMAX: Frequency = Frequency::MHz(24);
+
+	/// The PLL has no VCO, the limits are the ones of its output
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(24);
+	pub const VCOMIN: Frequency = Frequency::MHz(16);
+	pub const VCOMAX: Frequency = SYSCLKMAX;
+
+	/// HCLK per flash wait state, the same for every supply voltage
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(24), Frequency::MHz(24), Frequency::MHz(24), Frequency::MHz(24),
+	];
 }
 
 #[cfg(feature = "stm32f2")]
@@ -64,6 +199,21 @@ pub mod clockspeed {
 	pub const APB1MAX: Frequency = Frequency::MHz(30);
 	pub const APB2MAX: Frequency = Frequency::MHz(60);
 	pub const AHBMAX: Frequency = Frequency::MHz(120);
+
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(2);
+	pub const VCOMIN: Frequency = Frequency::MHz(64);
+	pub const VCOMAX: Frequency = Frequency::MHz(432);
+
+	/// HCLK per flash wait state, by `VoltageRange`
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(16), Frequency::MHz(18), Frequency::MHz(24), Frequency::MHz(30),
+	];
+
+	/// Highest HCLK of each regulator voltage scale and its VOS value, from the lowest power
+	pub const VOSCALE: &[(Frequency, u32)] = &[];
+	/// HCLK above which the regulator needs the over-drive
+	pub const OVERDRIVE: Option<Frequency> = None;
 }
 
 #[cfg(any(feature = "stm32f72x", feature = "stm32f73x", feature = "stm32f75x", feature = "stm32f74x"))]
@@ -78,6 +228,25 @@ pub mod clockspeed {
 	pub const APB1MAX: Frequency = Frequency::MHz(54);
 	pub const APB2MAX: Frequency = Frequency::MHz(108);
 	pub const AHBMAX: Frequency = Frequency::MHz(216);
+
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(2);
+	pub const VCOMIN: Frequency = Frequency::MHz(100);
+	pub const VCOMAX: Frequency = Frequency::MHz(432);
+
+	/// HCLK per flash wait state, by `VoltageRange`
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(20), Frequency::MHz(22), Frequency::MHz(24), Frequency::MHz(30),
+	];
+
+	/// Highest HCLK of each regulator voltage scale and its VOS value, from the lowest power
+	pub const VOSCALE: &[(Frequency, u32)] = &[
+		(Frequency::MHz(144), 0b01),
+		(Frequency::MHz(168), 0b10),
+		(Frequency::MHz(180), 0b11),
+	];
+	/// HCLK above which the regulator needs the over-drive
+	pub const OVERDRIVE: Option<Frequency> = Some(Frequency::MHz(180));
 }
 
 #[cfg(any(feature = "stm32f76x", feature = "stm32f77x"))]
@@ -92,9 +261,28 @@ pub mod clockspeed {
 	pub const APB1MAX: Frequency = Frequency::MHz(45);
 	pub const APB2MAX: Frequency = Frequency::MHz(90);
 	pub const AHBMAX: Frequency = Frequency::MHz(216);
+
+	pub const PLLINMIN: Frequency = Frequency::MHz(1);
+	pub const PLLINMAX: Frequency = Frequency::MHz(2);
+	pub const VCOMIN: Frequency = Frequency::MHz(100);
+	pub const VCOMAX: Frequency = Frequency::MHz(432);
+
+	/// HCLK per flash wait state, by `VoltageRange`
+	pub const WAITSTATE: [Frequency; 4] = [
+		Frequency::MHz(20), Frequency::MHz(22), Frequency::MHz(24), Frequency::MHz(30),
+	];
+
+	/// Highest HCLK of each regulator voltage scale and its VOS value, from the lowest power
+	pub const VOSCALE: &[(Frequency, u32)] = &[
+		(Frequency::MHz(144), 0b01),
+		(Frequency::MHz(168), 0b10),
+		(Frequency::MHz(180), 0b11),
+	];
+	/// HCLK above which the regulator needs the over-drive
+	pub const OVERDRIVE: Option<Frequency> = Some(Frequency::MHz(180));
 }
 
-#[cfg(any(feature = "stm32h7"))]
+#[cfg(feature = "stm32h7")]
 pub mod clockspeed {
 	use super::Frequency;
 
@@ -17,7 +17,7 @@ pub struct SPIConfig {
 
 impl SPIConfig {
 	pub fn cr1(&self) -> u32 {
-		0 |  if self.dline  { 1 << 15 } else { 0 }
+		(if self.dline      { 1 << 15 } else { 0 })
 		| if self.rxonly    { 0 }       else { 1 << 14}
 		| if self.crc       { 1 << 13}  else { 0 }
 		| if self.bit8      { 0 }       else { 1 << 11 }
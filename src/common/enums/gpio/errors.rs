@@ -0,0 +1,15 @@
+//! GPIO Errors
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GPIOError {
+	/// The pin number does not exist in the port
+	InvalidPin,
+	/// The pin has already been taken
+	PinTaken,
+	/// The pins do not belong to the same port
+	DifferentPorts,
+	/// The EXTI line is already routed to another port
+	LineTaken,
+	/// The lock key sequence did not lock the port
+	LockFailed,
+}
@@ -3,6 +3,7 @@
 reexport!{
 	private:
 		mod af;
+		mod errors;
 		mod pins;
 		mod portconfig;
 }
\ No newline at end of file
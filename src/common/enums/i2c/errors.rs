@@ -1,11 +1,28 @@
 //! I2C Errors
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum I2CError {
+	/// The address is not an I2C peripheral
+	InvalidAddress,
+	/// The pin cannot carry the signal of the peripheral
+	InvalidPin,
+	/// NACK received
+	NACK,
+	/// Bus error
+	Bus,
+	/// Arbitration loss
+	Arbitration,
+	/// Overrun - Slave mode only
+	Overrun,
+	/// PEC - SMBUS mode only
+	PEC,
+	/// Timeout - SMBUS mode, or SCL stretched too long on a bit-banged bus
+	Timeout,
+	/// Alert - SMBUS mode only
+	Alert,
 	WrongDataFormat,
 	FrequencyNotAllowed,
-	NotIn10BitMode,
-	NACK,
 	InvalidBusSpeed,
+	NotIn10BitMode,
 	Address2NotAllowed,
-}
\ No newline at end of file
+}
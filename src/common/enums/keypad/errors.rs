@@ -0,0 +1,7 @@
+//! Keypad Errors
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeypadError {
+	/// Two rows share two pressed columns, a phantom key may be read
+	Ghosting,
+}
@@ -0,0 +1,5 @@
+//! Keypad enums
+
+mod errors;
+
+pub use self::errors::*;
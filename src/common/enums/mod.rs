@@ -6,7 +6,11 @@ reexport!{
 		mod extilines;
 		mod gpio;
 		mod i2c;
+		mod keypad;
+		mod onewire;
+		mod pwr;
 		mod rcc;
+		mod serial;
 		mod spi;
 		mod tim;
 		mod trigger;
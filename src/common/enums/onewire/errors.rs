@@ -0,0 +1,11 @@
+//! 1-Wire Errors
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OneWireError {
+	/// No device answered the reset pulse
+	NoPresence,
+	/// The bus is held low
+	BusShorted,
+	/// The CRC of the data read does not match
+	Crc,
+}
@@ -0,0 +1,5 @@
+//! 1-Wire enums
+
+mod errors;
+
+pub use self::errors::*;
@@ -0,0 +1,14 @@
+//! PWR enums
+
+/// Range of the supply voltage, sets the flash wait states
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VoltageRange {
+	/// 1.7 V (1.8 V on some parts) to 2.1 V
+	V1_8,
+	/// 2.1 V to 2.4 V
+	V2_1,
+	/// 2.4 V to 2.7 V
+	V2_4,
+	/// 2.7 V to 3.6 V
+	V2_7,
+}
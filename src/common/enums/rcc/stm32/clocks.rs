@@ -21,6 +21,15 @@ pub enum SrcClock {
 	PLL,
 }
 
+/// Source of the HSE
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HSEMode {
+	/// Crystal or ceramic resonator on OSC_IN and OSC_OUT
+	Crystal,
+	/// External clock on OSC_IN, the oscillator is bypassed
+	Bypass,
+}
+
 impl DeviceClock {
 	/// Returns the clock offsets for ON / OFF
 	pub fn offsets(&self) -> (usize, usize) {
@@ -0,0 +1,56 @@
+//! RCC Errors
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RCCError {
+	/// The PLL was selected as SYSCLK but it was not configured
+	PLLNotConfigured,
+	/// The PLL must be disabled to change this setting
+	PLLEnabled,
+	/// The PLL must be enabled to change this setting
+	PLLDisabled,
+	/// The divider or multiplier is out of range
+	InvalidDivider,
+	/// The HSE is used but no HSE is configured
+	HSENotConfigured,
+	/// The HSE did not start in time, the clocks were left as they were
+	HSETimeout,
+	/// A clock or PLL factor is out of the limits of the device
+	/// Holds the value, in Hz for the clocks
+	OutOfRange(ClockLimit, u32),
+}
+
+/// Limits checked on a clock configuration
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClockLimit {
+	/// HSE out of 4..=26 MHz for a crystal, 1..=50 MHz in bypass
+	/// On the STM32F1, 4..=16 MHz for a crystal (24 MHz on the STM32F100), 1..=25 MHz in bypass
+	HSE,
+	/// SYSCLK over `SYSCLKMAX`
+	SYSCLK,
+	/// AHB clock over `AHBMAX`
+	AHB,
+	/// APB1 clock over `APB1MAX`
+	APB1,
+	/// APB2 clock over `APB2MAX`
+	APB2,
+	/// PLL or PLLI2S input (VCO input) out of `PLLINMIN..=PLLINMAX`
+	PLLInput,
+	/// PLL or PLLI2S VCO output out of `VCOMIN..=VCOMAX`, the PLL output on the STM32F1
+	VCO,
+	/// PLL48 clock (USB, SDIO, RNG) over 48 MHz
+	PLL48,
+	/// PLLM out of 2..=63, the predivider not 1 or 2 (always 2 for the HSI) on the STM32F1
+	PLLM,
+	/// PLLN out of 50..=432, PLLMUL out of 2..=16 on the STM32F1
+	PLLN,
+	/// PLLP not 2, 4, 6 or 8
+	PLLP,
+	/// PLLQ out of 2..=15
+	PLLQ,
+	/// PLLI2SM out of 2..=63
+	PLLI2SM,
+	/// PLLI2SN out of 50..=432
+	PLLI2SN,
+	/// PLLI2SR out of 2..=7
+	PLLI2SR,
+}
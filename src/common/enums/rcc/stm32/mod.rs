@@ -3,6 +3,7 @@
 reexport!{
 	private:
 		mod clocks;
+		mod errors;
 		mod interrupts;
 		mod mco;
 		mod peripherals;
@@ -0,0 +1,9 @@
+//! Serial Errors
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SerialError {
+	/// The stop bit was not found
+	Framing,
+	/// The start bit did not last
+	Noise,
+}
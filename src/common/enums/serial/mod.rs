@@ -0,0 +1,5 @@
+//! Serial enums
+
+mod errors;
+
+pub use self::errors::*;
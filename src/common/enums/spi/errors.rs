@@ -1,9 +1,18 @@
 //! SPI Errors
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SPIError {
+	/// The address is not an SPI peripheral
+	InvalidAddress,
+	/// The pin cannot carry the signal of the peripheral
+	InvalidPin,
+	/// The frequency cannot be divided from the clock of the bus
 	InvalidBus,
 	FreqHigherThanBus,
+	/// The peripheral must be disabled to change this setting
+	Enabled,
+	/// A transfer is ongoing
+	Busy,
 	SendErr,
 	ReadErr,
-}
\ No newline at end of file
+}
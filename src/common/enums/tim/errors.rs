@@ -0,0 +1,9 @@
+//! TIM Errors
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TIMError {
+	/// Both channels or inputs are the same
+	SameChannel,
+	/// The input mapping is not supported
+	UnsupportedMapping,
+}
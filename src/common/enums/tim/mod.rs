@@ -8,7 +8,9 @@ mod slavemode;
 mod trigger;
 mod prescaler;
 mod interrupts;
+mod errors;
 
+pub use self::errors::*;
 pub use self::interrupts::*;
 pub use self::prescaler::*;
 pub use self::channel::*;
@@ -11,6 +11,7 @@ pub enum TIMTrigger {
 	/// Internal3
 	ITR3,    
 	/// EdgeDetectorTI1
+	#[allow(non_camel_case_types)]
 	TI1F_ED, 
 	/// FilteredInput1
 	TI1FP1,  
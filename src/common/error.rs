@@ -0,0 +1,65 @@
+//! Crate wide error
+//! Every peripheral error converts into `Error`, so they can be mixed with `?`
+
+use super::enums::{ GPIOError, I2CError, KeypadError, OneWireError, RCCError, SerialError, SPIError, TIMError };
+
+/// Error of any peripheral
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+	Gpio(GPIOError),
+	I2c(I2CError),
+	Keypad(KeypadError),
+	OneWire(OneWireError),
+	Rcc(RCCError),
+	Serial(SerialError),
+	Spi(SPIError),
+	Tim(TIMError),
+}
+
+impl From<GPIOError> for Error {
+	fn from(e: GPIOError) -> Error {
+		Error::Gpio(e)
+	}
+}
+
+impl From<I2CError> for Error {
+	fn from(e: I2CError) -> Error {
+		Error::I2c(e)
+	}
+}
+
+impl From<KeypadError> for Error {
+	fn from(e: KeypadError) -> Error {
+		Error::Keypad(e)
+	}
+}
+
+impl From<OneWireError> for Error {
+	fn from(e: OneWireError) -> Error {
+		Error::OneWire(e)
+	}
+}
+
+impl From<RCCError> for Error {
+	fn from(e: RCCError) -> Error {
+		Error::Rcc(e)
+	}
+}
+
+impl From<SerialError> for Error {
+	fn from(e: SerialError) -> Error {
+		Error::Serial(e)
+	}
+}
+
+impl From<SPIError> for Error {
+	fn from(e: SPIError) -> Error {
+		Error::Spi(e)
+	}
+}
+
+impl From<TIMError> for Error {
+	fn from(e: TIMError) -> Error {
+		Error::Tim(e)
+	}
+}
@@ -1,150 +1,283 @@
 //! Abstraction over Frequency
-//! 
-//! 
+//!
+//! The frequency is stored exactly in Hz. The constructors and the
+//! arithmetic saturate instead of overflowing, use the `checked_*`
+//! methods to detect it.
 
 #[cfg(feature = "std")]
-use std::{cmp, ops, convert};
+use std::{ ops, convert };
 
 #[cfg(not(feature = "std"))]
-use core::{cmp, ops, convert};
-
-#[derive(Debug, Copy, Clone)]
-pub enum Frequency {
-	Hz(u32),
-	KHz(u32),
-	MHz(u32),
-	GHz(u32),
+use core::{ ops, convert };
+
+use super::time::Period;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Frequency {
+	hz: u32,
+}
+
+#[allow(non_snake_case)]
+impl Frequency {
+	/// Frequency of `a` Hz
+	pub const fn Hz(a: u32) -> Frequency {
+		Frequency { hz: a }
+	}
+
+	/// Frequency of `a` kHz, saturates at `u32::MAX` Hz
+	pub const fn KHz(a: u32) -> Frequency {
+		Frequency { hz: a.saturating_mul(1_000) }
+	}
+
+	/// Frequency of `a` MHz, saturates at `u32::MAX` Hz
+	pub const fn MHz(a: u32) -> Frequency {
+		Frequency { hz: a.saturating_mul(1_000_000) }
+	}
+
+	/// Frequency of `a` GHz, saturates at `u32::MAX` Hz
+	pub const fn GHz(a: u32) -> Frequency {
+		Frequency { hz: a.saturating_mul(1_000_000_000) }
+	}
 }
 
 impl Frequency {
+	/// Frequency of `a` kHz, `None` if it does not fit in `u32` Hz
+	pub const fn checked_khz(a: u32) -> Option<Frequency> {
+		match a.checked_mul(1_000) {
+			Some(hz) => Some( Frequency { hz } ),
+			None => None,
+		}
+	}
+
+	/// Frequency of `a` MHz, `None` if it does not fit in `u32` Hz
+	pub const fn checked_mhz(a: u32) -> Option<Frequency> {
+		match a.checked_mul(1_000_000) {
+			Some(hz) => Some( Frequency { hz } ),
+			None => None,
+		}
+	}
+
 	/// Returns the frequency value in Hz
-	pub fn hz(&self) -> u32 {
-		match *self {
-			Frequency::Hz(a)  => a,
-			Frequency::KHz(a) => a * 1_000,
-			Frequency::MHz(a) => a * 1_000_000,
-			Frequency::GHz(a) => a * 1_000_000_000,
+	pub const fn hz(&self) -> u32 {
+		self.hz
+	}
+
+	/// Returns the frequency value in kHz, rounded down
+	pub const fn khz(&self) -> u32 {
+		self.hz / 1_000
+	}
+
+	/// Returns the frequency value in MHz, rounded down
+	pub const fn mhz(&self) -> u32 {
+		self.hz / 1_000_000
+	}
+
+	/// Returns the frequency value in GHz, rounded down
+	pub const fn ghz(&self) -> u32 {
+		self.hz / 1_000_000_000
+	}
+
+	/// Returns the period of the frequency
+	pub const fn period(&self) -> Period {
+		Period::of(*self)
+	}
+
+	/// Returns `self * mul / div` without intermediate overflow
+	/// Used for the PLL and prescaler chains, saturates at `u32::MAX` Hz
+	pub const fn ratio(&self, mul: u32, div: u32) -> Frequency {
+		let hz = self.hz as u64 * mul as u64 / div as u64;
+
+		Frequency { hz: if hz > u32::MAX as u64 { u32::MAX } else { hz as u32 } }
+	}
+
+	/// Multiplication, `None` on overflow
+	pub const fn checked_mul(&self, n: u32) -> Option<Frequency> {
+		match self.hz.checked_mul(n) {
+			Some(hz) => Some( Frequency { hz } ),
+			None => None,
 		}
 	}
 
-	/// Returns the frequency value in kHz
-	pub fn khz(&self) -> u32 {
-		match *self {
-			Frequency::Hz(a)  => a / 1000,
-			Frequency::KHz(a) => a,
-			Frequency::MHz(a) => a * 1_000,
-			Frequency::GHz(a) => a * 1_000_000,
+	/// Division, `None` if `n` is 0
+	pub const fn checked_div(&self, n: u32) -> Option<Frequency> {
+		match self.hz.checked_div(n) {
+			Some(hz) => Some( Frequency { hz } ),
+			None => None,
 		}
 	}
 
-	/// Returns the frequency value in kHz
-	pub fn mhz(&self) -> u32 {
-		match *self {
-			Frequency::Hz(a)  => a / 1_000_000,
-			Frequency::KHz(a) => a / 1_000,
-			Frequency::MHz(a) => a,
-			Frequency::GHz(a) => a * 1_000,
+	/// Addition, `None` on overflow
+	pub const fn checked_add(&self, other: Frequency) -> Option<Frequency> {
+		match self.hz.checked_add(other.hz) {
+			Some(hz) => Some( Frequency { hz } ),
+			None => None,
 		}
 	}
 
-	/// Returns the frequency value in kHz
-	pub fn ghz(&self) -> u32 {
-		match *self {
-			Frequency::Hz(a)  => a / 1_000_000_000,
-			Frequency::KHz(a) => a / 1_000_000,
-			Frequency::MHz(a) => a / 1_000,
-			Frequency::GHz(a) => a,
+	/// Subtraction, `None` if `other` is higher
+	pub const fn checked_sub(&self, other: Frequency) -> Option<Frequency> {
+		match self.hz.checked_sub(other.hz) {
+			Some(hz) => Some( Frequency { hz } ),
+			None => None,
 		}
 	}
+
+	/// Multiplication, saturates at `u32::MAX` Hz
+	pub const fn saturating_mul(&self, n: u32) -> Frequency {
+		Frequency { hz: self.hz.saturating_mul(n) }
+	}
+
+	/// Addition, saturates at `u32::MAX` Hz
+	pub const fn saturating_add(&self, other: Frequency) -> Frequency {
+		Frequency { hz: self.hz.saturating_add(other.hz) }
+	}
+
+	/// Subtraction, saturates at 0 Hz
+	pub const fn saturating_sub(&self, other: Frequency) -> Frequency {
+		Frequency { hz: self.hz.saturating_sub(other.hz) }
+	}
 }
 
 impl convert::From<u32> for Frequency {
-	fn from(o: u32) -> Frequency {
-		match o {
-			0..=499_999 => Frequency::Hz(o),
-			500_000..=499_999_999 => Frequency::KHz(o / 1000),
-			500_000_000..=4_294_967_295 => Frequency::MHz(o / 1_000_000),
-		}
+	fn from(hz: u32) -> Frequency {
+		Frequency { hz }
 	}
 }
 
 impl convert::From<Frequency> for u32 {
 	fn from(f: Frequency) -> u32 {
-		f.hz()
+		f.hz
 	}
 }
 
-impl convert::From<&'static Frequency> for u32 {
+impl convert::From<&Frequency> for u32 {
 	fn from(f: &Frequency) -> u32 {
-		f.hz()
+		f.hz
 	}
 }
 
 impl PartialEq<u32> for Frequency {
 	fn eq(&self, other: &u32) -> bool {
-		self.hz() == *other
+		self.hz == *other
 	}
+}
 
-	fn ne(&self, other: &u32) -> bool {
-		self.hz() != *other
+/// Divides the frequency, e.g. by a prescaler
+/// Panics if `n` is 0
+impl ops::Div<u32> for Frequency {
+	type Output = Frequency;
+
+	fn div(self, n: u32) -> Frequency {
+		Frequency { hz: self.hz / n }
 	}
 }
 
+/// Ratio between two frequencies, rounded down
+/// Panics if `other` is 0 Hz
+impl ops::Div<Frequency> for Frequency {
+	type Output = u32;
 
-impl PartialEq<Frequency> for Frequency {
-	fn eq(&self, other: &Frequency) -> bool {
-		self.hz() == other.hz()
+	fn div(self, other: Frequency) -> u32 {
+		self.hz / other.hz
 	}
+}
+
+/// Multiplies the frequency, saturates at `u32::MAX` Hz
+impl ops::Mul<u32> for Frequency {
+	type Output = Frequency;
 
-	fn ne(&self, other: &Frequency) -> bool {
-		self.hz() != other.hz()
+	fn mul(self, n: u32) -> Frequency {
+		self.saturating_mul(n)
 	}
 }
 
-impl Eq for Frequency {}
+/// Saturates at `u32::MAX` Hz
+impl ops::Add<Frequency> for Frequency {
+	type Output = Frequency;
 
+	fn add(self, other: Frequency) -> Frequency {
+		self.saturating_add(other)
+	}
+}
 
-impl<T> ops::Div<T> for Frequency
-	where u32: From<T> {
+/// Saturates at 0 Hz
+impl ops::Sub<Frequency> for Frequency {
 	type Output = Frequency;
 
-	fn div(self, other: T) -> Frequency {
-		let new = self.hz() / u32::from(other);
-
-		match new {
-			0..=999 => Frequency::Hz(new),
-			1000..=999_999 => Frequency::KHz(new / 1000),
-			1000_000..=999_999_999 => Frequency::MHz(new / 1000_000),
-			_ => Frequency::GHz(new / 1000_000_000),
-		}
+	fn sub(self, other: Frequency) -> Frequency {
+		self.saturating_sub(other)
 	}
 }
 
 
-impl<T> ops::Mul<T> for Frequency
-	where u32: From<T> {
-	type Output = Frequency;
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
 
-	fn mul(self, other: T) -> Frequency {
-		let new = self.hz() * u32::from(other);
+	const MAX: u64 = u32::MAX as u64;
 
-		match new {
-			0..=999 => Frequency::Hz(new),
-			1000..=999_999 => Frequency::KHz(new / 1000),
-			1000_000..=999_999_999 => Frequency::MHz(new / 1000_000),
-			_ => Frequency::GHz(new / 1000_000_000),
-		}
-	}
-}
+	#[test]
+	fn constructors_saturate_at_the_edge() {
+		assert_eq!(Frequency::KHz(4_294_967), 4_294_967_000);
+		assert_eq!(Frequency::KHz(4_294_968), u32::MAX);
+		assert_eq!(Frequency::MHz(4_294), 4_294_000_000);
+		assert_eq!(Frequency::MHz(4_295), u32::MAX);
+		assert_eq!(Frequency::GHz(4), 4_000_000_000);
+		assert_eq!(Frequency::GHz(5), u32::MAX);
 
-impl cmp::Ord for Frequency {
-	fn cmp(&self, other: &Self) -> cmp::Ordering {
-		self.hz().cmp(&other.hz())
+		assert_eq!(Frequency::checked_khz(4_294_967), Some(Frequency::Hz(4_294_967_000)));
+		assert_eq!(Frequency::checked_khz(4_294_968), None);
+		assert_eq!(Frequency::checked_mhz(4_295), None);
 	}
-}
 
-impl cmp::PartialOrd for Frequency {
-	fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-		Some(self.cmp(other))
+	proptest! {
+		#[test]
+		fn saturating_constructors_match_the_checked_ones(a: u32) {
+			let max = Frequency::Hz(u32::MAX);
+
+			prop_assert_eq!(Frequency::KHz(a), Frequency::checked_khz(a).unwrap_or(max));
+			prop_assert_eq!(Frequency::MHz(a), Frequency::checked_mhz(a).unwrap_or(max));
+			prop_assert_eq!(Frequency::GHz(a).hz() as u64, (a as u64 * 1_000_000_000).min(MAX));
+		}
+
+		#[test]
+		fn units_round_down(hz: u32) {
+			let f = Frequency::Hz(hz);
+
+			for (value, unit) in [(f.khz(), 1_000), (f.mhz(), 1_000_000), (f.ghz(), 1_000_000_000)] {
+				let value = value as u64;
+				prop_assert!(value * unit <= hz as u64 && (hz as u64) < (value + 1) * unit);
+			}
+
+			prop_assert_eq!(u32::from(f), hz);
+			prop_assert_eq!(Frequency::from(hz), f);
+		}
+
+		#[test]
+		fn ratio_is_exact_until_it_saturates(hz: u32, mul: u32, div in 1..=u32::MAX) {
+			let exact = hz as u64 * mul as u64 / div as u64;
+			prop_assert_eq!(Frequency::Hz(hz).ratio(mul, div).hz() as u64, exact.min(MAX));
+		}
+
+		#[test]
+		fn operators_saturate(a: u32, b: u32, n: u32) {
+			let (fa, fb) = (Frequency::Hz(a), Frequency::Hz(b));
+
+			prop_assert_eq!((fa * n).hz() as u64, (a as u64 * n as u64).min(MAX));
+			prop_assert_eq!((fa + fb).hz() as u64, (a as u64 + b as u64).min(MAX));
+			prop_assert_eq!((fa - fb).hz(), a.saturating_sub(b));
+
+			prop_assert_eq!(fa.checked_mul(n).map(|f| f.hz()), a.checked_mul(n));
+			prop_assert_eq!(fa.checked_add(fb).map(|f| f.hz()), a.checked_add(b));
+			prop_assert_eq!(fa.checked_sub(fb).map(|f| f.hz()), a.checked_sub(b));
+			prop_assert_eq!(fa.checked_div(n).map(|f| f.hz()), a.checked_div(n));
+		}
+
+		#[test]
+		fn division_rounds_down(a: u32, b in 1..=u32::MAX) {
+			prop_assert_eq!((Frequency::Hz(a) / b).hz(), a / b);
+			prop_assert_eq!(Frequency::Hz(a) / Frequency::Hz(b), a / b);
+		}
 	}
-}
\ No newline at end of file
+}
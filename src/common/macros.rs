@@ -44,7 +44,7 @@ macro_rules! reexport {
 #[macro_export]
 macro_rules! mask {
 	( $x:expr ) => {
-		(1 << $x) - 1;
+		((1 << $x) - 1)
 	};
 }
 
@@ -112,6 +112,16 @@ macro_rules! impl_rwio {
 
 				self
 			}
+
+			/// Typed access to the register `S`
+			pub fn reg<S: $crate::common::register::RegisterSpec>(&mut self) -> $crate::common::register::Reg<'_, S> {
+				$crate::common::register::Reg::new(&mut self.block[S::INDEX])
+			}
+
+			/// Reads the register `S`
+			pub fn read_reg<S: $crate::common::register::RegisterSpec>(&self) -> $crate::common::register::R<S> {
+				$crate::common::register::R::new(self.block[S::INDEX].read())
+			}
 		}
 	};
 
@@ -145,6 +155,193 @@ macro_rules! impl_rwio {
 
 				self
 			}
+
+			/// Typed access to the register `S`
+			pub fn reg<S: $crate::common::register::RegisterSpec>(&mut self) -> $crate::common::register::Reg<'_, S> {
+				$crate::common::register::Reg::new(&mut self.block[S::INDEX])
+			}
+
+			/// Reads the register `S`
+			pub fn read_reg<S: $crate::common::register::RegisterSpec>(&self) -> $crate::common::register::R<S> {
+				$crate::common::register::R::new(self.block[S::INDEX].read())
+			}
+		}
+	};
+}
+
+/// Declares named registers with named fields
+///
+/// Each register becomes a type implementing `RegisterSpec`, and each field
+/// becomes a method of `R<REG>` (read) and/or `W<REG>` (write) depending on its access:
+///
+/// - `RO`: read only
+/// - `WO`: write only
+/// - `RW`: read and write
+/// - `W1C`: read, and cleared by writing a 1 (the writer method takes no value)
+/// - `W0C`: read, and cleared by writing a 0 (the writer method takes no value).
+///   `write` and `modify` write the other `W0C` fields as 1, so they are left as they are
+///
+/// Fields declared as `name[N]` are `N` consecutive fields of the same width,
+/// accessed with an index (e.g. one field per pin).
+///
+/// ```ignore
+/// registers! {
+/// 	/// Port mode register
+/// 	MODER @ 0, reset = 0x0000_0000 {
+/// 		/// Pin mode
+/// 		mode[16]: 0, 2, RW;
+/// 	}
+/// }
+///
+/// gpio.reg::<MODER>().modify(|_, w| w.mode(5, 0b01));
+/// let mode = gpio.read_reg::<MODER>().mode(5);
+/// ```
+#[macro_export]
+macro_rules! registers {
+	(@w1c W1C, $o:expr, $w:expr) => { ((((1u64 << $w) - 1) as u32) << $o) };
+	(@w1c W1C, $o:expr, $w:expr, $n:expr) => { ((((1u64 << ($w * $n)) - 1) as u32) << $o) };
+	(@w1c $a:ident, $o:expr, $w:expr $(, $n:expr)?) => { 0 };
+
+	(@w0c W0C, $o:expr, $w:expr) => { ((((1u64 << $w) - 1) as u32) << $o) };
+	(@w0c W0C, $o:expr, $w:expr, $n:expr) => { ((((1u64 << ($w * $n)) - 1) as u32) << $o) };
+	(@w0c $a:ident, $o:expr, $w:expr $(, $n:expr)?) => { 0 };
+
+	// Readers
+	(@read $reg:ident, $(#[$m:meta])* $f:ident, $o:expr, $w:expr) => {
+		impl $crate::common::register::R<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&self) -> u32 {
+				$crate::common::register::field_get(self.bits(), $o, $w)
+			}
+		}
+	};
+
+	(@read $reg:ident, $(#[$m:meta])* $f:ident [$n:expr], $o:expr, $w:expr) => {
+		impl $crate::common::register::R<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&self, n: usize) -> u32 {
+				debug_assert!(n < $n);
+				$crate::common::register::field_get(self.bits(), $o + n * $w, $w)
+			}
+		}
+	};
+
+	// Writers
+	(@write $reg:ident, $(#[$m:meta])* $f:ident, $o:expr, $w:expr) => {
+		impl $crate::common::register::W<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&mut self, value: u32) -> &mut Self {
+				self.set_bits($o, $w, value)
+			}
+		}
+	};
+
+	(@write $reg:ident, $(#[$m:meta])* $f:ident [$n:expr], $o:expr, $w:expr) => {
+		impl $crate::common::register::W<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&mut self, n: usize, value: u32) -> &mut Self {
+				debug_assert!(n < $n);
+				self.set_bits($o + n * $w, $w, value)
+			}
+		}
+	};
+
+	// Write one to clear
+	(@clear $reg:ident, $(#[$m:meta])* $f:ident, $o:expr, $w:expr) => {
+		impl $crate::common::register::W<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&mut self) -> &mut Self {
+				self.set_bits($o, $w, !0)
+			}
 		}
 	};
-}
\ No newline at end of file
+
+	(@clear $reg:ident, $(#[$m:meta])* $f:ident [$n:expr], $o:expr, $w:expr) => {
+		impl $crate::common::register::W<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&mut self, n: usize) -> &mut Self {
+				debug_assert!(n < $n);
+				self.set_bits($o + n * $w, $w, !0)
+			}
+		}
+	};
+
+	// Write zero to clear
+	(@clear0 $reg:ident, $(#[$m:meta])* $f:ident, $o:expr, $w:expr) => {
+		impl $crate::common::register::W<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&mut self) -> &mut Self {
+				self.set_bits($o, $w, 0)
+			}
+		}
+	};
+
+	(@clear0 $reg:ident, $(#[$m:meta])* $f:ident [$n:expr], $o:expr, $w:expr) => {
+		impl $crate::common::register::W<$reg> {
+			$(#[$m])*
+			#[inline]
+			pub fn $f(&mut self, n: usize) -> &mut Self {
+				debug_assert!(n < $n);
+				self.set_bits($o + n * $w, $w, 0)
+			}
+		}
+	};
+
+	// Access dispatch
+	(@field $reg:ident, $(#[$m:meta])* $f:ident $([$n:expr])?, $o:expr, $w:expr, RO) => {
+		registers!(@read $reg, $(#[$m])* $f $([$n])?, $o, $w);
+	};
+
+	(@field $reg:ident, $(#[$m:meta])* $f:ident $([$n:expr])?, $o:expr, $w:expr, WO) => {
+		registers!(@write $reg, $(#[$m])* $f $([$n])?, $o, $w);
+	};
+
+	(@field $reg:ident, $(#[$m:meta])* $f:ident $([$n:expr])?, $o:expr, $w:expr, RW) => {
+		registers!(@read $reg, $(#[$m])* $f $([$n])?, $o, $w);
+		registers!(@write $reg, $(#[$m])* $f $([$n])?, $o, $w);
+	};
+
+	(@field $reg:ident, $(#[$m:meta])* $f:ident $([$n:expr])?, $o:expr, $w:expr, W1C) => {
+		registers!(@read $reg, $(#[$m])* $f $([$n])?, $o, $w);
+		registers!(@clear $reg, $(#[$m])* $f $([$n])?, $o, $w);
+	};
+
+	(@field $reg:ident, $(#[$m:meta])* $f:ident $([$n:expr])?, $o:expr, $w:expr, W0C) => {
+		registers!(@read $reg, $(#[$m])* $f $([$n])?, $o, $w);
+		registers!(@clear0 $reg, $(#[$m])* $f $([$n])?, $o, $w);
+	};
+
+	($(
+		$(#[$rmeta:meta])*
+		$reg:ident @ $index:expr, reset = $reset:literal {
+			$(
+				$(#[$fmeta:meta])*
+				$field:ident $([$n:expr])? : $offset:expr, $width:expr, $access:ident;
+			)*
+		}
+	)*) => {
+		$(
+			$(#[$rmeta])*
+			#[derive(Debug, Copy, Clone)]
+			pub struct $reg;
+
+			impl $crate::common::register::RegisterSpec for $reg {
+				const INDEX: usize = $index;
+				const RESET: u32 = $reset;
+				const W1C: u32 = 0 $( | registers!(@w1c $access, $offset, $width $(, $n)?) )*;
+				const W0C: u32 = 0 $( | registers!(@w0c $access, $offset, $width $(, $n)?) )*;
+			}
+
+			$(
+				registers!(@field $reg, $(#[$fmeta])* $field $([$n])?, $offset, $width, $access);
+			)*
+		)*
+	};
+}
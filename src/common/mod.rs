@@ -4,15 +4,19 @@
 pub mod macros;
 
 pub mod frequency;
+pub mod time;
+pub mod monotonic;
 pub mod register;
 pub mod config;
 
 
 pub mod asm;
 
+mod clockspeed;
+
 reexport!{
 	private:
-		mod clockspeed;
+		mod error;
 		mod timestamp;
 	public:
 		mod enums;
@@ -20,11 +24,16 @@ reexport!{
 }
 
 
+#[allow(non_snake_case)]
 pub mod State {
 	pub const ON: bool = true;
 	pub const OFF: bool = false;
 }
 
 pub use self::frequency::Frequency;
+pub use self::time::{ Duration, Period };
+pub use self::monotonic::{ Instant, Monotonic };
 pub use self::register::{ Register, VolatileStruct };
-pub use self::enums::*;
+
+/// Clock limits of the device
+pub use self::clockspeed::clockspeed as ClockSpeeds;
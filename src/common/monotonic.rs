@@ -0,0 +1,299 @@
+//! Monotonic clock
+//!
+//! `Instant` is a 64-bit tick count of the clock started with `Monotonic`.
+//! The clock runs on the DWT cycle counter or, on parts without it (ARMv6-M),
+//! on SysTick. Both run at the core clock (HCLK) given by the `Clocks` of `Rcc::freeze`.
+//!
+//! ```ignore
+//! let clocks = rcc.freeze(cfg, p.flash, p.pwr)?;
+//! Monotonic::dwt(p.dwt, p.dcb, &clocks);
+//!
+//! let deadline = Instant::now() + Duration::from_millis(10);
+//! while !deadline.has_passed() {}
+//! ```
+//!
+//! The DWT counter wraps every 2^32 cycles (about 25 s at 168 MHz) and it is
+//! extended to 64 bits every time it is read, so `Instant::now` must be called
+//! at least once per wrap. SysTick wraps every 2^24 cycles, so the wraps are
+//! counted by `Monotonic::on_systick`, which must be called from the SysTick handler.
+//! The SysTick clock also keeps the SCB, to read a wrap still pending
+//! (ICSR.PENDSTSET) while the counter is read.
+
+#[cfg(feature = "std")]
+use std::{ cell::RefCell, ops };
+
+#[cfg(not(feature = "std"))]
+use core::{ cell::RefCell, ops };
+
+use crate::common::{ Clocks, Duration, Frequency };
+use crate::interrupt::{ self, Mutex };
+use crate::peripherals::core::scb::Scb;
+use crate::peripherals::core::syst::{ SysTick, SysTClock };
+
+#[cfg(not(armv6m))]
+use crate::peripherals::core::{ dcb::Dcb, dwt::Dwt };
+
+/// SysTick reload, the counter wraps every 2^24 ticks
+const SYST_RELOAD: u32 = 0x00FF_FFFF;
+
+/// Counter the clock is running on
+enum Counter {
+	#[cfg(not(armv6m))]
+	Dwt(&'static mut Dwt),
+	/// SysTick and the SCB to read its pending wrap from
+	SysTick(&'static mut SysTick, &'static mut Scb),
+}
+
+struct State {
+	counter: Option<Counter>,
+	freq: Frequency,
+	/// Last counter value (DWT) or number of wraps (SysTick)
+	last: u32,
+	/// Upper 32 bits of the tick count
+	high: u32,
+}
+
+static STATE: Mutex<RefCell<State>> = Mutex::new( RefCell::new( State {
+	counter: None,
+	freq: Frequency::Hz(0),
+	last: 0,
+	high: 0,
+} ) );
+
+/// Monotonic clock source
+pub struct Monotonic;
+
+impl Monotonic {
+	/// Starts the clock on the DWT cycle counter
+	/// Enables the trace and the cycle counter, the counter keeps its value
+	#[cfg(not(armv6m))]
+	pub fn dwt(dwt: &'static mut Dwt, dcb: &mut Dcb, clocks: &Clocks) {
+		dcb.enable_trace();
+		dwt.enable_cycle_counter();
+
+		let last = dwt.get_cycle_count();
+
+		Monotonic::start(Counter::Dwt(dwt), clocks.ahb1f, last);
+	}
+
+	/// Starts the clock on SysTick, running at the core clock
+	/// Enables the SysTick interrupt, its handler must call `Monotonic::on_systick`
+	/// The SCB is kept to catch a wrap pending while the clock is read
+	pub fn systick(syst: &'static mut SysTick, scb: &'static mut Scb, clocks: &Clocks) {
+		syst.counter_state(false)
+			.set_clock_source(SysTClock::Core)
+			.set_reload(SYST_RELOAD)
+			.clear_current()
+			.interrupt_state(true)
+			.counter_state(true);
+
+		Monotonic::start(Counter::SysTick(syst, scb), clocks.ahb1f, 0);
+	}
+
+	fn start(counter: Counter, freq: Frequency, last: u32) {
+		interrupt::free(|cs| {
+			*STATE.borrow(cs).borrow_mut() = State { counter: Some(counter), freq, last, high: 0 };
+		});
+	}
+
+	/// Counts a SysTick wrap, call it from the SysTick handler
+	/// Does nothing if the clock is not running on SysTick
+	pub fn on_systick() {
+		interrupt::free(|cs| {
+			let mut state = STATE.borrow(cs).borrow_mut();
+
+			if let Some(Counter::SysTick(..)) = state.counter {
+				let (wraps, carry) = state.last.overflowing_add(1);
+
+				state.last = wraps;
+				if carry { state.high += 1; }
+			}
+		});
+	}
+
+	/// Returns the frequency of the clock ticks, 0 Hz if the clock is not running
+	pub fn frequency() -> Frequency {
+		interrupt::free(|cs| STATE.borrow(cs).borrow().freq)
+	}
+
+	/// Returns `true` if the clock has been started
+	pub fn is_running() -> bool {
+		interrupt::free(|cs| STATE.borrow(cs).borrow().counter.is_some())
+	}
+
+	/// Returns the current 64-bit tick count
+	fn ticks() -> u64 {
+		interrupt::free(|cs| {
+			let mut state = STATE.borrow(cs).borrow_mut();
+			let State { counter, last, high, .. } = &mut *state;
+
+			match counter {
+				#[cfg(not(armv6m))]
+				Some(Counter::Dwt(dwt)) => {
+					let now = dwt.get_cycle_count();
+
+					if now < *last { *high += 1; }
+					*last = now;
+
+					((*high as u64) << 32) | now as u64
+				},
+
+				Some(Counter::SysTick(syst, scb)) => {
+					let mut wraps = ((*high as u64) << 32) | *last as u64;
+					let mut current = syst.get_current();
+
+					// A wrap that happened in the critical section is still pending,
+					// count it here and read again in case it happened after the read
+					if scb.is_pendst_pending() {
+						current = syst.get_current();
+						wraps += 1;
+					}
+
+					(wraps << 24) | (SYST_RELOAD - current) as u64
+				},
+
+				None => 0,
+			}
+		})
+	}
+}
+
+
+/// Point in time of the monotonic clock
+/// Only comparable with instants of the same clock run
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant {
+	ticks: u64,
+}
+
+impl Instant {
+	/// Returns the current instant
+	/// Always the same instant if the clock has not been started
+	pub fn now() -> Instant {
+		Instant { ticks: Monotonic::ticks() }
+	}
+
+	/// Instant `ticks` clock ticks after the start of the clock
+	pub const fn from_ticks(ticks: u64) -> Instant {
+		Instant { ticks }
+	}
+
+	/// Returns the clock ticks since the start of the clock
+	pub const fn ticks(&self) -> u64 {
+		self.ticks
+	}
+
+	/// Returns the time elapsed since this instant
+	pub fn elapsed(&self) -> Duration {
+		Instant::now().duration_since(*self)
+	}
+
+	/// Returns the time elapsed from `earlier` to this instant
+	/// Zero if `earlier` is later than this instant
+	pub fn duration_since(&self, earlier: Instant) -> Duration {
+		self.checked_duration_since(earlier).unwrap_or(Duration::ZERO)
+	}
+
+	/// Returns the time elapsed from `earlier` to this instant
+	/// `None` if `earlier` is later than this instant or the clock is not running
+	pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+		let freq = Monotonic::frequency();
+
+		if freq.hz() == 0 { return None; }
+
+		self.ticks.checked_sub(earlier.ticks).map(|t| Duration::from_cycles(t, freq))
+	}
+
+	/// Instant `d` after this one, `None` on overflow or if the clock is not running
+	pub fn checked_add(&self, d: Duration) -> Option<Instant> {
+		let freq = Monotonic::frequency();
+
+		if freq.hz() == 0 { return None; }
+
+		self.ticks.checked_add( d.to_cycles(freq) ).map(|ticks| Instant { ticks })
+	}
+
+	/// Instant `d` before this one, `None` if it is before the start of the clock
+	pub fn checked_sub(&self, d: Duration) -> Option<Instant> {
+		let freq = Monotonic::frequency();
+
+		if freq.hz() == 0 { return None; }
+
+		self.ticks.checked_sub( d.to_cycles(freq) ).map(|ticks| Instant { ticks })
+	}
+
+	/// Returns `true` if this instant, used as a deadline, has been reached
+	pub fn has_passed(&self) -> bool {
+		Instant::now() >= *self
+	}
+
+	/// Returns the time left until this deadline, zero if it has passed
+	pub fn remaining(&self) -> Duration {
+		self.duration_since( Instant::now() )
+	}
+}
+
+/// Saturates at the last representable instant
+impl ops::Add<Duration> for Instant {
+	type Output = Instant;
+
+	fn add(self, d: Duration) -> Instant {
+		self.checked_add(d).unwrap_or( Instant { ticks: u64::MAX } )
+	}
+}
+
+/// Saturates at the start of the clock
+impl ops::Sub<Duration> for Instant {
+	type Output = Instant;
+
+	fn sub(self, d: Duration) -> Instant {
+		self.checked_sub(d).unwrap_or( Instant { ticks: 0 } )
+	}
+}
+
+/// Zero if `other` is later
+impl ops::Sub<Instant> for Instant {
+	type Output = Duration;
+
+	fn sub(self, other: Instant) -> Duration {
+		self.duration_since(other)
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::peripherals::core::{ scb::{ self, ICSR }, syst::{ self, CVR } };
+
+	/// PENDSTSET in ICSR
+	const PENDST: u32 = 1 << 26;
+
+	// The clock is global, so a single test drives it
+	#[test]
+	fn systick_counts_the_wraps() {
+		let syst = SimBlock::<{ syst::SIZE }>::new();
+		let scb = SimBlock::<{ scb::SIZE }>::new();
+		let clocks = Clocks { ahb1f: Frequency::MHz(16), ..Clocks::default() };
+
+		Monotonic::systick(unsafe { syst.view() }, unsafe { scb.view() }, &clocks);
+		assert!(Monotonic::is_running());
+
+		syst.poke(CVR::INDEX, SYST_RELOAD - 100);
+		assert_eq!(Instant::now().ticks(), 100);
+
+		Monotonic::on_systick();
+		assert_eq!(Instant::now().ticks(), (1 << 24) + 100);
+
+		// Wrapped in the critical section, before the handler ran
+		syst.poke(CVR::INDEX, SYST_RELOAD - 3);
+		scb.poke(ICSR::INDEX, PENDST);
+		assert_eq!(Instant::now().ticks(), (2 << 24) + 3);
+		assert_eq!(scb.writes(ICSR::INDEX), []);
+
+		let start = Instant::from_ticks(0);
+		assert_eq!(Instant::from_ticks(16) - start, Duration::from_micros(1));
+		assert_eq!(start + Duration::from_nanos(1), Instant::from_ticks(1));
+	}
+}
@@ -0,0 +1,195 @@
+//! Abstraction over a Read/Write register
+//!
+//! With the `sim` feature every access goes through the simulated backend in
+//! [`sim`](sim/index.html), which allows the drivers to run on the host
+
+#[cfg(feature = "std")]
+use std::{ ptr, ops, cell::UnsafeCell, marker::PhantomData };
+
+#[cfg(not(feature = "std"))]
+use core::{ ptr, ops, cell::UnsafeCell, marker::PhantomData };
+
+#[cfg(feature = "sim")]
+pub mod sim;
+
+
+#[repr(transparent)]
+pub struct Register<T>(UnsafeCell<T>);
+
+impl<T> Register<T> {
+	/// Read the register value
+	pub fn read(&self) -> T {
+		#[cfg(feature = "sim")]
+		sim::before_read(self.0.get() as usize);
+
+		unsafe {
+			ptr::read_volatile(self.0.get())
+		}
+	}
+
+	/// Writes a value to the register
+	pub fn write(&mut self, data: T) {
+		#[cfg(feature = "sim")]
+		let old = sim::peek(self.0.get() as usize);
+
+		unsafe {
+			ptr::write_volatile(self.0.get(), data)
+		}
+
+		#[cfg(feature = "sim")]
+		sim::after_write(self.0.get() as usize, old);
+	}
+}
+
+impl<T> ops::BitOrAssign<T> for Register<T>
+	where T: ops::BitOr<T, Output=T>
+{
+	fn bitor_assign(&mut self, other: T) {
+		let new = self.read() | other;
+		self.write(new);
+	}
+}
+
+impl<T> ops::BitAndAssign<T> for Register<T>
+	where T: ops::BitAnd<T, Output=T>
+{
+	fn bitand_assign(&mut self, other: T) {
+		let new = self.read() & other;
+		self.write(new);
+	}
+}
+
+pub trait VolatileStruct: Sized {
+	/// Gets the peripheral at the given address
+	///
+	/// # Safety
+	///
+	/// `addr` must point to a register block of the peripheral, not aliased by any other reference
+	unsafe fn from_addr(addr: u32) -> &'static mut Self {
+		Self::from_ptr(addr as *mut Self)
+	}
+
+	/// Gets the peripheral at the given pointer
+	///
+	/// # Safety
+	///
+	/// Same as `from_addr`
+	unsafe fn from_ptr(addr: *mut Self) -> &'static mut Self {
+		&mut *addr
+	}
+}
+
+
+/// Definition of a named register, generated by `registers!`
+pub trait RegisterSpec {
+	/// Index of the register in the peripheral block
+	const INDEX: usize;
+	/// Value of the register after reset
+	const RESET: u32;
+	/// Bits that are cleared by writing a 1 (W1C)
+	/// These are never written back by `modify`
+	const W1C: u32;
+	/// Bits that are cleared by writing a 0 (W0C)
+	/// These are always written as 1 unless cleared on purpose
+	const W0C: u32;
+}
+
+/// Reads `width` bits at `offset`
+#[inline]
+pub const fn field_get(bits: u32, offset: usize, width: usize) -> u32 {
+	(bits >> offset) & ((1u64 << width) - 1) as u32
+}
+
+/// Writes `value` into the `width` bits at `offset`
+#[inline]
+pub const fn field_set(bits: u32, offset: usize, width: usize, value: u32) -> u32 {
+	let mask = ((1u64 << width) - 1) as u32;
+	(bits & !(mask << offset)) | ((value & mask) << offset)
+}
+
+/// Value read from the register `S`
+pub struct R<S> {
+	bits: u32,
+	_s: PhantomData<S>,
+}
+
+impl<S> R<S> {
+	pub fn new(bits: u32) -> Self {
+		R { bits, _s: PhantomData }
+	}
+
+	/// Raw value of the register
+	#[inline]
+	pub fn bits(&self) -> u32 {
+		self.bits
+	}
+}
+
+/// Value to be written to the register `S`
+pub struct W<S> {
+	bits: u32,
+	_s: PhantomData<S>,
+}
+
+impl<S> W<S> {
+	pub fn new(bits: u32) -> Self {
+		W { bits, _s: PhantomData }
+	}
+
+	/// Raw value to be written
+	#[inline]
+	pub fn bits(&self) -> u32 {
+		self.bits
+	}
+
+	/// Writes the raw value of a field
+	/// Prefer the named field methods, this does not check the field access
+	#[inline]
+	pub fn set_bits(&mut self, offset: usize, width: usize, value: u32) -> &mut Self {
+		self.bits = field_set(self.bits, offset, width, value);
+		self
+	}
+}
+
+/// Typed access to the register `S` of a peripheral
+pub struct Reg<'a, S> {
+	reg: &'a mut Register<u32>,
+	_s: PhantomData<S>,
+}
+
+impl<'a, S: RegisterSpec> Reg<'a, S> {
+	pub fn new(reg: &'a mut Register<u32>) -> Self {
+		Reg { reg, _s: PhantomData }
+	}
+
+	/// Reads the register
+	#[inline]
+	pub fn read(&self) -> R<S> {
+		R::new(self.reg.read())
+	}
+
+	/// Writes the register starting from its reset value, with the W0C bits set
+	#[inline]
+	pub fn write<F>(&mut self, f: F) where F: FnOnce(&mut W<S>) -> &mut W<S> {
+		let mut w = W::new(S::RESET | S::W0C);
+		f(&mut w);
+		self.reg.write(w.bits);
+	}
+
+	/// Read-modify-write of the register
+	/// W1C bits are not written back and W0C bits are written as 1,
+	/// so pending flags are not cleared by accident
+	#[inline]
+	pub fn modify<F>(&mut self, f: F) where F: for<'w> FnOnce(&R<S>, &'w mut W<S>) -> &'w mut W<S> {
+		let r = self.read();
+		let mut w = W::new((r.bits & !S::W1C) | S::W0C);
+		f(&r, &mut w);
+		self.reg.write(w.bits);
+	}
+
+	/// Writes the reset value to the register
+	#[inline]
+	pub fn reset(&mut self) {
+		self.reg.write(S::RESET);
+	}
+}
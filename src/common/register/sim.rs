@@ -0,0 +1,313 @@
+//! Simulated register backend
+//!
+//! Allows constructing the peripherals over an in-memory block on the host.
+//! Every read and write done through a `Register` is recorded, and the
+//! hardware side of a register can be scripted with hooks (e.g. a flag that
+//! becomes set after N polls, or a bit that is cleared by writing a 1).
+//!
+//! The simulated registers are 32-bit wide. All state is kept per thread, so
+//! tests running in parallel do not see each other's accesses.
+//!
+//! ```ignore
+//! let block = sim::SimBlock::<{ rcc::SIZE }>::new();
+//! // PLLRDY is raised after the driver polls CR 3 times
+//! block.set_after_reads(0, 1 << 25, 3);
+//!
+//! let mut rcc = rcc::Rcc::from_block( unsafe { block.regs() } );
+//! ```
+
+use std::boxed::Box;
+use std::cell::RefCell;
+use std::vec::Vec;
+use std::{ mem, ptr };
+
+use super::{ Register, VolatileStruct };
+
+/// Type of register access
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccessKind {
+	Read,
+	Write,
+}
+
+/// A recorded register access
+/// For reads `value` is the value returned, for writes it's the value stored
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Access {
+	pub kind: AccessKind,
+	pub addr: usize,
+	pub value: u32,
+}
+
+/// Hardware behaviour of a register
+enum Hook {
+	/// Called before a read with the current value, returns the new value
+	Read(Box<dyn FnMut(u32) -> u32>),
+	/// Called after a write with (old value, written value), returns the new value
+	Write(Box<dyn FnMut(u32, u32) -> u32>),
+}
+
+struct State {
+	log: Vec<Access>,
+	hooks: Vec<(usize, Hook)>,
+	/// (block base, hardware address it stands for)
+	aliases: Vec<(usize, usize)>,
+}
+
+thread_local! {
+	static STATE: RefCell<State> = const { RefCell::new( State { log: Vec::new(), hooks: Vec::new(), aliases: Vec::new() } ) };
+}
+
+/// Reads the raw value at `addr` without going through the hooks
+pub fn peek(addr: usize) -> u32 {
+	unsafe { ptr::read_volatile(addr as *const u32) }
+}
+
+/// Writes the raw value at `addr` without going through the hooks
+pub fn poke(addr: usize, value: u32) {
+	unsafe { ptr::write_volatile(addr as *mut u32, value) }
+}
+
+/// Applies the read hooks of `addr` and records the read
+pub(crate) fn before_read(addr: usize) {
+	STATE.with(|s| {
+		let mut s = s.borrow_mut();
+
+		for (a, hook) in s.hooks.iter_mut() {
+			if *a != addr { continue; }
+
+			if let Hook::Read(f) = hook {
+				poke(addr, f(peek(addr)));
+			}
+		}
+
+		s.log.push( Access { kind: AccessKind::Read, addr, value: peek(addr) } );
+	})
+}
+
+/// Records the write and applies the write hooks of `addr`
+pub(crate) fn after_write(addr: usize, old: u32) {
+	STATE.with(|s| {
+		let mut s = s.borrow_mut();
+
+		let written = peek(addr);
+		s.log.push( Access { kind: AccessKind::Write, addr, value: written } );
+
+		for (a, hook) in s.hooks.iter_mut() {
+			if *a != addr { continue; }
+
+			if let Hook::Write(f) = hook {
+				poke(addr, f(old, peek(addr)));
+			}
+		}
+	})
+}
+
+/// Clears the access log, the hooks and the aliases of the current thread
+pub fn reset() {
+	STATE.with(|s| {
+		let mut s = s.borrow_mut();
+		s.log.clear();
+		s.hooks.clear();
+		s.aliases.clear();
+	})
+}
+
+/// Returns the hardware address the block at `base` stands for, see `SimBlock::stand_for`
+/// Addresses that are not simulated blocks are returned as they are
+pub fn hardware_address(base: usize) -> usize {
+	STATE.with(|s| {
+		s.borrow().aliases.iter()
+			.find(|(b, _)| *b == base)
+			.map_or(base, |&(_, address)| address)
+	})
+}
+
+/// Returns the base of the block standing for the hardware `address`, the last one
+/// if several do. Used by the drivers that reach a peripheral they do not own
+/// Addresses that no block stands for are returned as they are
+pub fn simulated_address(address: usize) -> usize {
+	STATE.with(|s| {
+		s.borrow().aliases.iter()
+			.rev()
+			.find(|(_, a)| *a == address)
+			.map_or(address, |&(base, _)| base)
+	})
+}
+
+/// Returns all the accesses recorded in the current thread
+pub fn log() -> Vec<Access> {
+	STATE.with(|s| s.borrow().log.clone())
+}
+
+/// Clears the access log of the current thread, keeping the hooks
+pub fn clear_log() {
+	STATE.with(|s| s.borrow_mut().log.clear())
+}
+
+/// Adds a hook called before every read of `addr`
+/// The hook receives the current value and returns the value the register will hold
+pub fn on_read<F>(addr: usize, f: F) where F: FnMut(u32) -> u32 + 'static {
+	STATE.with(|s| s.borrow_mut().hooks.push( (addr, Hook::Read(Box::new(f))) ))
+}
+
+/// Adds a hook called after every write to `addr`
+/// The hook receives the old and the written value and returns the value the register will hold
+pub fn on_write<F>(addr: usize, f: F) where F: FnMut(u32, u32) -> u32 + 'static {
+	STATE.with(|s| s.borrow_mut().hooks.push( (addr, Hook::Write(Box::new(f))) ))
+}
+
+
+/// Handle to a simulated block of `N` 32-bit registers
+/// The memory is leaked, so the block lives as long as the peripherals built over it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SimBlock<const N: usize> {
+	base: usize,
+}
+
+impl<const N: usize> Default for SimBlock<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<const N: usize> SimBlock<N> {
+	/// Creates a new block with all registers set to 0
+	pub fn new() -> Self {
+		Self::with_reset([0; N])
+	}
+
+	/// Creates a new block with the given reset values
+	pub fn with_reset(reset: [u32; N]) -> Self {
+		let block: &'static mut [u32; N] = Box::leak( Box::new(reset) );
+
+		SimBlock {
+			base: block.as_mut_ptr() as usize,
+		}
+	}
+
+	/// Returns the registers of the block, to build a peripheral over it
+	///
+	/// # Safety
+	///
+	/// The returned reference aliases every other reference obtained from this block
+	pub unsafe fn regs(&self) -> &'static mut [Register<u32>; N] {
+		&mut *(self.base as *mut _)
+	}
+
+	/// Returns the block as a `VolatileStruct` peripheral
+	///
+	/// # Safety
+	///
+	/// The returned reference aliases every other reference obtained from this block
+	pub unsafe fn view<T: VolatileStruct>(&self) -> &'static mut T {
+		assert!(mem::size_of::<T>() <= N * 4, "Peripheral does not fit in the simulated block");
+		T::from_ptr(self.base as *mut T)
+	}
+
+	/// Makes the block stand for the peripheral at `address`
+	/// Needed by the drivers that tell the peripherals apart by their address (e.g. the GPIO port of a pin)
+	pub fn stand_for(&self, address: u32) -> &Self {
+		STATE.with(|s| s.borrow_mut().aliases.push( (self.base, address as usize) ));
+		self
+	}
+
+	/// Base address of the block
+	pub fn base(&self) -> usize {
+		self.base
+	}
+
+	/// Address of the register `i`
+	pub fn addr(&self, i: usize) -> usize {
+		assert!(i < N, "Register index out of the simulated block");
+		self.base + i * 4
+	}
+
+	/// Reads register `i` without going through the hooks or the log
+	pub fn peek(&self, i: usize) -> u32 {
+		peek(self.addr(i))
+	}
+
+	/// Writes register `i` without going through the hooks or the log
+	pub fn poke(&self, i: usize, value: u32) -> &Self {
+		poke(self.addr(i), value);
+		self
+	}
+
+	/// Returns the accesses to this block as (kind, register index, value)
+	pub fn accesses(&self) -> Vec<(AccessKind, usize, u32)> {
+		log().into_iter()
+			.filter(|a| a.addr >= self.base && a.addr < self.base + N * 4)
+			.map(|a| (a.kind, (a.addr - self.base) / 4, a.value))
+			.collect()
+	}
+
+	/// Returns the number of reads of register `i`
+	pub fn reads(&self, i: usize) -> usize {
+		let addr = self.addr(i);
+		log().iter().filter(|a| a.kind == AccessKind::Read && a.addr == addr).count()
+	}
+
+	/// Returns all values written to register `i`, in order
+	pub fn writes(&self, i: usize) -> Vec<u32> {
+		let addr = self.addr(i);
+		log().iter().filter(|a| a.kind == AccessKind::Write && a.addr == addr).map(|a| a.value).collect()
+	}
+
+	/// Adds a read hook to register `i`
+	pub fn on_read<F>(&self, i: usize, f: F) -> &Self where F: FnMut(u32) -> u32 + 'static {
+		on_read(self.addr(i), f);
+		self
+	}
+
+	/// Adds a write hook to register `i`
+	pub fn on_write<F>(&self, i: usize, f: F) -> &Self where F: FnMut(u32, u32) -> u32 + 'static {
+		on_write(self.addr(i), f);
+		self
+	}
+
+	/// Sets the bits in `mask` of register `i` once it has been read `n` times
+	pub fn set_after_reads(&self, i: usize, mask: u32, n: usize) -> &Self {
+		let mut count = 0;
+
+		self.on_read(i, move |value| {
+			count += 1;
+			if count >= n { value | mask }
+			else { value }
+		})
+	}
+
+	/// Bits in `mask` of register `i` are cleared by writing a 1 and unaffected by writing a 0
+	pub fn write_one_to_clear(&self, i: usize, mask: u32) -> &Self {
+		self.on_write(i, move |old, new| (old & !(new & mask)) | (new & !mask))
+	}
+
+	/// Bits in `mask` of register `i` are cleared by writing a 0 and unaffected by writing a 1
+	pub fn write_zero_to_clear(&self, i: usize, mask: u32) -> &Self {
+		self.on_write(i, move |old, new| (old & new & mask) | (new & !mask))
+	}
+
+	/// Bits in `mask` of register `i` are read only, writes do not change them
+	pub fn read_only(&self, i: usize, mask: u32) -> &Self {
+		self.on_write(i, move |old, new| (old & mask) | (new & !mask))
+	}
+
+	/// When a write leaves all bits of `trigger` set in register `i`,
+	/// the bits in `flag` of register `target` are set. They are cleared when `trigger` is cleared.
+	/// Models the ON/RDY bit pairs (e.g. PLLON and PLLRDY)
+	pub fn follow(&self, i: usize, trigger: u32, target: usize, flag: u32) -> &Self {
+		let target = self.addr(target);
+		let same = target == self.addr(i);
+
+		self.on_write(i, move |_, new| {
+			let flagged = if new & trigger == trigger { peek(target) | flag }
+				else { peek(target) & !flag };
+
+			if same { (new & !flag) | (flagged & flag) }
+			else {
+				poke(target, flagged);
+				new
+			}
+		})
+	}
+}
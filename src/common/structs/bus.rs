@@ -0,0 +1,229 @@
+//! Parallel bus over the pins of a port
+//!
+//! The `N` pins are written at once with a single BSRR write, so an interrupt
+//! touching other pins of the port never races with the bus.
+//!
+//! ```ignore
+//! // HD44780 in 4-bit mode on PD4..PD7
+//! let mut bus = ParallelBus::new([d4, d5, d6, d7]).map_err(|(e, _)| e)?;
+//! bus.write(0b0011);
+//!
+//! // Bit `i` of the word drives the `i`th pin
+//! // 0b0011 sets PD4 and PD5 and resets PD6 and PD7
+//! assert_eq!(bus.bsrr(0b0011), 0x00C0_0030);
+//! ```
+
+#[cfg(feature = "std")]
+use std::array;
+
+#[cfg(not(feature = "std"))]
+use core::array;
+
+use crate::common::GPIOError;
+use crate::common::register::{ Reg, RegisterSpec };
+use crate::interrupt;
+use crate::peripherals::extended::gpio::{ self, IDR, BSRR };
+
+use super::pins::{ Pin, Output, Unknown };
+
+/// Error of `ParallelBus::new`, with the pins it was given
+pub type BusError<OTYPE, const N: usize> = (GPIOError, [Pin<Output<OTYPE>>; N]);
+
+/// `N`-bit bus over pins of the same port
+pub struct ParallelBus<const N: usize> {
+	base: usize,
+	pins: [u8; N],
+	/// Number of the first pin if the pins are contiguous and in order
+	shift: Option<u32>,
+}
+
+impl<const N: usize> ParallelBus<N> {
+	/// Builds the bus from output pins of one port, bit `i` of the words is `pins[i]`
+	/// On error the pins are given back with it
+	pub fn new<OTYPE>(pins: [Pin<Output<OTYPE>>; N]) -> Result<Self, BusError<OTYPE, N>> {
+		if N == 0 || N > 16 { return Err((GPIOError::InvalidPin, pins)); }
+
+		let base = pins[0].base();
+
+		if pins.iter().any(|p| p.base() != base) {
+			return Err((GPIOError::DifferentPorts, pins));
+		}
+
+		let mut numbers = [0u8; N];
+		for (i, p) in pins.iter().enumerate() {
+			numbers[i] = p.number() as u8;
+		}
+
+		let first = numbers[0] as u32;
+		let contiguous = numbers.iter().enumerate().all(|(i, &n)| n as u32 == first + i as u32);
+
+		Ok( ParallelBus {
+			base,
+			pins: numbers,
+			shift: if contiguous { Some(first) } else { None },
+		} )
+	}
+
+	/// Releases the pins, in the mode the bus left them
+	pub fn free(self) -> [Pin<Unknown>; N] {
+		array::from_fn(|i| Pin::new(self.base, self.pins[i] as u32))
+	}
+
+	/// Typed access to the register `S` of the bus port
+	fn reg<S: RegisterSpec>(&self) -> Reg<'static, S> {
+		gpio::reg(self.base)
+	}
+
+	/// Returns the pins of the bus as a port mask
+	pub fn mask(&self) -> u16 {
+		match self.shift {
+			Some(s) => (((1u32 << N) - 1) << s) as u16,
+			None => self.pins.iter().fold(0, |m, &n| m | (1 << n)),
+		}
+	}
+
+	/// Returns the BSRR value that outputs `word` on the bus
+	pub fn bsrr(&self, word: u32) -> u32 {
+		let value = match self.shift {
+			Some(s) => (word << s) as u16,
+			None => self.pins.iter().enumerate()
+				.fold(0u16, |v, (i, &n)| v | ((((word >> i) & 1) as u16) << n)),
+		};
+
+		masked_bsrr(self.mask(), value)
+	}
+
+	/// Outputs `word`, only the lower `N` bits are used
+	pub fn write(&mut self, word: u32) -> &mut Self {
+		let bsrr = self.bsrr(word);
+		self.reg::<BSRR>().write(|w| w.set_bits(0, 32, bsrr));
+		self
+	}
+
+	/// Reads an `N`-bit word from the input data register
+	/// Set the bus as an input first to read what another device drives
+	pub fn read(&self) -> u32 {
+		let idr = self.reg::<IDR>().read().bits();
+
+		match self.shift {
+			Some(s) => (idr >> s) & ((1 << N) - 1),
+			None => self.pins.iter().enumerate()
+				.fold(0, |w, (i, &n)| w | (((idr >> n) & 1) << i)),
+		}
+	}
+
+	/// Sets the pins of the bus as outputs (`true`) or floating inputs (`false`)
+	/// The output type, speed and pull-up/pull-down are not changed, except on
+	/// the STM32F1 where the outputs become push-pull at 2 MHz
+	pub fn output_state(&mut self, s: bool) -> &mut Self {
+		let mask = self.mask();
+
+		interrupt::free(|_| gpio::set_direction(self.base, mask, s));
+
+		self
+	}
+}
+
+/// BSRR value that sets the pins of `mask` to `value` and leaves the others untouched
+pub const fn masked_bsrr(mask: u16, value: u16) -> u32 {
+	let set = (mask & value) as u32;
+	let reset = (mask & !value) as u32;
+
+	(reset << 16) | set
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::sim::SimBlock;
+	use crate::common::structs::PushPull;
+	use crate::peripherals::extended::gpio::{ Gpio, SIZE };
+
+	fn gpio() -> (SimBlock<SIZE>, Gpio) {
+		let block = SimBlock::<SIZE>::new();
+		let gpio = Gpio::from_block( unsafe { block.regs() } );
+		(block, gpio)
+	}
+
+	fn bus<const N: usize>(gpio: &mut Gpio, pins: [u32; N]) -> ParallelBus<N> {
+		ParallelBus::new( pins.map(|n| gpio.pin(n).unwrap().into_push_pull_output()) ).map_err(|(e, _)| e).unwrap()
+	}
+
+	#[test]
+	fn masked_bsrr_leaves_the_other_pins() {
+		assert_eq!(masked_bsrr(0xFFFF, 0xA5C3), 0x5A3C_A5C3);
+		assert_eq!(masked_bsrr(0x00F0, 0xFFFF), 0x0000_00F0);
+		assert_eq!(masked_bsrr(0x00F0, 0x0000), 0x00F0_0000);
+		assert_eq!(masked_bsrr(0x8001, 0x0001), 0x8000_0001);
+		assert_eq!(masked_bsrr(0x0000, 0xFFFF), 0);
+	}
+
+	#[test]
+	fn contiguous_bus() {
+		let (block, mut gpio) = gpio();
+		let mut bus = bus(&mut gpio, [4, 5, 6, 7]);
+
+		assert_eq!(bus.mask(), 0x00F0);
+		assert_eq!(bus.bsrr(0b0011), 0x00C0_0030);
+		// Bits above the bus are ignored
+		assert_eq!(bus.bsrr(0b1_0011), 0x00C0_0030);
+
+		block.poke(BSRR::INDEX, 0);
+		bus.write(0b1010);
+		assert_eq!(block.writes(BSRR::INDEX).last(), Some(&0x0050_00A0));
+
+		block.poke(IDR::INDEX, 0xFF5F);
+		assert_eq!(bus.read(), 0b0101);
+	}
+
+	#[test]
+	fn scattered_bus() {
+		let (block, mut gpio) = gpio();
+		let mut bus = bus(&mut gpio, [3, 0, 9]);
+
+		assert_eq!(bus.mask(), 0x0209);
+		// Bit 0 on P3, bit 1 on P0, bit 2 on P9
+		assert_eq!(bus.bsrr(0b101), 0x0001_0208);
+		assert_eq!(bus.bsrr(0b010), 0x0208_0001);
+
+		bus.write(0b111);
+		assert_eq!(block.writes(BSRR::INDEX).last(), Some(&0x0000_0209));
+
+		block.poke(IDR::INDEX, 0x0201);
+		assert_eq!(bus.read(), 0b110);
+	}
+
+	#[test]
+	fn bus_writes_once() {
+		let (block, mut gpio) = gpio();
+		let mut bus = bus(&mut gpio, [1, 2]);
+
+		let before = block.writes(BSRR::INDEX).len();
+		bus.write(0b01);
+		assert_eq!(block.writes(BSRR::INDEX).len(), before + 1);
+	}
+
+	#[test]
+	fn bus_rejects_different_ports() {
+		let (_, mut gpioa) = gpio();
+		let (_, mut gpiob) = gpio();
+
+		let pins = [gpioa.pin(0).unwrap().into_push_pull_output(), gpiob.pin(1).unwrap().into_push_pull_output()];
+		let (e, pins) = match ParallelBus::new(pins) {
+			Err(e) => e,
+			Ok(_) => panic!("bus over two ports"),
+		};
+
+		// The pins are given back as they were
+		assert_eq!(e, GPIOError::DifferentPorts);
+		assert_eq!(pins.iter().map(|p| p.number()).collect::<Vec<_>>(), [0, 1]);
+		pins[1].set();
+	}
+
+	#[test]
+	fn bus_rejects_no_pins() {
+		let pins: [Pin<Output<PushPull>>; 0] = [];
+		assert_eq!(ParallelBus::new(pins).err().map(|(e, _)| e), Some(GPIOError::InvalidPin));
+	}
+}
@@ -0,0 +1,176 @@
+//! Charlieplexed LEDs
+//!
+//! `N` pins drive `N * (N - 1)` LEDs, one between each ordered pair of pins.
+//! The LED from anode `a` to cathode `k` is lit by driving `a` high, `k` low and
+//! leaving the other pins Hi-Z. The LEDs are multiplexed by anode: each `tick`
+//! drives the next anode and the cathodes of its lit LEDs, so a frame takes `N` ticks.
+//!
+//! ```ignore
+//! let pins = [p0, p1, p2, p3].map(|p| p.into_dynamic());
+//! let mut leds = Charlieplex::new(pins);
+//! leds.set(Charlieplex::<4>::led(0, 3), true);
+//!
+//! // Timer interrupt, at least 100 frames per second
+//! leds.tick();
+//! ```
+
+use super::pins::{ Pin, Dynamic, PinMode };
+
+/// `N` pins driving `N * (N - 1)` LEDs, at most 8 pins
+pub struct Charlieplex<const N: usize> {
+	pins: [Pin<Dynamic>; N],
+	/// Lit LEDs, bit `i` is LED `i`
+	frame: u64,
+	/// Anode driven by the last tick
+	anode: usize,
+}
+
+impl<const N: usize> Charlieplex<N> {
+	/// Checked when the LEDs are built, so other pin counts do not compile
+	const PINS: () = assert!(N >= 2 && N <= 8, "Charlieplexing needs 2 to 8 pins");
+
+	/// Leaves all the pins Hi-Z
+	pub fn new(pins: [Pin<Dynamic>; N]) -> Self {
+		let () = Self::PINS;
+
+		let mut leds = Charlieplex { pins, frame: 0, anode: N - 1 };
+		leds.off();
+		leds
+	}
+
+	/// Returns the pins, left Hi-Z
+	pub fn free(mut self) -> [Pin<Dynamic>; N] {
+		self.off();
+		self.pins
+	}
+
+	/// Returns the number of the LED from pin `anode` to pin `cathode`
+	/// LEDs are numbered by anode, then by cathode skipping the anode
+	pub const fn led(anode: usize, cathode: usize) -> usize {
+		let k = if cathode > anode { cathode - 1 } else { cathode };
+		anode * (N - 1) + k
+	}
+
+	/// Returns the (anode, cathode) pins of LED `led`
+	pub const fn pins_of(led: usize) -> (usize, usize) {
+		let anode = led / (N - 1);
+		let k = led % (N - 1);
+
+		(anode, if k >= anode { k + 1 } else { k })
+	}
+
+	/// Turns LED `led` on or off, shown from the next tick
+	pub fn set(&mut self, led: usize, on: bool) -> &mut Self {
+		if led < N * (N - 1) {
+			match on {
+				true => self.frame |= 1 << led,
+				_ => self.frame &= !(1 << led),
+			}
+		}
+
+		self
+	}
+
+	/// Sets all the LEDs, bit `i` is LED `i`
+	pub fn set_all(&mut self, frame: u64) -> &mut Self {
+		self.frame = frame;
+		self
+	}
+
+	/// Checks if LED `led` is on
+	pub fn is_on(&self, led: usize) -> bool {
+		led < N * (N - 1) && self.frame & (1 << led) != 0
+	}
+
+	/// Leaves all the pins Hi-Z, the LEDs are dark until the next tick
+	pub fn off(&mut self) -> &mut Self {
+		for pin in self.pins.iter_mut() {
+			pin.mode(PinMode::HiZ);
+		}
+
+		self
+	}
+
+	/// Lights the LEDs of the next anode
+	/// The pins are floated first, so no LED of the previous anode glows
+	pub fn tick(&mut self) -> &mut Self {
+		self.off();
+
+		self.anode = (self.anode + 1) % N;
+
+		let lit = (self.frame >> (self.anode * (N - 1))) & ((1 << (N - 1)) - 1);
+
+		if lit == 0 { return self; }
+
+		for k in 0..N - 1 {
+			if lit & (1 << k) != 0 {
+				let cathode = if k >= self.anode { k + 1 } else { k };
+				self.pins[cathode].mode(PinMode::Low);
+			}
+		}
+
+		self.pins[self.anode].mode(PinMode::High);
+
+		self
+	}
+}
+
+
+#[cfg(all(test, feature = "sim", not(feature = "stm32f1")))]
+mod tests {
+	use super::*;
+	use crate::bitbang::tests::port;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::peripherals::extended::gpio::{ SIZE, MODER, ODR };
+
+	fn leds() -> (SimBlock<SIZE>, Charlieplex<3>) {
+		let (block, mut gpio) = port();
+		let pins = [0, 1, 2].map(|n| gpio.pin(n).unwrap().into_dynamic());
+
+		(block, Charlieplex::new(pins))
+	}
+
+	/// (outputs, driven high) of P0..P2
+	fn lines(block: &SimBlock<SIZE>) -> (u32, u32) {
+		let moder = block.peek(MODER::INDEX);
+		let outputs = (0..3).filter(|n| (moder >> (2 * n)) & 0b11 == 0b01).fold(0, |m, n| m | (1 << n));
+
+		(outputs, block.peek(ODR::INDEX) & outputs)
+	}
+
+	#[test]
+	fn led_numbers() {
+		assert_eq!(Charlieplex::<3>::led(0, 1), 0);
+		assert_eq!(Charlieplex::<3>::led(0, 2), 1);
+		assert_eq!(Charlieplex::<3>::led(2, 1), 5);
+
+		for led in 0..6 {
+			let (a, k) = Charlieplex::<3>::pins_of(led);
+			assert_ne!(a, k);
+			assert_eq!(Charlieplex::<3>::led(a, k), led);
+		}
+	}
+
+	#[test]
+	fn tick_drives_one_anode() {
+		let (block, mut leds) = leds();
+
+		leds.set(Charlieplex::<3>::led(0, 2), true)
+			.set(Charlieplex::<3>::led(1, 0), true);
+
+		// P0 high, P2 low, P1 Hi-Z
+		leds.tick();
+		assert_eq!(lines(&block), (0b101, 0b001));
+
+		// P1 high, P0 low
+		leds.tick();
+		assert_eq!(lines(&block), (0b011, 0b010));
+
+		// Nothing lit on P2, all Hi-Z
+		leds.tick();
+		assert_eq!(lines(&block), (0, 0));
+
+		leds.tick();
+		assert_eq!(lines(&block), (0b101, 0b001));
+	}
+}
@@ -1,8 +1,13 @@
 
-use crate::common::enums::SrcClock;
+use crate::common::Frequency;
+use crate::common::enums::{ HSEMode, SrcClock, VoltageRange };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct ClockCfg {
+	/// HSE Configuration
+	/// (Frequency, Crystal or Bypass), `None` if the board has no HSE
+	pub hse: Option<(Frequency, HSEMode)>,
+
 	/// PLL Configuration
 	/// ((plln, pllm, pllp, pllq), Source Clock)
 	pub pllcfg: Option<((u32, u32, u32, u32), SrcClock)>,
@@ -19,5 +24,8 @@ pub struct ClockCfg {
 
 	/// I2S Configuration
 	/// (i2sn, i2sm, i2sr)
-	pub i2scfg: (u32, u32, u32)
+	pub i2scfg: (u32, u32, u32),
+
+	/// Supply voltage range, sets the flash wait states
+	pub vdd: VoltageRange,
 }
\ No newline at end of file
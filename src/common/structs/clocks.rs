@@ -6,14 +6,9 @@ use std::{ default };
 #[cfg(not(feature = "std"))]
 use core::{ default };
 
-use crate::common::{ Frequency, asm, VolatileStruct };
+use crate::common::Frequency;
 
-use crate::peripherals::core::syst::{ ADDRESS, SysTick, SysTClock };
-
-use embedded_hal::blocking::delay::{ DelayMs, DelayUs };
-
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Clocks {
 	pub sysf: Frequency,
 	
@@ -30,30 +25,6 @@ pub struct Clocks {
 	pub i2sf: Frequency,
 }
 
-impl DelayMs<u32> for Clocks {
-	fn delay_ms(&mut self, ms: u32) {
-		self.delay_us(ms * 1000);
-	}
-}
-
-impl DelayUs<u32> for Clocks {
-	fn delay_us(&mut self, us: u32) {
-		let rvr = us * self.ahb1f.mhz();
-
-		let mut SYST = unsafe { SysTick::from_addr(ADDRESS) };
-
-		SYST.set_clock_source(SysTClock::Core);
-
-		SYST.set_reload(rvr);
-		SYST.clear_current();
-		SYST.counter_state(true);
-
-		while !SYST.has_wrapped() {}
-
-		SYST.counter_state(false);
-	}
-}
-
 impl default::Default for Clocks {
 	fn default() -> Clocks {
 		Clocks {
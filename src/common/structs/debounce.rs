@@ -0,0 +1,314 @@
+//! Debounced digital input
+//!
+//! The input is sampled by calling `tick` at a fixed rate, from the SysTick or a
+//! timer interrupt. Every tick returns at most one event.
+//!
+//! ```ignore
+//! static BUTTON: Mutex<RefCell<Option<Debounced<Pin<Input<PullUp>>>>>> = ...;
+//!
+//! let mut button = Debounced::new(gpioc.pin(13)?.into_pull_up_input(), true);
+//! button.filter(DebounceFilter::Integrator(5))
+//! 	.long_press(1000)
+//! 	.repeat(100);
+//!
+//! // SysTick handler, every millisecond
+//! match button.tick() {
+//! 	Ok( Some(ButtonEvent::Pressed) ) => ...,
+//! 	Ok( Some(ButtonEvent::Repeat) ) => ...,
+//! 	_ => (),
+//! }
+//! ```
+//!
+//! With an `ExtiPin` the ticks can be stopped while the input is idle: start the
+//! timer from the EXTI handler and stop it when `is_idle` returns `true`.
+
+use embedded_hal::digital::v2::InputPin;
+
+/// Events of a debounced input
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ButtonEvent {
+	/// The input became active
+	Pressed,
+	/// The input became inactive
+	Released,
+	/// The input has been active for the long press time
+	LongPress,
+	/// The input is still active after a long press, sent every repeat time
+	Repeat,
+}
+
+/// Debounce algorithm
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DebounceFilter {
+	/// A counter moves one step towards each sample, the state changes when it
+	/// reaches 0 or the given count. Tolerates isolated glitches while bouncing
+	Integrator(u8),
+	/// The state changes after the given number of identical consecutive samples
+	/// At most 32
+	ShiftRegister(u8),
+}
+
+/// Input debounced by sampling on a tick
+pub struct Debounced<P> {
+	pin: P,
+	active_low: bool,
+	filter: DebounceFilter,
+
+	/// Integrator count or shift register of samples
+	history: u32,
+	pressed: bool,
+
+	/// Ticks since the input was pressed
+	held: u32,
+	long_press: u32,
+	repeat: u32,
+}
+
+impl<P: InputPin> Debounced<P> {
+	/// Debounces `pin`, which is active when low if `active_low`
+	/// The default filter integrates 5 samples, long press and repeat are disabled
+	pub fn new(pin: P, active_low: bool) -> Self {
+		Debounced {
+			pin,
+			active_low,
+			filter: DebounceFilter::Integrator(5),
+			history: 0,
+			pressed: false,
+			held: 0,
+			long_press: 0,
+			repeat: 0,
+		}
+	}
+
+	/// Samples the pin and runs the debounce
+	/// Call it at a fixed rate, the times are counted in ticks
+	pub fn tick(&mut self) -> Result<Option<ButtonEvent>, P::Error> {
+		let level = self.pin.is_high()?;
+		Ok( self.update(level) )
+	}
+
+	/// Releases the pin
+	pub fn free(self) -> P {
+		self.pin
+	}
+}
+
+impl<P> Debounced<P> {
+	/// Sets the debounce algorithm and restarts it from the released state
+	pub fn filter(&mut self, filter: DebounceFilter) -> &mut Self {
+		self.filter = filter;
+		self.history = 0;
+		self.pressed = false;
+		self.held = 0;
+		self
+	}
+
+	/// Sets the ticks after which a held input sends `LongPress`, 0 disables it
+	pub fn long_press(&mut self, ticks: u32) -> &mut Self {
+		self.long_press = ticks;
+		self
+	}
+
+	/// Sets the ticks between `Repeat` events after a long press, 0 disables them
+	pub fn repeat(&mut self, ticks: u32) -> &mut Self {
+		self.repeat = ticks;
+		self
+	}
+
+	/// Returns the pin
+	pub fn pin(&self) -> &P {
+		&self.pin
+	}
+
+	/// Checks if the debounced input is active
+	pub fn is_pressed(&self) -> bool {
+		self.pressed
+	}
+
+	/// Returns the ticks the input has been pressed, 0 if it is released
+	pub fn held(&self) -> u32 {
+		self.held
+	}
+
+	/// Checks if the input is released and settled, so no event can come until it changes
+	pub fn is_idle(&self) -> bool {
+		!self.pressed && self.history & self.window() == 0
+	}
+
+	/// Returns the samples of the history used by the filter
+	fn window(&self) -> u32 {
+		match self.filter {
+			DebounceFilter::ShiftRegister(0) | DebounceFilter::ShiftRegister(1) => 1,
+			DebounceFilter::ShiftRegister(n) if n < 32 => (1 << n) - 1,
+			_ => u32::MAX,
+		}
+	}
+
+	/// Runs the debounce over a sample of the pin level taken outside `tick`
+	/// (e.g. read in an interrupt handler, or scripted on the host)
+	pub fn update(&mut self, level: bool) -> Option<ButtonEvent> {
+		let active = level != self.active_low;
+
+		let changed = match self.filter {
+			DebounceFilter::Integrator(n) => {
+				let n = n.max(1) as u32;
+
+				self.history = match active {
+					true => (self.history + 1).min(n),
+					_ => self.history.saturating_sub(1),
+				};
+
+				(self.pressed && self.history == 0) || (!self.pressed && self.history == n)
+			},
+
+			DebounceFilter::ShiftRegister(_) => {
+				let mask = self.window();
+
+				self.history = (self.history << 1) | active as u32;
+
+				match self.pressed {
+					true => self.history & mask == 0,
+					_ => self.history & mask == mask,
+				}
+			},
+		};
+
+		if changed {
+			self.pressed = !self.pressed;
+			self.held = 0;
+
+			return match self.pressed {
+				true => Some(ButtonEvent::Pressed),
+				_ => Some(ButtonEvent::Released),
+			};
+		}
+
+		if !self.pressed { return None; }
+
+		self.held = self.held.saturating_add(1);
+
+		if self.long_press == 0 { return None; }
+
+		match self.held.checked_sub(self.long_press) {
+			Some(0) => Some(ButtonEvent::LongPress),
+			Some(t) if self.repeat != 0 && t % self.repeat == 0 => Some(ButtonEvent::Repeat),
+			_ => None,
+		}
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use std::cell::Cell;
+	use std::convert::Infallible;
+	use std::rc::Rc;
+
+	use super::*;
+
+	/// Pin whose level is set by the test
+	struct Line(Rc<Cell<bool>>);
+
+	impl InputPin for Line {
+		type Error = Infallible;
+
+		fn is_high(&self) -> Result<bool, Infallible> {
+			Ok( self.0.get() )
+		}
+
+		fn is_low(&self) -> Result<bool, Infallible> {
+			Ok( !self.0.get() )
+		}
+	}
+
+	fn button(active_low: bool, filter: DebounceFilter) -> Debounced<Line> {
+		let mut button = Debounced::new(Line(Rc::new(Cell::new(active_low))), active_low);
+		button.filter(filter);
+		button
+	}
+
+	/// Feeds the levels and returns the events with the index of their sample
+	fn run(button: &mut Debounced<Line>, levels: &[bool]) -> Vec<(usize, ButtonEvent)> {
+		levels.iter().enumerate()
+			.filter_map(|(i, &level)| button.update(level).map(|e| (i, e)))
+			.collect()
+	}
+
+	#[test]
+	fn integrator_rides_over_glitches() {
+		let mut b = button(true, DebounceFilter::Integrator(3));
+
+		// Active low: the glitch at 2 delays the press by two samples
+		assert_eq!(run(&mut b, &[false, false, true, false, false]), [(4, ButtonEvent::Pressed)]);
+		assert!(b.is_pressed());
+
+		assert_eq!(run(&mut b, &[true, false, true, true, true]), [(4, ButtonEvent::Released)]);
+		assert!(b.is_idle());
+	}
+
+	#[test]
+	fn integrator_never_settles_on_a_bouncing_input() {
+		let mut b = button(false, DebounceFilter::Integrator(2));
+
+		assert!(run(&mut b, &[true, false, true, false, true, false]).is_empty());
+		assert!(!b.is_pressed());
+	}
+
+	#[test]
+	fn shift_register_needs_consecutive_samples() {
+		let mut b = button(false, DebounceFilter::ShiftRegister(3));
+
+		assert_eq!(run(&mut b, &[true, true, false, true, true, true]), [(5, ButtonEvent::Pressed)]);
+		assert!(!b.is_idle());
+
+		assert_eq!(run(&mut b, &[false, false, true, false, false, false]), [(5, ButtonEvent::Released)]);
+		assert!(b.is_idle());
+	}
+
+	#[test]
+	fn shift_register_of_32_samples() {
+		let mut b = button(false, DebounceFilter::ShiftRegister(32));
+
+		let events = run(&mut b, &[true; 40]);
+		assert_eq!(events, [(31, ButtonEvent::Pressed)]);
+	}
+
+	#[test]
+	fn long_press_and_repeat() {
+		let mut b = button(false, DebounceFilter::Integrator(1));
+		b.long_press(5).repeat(2);
+
+		let events = run(&mut b, &[true; 11]);
+		assert_eq!(events, [
+			(0, ButtonEvent::Pressed),
+			(5, ButtonEvent::LongPress),
+			(7, ButtonEvent::Repeat),
+			(9, ButtonEvent::Repeat),
+		]);
+		assert_eq!(b.held(), 10);
+
+		// Releasing restarts the count
+		assert_eq!(run(&mut b, &[false, true]), [(0, ButtonEvent::Released), (1, ButtonEvent::Pressed)]);
+		assert_eq!(b.held(), 0);
+	}
+
+	#[test]
+	fn long_press_without_repeat() {
+		let mut b = button(false, DebounceFilter::Integrator(1));
+		b.long_press(3);
+
+		assert_eq!(run(&mut b, &[true; 20]), [(0, ButtonEvent::Pressed), (3, ButtonEvent::LongPress)]);
+	}
+
+	#[test]
+	fn tick_samples_the_pin() {
+		let level = Rc::new(Cell::new(true));
+		let mut b = Debounced::new(Line(level.clone()), true);
+		b.filter(DebounceFilter::Integrator(2));
+
+		assert_eq!(b.tick(), Ok(None));
+		level.set(false);
+		assert_eq!(b.tick(), Ok(None));
+		assert_eq!(b.tick(), Ok(Some(ButtonEvent::Pressed)));
+	}
+}
@@ -0,0 +1,185 @@
+//! Input pin routed to its EXTI line
+//!
+//! ```ignore
+//! let button = gpioc.pin(13)?.into_pull_up_input()
+//! 	.into_interrupt(p.exti, p.syscfg, &mut p.rcc, Trigger::Falling)?;
+//!
+//! // In the EXTI15_10 handler
+//! button.clear_pending(exti);
+//! ```
+
+#[cfg(feature = "std")]
+use std::convert::Infallible;
+
+#[cfg(not(feature = "std"))]
+use core::convert::Infallible;
+
+use crate::common::{ GPIOError, Trigger, EXTILine };
+use crate::peripherals::extended::{ exti::Exti, rcc::Rcc, gpio, syscfg::Syscfg };
+
+use embedded_hal::digital::v2::InputPin;
+
+use super::pins::{ Pin, Input };
+
+/// Input pin that raises its EXTI line
+pub struct ExtiPin<PULL> {
+	pin: Pin<Input<PULL>>,
+	line: EXTILine,
+}
+
+/// EXTI line of the pins numbered `n`
+fn line(n: u32) -> EXTILine {
+	match n {
+		0 => EXTILine::Line0,
+		1 => EXTILine::Line1,
+		2 => EXTILine::Line2,
+		3 => EXTILine::Line3,
+		4 => EXTILine::Line4,
+		5 => EXTILine::Line5,
+		6 => EXTILine::Line6,
+		7 => EXTILine::Line7,
+		8 => EXTILine::Line8,
+		9 => EXTILine::Line9,
+		10 => EXTILine::Line10,
+		11 => EXTILine::Line11,
+		12 => EXTILine::Line12,
+		13 => EXTILine::Line13,
+		14 => EXTILine::Line14,
+		_ => EXTILine::Line15,
+	}
+}
+
+impl<PULL> Pin<Input<PULL>> {
+	/// Routes the pin to its EXTI line, sets the trigger and unmasks the line
+	/// Enables the SYSCFG (AFIO on the STM32F1) in the RCC
+	/// Fails if a pin with the same number of another port is already routed
+	pub fn into_interrupt(self, exti: &mut Exti, syscfg: &mut Syscfg, rcc: &mut Rcc, trigger: Trigger) -> Result<ExtiPin<PULL>, GPIOError> {
+		let n = self.number();
+
+		syscfg.enable(rcc)
+			.route_exti(n, gpio::port_number(self.base()))?;
+
+		let line = line(n);
+
+		exti.set_trigger(line, Trigger::RiseFall, false)
+			.set_trigger(line, trigger, true)
+			.int_state(line, true);
+
+		Ok( ExtiPin { pin: self, line } )
+	}
+}
+
+impl<PULL> ExtiPin<PULL> {
+	/// Returns the EXTI line of the pin
+	pub fn line(&self) -> EXTILine {
+		self.line
+	}
+
+	/// Returns the pin
+	pub fn pin(&self) -> &Pin<Input<PULL>> {
+		&self.pin
+	}
+
+	/// Checks if the line is pending
+	pub fn is_pending(&self, exti: &Exti) -> bool {
+		exti.is_raised(self.line)
+	}
+
+	/// Clears the pending line, call it from the interrupt handler
+	pub fn clear_pending(&self, exti: &mut Exti) {
+		exti.clear_pending(self.line);
+	}
+
+	/// Masks the line and frees it for other ports
+	pub fn release(self, exti: &mut Exti, syscfg: &mut Syscfg) -> Pin<Input<PULL>> {
+		exti.int_state(self.line, false)
+			.set_trigger(self.line, Trigger::RiseFall, false);
+
+		syscfg.release_exti(self.pin.number());
+
+		self.pin
+	}
+}
+
+impl<PULL> InputPin for ExtiPin<PULL> {
+	type Error = Infallible;
+
+	fn is_high(&self) -> Result<bool, Infallible> {
+		Ok( self.pin.is_high() )
+	}
+
+	fn is_low(&self) -> Result<bool, Infallible> {
+		Ok( self.pin.is_low() )
+	}
+}
+
+
+#[cfg(all(test, feature = "sim", any(feature = "stm32f1", feature = "stm32f4")))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::peripherals::extended::{ exti, rcc, syscfg::{ self, EXTICR4 } };
+	use crate::peripherals::map;
+
+	fn port(address: u32) -> gpio::Gpio {
+		let block = SimBlock::<{ gpio::SIZE }>::new();
+		block.stand_for(address);
+
+		gpio::Gpio::from_block( unsafe { block.regs() } )
+	}
+
+	#[test]
+	fn into_interrupt_routes_and_unmasks_the_line() {
+		let (rcc_block, mut rcc) = rcc::tests::rcc();
+		let (syscfg_block, mut syscfg) = syscfg::tests::syscfg();
+		let exti_block = SimBlock::<{ exti::SIZE }>::new();
+		let exti = unsafe { exti_block.view::<Exti>() };
+
+		// A rising trigger left over from a previous use of the line
+		exti_block.poke(exti::RTSR::INDEX, 1 << 13);
+
+		let mut gpioc = port(map::GPIOC);
+		let button = gpioc.pin(13).unwrap().into_pull_up_input()
+			.into_interrupt(exti, &mut syscfg, &mut rcc, Trigger::Falling).unwrap();
+
+		assert_eq!(button.line() as u32, 13);
+		assert_eq!(rcc_block.peek(syscfg::tests::ENABLE.0) & syscfg::tests::ENABLE.1, syscfg::tests::ENABLE.1);
+		assert_eq!(syscfg_block.peek(EXTICR4::INDEX), 2 << 4);
+		assert_eq!(exti_block.peek(exti::IMR::INDEX), 1 << 13);
+		assert_eq!(exti_block.peek(exti::FTSR::INDEX), 1 << 13);
+		assert_eq!(exti_block.peek(exti::RTSR::INDEX), 0);
+
+		exti_block.poke(exti::PR::INDEX, 1 << 13);
+		assert!(button.is_pending(exti));
+		button.clear_pending(exti);
+		assert_eq!(exti_block.writes(exti::PR::INDEX), [1 << 13]);
+	}
+
+	#[test]
+	fn a_line_is_taken_until_the_pin_is_released() {
+		let (_, mut rcc) = rcc::tests::rcc();
+		let (syscfg_block, mut syscfg) = syscfg::tests::syscfg();
+		let exti_block = SimBlock::<{ exti::SIZE }>::new();
+		let exti = unsafe { exti_block.view::<Exti>() };
+
+		let mut gpioc = port(map::GPIOC);
+		let button = gpioc.pin(13).unwrap().into_pull_up_input()
+			.into_interrupt(exti, &mut syscfg, &mut rcc, Trigger::Falling).unwrap();
+
+		let taken = port(map::GPIOA).pin(13).unwrap().into_floating_input()
+			.into_interrupt(exti, &mut syscfg, &mut rcc, Trigger::Rising);
+		assert_eq!(taken.err(), Some(GPIOError::LineTaken));
+		assert_eq!(syscfg_block.peek(EXTICR4::INDEX), 2 << 4);
+
+		let _pc13 = button.release(exti, &mut syscfg);
+		assert_eq!(exti_block.peek(exti::IMR::INDEX), 0);
+		assert_eq!(exti_block.peek(exti::FTSR::INDEX), 0);
+
+		let pa13 = port(map::GPIOA).pin(13).unwrap().into_floating_input()
+			.into_interrupt(exti, &mut syscfg, &mut rcc, Trigger::Rising).unwrap();
+		assert_eq!(pa13.line() as u32, 13);
+		assert_eq!(syscfg_block.peek(EXTICR4::INDEX), 0);
+		assert_eq!(exti_block.peek(exti::RTSR::INDEX), 1 << 13);
+		assert_eq!(exti_block.peek(exti::IMR::INDEX), 1 << 13);
+	}
+}
@@ -0,0 +1,260 @@
+//! Matrix keypad scanner
+//!
+//! The rows are driven low one at a time and the columns, with pull-ups, are
+//! read low where a key of the row is pressed. Every key can be pressed at the
+//! same time (n-key rollover) as long as the scan is not ambiguous.
+//!
+//! Without a diode per key, three pressed keys on the corners of a rectangle make
+//! the fourth corner read as pressed (ghosting). Such scans are rejected with
+//! `KeypadError::Ghosting` and the previous state is kept.
+//!
+//! ```ignore
+//! let rows = [r0, r1, r2, r3].map(|p| p.into_open_drain_output());
+//! let cols = [c0, c1, c2, c3].map(|p| p.into_pull_up_input());
+//!
+//! let mut keypad = Keypad::new(rows, cols);
+//! keypad.settle(10);
+//!
+//! // Every 10 ms
+//! if keypad.scan(&mut delay).is_ok() {
+//! 	for event in keypad.events() { ... }
+//! }
+//! ```
+
+#[cfg(feature = "std")]
+use std::convert::Infallible;
+
+#[cfg(not(feature = "std"))]
+use core::convert::Infallible;
+
+use crate::common::KeypadError;
+
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::{ InputPin, OutputPin };
+
+/// Change of a key, as (row, column)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyEvent {
+	Pressed(u8, u8),
+	Released(u8, u8),
+}
+
+/// Keypad of `R` rows and `C` columns, at most 16 columns
+/// Use open-drain rows, so two keys of a column pressed at once do not short two rows
+pub struct Keypad<ROW, COL, const R: usize, const C: usize> {
+	rows: [ROW; R],
+	cols: [COL; C],
+	settle: u32,
+
+	/// Pressed columns of each row
+	keys: [u16; R],
+	last: [u16; R],
+}
+
+impl<ROW, COL, const R: usize, const C: usize> Keypad<ROW, COL, R, C> {
+	/// Checked when the keypad is built, so more than 16 columns do not compile
+	const COLUMNS: () = assert!(C <= 16, "A keypad has at most 16 columns");
+}
+
+impl<ROW, COL, const R: usize, const C: usize> Keypad<ROW, COL, R, C>
+	where ROW: OutputPin<Error = Infallible>,
+		COL: InputPin<Error = Infallible>
+{
+	/// Releases all the rows
+	pub fn new(rows: [ROW; R], cols: [COL; C]) -> Self {
+		let () = Self::COLUMNS;
+
+		let mut keypad = Keypad { rows, cols, settle: 1, keys: [0; R], last: [0; R] };
+
+		for row in keypad.rows.iter_mut() {
+			let _ = row.set_high();
+		}
+
+		keypad
+	}
+
+	/// Returns the pins
+	pub fn free(self) -> ([ROW; R], [COL; C]) {
+		(self.rows, self.cols)
+	}
+
+	/// Sets the microseconds waited between driving a row and reading the columns
+	pub fn settle(&mut self, us: u32) -> &mut Self {
+		self.settle = us;
+		self
+	}
+
+	/// Reads all the keys
+	/// Fails if the scan shows ghosting, the previous state is kept
+	pub fn scan<D: DelayUs<u32>>(&mut self, delay: &mut D) -> Result<(), KeypadError> {
+		let mut keys = [0u16; R];
+
+		for (r, row) in self.rows.iter_mut().enumerate() {
+			let _ = row.set_low();
+			delay.delay_us(self.settle);
+
+			for (c, col) in self.cols.iter().enumerate() {
+				if col.is_low().unwrap_or(false) {
+					keys[r] |= 1 << c;
+				}
+			}
+
+			let _ = row.set_high();
+		}
+
+		if ghosting(&keys) {
+			return Err(KeypadError::Ghosting);
+		}
+
+		self.last = self.keys;
+		self.keys = keys;
+
+		Ok(())
+	}
+}
+
+impl<ROW, COL, const R: usize, const C: usize> Keypad<ROW, COL, R, C> {
+	/// Checks if the key at `row`, `col` is pressed
+	pub fn is_pressed(&self, row: usize, col: usize) -> bool {
+		row < R && col < C && self.keys[row] & (1 << col) != 0
+	}
+
+	/// Returns the number of pressed keys
+	pub fn pressed(&self) -> u32 {
+		self.keys.iter().map(|k| k.count_ones()).sum()
+	}
+
+	/// Returns the changes of the last scan
+	pub fn events(&self) -> impl Iterator<Item = KeyEvent> + '_ {
+		(0..R).flat_map(move |r| (0..C).filter_map(move |c| {
+			let now = self.keys[r] & (1 << c) != 0;
+			let before = self.last[r] & (1 << c) != 0;
+
+			match (before, now) {
+				(false, true) => Some(KeyEvent::Pressed(r as u8, c as u8)),
+				(true, false) => Some(KeyEvent::Released(r as u8, c as u8)),
+				_ => None,
+			}
+		}))
+	}
+}
+
+/// Checks if two rows share two pressed columns, the corners of a rectangle
+/// One of the four keys may not be pressed
+fn ghosting(keys: &[u16]) -> bool {
+	keys.iter().enumerate().any(|(i, &a)| {
+		keys[i + 1..].iter().any(|&b| (a & b).count_ones() >= 2)
+	})
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+
+	use super::*;
+	use crate::common::register::{ sim::{ self, SimBlock }, RegisterSpec };
+	use crate::common::structs::{ Pin, Input, Output, OpenDrain, PullUp };
+	use crate::peripherals::extended::gpio::{ Gpio, SIZE, BSRR, IDR, ODR };
+
+	type Pad = Keypad<Pin<Output<OpenDrain>>, Pin<Input<PullUp>>, 4, 4>;
+
+	struct Delay;
+
+	impl DelayUs<u32> for Delay {
+		fn delay_us(&mut self, _: u32) {}
+	}
+
+	/// 4x4 keypad without diodes, with the rows on P0..P3 and the columns on P4..P7
+	/// Bit `4 * row + col` of the returned cell presses the key
+	fn keypad() -> (Rc<Cell<u16>>, Pad) {
+		let block = SimBlock::<SIZE>::new();
+		let odr = block.addr(ODR::INDEX);
+		let keys = Rc::new(Cell::new(0u16));
+		let pressed = keys.clone();
+
+		block.poke(ODR::INDEX, 0xFFFF)
+			.on_write(BSRR::INDEX, move |_, bsrr| {
+				sim::poke(odr, (sim::peek(odr) | (bsrr & 0xFFFF)) & !(bsrr >> 16));
+				0
+			})
+			.on_read(IDR::INDEX, move |_| {
+				// Without diodes a pressed key shorts its row and column,
+				// the low level spreads until no more lines are pulled down
+				let mut low = !sim::peek(odr) & 0x000F;
+
+				loop {
+					let mut spread = low;
+
+					for r in 0..4 {
+						for c in 0..4 {
+							let (row, col) = (1 << r, 1 << (4 + c));
+
+							if pressed.get() & (1 << (4 * r + c)) != 0 && low & (row | col) != 0 {
+								spread |= row | col;
+							}
+						}
+					}
+
+					if spread == low { break; }
+					low = spread;
+				}
+
+				0xFFFF & !low
+			});
+
+		let mut gpio = Gpio::from_block( unsafe { block.regs() } );
+		let rows = [0, 1, 2, 3].map(|n| gpio.pin(n).unwrap().into_open_drain_output());
+		let cols = [4, 5, 6, 7].map(|n| gpio.pin(n).unwrap().into_pull_up_input());
+
+		(keys, Keypad::new(rows, cols))
+	}
+
+	#[test]
+	fn scan_reports_the_changes() {
+		let (keys, mut pad) = keypad();
+
+		keys.set(1 << (4 * 2 + 1));
+		assert_eq!(pad.scan(&mut Delay), Ok(()));
+		assert!(pad.is_pressed(2, 1));
+		assert_eq!(pad.pressed(), 1);
+		assert_eq!(pad.events().collect::<Vec<_>>(), [KeyEvent::Pressed(2, 1)]);
+
+		// Unchanged, no events
+		pad.scan(&mut Delay).unwrap();
+		assert_eq!(pad.events().count(), 0);
+
+		keys.set(1 << 3);
+		pad.scan(&mut Delay).unwrap();
+		assert_eq!(pad.events().collect::<Vec<_>>(), [KeyEvent::Pressed(0, 3), KeyEvent::Released(2, 1)]);
+	}
+
+	#[test]
+	fn scan_reads_every_key() {
+		let (keys, mut pad) = keypad();
+
+		// A diagonal and a full row do not ghost
+		keys.set(0b1000_0100_0010_0001);
+		pad.scan(&mut Delay).unwrap();
+		assert_eq!(pad.pressed(), 4);
+
+		keys.set(0x00F0);
+		pad.scan(&mut Delay).unwrap();
+		assert!((0..4).all(|c| pad.is_pressed(1, c)));
+	}
+
+	#[test]
+	fn scan_rejects_ghosting() {
+		let (keys, mut pad) = keypad();
+
+		keys.set(1);
+		pad.scan(&mut Delay).unwrap();
+
+		// Three corners of a rectangle
+		keys.set((1 << 0) | (1 << 2) | (1 << 4));
+		assert_eq!(pad.scan(&mut Delay), Err(KeypadError::Ghosting));
+		assert_eq!(pad.pressed(), 1);
+		assert!(pad.is_pressed(0, 0));
+	}
+}
@@ -4,6 +4,11 @@
 reexport!{
 	private:
 		mod pins;
+		mod bus;
+		mod extipin;
+		mod debounce;
+		mod keypad;
+		mod charlieplex;
 		mod clocks;
 		mod clockcfg;
 }
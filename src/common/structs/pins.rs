@@ -1,100 +1,446 @@
 //! Abstraction over a Pin
-//! Allows to set it or clear it
+//!
+//! The mode of the pin is tracked in its type. The `into_*` conversions consume
+//! the pin and return it in the new mode, so a pin can only be driven as an output
+//! when it is one, and a pin given to a peripheral cannot be reconfigured.
+//!
+//! ```ignore
+//! let led = gpiod.pin(12)?.into_push_pull_output();
+//! led.set();
+//!
+//! let sck = gpioa.pin(5)?.into_alternate::<AF5>();
+//! ```
 
 #[cfg(feature = "std")]
-use std::{ ptr };
+use std::{ convert::Infallible, marker::PhantomData };
 
 #[cfg(not(feature = "std"))]
-use core::{ ptr };
+use core::{ convert::Infallible, marker::PhantomData };
 
-#[derive(Debug, Copy, Clone)]
+use crate::common::{ GPIOSpeed, OutputType, PUPD };
+use crate::common::register::{ Reg, RegisterSpec };
+use crate::interrupt;
+use crate::peripherals::af::{ self, Port, Signal };
+use crate::peripherals::extended::gpio::{ self, PinConfig, IDR, ODR, BSRR };
+
+use embedded_hal::digital::v2::{ InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin };
+
+/// Mode not known, e.g. the pin has been released by a peripheral
+pub struct Unknown;
+
+/// Input mode
+pub struct Input<PULL> {
+	_pull: PhantomData<PULL>,
+}
+
+/// Input without pull-up or pull-down
+pub struct Floating;
+
+/// Input with pull-up
+pub struct PullUp;
+
+/// Input with pull-down
+pub struct PullDown;
+
+/// Output mode
+pub struct Output<OTYPE> {
+	_otype: PhantomData<OTYPE>,
+}
+
+/// Push-pull output
+pub struct PushPull;
+
+/// Open-drain output
+pub struct OpenDrain;
+
+/// Alternate function mode
+pub struct Alternate<AF, OTYPE = PushPull> {
+	_af: PhantomData<AF>,
+	_otype: PhantomData<OTYPE>,
+}
+
+/// Analog mode
+pub struct Analog;
+
+/// Mode changed at runtime with `Pin::mode`
+/// Used by drivers that switch pins between driving and Hi-Z, e.g. charlieplexing
+pub struct Dynamic;
+
+/// Modes of a `Dynamic` pin
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PinMode {
+	/// Push-pull output driving high
+	High,
+	/// Push-pull output driving low
+	Low,
+	/// Floating input
+	HiZ,
+}
+
+/// Alternate function number
+pub trait AltFn {
+	const AF: u32;
+}
+
+macro_rules! altfns {
+	($($af:ident => $n:expr,)+) => {
+		$(
+			#[doc = "Alternate function marker"]
+			pub struct $af;
+
+			impl AltFn for $af {
+				const AF: u32 = $n;
+			}
+		)+
+	};
+}
+
+altfns! {
+	AF0  =>  0, AF1  =>  1, AF2  =>  2, AF3  =>  3,
+	AF4  =>  4, AF5  =>  5, AF6  =>  6, AF7  =>  7,
+	AF8  =>  8, AF9  =>  9, AF10 => 10, AF11 => 11,
+	AF12 => 12, AF13 => 13, AF14 => 14, AF15 => 15,
+}
+
+
+/// GPIO pin in mode `MODE`
 #[repr(C)]
-pub struct Pin {
-	base: u32,
+pub struct Pin<MODE> {
+	base: usize,
 	n: u32,
+	_mode: PhantomData<MODE>,
 }
 
-impl Pin {
+impl Pin<Unknown> {
 	/// Sets up a new GPIO pin
-	pub fn new(base: u32, n: u32) -> Self {
-		Self {
-			base,
-			n,
+	/// Only `Gpio::pin` creates pins, so each pin exists once
+	pub(crate) fn new(base: usize, n: u32) -> Self {
+		Pin { base, n, _mode: PhantomData }
+	}
+}
+
+impl<MODE> Pin<MODE> {
+	/// Returns the number of the pin in its port
+	pub fn number(&self) -> u32 {
+		self.n
+	}
+
+	/// Returns the address of the pin's port
+	pub(crate) fn base(&self) -> usize {
+		self.base
+	}
+
+	/// Returns the port of the pin
+	pub fn port(&self) -> Option<Port> {
+		af::port( gpio::port_number(self.base) )
+	}
+
+	/// Returns the alternate function with which the pin carries `signal`
+	/// `None` if the pin cannot carry it
+	pub fn af(&self, signal: Signal) -> Option<u32> {
+		match self.port() {
+			Some(port) => af::lookup(port, self.n as u8, signal),
+			None => None,
 		}
 	}
 
+	/// Returns the alternate functions with which the pin carries `signal`, see `af::functions`
+	/// On the STM32F1 these are the remaps of the peripheral that route `signal` to the pin
+	pub fn functions(&self, signal: Signal) -> u32 {
+		match self.port() {
+			Some(port) => af::functions(port, self.n as u8, signal),
+			None => 0,
+		}
+	}
+
+	/// Configures the pin at high speed as the alternate function that carries `signal`
+	/// Used by the drivers, which keep the pin until they are released
+	/// On the STM32F1 the inputs of the peripherals (e.g. MISO) are floating inputs
+	pub(crate) fn into_signal(self, signal: Signal, otype: OutputType) -> Option<Pin<Unknown>> {
+		let af = self.af(signal)?;
+
+		#[cfg(feature = "stm32f1")]
+		let config = match signal.is_input() {
+			true => PinConfig::Input(PUPD::NONE),
+			_ => PinConfig::Alternate(af, otype),
+		};
+
+		#[cfg(not(feature = "stm32f1"))]
+		let config = PinConfig::Alternate(af, otype);
+
+		self.configure(config);
+		gpio::set_speed(self.base, self.n, GPIOSpeed::High);
+
+		Some( self.into_mode() )
+	}
+
+	/// Typed access to the register `S` of the pin's port
+	fn reg<S: RegisterSpec>(&self) -> Reg<'static, S> {
+		gpio::reg(self.base)
+	}
+
+	/// Changes the type of the pin, the registers are not touched
+	fn into_mode<NEW>(self) -> Pin<NEW> {
+		Pin { base: self.base, n: self.n, _mode: PhantomData }
+	}
+
+	/// Forgets the mode of the pin
+	pub fn into_unknown(self) -> Pin<Unknown> {
+		self.into_mode()
+	}
+
+	fn configure(&self, config: PinConfig) {
+		gpio::configure(self.base, self.n, config);
+	}
+
+	/// Reads the input data register
+	fn input(&self) -> bool {
+		self.reg::<IDR>().read().idr(self.n as usize) == 1
+	}
+
+	/// Configures the pin as a floating input
+	pub fn into_floating_input(self) -> Pin<Input<Floating>> {
+		self.configure(PinConfig::Input(PUPD::NONE));
+		self.into_mode()
+	}
+
+	/// Configures the pin as an input with pull-up
+	pub fn into_pull_up_input(self) -> Pin<Input<PullUp>> {
+		self.configure(PinConfig::Input(PUPD::PullUp));
+		self.into_mode()
+	}
+
+	/// Configures the pin as an input with pull-down
+	pub fn into_pull_down_input(self) -> Pin<Input<PullDown>> {
+		self.configure(PinConfig::Input(PUPD::PullDown));
+		self.into_mode()
+	}
+
+	/// Configures the pin as a push-pull output
+	pub fn into_push_pull_output(self) -> Pin<Output<PushPull>> {
+		self.configure(PinConfig::Output(OutputType::PushPull));
+		self.into_mode()
+	}
+
+	/// Configures the pin as an open-drain output
+	pub fn into_open_drain_output(self) -> Pin<Output<OpenDrain>> {
+		self.configure(PinConfig::Output(OutputType::OpenDrain));
+		self.into_mode()
+	}
+
+	/// Configures the pin as the push-pull alternate function `AF`
+	/// On the STM32F1 `AF` is ignored, the function is selected with `Syscfg::remap`
+	pub fn into_alternate<AF: AltFn>(self) -> Pin<Alternate<AF, PushPull>> {
+		self.configure(PinConfig::Alternate(AF::AF, OutputType::PushPull));
+		self.into_mode()
+	}
+
+	/// Configures the pin as the open-drain alternate function `AF`
+	/// On the STM32F1 `AF` is ignored, the function is selected with `Syscfg::remap`
+	pub fn into_alternate_open_drain<AF: AltFn>(self) -> Pin<Alternate<AF, OpenDrain>> {
+		self.configure(PinConfig::Alternate(AF::AF, OutputType::OpenDrain));
+		self.into_mode()
+	}
+
+	/// Configures the pin as analog
+	pub fn into_analog(self) -> Pin<Analog> {
+		self.configure(PinConfig::Analog);
+		self.into_mode()
+	}
+
+	/// Configures the pin as a floating input whose mode can be changed at runtime
+	pub fn into_dynamic(self) -> Pin<Dynamic> {
+		self.configure(PinConfig::Input(PUPD::NONE));
+		self.into_mode()
+	}
+}
+
+impl<PULL> Pin<Input<PULL>> {
+	/// Checks if the input is high
+	pub fn is_high(&self) -> bool {
+		self.input()
+	}
+
+	/// Checks if the input is low
+	pub fn is_low(&self) -> bool {
+		!self.input()
+	}
+}
+
+impl Pin<Dynamic> {
+	/// Drives the pin high or low, or floats it
+	/// The output level is set before the pin becomes an output, so it does not glitch
+	pub fn mode(&mut self, mode: PinMode) -> &mut Self {
+		match mode {
+			PinMode::High => {
+				self.reg::<BSRR>().write(|w| w.bs(self.n as usize, 1));
+				self.configure(PinConfig::Output(OutputType::PushPull));
+			},
+
+			PinMode::Low => {
+				self.reg::<BSRR>().write(|w| w.br(self.n as usize, 1));
+				self.configure(PinConfig::Output(OutputType::PushPull));
+			},
+
+			PinMode::HiZ => self.configure(PinConfig::Input(PUPD::NONE)),
+		}
+
+		self
+	}
+
+	/// Checks if the line is high
+	pub fn is_high(&self) -> bool {
+		self.input()
+	}
+
+	/// Checks if the line is low
+	pub fn is_low(&self) -> bool {
+		!self.input()
+	}
+}
+
+impl<OTYPE> Pin<Output<OTYPE>> {
 	/// Set the pin
 	pub fn set(&self) {
-		unsafe { ptr::write_volatile((self.base + 0x18) as *mut _, 1u32 << self.n) }
-		//self.reg.write(1 << self.n);
+		self.reg::<BSRR>().write(|w| w.bs(self.n as usize, 1));
 	}
 
 	/// Reset the pin
 	pub fn reset(&self) {
-		unsafe { ptr::write_volatile((self.base + 0x18) as *mut _, 1u32 << (self.n + 16)) }
-		//self.reg.write(1 << (self.n + 16))
+		self.reg::<BSRR>().write(|w| w.br(self.n as usize, 1));
 	}
 
-	/// Set Alternative function
-	pub fn altfn(&self, af: u32) -> &Self {
-		let offsets = match self.n {
-			0..=7 => (self.base + 0x20,  self.n * 4      ),
-			_     => (self.base + 0x24, (self.n - 8) * 4 ),
-		};
-
-		let og: u32 = unsafe { ptr::read_volatile(offsets.0 as *mut _) };
+	/// Checks if the pin is set, reads the output data register
+	pub fn is_set(&self) -> bool {
+		self.reg::<ODR>().read().odr(self.n as usize) == 1
+	}
 
-		unsafe {
-			ptr::write_volatile(offsets.0 as *mut _, (og & !(0b1111 << offsets.1)) | (af << offsets.1) );
-		}
+	/// Toggles the pin
+	/// Written through BSRR, so the other pins of the port are not affected,
+	/// and in a critical section so an interrupt cannot change the pin in between
+	pub fn toggle(&self) {
+		interrupt::free(|_| {
+			if self.is_set() { self.reset() }
+			else { self.set() }
+		});
+	}
 
+	/// Set output speed
+	pub fn speed(&self, speed: GPIOSpeed) -> &Self {
+		gpio::set_speed(self.base, self.n, speed);
 		self
 	}
+}
 
-	/// Set PullUp PullDown
-	pub fn pupd(&self, pupd: u32) -> &Self {
-		let dir = self.base + 0x0C;
-		let og: u32 = unsafe { ptr::read_volatile(dir as *mut _) };
+impl<AF: AltFn, OTYPE> Pin<Alternate<AF, OTYPE>> {
+	/// Returns `functions(signal)` restricted to `AF`, 0 if the pin does not carry `signal` as `AF`
+	/// On the STM32F1 `AF` is ignored, all the remaps that route `signal` to the pin are kept
+	pub fn carries(&self, signal: Signal) -> u32 {
+		#[cfg(feature = "stm32f1")]
+		let mask = u32::MAX;
 
-		unsafe {
-			ptr::write_volatile( dir as *mut _, (og & !(0b11 << (self.n*2))) | (pupd << (self.n*2)) );
-		}
+		#[cfg(not(feature = "stm32f1"))]
+		let mask = 1 << AF::AF;
 
-		self
+		self.functions(signal) & mask
 	}
+}
 
+impl<AF, OTYPE> Pin<Alternate<AF, OTYPE>> {
 	/// Set output speed
-	pub fn speed(&self, ospeed: u32) -> &Self {
-		let dir = self.base + 0x08;
-		let og: u32 = unsafe { ptr::read_volatile(dir as *mut _) };
+	pub fn speed(&self, speed: GPIOSpeed) -> &Self {
+		gpio::set_speed(self.base, self.n, speed);
+		self
+	}
+}
 
-		unsafe {
-			ptr::write_volatile( dir as *mut _, (og & !(0b11 << (self.n*2))) | (ospeed << (self.n*2)) );
-		}
+/// The STM32F1 has no pull-up on its outputs
+#[cfg(not(feature = "stm32f1"))]
+impl Pin<Output<OpenDrain>> {
+	/// Enables/Disables the internal pull-up
+	/// Used by open-drain buses without external pull-ups (e.g. 1-Wire)
+	pub fn pull_up_state(&self, s: bool) -> &Self {
+		let pupd = match s {
+			true => PUPD::PullUp,
+			_ => PUPD::NONE,
+		};
 
+		gpio::set_pull(self.base, self.n, pupd);
 		self
 	}
+}
 
-	/// Set the mode
-	pub fn mode(&self, mode: u32) -> &Self {
-		let og: u32 = unsafe { ptr::read_volatile(self.base as *mut _) };
-
-		unsafe {
-			ptr::write_volatile( self.base as *mut _, (og & !(0b11 << (self.n*2))) | (mode << (self.n*2)) );
-		}
+/// The STM32F1 has no pull-up on its outputs
+#[cfg(not(feature = "stm32f1"))]
+impl<AF> Pin<Alternate<AF, OpenDrain>> {
+	/// Enables/Disables the internal pull-up
+	/// Used by open-drain buses without external pull-ups
+	pub fn pull_up_state(&self, s: bool) -> &Self {
+		let pupd = match s {
+			true => PUPD::PullUp,
+			_ => PUPD::NONE,
+		};
 
+		gpio::set_pull(self.base, self.n, pupd);
 		self
 	}
+}
 
-	/// Set the output type
-	pub fn otype(&self, otype: u32) -> &Self {
-		let dir = self.base + 0x04;
-		let og: u32 = unsafe { ptr::read_volatile(dir as *mut _) };
 
-		unsafe {
-			ptr::write_volatile( dir as *mut _, (og & !(0b11 << self.n)) | (otype << self.n) );
-		}
+impl<PULL> InputPin for Pin<Input<PULL>> {
+	type Error = Infallible;
 
-		self
+	fn is_high(&self) -> Result<bool, Infallible> {
+		Ok( self.input() )
+	}
+
+	fn is_low(&self) -> Result<bool, Infallible> {
+		Ok( !self.input() )
+	}
+}
+
+/// Reads the level of the line, which an open-drain output does not always drive
+impl<OTYPE> InputPin for Pin<Output<OTYPE>> {
+	type Error = Infallible;
+
+	fn is_high(&self) -> Result<bool, Infallible> {
+		Ok( self.input() )
+	}
+
+	fn is_low(&self) -> Result<bool, Infallible> {
+		Ok( !self.input() )
+	}
+}
+
+impl<OTYPE> OutputPin for Pin<Output<OTYPE>> {
+	type Error = Infallible;
+
+	fn set_high(&mut self) -> Result<(), Infallible> {
+		self.set();
+		Ok(())
 	}
-}
\ No newline at end of file
+
+	fn set_low(&mut self) -> Result<(), Infallible> {
+		self.reset();
+		Ok(())
+	}
+}
+
+impl<OTYPE> StatefulOutputPin for Pin<Output<OTYPE>> {
+	fn is_set_high(&self) -> Result<bool, Infallible> {
+		Ok( self.is_set() )
+	}
+
+	fn is_set_low(&self) -> Result<bool, Infallible> {
+		Ok( !self.is_set() )
+	}
+}
+
+impl<OTYPE> ToggleableOutputPin for Pin<Output<OTYPE>> {
+	type Error = Infallible;
+
+	fn toggle(&mut self) -> Result<(), Infallible> {
+		Pin::toggle(self);
+		Ok(())
+	}
+}
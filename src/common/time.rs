@@ -0,0 +1,296 @@
+//! Time units
+//!
+//! `Duration` is a span of time in nanoseconds, `Period` is the exact
+//! period of a `Frequency`. Both convert to and from clock cycles.
+
+#[cfg(feature = "std")]
+use std::ops;
+
+#[cfg(not(feature = "std"))]
+use core::ops;
+
+use super::frequency::Frequency;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Span of time, stored in nanoseconds
+/// The arithmetic saturates instead of overflowing
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+	ns: u64,
+}
+
+impl Duration {
+	/// Zero duration
+	pub const ZERO: Duration = Duration { ns: 0 };
+
+	/// Longest representable duration
+	pub const MAX: Duration = Duration { ns: u64::MAX };
+
+	pub const fn from_nanos(ns: u64) -> Duration {
+		Duration { ns }
+	}
+
+	pub const fn from_micros(us: u64) -> Duration {
+		Duration { ns: us.saturating_mul(1_000) }
+	}
+
+	pub const fn from_millis(ms: u64) -> Duration {
+		Duration { ns: ms.saturating_mul(1_000_000) }
+	}
+
+	pub const fn from_secs(s: u64) -> Duration {
+		Duration { ns: s.saturating_mul(NANOS_PER_SEC) }
+	}
+
+	pub const fn as_nanos(&self) -> u64 {
+		self.ns
+	}
+
+	/// Returns the duration in microseconds, rounded down
+	pub const fn as_micros(&self) -> u64 {
+		self.ns / 1_000
+	}
+
+	/// Returns the duration in milliseconds, rounded down
+	pub const fn as_millis(&self) -> u64 {
+		self.ns / 1_000_000
+	}
+
+	/// Returns the duration in seconds, rounded down
+	pub const fn as_secs(&self) -> u64 {
+		self.ns / NANOS_PER_SEC
+	}
+
+	/// Number of cycles of `freq` in this duration, rounded up
+	/// so waiting that many cycles never takes less than the duration
+	pub const fn to_cycles(&self, freq: Frequency) -> u64 {
+		let hz = freq.hz() as u64;
+		let secs = self.ns / NANOS_PER_SEC;
+		let rem = self.ns % NANOS_PER_SEC;
+
+		// rem * hz < 1e9 * 2^32, does not overflow
+		secs.saturating_mul(hz).saturating_add( (rem * hz).div_ceil(NANOS_PER_SEC) )
+	}
+
+	/// Duration of `cycles` cycles of `freq`, rounded down
+	/// Panics if `freq` is 0 Hz
+	pub const fn from_cycles(cycles: u64, freq: Frequency) -> Duration {
+		let hz = freq.hz() as u64;
+		let secs = cycles / hz;
+		let rem = cycles % hz;
+
+		// rem < 2^32, rem * 1e9 does not overflow
+		Duration { ns: secs.saturating_mul(NANOS_PER_SEC).saturating_add(rem * NANOS_PER_SEC / hz) }
+	}
+
+	pub const fn checked_add(&self, other: Duration) -> Option<Duration> {
+		match self.ns.checked_add(other.ns) {
+			Some(ns) => Some( Duration { ns } ),
+			None => None,
+		}
+	}
+
+	pub const fn checked_sub(&self, other: Duration) -> Option<Duration> {
+		match self.ns.checked_sub(other.ns) {
+			Some(ns) => Some( Duration { ns } ),
+			None => None,
+		}
+	}
+
+	pub const fn saturating_add(&self, other: Duration) -> Duration {
+		Duration { ns: self.ns.saturating_add(other.ns) }
+	}
+
+	pub const fn saturating_sub(&self, other: Duration) -> Duration {
+		Duration { ns: self.ns.saturating_sub(other.ns) }
+	}
+}
+
+impl ops::Add<Duration> for Duration {
+	type Output = Duration;
+
+	fn add(self, other: Duration) -> Duration {
+		self.saturating_add(other)
+	}
+}
+
+impl ops::Sub<Duration> for Duration {
+	type Output = Duration;
+
+	fn sub(self, other: Duration) -> Duration {
+		self.saturating_sub(other)
+	}
+}
+
+impl ops::Mul<u32> for Duration {
+	type Output = Duration;
+
+	fn mul(self, n: u32) -> Duration {
+		Duration { ns: self.ns.saturating_mul(n as u64) }
+	}
+}
+
+impl ops::Div<u32> for Duration {
+	type Output = Duration;
+
+	fn div(self, n: u32) -> Duration {
+		Duration { ns: self.ns / n as u64 }
+	}
+}
+
+
+/// Period of a frequency
+/// Kept as the frequency itself so no precision is lost
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Period {
+	freq: Frequency,
+}
+
+impl Period {
+	/// Period of `freq`
+	pub const fn of(freq: Frequency) -> Period {
+		Period { freq }
+	}
+
+	/// Returns the frequency of this period
+	pub const fn frequency(&self) -> Frequency {
+		self.freq
+	}
+
+	/// Returns the period in nanoseconds, rounded down
+	/// Panics if the frequency is 0 Hz
+	pub const fn as_nanos(&self) -> u64 {
+		NANOS_PER_SEC / self.freq.hz() as u64
+	}
+
+	/// Returns the duration of `n` periods
+	pub const fn times(&self, n: u64) -> Duration {
+		Duration::from_cycles(n, self.freq)
+	}
+
+	/// Returns the number of full periods in `d`
+	pub const fn count(&self, d: Duration) -> u64 {
+		let hz = self.freq.hz() as u64;
+		let secs = d.as_nanos() / NANOS_PER_SEC;
+		let rem = d.as_nanos() % NANOS_PER_SEC;
+
+		secs.saturating_mul(hz).saturating_add(rem * hz / NANOS_PER_SEC)
+	}
+}
+
+impl ops::Mul<u32> for Period {
+	type Output = Duration;
+
+	fn mul(self, n: u32) -> Duration {
+		self.times(n as u64)
+	}
+}
+
+impl From<Period> for Duration {
+	fn from(p: Period) -> Duration {
+		p.times(1)
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+
+	const NS: u128 = NANOS_PER_SEC as u128;
+
+	/// Saturates an exact result to the range of a `Duration`
+	fn saturate(x: u128) -> u64 {
+		x.min(u64::MAX as u128) as u64
+	}
+
+	#[test]
+	fn conversions_saturate_at_the_edge() {
+		assert_eq!(Duration::from_secs(u64::MAX / NANOS_PER_SEC).as_secs(), u64::MAX / NANOS_PER_SEC);
+		assert_eq!(Duration::from_secs(u64::MAX / NANOS_PER_SEC + 1), Duration::MAX);
+		assert_eq!(Duration::from_millis(u64::MAX), Duration::MAX);
+
+		assert_eq!(Duration::MAX.to_cycles(Frequency::Hz(u32::MAX)), u64::MAX);
+		assert_eq!(Duration::from_cycles(u64::MAX, Frequency::Hz(1)), Duration::MAX);
+		assert_eq!(Period::of(Frequency::Hz(u32::MAX)).count(Duration::MAX), u64::MAX);
+	}
+
+	#[test]
+	fn cycles_round_towards_the_duration() {
+		let f = Frequency::MHz(3);
+
+		// 1 us is 3 cycles, 1 ns is a third of a cycle
+		assert_eq!(Duration::from_micros(1).to_cycles(f), 3);
+		assert_eq!(Duration::from_nanos(1).to_cycles(f), 1);
+		assert_eq!(Duration::from_cycles(1, f), Duration::from_nanos(333));
+		assert_eq!(Period::of(f).as_nanos(), 333);
+		assert_eq!(Period::of(f).count(Duration::from_nanos(333)), 0);
+	}
+
+	proptest! {
+		#[test]
+		fn units_are_exact_until_they_saturate(x: u64) {
+			prop_assert_eq!(Duration::from_micros(x).as_nanos(), saturate(x as u128 * 1_000));
+			prop_assert_eq!(Duration::from_millis(x).as_nanos(), saturate(x as u128 * 1_000_000));
+			prop_assert_eq!(Duration::from_secs(x).as_nanos(), saturate(x as u128 * NS));
+		}
+
+		#[test]
+		fn units_round_down(ns: u64) {
+			let d = Duration::from_nanos(ns);
+
+			for (value, unit) in [(d.as_micros(), 1_000), (d.as_millis(), 1_000_000), (d.as_secs(), NS)] {
+				let value = value as u128;
+				prop_assert!(value * unit <= ns as u128 && (ns as u128) < (value + 1) * unit);
+			}
+		}
+
+		#[test]
+		fn to_cycles_rounds_up(ns: u64, hz: u32) {
+			let exact = (ns as u128 * hz as u128).div_ceil(NS);
+			prop_assert_eq!(Duration::from_nanos(ns).to_cycles(Frequency::Hz(hz)), saturate(exact));
+		}
+
+		#[test]
+		fn from_cycles_rounds_down(cycles: u64, hz in 1..=u32::MAX) {
+			let exact = cycles as u128 * NS / hz as u128;
+			prop_assert_eq!(Duration::from_cycles(cycles, Frequency::Hz(hz)).as_nanos(), saturate(exact));
+		}
+
+		#[test]
+		fn cycles_round_trip(ns in 0..u64::MAX / 8, cycles in 0..u64::MAX / 8, hz in 1..=u32::MAX) {
+			let f = Frequency::Hz(hz);
+			let d = Duration::from_nanos(ns);
+
+			// Waiting the cycles never takes less than the duration
+			prop_assert!(Duration::from_cycles(d.to_cycles(f), f) >= d);
+			prop_assert!(Duration::from_cycles(cycles, f).to_cycles(f) <= cycles);
+		}
+
+		#[test]
+		fn period_counts_full_periods(ns: u64, n in 0..u64::MAX / 8, hz in 1..=u32::MAX) {
+			let p = Period::of(Frequency::Hz(hz));
+
+			prop_assert_eq!(p.count(Duration::from_nanos(ns)), saturate(ns as u128 * hz as u128 / NS));
+			prop_assert_eq!(p.as_nanos(), NANOS_PER_SEC / hz as u64);
+
+			// `times` rounds down to the nanosecond, which holds several periods above 1 GHz
+			prop_assume!(n as u128 * NS / hz as u128 <= u64::MAX as u128);
+			let count = p.count(p.times(n));
+			prop_assert!(count <= n && count + 1 + hz as u64 / NANOS_PER_SEC >= n);
+		}
+
+		#[test]
+		fn operators_saturate(a: u64, b: u64, n: u32) {
+			let (da, db) = (Duration::from_nanos(a), Duration::from_nanos(b));
+
+			prop_assert_eq!((da + db).as_nanos(), a.saturating_add(b));
+			prop_assert_eq!((da - db).as_nanos(), a.saturating_sub(b));
+			prop_assert_eq!((da * n).as_nanos(), a.saturating_mul(n as u64));
+			prop_assert_eq!(da.checked_add(db).map(|d| d.as_nanos()), a.checked_add(b));
+			prop_assert_eq!(da.checked_sub(db).map(|d| d.as_nanos()), a.checked_sub(b));
+		}
+	}
+}
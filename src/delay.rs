@@ -0,0 +1,306 @@
+//! Blocking delays
+//!
+//! `embedded-hal` `DelayMs` and `DelayUs` on SysTick, on the DWT cycle counter
+//! and on a general purpose timer. All of them are calibrated with the `Clocks`
+//! given by `Rcc::freeze` and split long delays in as many counter periods as needed.
+//!
+//! ```ignore
+//! let mut delay = SysTickDelay::new(p.syst, &rcc.clocks());
+//! delay.delay_ms(1500u32);
+//! ```
+
+#[cfg(feature = "std")]
+use std::cmp;
+
+#[cfg(not(feature = "std"))]
+use core::cmp;
+
+use crate::common::{ Clocks, Duration, Frequency, PeripheralBus };
+use crate::peripherals::core::syst::{ SysTick, SysTClock };
+use crate::peripherals::extended::gptim::GpTim;
+
+#[cfg(not(armv6m))]
+use crate::peripherals::core::{ dcb::Dcb, dwt::Dwt };
+
+use embedded_hal::blocking::delay::{ DelayMs, DelayUs };
+
+/// Implements `DelayMs` and `DelayUs` for `u32`, `u16` and `u8` over `Self::delay`
+macro_rules! impl_delay {
+	($name:ident) => {
+		impl DelayMs<u32> for $name {
+			fn delay_ms(&mut self, ms: u32) {
+				self.delay( Duration::from_millis(ms as u64) );
+			}
+		}
+
+		impl DelayMs<u16> for $name {
+			fn delay_ms(&mut self, ms: u16) {
+				self.delay( Duration::from_millis(ms as u64) );
+			}
+		}
+
+		impl DelayMs<u8> for $name {
+			fn delay_ms(&mut self, ms: u8) {
+				self.delay( Duration::from_millis(ms as u64) );
+			}
+		}
+
+		impl DelayUs<u32> for $name {
+			fn delay_us(&mut self, us: u32) {
+				self.delay( Duration::from_micros(us as u64) );
+			}
+		}
+
+		impl DelayUs<u16> for $name {
+			fn delay_us(&mut self, us: u16) {
+				self.delay( Duration::from_micros(us as u64) );
+			}
+		}
+
+		impl DelayUs<u8> for $name {
+			fn delay_us(&mut self, us: u8) {
+				self.delay( Duration::from_micros(us as u64) );
+			}
+		}
+	};
+}
+
+
+/// Longest SysTick period, the reload value is 24-bit
+const SYST_MAX: u64 = 1 << 24;
+
+/// Delay on SysTick, running at the core clock
+pub struct SysTickDelay {
+	syst: &'static mut SysTick,
+	freq: Frequency,
+}
+
+impl SysTickDelay {
+	pub fn new(syst: &'static mut SysTick, clocks: &Clocks) -> Self {
+		syst.counter_state(false)
+			.set_clock_source(SysTClock::Core);
+
+		SysTickDelay { syst, freq: clocks.ahb1f }
+	}
+
+	/// Returns the SysTick
+	pub fn free(self) -> &'static mut SysTick {
+		self.syst
+	}
+
+	/// Waits for at least `d`
+	pub fn delay(&mut self, d: Duration) {
+		let mut cycles = d.to_cycles(self.freq);
+
+		// A reload value of 0 never wraps, shorter delays are already over
+		while cycles > 1 {
+			let n = cmp::min(cycles, SYST_MAX);
+
+			// Writing the current value also clears the wrap flag
+			self.syst.set_reload( (n - 1) as u32 )
+				.clear_current()
+				.counter_state(true);
+
+			while !self.syst.has_wrapped() {}
+
+			self.syst.counter_state(false);
+
+			cycles -= n;
+		}
+	}
+}
+
+impl_delay!(SysTickDelay);
+
+
+/// Delay on the DWT cycle counter
+#[cfg(not(armv6m))]
+pub struct DwtDelay {
+	dwt: &'static mut Dwt,
+	freq: Frequency,
+}
+
+#[cfg(not(armv6m))]
+impl DwtDelay {
+	/// Enables the trace and the cycle counter
+	pub fn new(dwt: &'static mut Dwt, dcb: &mut Dcb, clocks: &Clocks) -> Self {
+		dcb.enable_trace();
+		dwt.enable_cycle_counter();
+
+		DwtDelay { dwt, freq: clocks.ahb1f }
+	}
+
+	/// Returns the DWT
+	pub fn free(self) -> &'static mut Dwt {
+		self.dwt
+	}
+
+	/// Waits for at least `d`
+	pub fn delay(&mut self, d: Duration) {
+		let mut cycles = d.to_cycles(self.freq);
+		let mut start = self.dwt.get_cycle_count();
+
+		// Wait in chunks of less than half the counter range so a wrap is never missed
+		while cycles > 0 {
+			let n = cmp::min(cycles, 1 << 31) as u32;
+
+			while self.dwt.get_cycle_count().wrapping_sub(start) < n {}
+
+			start = start.wrapping_add(n);
+			cycles -= n as u64;
+		}
+	}
+}
+
+#[cfg(not(armv6m))]
+impl_delay!(DwtDelay);
+
+
+/// Delay on a general purpose timer, counting microseconds
+/// The timer must be enabled in the RCC
+pub struct TimDelay {
+	tim: &'static mut GpTim,
+}
+
+impl TimDelay {
+	/// Sets the timer on `bus` to count at 1 MHz in one-pulse mode
+	/// The timer clock must be a multiple of 1 MHz for the delays to be exact
+	pub fn new(tim: &'static mut GpTim, clocks: &Clocks, bus: PeripheralBus) -> Self {
+		let psc = GpTim::clock(clocks, bus).mhz().saturating_sub(1);
+
+		tim.counter_state(false)
+			.one_pulse_state(true)
+			.set_prescaler( cmp::min(psc, 0xFFFF) as u16 )
+			.update()
+			.clear_update();
+
+		TimDelay { tim }
+	}
+
+	/// Returns the timer
+	pub fn free(self) -> &'static mut GpTim {
+		self.tim
+	}
+
+	/// Waits for at least `d`, rounded up to a microsecond
+	pub fn delay(&mut self, d: Duration) {
+		let mut us = d.as_nanos().saturating_add(999) / 1_000;
+
+		// 16-bit auto-reload, an auto-reload of 0 does not count
+		while us > 0 {
+			let n = us.clamp(2, 1 << 16);
+
+			self.tim.set_autoreload( (n - 1) as u32 )
+				.set_counter(0)
+				.clear_update()
+				.counter_state(true);
+
+			while !self.tim.is_updated() {}
+
+			us = us.saturating_sub(n);
+		}
+
+		self.tim.clear_update();
+	}
+}
+
+impl_delay!(TimDelay);
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::peripherals::core::syst::{ self, CSR, RVR, CVR };
+	use crate::peripherals::extended::gptim::{ self, ARR, CNT, CR1, PSC };
+
+	fn clocks(ahb: u32) -> Clocks {
+		Clocks { ahb1f: Frequency::MHz(ahb), apb1f: Frequency::MHz(ahb / 2), apb2f: Frequency::MHz(ahb), ..Clocks::default() }
+	}
+
+	#[test]
+	fn systick_splits_long_delays_in_reloads() {
+		let block = SimBlock::<{ syst::SIZE }>::new();
+		// Wraps as soon as it's checked
+		block.on_read(CSR::INDEX, |v| v | (1 << 16));
+
+		let mut delay = SysTickDelay::new(unsafe { block.view() }, &clocks(1));
+		assert_eq!(block.peek(CSR::INDEX) & 0b101, 0b100);
+
+		// 40 s at 1 MHz, more than two full 24-bit periods
+		delay.delay_ms(40_000u32);
+
+		let rest = 40_000_000 - 2 * SYST_MAX as u32;
+		assert_eq!(block.writes(RVR::INDEX), [(1 << 24) - 1, (1 << 24) - 1, rest - 1]);
+		assert_eq!(block.writes(CVR::INDEX), [0, 0, 0]);
+		assert_eq!(block.peek(CSR::INDEX) & 1, 0);
+	}
+
+	#[test]
+	fn systick_skips_delays_of_a_cycle() {
+		let block = SimBlock::<{ syst::SIZE }>::new();
+		let mut delay = SysTickDelay::new(unsafe { block.view() }, &clocks(1));
+
+		delay.delay_us(0u32);
+		delay.delay_us(1u32);
+		assert!(block.writes(RVR::INDEX).is_empty());
+
+		block.on_read(CSR::INDEX, |v| v | (1 << 16));
+		delay.delay_us(2u32);
+		assert_eq!(block.writes(RVR::INDEX), [1]);
+	}
+
+	#[cfg(not(armv6m))]
+	#[test]
+	fn dwt_follows_the_cycle_counter_across_wraps() {
+		use std::{ cell::Cell, rc::Rc };
+		use crate::peripherals::core::{ dcb, dwt };
+
+		let dwt = SimBlock::<{ dwt::SIZE }>::new();
+		let dcb = SimBlock::<{ dcb::SIZE }>::new();
+
+		// CYCCNT about to wrap, counting 1 << 24 cycles per read
+		const STEP: u64 = 1 << 24;
+		let elapsed = Rc::new( Cell::new(0u64) );
+		let ticks = elapsed.clone();
+
+		dwt.poke(1, u32::MAX - 5);
+		dwt.on_read(1, move |v| {
+			ticks.set(ticks.get() + STEP);
+			v.wrapping_add(STEP as u32)
+		});
+
+		let mut delay = DwtDelay::new(unsafe { dwt.view() }, unsafe { dcb.view() }, &clocks(100));
+		assert_eq!(dwt.peek(0) & 1, 1);
+		assert_eq!(dcb.peek(3), 1 << 24);
+
+		// 50 s at 100 MHz, the counter wraps more than once
+		delay.delay_ms(50_000u32);
+
+		// The first read is the start
+		let cycles = 5_000_000_000;
+		let waited = elapsed.get() - STEP;
+		assert!(waited >= cycles && waited < cycles + STEP, "waited {} cycles", waited);
+	}
+
+	#[test]
+	fn tim_counts_microseconds_in_16_bit_periods() {
+		let (block, tim) = gptim::tests::tim();
+		// The update event is raised as soon as it's checked
+		block.on_read(gptim::SR::INDEX, |v| v | 1);
+
+		// 84 MHz timer clock on APB1 at 42 MHz
+		let mut delay = TimDelay::new(tim, &clocks(84), PeripheralBus::APB1);
+		assert_eq!(block.peek(PSC::INDEX), 83);
+		assert_eq!(block.peek(CR1::INDEX) & 0b1001, 0b1000);
+
+		delay.delay_us(1u32);
+		delay.delay_us(100u32);
+		delay.delay_ms(150u32);
+		delay.delay_us(65_537u32);
+
+		assert_eq!(block.writes(ARR::INDEX), [1, 99, 65_535, 65_535, 18_927, 65_535, 1]);
+		assert!(block.writes(CNT::INDEX).iter().all(|&cnt| cnt == 0));
+		assert_eq!(block.peek(gptim::SR::INDEX) & 1, 0);
+	}
+}
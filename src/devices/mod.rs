@@ -0,0 +1,84 @@
+//! Generated device definitions
+//!
+//! Every module is the output of `tools/svdgen` for the description of the device in
+//! `tools/svdgen/devices`, the enums `RCCPeripheral`, `EXTILine` and `Interrupt` of the
+//! selected device are re-exported through `common` and `interrupt`
+
+#[cfg(feature = "stm32f100")]
+mod stm32f100;
+
+#[cfg(all(feature = "stm32f1", not(feature = "stm32f100")))]
+mod stm32f103;
+
+#[cfg(feature = "stm32f401")]
+mod stm32f401;
+
+#[cfg(feature = "stm32f410")]
+mod stm32f410;
+
+#[cfg(feature = "stm32f411")]
+mod stm32f411;
+
+#[cfg(feature = "stm32f412")]
+mod stm32f412;
+
+#[cfg(feature = "stm32f4x3")]
+mod stm32f4x3;
+
+#[cfg(feature = "stm32f4x5")]
+mod stm32f4x5;
+
+#[cfg(feature = "stm32f4x7")]
+mod stm32f4x7;
+
+#[cfg(feature = "stm32f4x9")]
+mod stm32f4x9;
+
+#[cfg(feature = "stm32f446")]
+mod stm32f446;
+
+#[cfg(feature = "stm32f72x")]
+mod stm32f72x;
+
+#[cfg(feature = "stm32f73x")]
+mod stm32f73x;
+
+
+#[cfg(feature = "stm32f100")]
+pub use self::stm32f100::*;
+
+#[cfg(all(feature = "stm32f1", not(feature = "stm32f100")))]
+pub use self::stm32f103::*;
+
+#[cfg(feature = "stm32f401")]
+pub use self::stm32f401::*;
+
+#[cfg(feature = "stm32f410")]
+pub use self::stm32f410::*;
+
+#[cfg(feature = "stm32f411")]
+pub use self::stm32f411::*;
+
+#[cfg(feature = "stm32f412")]
+pub use self::stm32f412::*;
+
+#[cfg(feature = "stm32f4x3")]
+pub use self::stm32f4x3::*;
+
+#[cfg(feature = "stm32f4x5")]
+pub use self::stm32f4x5::*;
+
+#[cfg(feature = "stm32f4x7")]
+pub use self::stm32f4x7::*;
+
+#[cfg(feature = "stm32f4x9")]
+pub use self::stm32f4x9::*;
+
+#[cfg(feature = "stm32f446")]
+pub use self::stm32f446::*;
+
+#[cfg(feature = "stm32f72x")]
+pub use self::stm32f72x::*;
+
+#[cfg(feature = "stm32f73x")]
+pub use self::stm32f73x::*;
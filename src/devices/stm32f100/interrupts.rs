@@ -0,0 +1,56 @@
+//! Interrupts of the STM32F100
+//! Generated by `svdgen`, do not edit
+
+/// Device interrupts
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interrupt {
+	WWDG = 0,
+	PVD = 1,
+	TAMPER = 2,
+	RTC = 3,
+	FLASH = 4,
+	RCC = 5,
+	EXTI0 = 6,
+	EXTI1 = 7,
+	EXTI2 = 8,
+	EXTI3 = 9,
+	EXTI4 = 10,
+	DMA1_Channel1 = 11,
+	DMA1_Channel2 = 12,
+	DMA1_Channel3 = 13,
+	DMA1_Channel4 = 14,
+	DMA1_Channel5 = 15,
+	DMA1_Channel6 = 16,
+	DMA1_Channel7 = 17,
+	ADC1 = 18,
+	EXTI9_5 = 23,
+	TIM1_BRK_TIM15 = 24,
+	TIM1_UP_TIM16 = 25,
+	TIM1_TRG_COM_TIM17 = 26,
+	TIM1_CC = 27,
+	TIM2 = 28,
+	TIM3 = 29,
+	TIM4 = 30,
+	I2C1_EV = 31,
+	I2C1_ER = 32,
+	I2C2_EV = 33,
+	I2C2_ER = 34,
+	SPI1 = 35,
+	SPI2 = 36,
+	USART1 = 37,
+	USART2 = 38,
+	USART3 = 39,
+	EXTI15_10 = 40,
+	RTCAlarm = 41,
+	CEC = 42,
+	TIM6_DAC = 54,
+	TIM7 = 55,
+}
+
+unsafe impl crate::interrupt::Nr for Interrupt {
+	#[inline]
+	fn nr(&self) -> u8 {
+		*self as u8
+	}
+}
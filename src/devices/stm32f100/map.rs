@@ -0,0 +1,6 @@
+//! Memory map of the STM32F100
+//! Generated by `svdgen`, do not edit
+
+pub const EXTI: u32 = 0x4001_0400;
+pub const RCC: u32 = 0x4002_1000;
+pub const NVIC: u32 = 0xE000_E100;
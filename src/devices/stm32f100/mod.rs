@@ -0,0 +1,13 @@
+//! Generated definitions of the STM32F100
+//! Generated by `svdgen`, do not edit
+
+pub mod map;
+pub mod registers;
+
+mod extilines;
+mod interrupts;
+mod rcc;
+
+pub use self::extilines::*;
+pub use self::interrupts::*;
+pub use self::rcc::*;
@@ -0,0 +1,56 @@
+//! Registers of the STM32F100
+//! Generated by `svdgen`, do not edit
+
+pub mod rcc {
+	registers! {
+		APB2RSTR @ 3, reset = 0x0000_0000 {
+			afiorst: 0, 1, RW;
+			ioparst: 2, 1, RW;
+			iopbrst: 3, 1, RW;
+			iopcrst: 4, 1, RW;
+			iopdrst: 5, 1, RW;
+			ioperst: 6, 1, RW;
+			adc1rst: 9, 1, RW;
+			tim1rst: 11, 1, RW;
+			spi1rst: 12, 1, RW;
+			usart1rst: 14, 1, RW;
+			tim15rst: 16, 1, RW;
+			tim16rst: 17, 1, RW;
+			tim17rst: 18, 1, RW;
+		}
+
+		APB1RSTR @ 4, reset = 0x0000_0000 {
+			tim2rst: 0, 1, RW;
+			tim3rst: 1, 1, RW;
+			tim4rst: 2, 1, RW;
+			tim6rst: 4, 1, RW;
+			tim7rst: 5, 1, RW;
+			wwdgrst: 11, 1, RW;
+			spi2rst: 14, 1, RW;
+			usart2rst: 17, 1, RW;
+			usart3rst: 18, 1, RW;
+			i2c1rst: 21, 1, RW;
+			i2c2rst: 22, 1, RW;
+			bkprst: 27, 1, RW;
+			pwrrst: 28, 1, RW;
+			dacrst: 29, 1, RW;
+			cecrst: 30, 1, RW;
+		}
+
+		AHBENR @ 5, reset = 0x0000_0014 {
+			dma1en: 0, 1, RW;
+			sramen: 2, 1, RW;
+			flitfen: 4, 1, RW;
+			crcen: 6, 1, RW;
+		}
+	}
+}
+
+pub mod exti {
+	registers! {
+		IMR @ 0, reset = 0x0000_0000 {
+			mr[18]: 0, 1, RW;
+		}
+	}
+}
+
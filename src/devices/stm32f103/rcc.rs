@@ -0,0 +1,39 @@
+//! RCC controlled peripherals of the STM32F103
+//! Generated by `svdgen`, do not edit
+
+/// Peripherals
+/// Encoded as `(register index << 8) | bit`, the register is the reset register
+/// of the bus, or the enable register if the bus has no reset register
+#[derive(Debug, Copy, Clone)]
+pub enum RCCPeripheral {
+	USART1  = 782,	// USART1  @ APB2 14
+	SPI1    = 780,	// SPI1    @ APB2 12
+	TIM1    = 779,	// TIM1    @ APB2 11
+	ADC2    = 778,	// ADC2    @ APB2 10
+	ADC1    = 777,	// ADC1    @ APB2  9
+	IOPE    = 774,	// IOPE    @ APB2  6
+	IOPD    = 773,	// IOPD    @ APB2  5
+	IOPC    = 772,	// IOPC    @ APB2  4
+	IOPB    = 771,	// IOPB    @ APB2  3
+	IOPA    = 770,	// IOPA    @ APB2  2
+	AFIO    = 768,	// AFIO    @ APB2  0
+
+	PWR     = 1052,	// PWR     @ APB1 28
+	BKP     = 1051,	// BKP     @ APB1 27
+	CAN     = 1049,	// CAN     @ APB1 25
+	USB     = 1047,	// USB     @ APB1 23
+	I2C2    = 1046,	// I2C2    @ APB1 22
+	I2C1    = 1045,	// I2C1    @ APB1 21
+	USART3  = 1042,	// USART3  @ APB1 18
+	USART2  = 1041,	// USART2  @ APB1 17
+	SPI2    = 1038,	// SPI2    @ APB1 14
+	WWDG    = 1035,	// WWDG    @ APB1 11
+	TIM4    = 1026,	// TIM4    @ APB1  2
+	TIM3    = 1025,	// TIM3    @ APB1  1
+	TIM2    = 1024,	// TIM2    @ APB1  0
+
+	CRC     = 1286,	// CRC     @ AHB  6
+	FLITF   = 1284,	// FLITF   @ AHB  4
+	SRAM    = 1282,	// SRAM    @ AHB  2
+	DMA1    = 1280,	// DMA1    @ AHB  0
+}
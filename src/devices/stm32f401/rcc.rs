@@ -0,0 +1,45 @@
+//! RCC controlled peripherals of the STM32F401
+//! Generated by `svdgen`, do not edit
+
+/// Peripherals
+/// Encoded as `(register index << 8) | bit`, the register is the reset register
+/// of the bus, or the enable register if the bus has no reset register
+#[derive(Debug, Copy, Clone)]
+pub enum RCCPeripheral {
+	DMA2    = 1046,	// DMA2    @ AHB1 22
+	DMA1    = 1045,	// DMA1    @ AHB1 21
+	CRC     = 1036,	// CRC     @ AHB1 12
+	GPIOH   = 1031,	// GPIOH   @ AHB1  7
+	GPIOE   = 1028,	// GPIOE   @ AHB1  4
+	GPIOD   = 1027,	// GPIOD   @ AHB1  3
+	GPIOC   = 1026,	// GPIOC   @ AHB1  2
+	GPIOB   = 1025,	// GPIOB   @ AHB1  1
+	GPIOA   = 1024,	// GPIOA   @ AHB1  0
+
+	OTGFS   = 1287,	// OTGFS   @ AHB2  7
+
+	PWR     = 2076,	// PWR     @ APB1 28
+	I2C3    = 2071,	// I2C3    @ APB1 23
+	I2C2    = 2070,	// I2C2    @ APB1 22
+	I2C1    = 2069,	// I2C1    @ APB1 21
+	USART2  = 2065,	// USART2  @ APB1 17
+	SPI3    = 2063,	// SPI3    @ APB1 15
+	SPI2    = 2062,	// SPI2    @ APB1 14
+	WWDG    = 2059,	// WWDG    @ APB1 11
+	TIM5    = 2051,	// TIM5    @ APB1  3
+	TIM4    = 2050,	// TIM4    @ APB1  2
+	TIM3    = 2049,	// TIM3    @ APB1  1
+	TIM2    = 2048,	// TIM2    @ APB1  0
+
+	TIM11   = 2322,	// TIM11   @ APB2 18
+	TIM10   = 2321,	// TIM10   @ APB2 17
+	TIM9    = 2320,	// TIM9    @ APB2 16
+	SYSCFG  = 2318,	// SYSCFG  @ APB2 14
+	SPI4    = 2317,	// SPI4    @ APB2 13
+	SPI1    = 2316,	// SPI1    @ APB2 12
+	SDIO    = 2315,	// SDIO    @ APB2 11
+	ADC     = 2312,	// ADC     @ APB2  8
+	USART6  = 2309,	// USART6  @ APB2  5
+	USART1  = 2308,	// USART1  @ APB2  4
+	TIM1    = 2304,	// TIM1    @ APB2  0
+}
@@ -0,0 +1,67 @@
+//! Interrupts of the STM32F410
+//! Generated by `svdgen`, do not edit
+
+/// Device interrupts
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interrupt {
+	WWDG = 0,
+	PVD = 1,
+	TAMP_STAMP = 2,
+	RTC_WKUP = 3,
+	FLASH = 4,
+	RCC = 5,
+	EXTI0 = 6,
+	EXTI1 = 7,
+	EXTI2 = 8,
+	EXTI3 = 9,
+	EXTI4 = 10,
+	DMA1_Stream0 = 11,
+	DMA1_Stream1 = 12,
+	DMA1_Stream2 = 13,
+	DMA1_Stream3 = 14,
+	DMA1_Stream4 = 15,
+	DMA1_Stream5 = 16,
+	DMA1_Stream6 = 17,
+	ADC = 18,
+	EXTI9_5 = 23,
+	TIM1_BRK_TIM9 = 24,
+	TIM1_UP = 25,
+	TIM1_TRG_COM_TIM11 = 26,
+	TIM1_CC = 27,
+	I2C1_EV = 31,
+	I2C1_ER = 32,
+	I2C2_EV = 33,
+	I2C2_ER = 34,
+	SPI1 = 35,
+	SPI2 = 36,
+	USART1 = 37,
+	USART2 = 38,
+	EXTI15_10 = 40,
+	RTC_Alarm = 41,
+	DMA1_Stream7 = 47,
+	TIM5 = 50,
+	TIM6_DAC = 54,
+	DMA2_Stream0 = 56,
+	DMA2_Stream1 = 57,
+	DMA2_Stream2 = 58,
+	DMA2_Stream3 = 59,
+	DMA2_Stream4 = 60,
+	DMA2_Stream5 = 68,
+	DMA2_Stream6 = 69,
+	DMA2_Stream7 = 70,
+	USART6 = 71,
+	RNG = 80,
+	FPU = 81,
+	SPI5 = 85,
+	FMPI2C1_EV = 95,
+	FMPI2C1_ER = 96,
+	LPTIM1 = 97,
+}
+
+unsafe impl crate::interrupt::Nr for Interrupt {
+	#[inline]
+	fn nr(&self) -> u8 {
+		*self as u8
+	}
+}
@@ -0,0 +1,39 @@
+//! RCC controlled peripherals of the STM32F410
+//! Generated by `svdgen`, do not edit
+
+/// Peripherals
+/// Encoded as `(register index << 8) | bit`, the register is the reset register
+/// of the bus, or the enable register if the bus has no reset register
+#[derive(Debug, Copy, Clone)]
+pub enum RCCPeripheral {
+	RNG     = 1055,	// RNG     @ AHB1 31
+	DMA2    = 1046,	// DMA2    @ AHB1 22
+	DMA1    = 1045,	// DMA1    @ AHB1 21
+	CRC     = 1036,	// CRC     @ AHB1 12
+	GPIOH   = 1031,	// GPIOH   @ AHB1  7
+	GPIOC   = 1026,	// GPIOC   @ AHB1  2
+	GPIOB   = 1025,	// GPIOB   @ AHB1  1
+	GPIOA   = 1024,	// GPIOA   @ AHB1  0
+
+	DAC     = 2077,	// DAC     @ APB1 29
+	PWR     = 2076,	// PWR     @ APB1 28
+	FMPI2C1 = 2072,	// FMPI2C1 @ APB1 24
+	I2C2    = 2070,	// I2C2    @ APB1 22
+	I2C1    = 2069,	// I2C1    @ APB1 21
+	USART2  = 2065,	// USART2  @ APB1 17
+	SPI2    = 2062,	// SPI2    @ APB1 14
+	WWDG    = 2059,	// WWDG    @ APB1 11
+	LPTIM1  = 2057,	// LPTIM1  @ APB1  9
+	TIM6    = 2052,	// TIM6    @ APB1  4
+	TIM5    = 2051,	// TIM5    @ APB1  3
+
+	SPI5    = 2324,	// SPI5    @ APB2 20
+	TIM11   = 2322,	// TIM11   @ APB2 18
+	TIM9    = 2320,	// TIM9    @ APB2 16
+	SYSCFG  = 2318,	// SYSCFG  @ APB2 14
+	SPI1    = 2316,	// SPI1    @ APB2 12
+	ADC     = 2312,	// ADC     @ APB2  8
+	USART6  = 2309,	// USART6  @ APB2  5
+	USART1  = 2308,	// USART1  @ APB2  4
+	TIM1    = 2304,	// TIM1    @ APB2  0
+}
@@ -0,0 +1,72 @@
+//! Registers of the STM32F410
+//! Generated by `svdgen`, do not edit
+
+pub mod rcc {
+	registers! {
+		AHB1RSTR @ 4, reset = 0x0000_0000 {
+			gpioarst: 0, 1, RW;
+			gpiobrst: 1, 1, RW;
+			gpiocrst: 2, 1, RW;
+			gpiohrst: 7, 1, RW;
+			crcrst: 12, 1, RW;
+			dma1rst: 21, 1, RW;
+			dma2rst: 22, 1, RW;
+			rngrst: 31, 1, RW;
+		}
+
+		APB1RSTR @ 8, reset = 0x0000_0000 {
+			tim5rst: 3, 1, RW;
+			tim6rst: 4, 1, RW;
+			lptim1rst: 9, 1, RW;
+			wwdgrst: 11, 1, RW;
+			spi2rst: 14, 1, RW;
+			usart2rst: 17, 1, RW;
+			i2c1rst: 21, 1, RW;
+			i2c2rst: 22, 1, RW;
+			fmpi2c1rst: 24, 1, RW;
+			pwrrst: 28, 1, RW;
+			dacrst: 29, 1, RW;
+		}
+
+		APB2RSTR @ 9, reset = 0x0000_0000 {
+			tim1rst: 0, 1, RW;
+			usart1rst: 4, 1, RW;
+			usart6rst: 5, 1, RW;
+			adcrst: 8, 1, RW;
+			spi1rst: 12, 1, RW;
+			syscfgrst: 14, 1, RW;
+			tim9rst: 16, 1, RW;
+			tim11rst: 18, 1, RW;
+			spi5rst: 20, 1, RW;
+		}
+	}
+}
+
+pub mod exti {
+	registers! {
+		IMR @ 0, reset = 0x0000_0000 {
+			mr0: 0, 1, RW;
+			mr1: 1, 1, RW;
+			mr2: 2, 1, RW;
+			mr3: 3, 1, RW;
+			mr4: 4, 1, RW;
+			mr5: 5, 1, RW;
+			mr6: 6, 1, RW;
+			mr7: 7, 1, RW;
+			mr8: 8, 1, RW;
+			mr9: 9, 1, RW;
+			mr10: 10, 1, RW;
+			mr11: 11, 1, RW;
+			mr12: 12, 1, RW;
+			mr13: 13, 1, RW;
+			mr14: 14, 1, RW;
+			mr15: 15, 1, RW;
+			mr16: 16, 1, RW;
+			mr17: 17, 1, RW;
+			mr18: 18, 1, RW;
+			mr21: 21, 1, RW;
+			mr22: 22, 1, RW;
+		}
+	}
+}
+
@@ -0,0 +1,6 @@
+//! Memory map of the STM32F412
+//! Generated by `svdgen`, do not edit
+
+pub const EXTI: u32 = 0x4001_3C00;
+pub const RCC: u32 = 0x4002_3800;
+pub const NVIC: u32 = 0xE000_E100;
@@ -0,0 +1,82 @@
+//! Registers of the STM32F405
+//! Generated by `svdgen`, do not edit
+
+pub mod rcc {
+	registers! {
+		AHB1RSTR @ 4, reset = 0x0000_0000 {
+			gpioarst: 0, 1, RW;
+			gpiobrst: 1, 1, RW;
+			gpiocrst: 2, 1, RW;
+			gpiodrst: 3, 1, RW;
+			gpioerst: 4, 1, RW;
+			gpiofrst: 5, 1, RW;
+			gpiogrst: 6, 1, RW;
+			gpiohrst: 7, 1, RW;
+			gpioirst: 8, 1, RW;
+			crcrst: 12, 1, RW;
+			dma1rst: 21, 1, RW;
+			dma2rst: 22, 1, RW;
+			otghsrst: 29, 1, RW;
+		}
+
+		AHB2RSTR @ 5, reset = 0x0000_0000 {
+			cryprst: 4, 1, RW;
+			hashrst: 5, 1, RW;
+			rngrst: 6, 1, RW;
+			otgfsrst: 7, 1, RW;
+		}
+
+		AHB3RSTR @ 6, reset = 0x0000_0000 {
+			fsmcrst: 0, 1, RW;
+		}
+
+		APB1RSTR @ 8, reset = 0x0000_0000 {
+			tim2rst: 0, 1, RW;
+			tim3rst: 1, 1, RW;
+			tim4rst: 2, 1, RW;
+			tim5rst: 3, 1, RW;
+			tim6rst: 4, 1, RW;
+			tim7rst: 5, 1, RW;
+			tim12rst: 6, 1, RW;
+			tim13rst: 7, 1, RW;
+			tim14rst: 8, 1, RW;
+			wwdgrst: 11, 1, RW;
+			spi2rst: 14, 1, RW;
+			spi3rst: 15, 1, RW;
+			usart2rst: 17, 1, RW;
+			usart3rst: 18, 1, RW;
+			uart4rst: 19, 1, RW;
+			uart5rst: 20, 1, RW;
+			i2c1rst: 21, 1, RW;
+			i2c2rst: 22, 1, RW;
+			i2c3rst: 23, 1, RW;
+			can1rst: 25, 1, RW;
+			can2rst: 26, 1, RW;
+			pwrrst: 28, 1, RW;
+			dacrst: 29, 1, RW;
+		}
+
+		APB2RSTR @ 9, reset = 0x0000_0000 {
+			tim1rst: 0, 1, RW;
+			tim8rst: 1, 1, RW;
+			usart1rst: 4, 1, RW;
+			usart6rst: 5, 1, RW;
+			adcrst: 8, 1, RW;
+			sdiorst: 11, 1, RW;
+			spi1rst: 12, 1, RW;
+			syscfgrst: 14, 1, RW;
+			tim9rst: 16, 1, RW;
+			tim10rst: 17, 1, RW;
+			tim11rst: 18, 1, RW;
+		}
+	}
+}
+
+pub mod exti {
+	registers! {
+		IMR @ 0, reset = 0x0000_0000 {
+			mr[23]: 0, 1, RW;
+		}
+	}
+}
+
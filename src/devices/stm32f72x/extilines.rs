@@ -1,7 +1,7 @@
-//! STM32 Line F7
+//! EXTI lines of the STM32F722
+//! Generated by `svdgen`, do not edit
 
-/// Full EXTI 
-#[cfg(any(feature = "stm32f7"))]
+/// EXTI lines
 #[derive(Debug, Copy, Clone)]
 pub enum EXTILine {
 	Line0  =  0,
@@ -28,7 +28,4 @@ pub enum EXTILine {
 	Line21 = 21,
 	Line22 = 22,
 	Line23 = 23,
-	#[cfg(any(feature = "stm32f76", feature = "stm32f77"))]
-	Line24 = 24,
 }
-
@@ -1,8 +1,116 @@
 //! Interrupts
+//!
+//! Critical sections and data shared with interrupt handlers
+//!
+//! ```ignore
+//! static COUNTER: Mutex<RefCell<u32>> = Mutex::new( RefCell::new(0) );
+//!
+//! interrupt::free(|cs| *COUNTER.borrow(cs).borrow_mut() += 1);
+//! ```
 
-// use core::sync::atomic::{self, Ordering};
+#[cfg(feature = "std")]
+use std::cell::UnsafeCell;
 
-pub use bare_metal::{CriticalSection, Mutex, Nr};
+#[cfg(not(feature = "std"))]
+use core::cell::UnsafeCell;
+
+use crate::register::primask;
+#[cfg(not(armv6m))]
+use crate::register::{ basepri, basepri_max };
+
+
+/// Critical section token
+/// Proves that the interrupts are disabled while it lives
+pub struct CriticalSection {
+	_0: (),
+}
+
+impl CriticalSection {
+	/// Creates a critical section token
+	///
+	/// # Safety
+	///
+	/// Must only be created while the interrupts are disabled
+	#[inline]
+	pub unsafe fn new() -> Self {
+		CriticalSection { _0: () }
+	}
+}
+
+/// Data that can only be accessed inside a critical section
+/// Use it with a `Cell` or `RefCell` to get mutable access
+pub struct Mutex<T> {
+	inner: UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+	/// Creates a new mutex
+	pub const fn new(value: T) -> Self {
+		Mutex { inner: UnsafeCell::new(value) }
+	}
+
+	/// Borrows the data for the duration of the critical section
+	#[inline]
+	pub fn borrow<'cs>(&'cs self, _cs: &'cs CriticalSection) -> &'cs T {
+		unsafe { &*self.inner.get() }
+	}
+
+	/// Returns the data
+	pub fn into_inner(self) -> T {
+		self.inner.into_inner()
+	}
+}
+
+// Accesses are serialized by the critical section
+unsafe impl<T> Sync for Mutex<T> where T: Send {}
+
+// Generated per device, see `devices`
+pub use crate::devices::Interrupt;
+
+/// Interrupt number
+///
+/// # Safety
+///
+/// `nr` must return a valid interrupt number of the device
+pub unsafe trait Nr {
+	/// Returns the number of the interrupt
+	fn nr(&self) -> u8;
+}
+
+/// Runs `f` in a critical section
+/// The interrupts are disabled and, if they were active, enabled again afterwards
+#[inline]
+pub fn free<F, R>(f: F) -> R where F: FnOnce(&CriticalSection) -> R {
+	let primask = primask::read();
+
+	disable();
+
+	let r = f( unsafe { &CriticalSection::new() } );
+
+	if primask.is_active() {
+		unsafe { enable() }
+	}
+
+	r
+}
+
+/// Runs `f` with BASEPRI raised to `priority`, the previous BASEPRI is restored afterwards
+/// Only interrupts with a priority lower or equal than `priority` are masked, so this
+/// does not give a `CriticalSection`. `priority` uses the hardware encoding (e.g. `2 << 4`)
+/// and a `priority` lower than the current BASEPRI does nothing
+#[cfg(not(armv6m))]
+#[inline]
+pub fn ceiling<F, R>(priority: u8, f: F) -> R where F: FnOnce() -> R {
+	let old = basepri::read();
+
+	basepri_max::write(priority);
+
+	let r = f();
+
+	unsafe { basepri::write(old) }
+
+	r
+}
 
 /// Disables all interrupts
 #[inline]
@@ -23,7 +131,11 @@ pub fn disable() {
 			__cpsid();
 		},
 
-		#[cfg(not(cortex_m))]
+		// The simulated backend has no interrupts
+		#[cfg(all(not(cortex_m), feature = "sim"))]
+		() => (),
+
+		#[cfg(all(not(cortex_m), not(feature = "sim")))]
 		() => unimplemented!(),
 	}
 }
@@ -49,7 +161,10 @@ pub unsafe fn enable() {
 			__cpsie();
 		}
 
-		#[cfg(not(cortex_m))]
+		#[cfg(all(not(cortex_m), feature = "sim"))]
+		() => (),
+
+		#[cfg(all(not(cortex_m), not(feature = "sim")))]
 		() => unimplemented!(),
 	}
 }
@@ -2,13 +2,21 @@
 
 #![cfg_attr(feature = "inline-asm", feature(asm))]
 
-#![feature(const_fn)]
 
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Docs use tabs like the code, and `clockspeed::clockspeed` selects the device limits
+#![allow(clippy::tabs_in_doc_comments, clippy::module_inception)]
 
 
 #[macro_use]
 pub mod common;
 
+pub mod bitbang;
+pub mod delay;
+pub mod devices;
+pub mod interrupt;
 pub mod peripherals;
 pub mod register;
+
+pub use crate::common::Error;
@@ -0,0 +1,244 @@
+//! Alternate function tables
+//!
+//! Maps the pins of each device family to the peripheral signals they can carry,
+//! from the alternate function mapping tables of the datasheets. The drivers use
+//! them to check the pins they are given.
+//!
+//! `lookup` is a `const fn`, so a pin assignment known at compile time can be
+//! checked at compile time:
+//!
+//! ```ignore
+//! const SCL_AF: u32 = match af::lookup(Port::B, 6, Signal::I2C1_SCL) {
+//! 	Some(af) => af,
+//! 	None => panic!("PB6 is not I2C1_SCL"),
+//! };
+//! ```
+//!
+//! The STM32F1 has no alternate function selection, its peripherals are remapped
+//! by the AFIO. Its table lists a pin once for each remap that routes a signal to it,
+//! `functions` returns all of them.
+
+/// Builds a table from `Port pin: AF => Signal` entries
+/// An entry can be gated with `#[cfg(..)]` to the devices that have it
+macro_rules! af_table {
+	($($(#[$attr:meta])* $port:ident $pin:literal: $af:literal => $signal:ident,)+) => {
+		pub const TABLE: &[super::AfEntry] = &[
+			$(
+				$(#[$attr])*
+				super::AfEntry {
+					port: super::Port::$port,
+					pin: $pin,
+					af: $af,
+					signal: super::Signal::$signal,
+				},
+			)+
+		];
+	};
+}
+
+#[cfg(feature = "stm32f0")]
+mod stm32f0;
+
+#[cfg(feature = "stm32f1")]
+mod stm32f1;
+
+#[cfg(feature = "stm32f4")]
+mod stm32f4;
+
+#[cfg(feature = "stm32f7")]
+mod stm32f7;
+
+
+#[cfg(feature = "stm32f0")]
+pub use self::stm32f0::TABLE;
+
+#[cfg(feature = "stm32f1")]
+pub use self::stm32f1::TABLE;
+
+#[cfg(feature = "stm32f4")]
+pub use self::stm32f4::TABLE;
+
+#[cfg(feature = "stm32f7")]
+pub use self::stm32f7::TABLE;
+
+
+/// GPIO ports
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Port {
+	A = 0,
+	B = 1,
+	C = 2,
+	D = 3,
+	E = 4,
+	F = 5,
+	G = 6,
+	H = 7,
+	I = 8,
+	J = 9,
+	K = 10,
+}
+
+/// Peripheral signals that can be carried by a pin
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Signal {
+	I2C1_SCL, I2C1_SDA,
+	I2C2_SCL, I2C2_SDA,
+	I2C3_SCL, I2C3_SDA,
+
+	SPI1_NSS, SPI1_SCK, SPI1_MISO, SPI1_MOSI,
+	SPI2_NSS, SPI2_SCK, SPI2_MISO, SPI2_MOSI,
+	SPI3_NSS, SPI3_SCK, SPI3_MISO, SPI3_MOSI,
+	SPI4_NSS, SPI4_SCK, SPI4_MISO, SPI4_MOSI,
+	SPI5_NSS, SPI5_SCK, SPI5_MISO, SPI5_MOSI,
+
+	USART1_TX, USART1_RX,
+	USART2_TX, USART2_RX,
+	USART3_TX, USART3_RX,
+	UART4_TX,  UART4_RX,
+	UART5_TX,  UART5_RX,
+	USART6_TX, USART6_RX,
+
+	TIM1_CH1, TIM1_CH2, TIM1_CH3, TIM1_CH4,
+	TIM2_CH1, TIM2_CH2, TIM2_CH3, TIM2_CH4,
+	TIM3_CH1, TIM3_CH2, TIM3_CH3, TIM3_CH4,
+	TIM4_CH1, TIM4_CH2, TIM4_CH3, TIM4_CH4,
+
+	MCO1, MCO2,
+}
+
+impl Signal {
+	/// Checks if the signal is an input of its peripheral
+	pub const fn is_input(self) -> bool {
+		matches!(self,
+			Signal::SPI1_MISO | Signal::SPI2_MISO | Signal::SPI3_MISO |
+			Signal::SPI4_MISO | Signal::SPI5_MISO |
+			Signal::USART1_RX | Signal::USART2_RX | Signal::USART3_RX |
+			Signal::UART4_RX  | Signal::UART5_RX  | Signal::USART6_RX
+		)
+	}
+}
+
+/// `pin` of `port` carries `signal` on the alternate function `af`
+#[derive(Debug, Copy, Clone)]
+pub struct AfEntry {
+	pub port: Port,
+	pub pin: u8,
+	pub af: u8,
+	pub signal: Signal,
+}
+
+/// Returns the alternate function with which `pin` of `port` carries `signal`
+/// `None` if the pin cannot carry it, the lowest remap on the STM32F1
+pub const fn lookup(port: Port, pin: u8, signal: Signal) -> Option<u32> {
+	match functions(port, pin, signal) {
+		0 => None,
+		mask => Some(mask.trailing_zeros()),
+	}
+}
+
+/// Returns the alternate functions with which `pin` of `port` carries `signal`,
+/// as a mask with the bit of each function set
+/// 0 if the pin cannot carry it
+pub const fn functions(port: Port, pin: u8, signal: Signal) -> u32 {
+	let mut mask = 0;
+	let mut i = 0;
+
+	while i < TABLE.len() {
+		let e = &TABLE[i];
+
+		if e.port as u8 == port as u8 && e.pin == pin && e.signal as u8 == signal as u8 {
+			mask |= 1 << e.af;
+		}
+
+		i += 1;
+	}
+
+	mask
+}
+
+/// Returns the port with the given number (0 for port A)
+pub const fn port(n: u32) -> Option<Port> {
+	match n {
+		0 => Some(Port::A),
+		1 => Some(Port::B),
+		2 => Some(Port::C),
+		3 => Some(Port::D),
+		4 => Some(Port::E),
+		5 => Some(Port::F),
+		6 => Some(Port::G),
+		7 => Some(Port::H),
+		8 => Some(Port::I),
+		9 => Some(Port::J),
+		10 => Some(Port::K),
+		_ => None,
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn a_pin_carries_a_signal_on_one_function() {
+		for (i, a) in TABLE.iter().enumerate() {
+			for b in &TABLE[i + 1..] {
+				// The remaps of the STM32F1 can route a signal to the same pin
+				let same = a.port == b.port && a.pin == b.pin && a.signal == b.signal;
+				let twice = same && (cfg!(not(feature = "stm32f1")) || a.af == b.af);
+
+				assert!(!twice, "{:?} listed twice", a);
+			}
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f1")]
+	fn partial_remaps_share_pins() {
+		// TIM2_CH3 is on PA2 without remap and with the partial remap 1
+		assert_eq!(functions(Port::A, 2, Signal::TIM2_CH3), 0b0011);
+		assert_eq!(functions(Port::B, 10, Signal::TIM2_CH3), 0b1100);
+		assert_eq!(lookup(Port::B, 10, Signal::TIM2_CH3), Some(2));
+
+		// TIM3_CH3 is on PB0 unless fully remapped
+		assert_eq!(functions(Port::B, 0, Signal::TIM3_CH3), 0b0101);
+
+		// SPI1 is either on PA5 or on PB3
+		assert_eq!(functions(Port::A, 5, Signal::SPI1_SCK), 0b01);
+		assert_eq!(functions(Port::B, 3, Signal::SPI1_SCK), 0b10);
+		assert_eq!(functions(Port::B, 3, Signal::SPI2_SCK), 0);
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f4")]
+	fn functions_routed_by_some_devices_only() {
+		let af9 = cfg!(any(feature = "stm32f401", feature = "stm32f411", feature = "stm32f412"));
+		assert_eq!(lookup(Port::B, 3, Signal::I2C2_SDA), af9.then_some(9));
+		assert_eq!(lookup(Port::B, 4, Signal::I2C3_SDA), af9.then_some(9));
+
+		// USART6 on PA11/PA12 is on the small devices only
+		let small = cfg!(any(feature = "stm32f401", feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"));
+		assert_eq!(lookup(Port::A, 11, Signal::USART6_TX), small.then_some(8));
+
+		// Common to the family
+		assert_eq!(lookup(Port::B, 6, Signal::I2C1_SCL), Some(4));
+		assert_eq!(lookup(Port::A, 5, Signal::SPI1_SCK), Some(5));
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f4")]
+	fn no_function_of_a_missing_peripheral() {
+		// The STM32F410 has neither SPI3, I2C3 nor TIM2
+		assert_eq!(lookup(Port::C, 10, Signal::SPI3_SCK).is_some(), cfg!(has_spi3));
+		assert_eq!(lookup(Port::C, 9, Signal::I2C3_SDA).is_some(), cfg!(has_i2c3));
+		assert_eq!(lookup(Port::A, 0, Signal::TIM2_CH1).is_some(), cfg!(has_tim2));
+
+		// UART4 and USART3 are not on the STM32F401/STM32F411
+		assert_eq!(lookup(Port::A, 0, Signal::UART4_TX).is_some(), cfg!(has_uart4));
+		assert_eq!(lookup(Port::B, 10, Signal::USART3_TX).is_some(), cfg!(has_usart3));
+
+		// No port F on the STM32F401/STM32F411
+		assert_eq!(lookup(Port::F, 0, Signal::I2C2_SDA).is_some(), cfg!(has_gpiof));
+	}
+}
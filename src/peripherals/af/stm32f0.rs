@@ -0,0 +1,89 @@
+//! Alternate functions of the STM32F0 family
+//! The entries are gated to the devices that have the pin and the peripheral
+
+af_table! {
+	// MCO
+	A 8: 0 => MCO1,
+
+	// I2C
+	B 6: 1 => I2C1_SCL,
+	B 7: 1 => I2C1_SDA,
+	B 8: 1 => I2C1_SCL,
+	B 9: 1 => I2C1_SDA,
+	#[cfg(has_i2c2)]
+	B 10: 1 => I2C2_SCL,
+	#[cfg(has_i2c2)]
+	B 11: 1 => I2C2_SDA,
+
+	// SPI
+	A 4: 0 => SPI1_NSS,
+	A 5: 0 => SPI1_SCK,
+	A 6: 0 => SPI1_MISO,
+	A 7: 0 => SPI1_MOSI,
+	A 15: 0 => SPI1_NSS,
+	B 3: 0 => SPI1_SCK,
+	B 4: 0 => SPI1_MISO,
+	B 5: 0 => SPI1_MOSI,
+	#[cfg(has_spi2)]
+	B 12: 0 => SPI2_NSS,
+	#[cfg(has_spi2)]
+	B 13: 0 => SPI2_SCK,
+	#[cfg(has_spi2)]
+	B 14: 0 => SPI2_MISO,
+	#[cfg(has_spi2)]
+	B 15: 0 => SPI2_MOSI,
+
+	// USART
+	A 9: 1 => USART1_TX,
+	A 10: 1 => USART1_RX,
+	B 6: 0 => USART1_TX,
+	B 7: 0 => USART1_RX,
+	A 2: 1 => USART2_TX,
+	A 3: 1 => USART2_RX,
+	A 14: 1 => USART2_TX,
+	A 15: 1 => USART2_RX,
+
+	// Timers
+	A 8: 2 => TIM1_CH1,
+	A 9: 2 => TIM1_CH2,
+	A 10: 2 => TIM1_CH3,
+	A 11: 2 => TIM1_CH4,
+	#[cfg(has_tim2)]
+	A 0: 2 => TIM2_CH1,
+	#[cfg(has_tim2)]
+	A 5: 2 => TIM2_CH1,
+	#[cfg(has_tim2)]
+	A 15: 2 => TIM2_CH1,
+	#[cfg(has_tim2)]
+	A 1: 2 => TIM2_CH2,
+	#[cfg(has_tim2)]
+	B 3: 2 => TIM2_CH2,
+	#[cfg(has_tim2)]
+	A 2: 2 => TIM2_CH3,
+	#[cfg(has_tim2)]
+	B 10: 2 => TIM2_CH3,
+	#[cfg(has_tim2)]
+	A 3: 2 => TIM2_CH4,
+	#[cfg(has_tim2)]
+	B 11: 2 => TIM2_CH4,
+	#[cfg(has_tim3)]
+	A 6: 1 => TIM3_CH1,
+	#[cfg(has_tim3)]
+	A 7: 1 => TIM3_CH2,
+	#[cfg(has_tim3)]
+	B 0: 1 => TIM3_CH3,
+	#[cfg(has_tim3)]
+	B 1: 1 => TIM3_CH4,
+	#[cfg(has_tim3)]
+	B 4: 1 => TIM3_CH1,
+	#[cfg(has_tim3)]
+	B 5: 1 => TIM3_CH2,
+	#[cfg(has_tim3)]
+	C 6: 0 => TIM3_CH1,
+	#[cfg(has_tim3)]
+	C 7: 0 => TIM3_CH2,
+	#[cfg(has_tim3)]
+	C 8: 0 => TIM3_CH3,
+	#[cfg(has_tim3)]
+	C 9: 0 => TIM3_CH4,
+}
@@ -0,0 +1,104 @@
+//! Alternate functions of the STM32F1 family (low and medium density)
+//! The STM32F1 does not select the function per pin, the AFIO moves whole
+//! peripherals between sets of pins. The `af` of an entry is the value of the
+//! peripheral's remap field (see `Syscfg::remap`) that routes the signal to the pin.
+//! A pin routed by several remaps (e.g. the partial remaps of the timers) is listed
+//! once for each of them
+
+af_table! {
+	// MCO
+	A 8: 0 => MCO1,
+
+	// I2C
+	B 6: 0 => I2C1_SCL,
+	B 7: 0 => I2C1_SDA,
+	B 8: 1 => I2C1_SCL,
+	B 9: 1 => I2C1_SDA,
+	B 10: 0 => I2C2_SCL,
+	B 11: 0 => I2C2_SDA,
+
+	// SPI
+	A 4: 0 => SPI1_NSS,
+	A 5: 0 => SPI1_SCK,
+	A 6: 0 => SPI1_MISO,
+	A 7: 0 => SPI1_MOSI,
+	A 15: 1 => SPI1_NSS,
+	B 3: 1 => SPI1_SCK,
+	B 4: 1 => SPI1_MISO,
+	B 5: 1 => SPI1_MOSI,
+	B 12: 0 => SPI2_NSS,
+	B 13: 0 => SPI2_SCK,
+	B 14: 0 => SPI2_MISO,
+	B 15: 0 => SPI2_MOSI,
+
+	// USART
+	A 9: 0 => USART1_TX,
+	A 10: 0 => USART1_RX,
+	B 6: 1 => USART1_TX,
+	B 7: 1 => USART1_RX,
+	A 2: 0 => USART2_TX,
+	A 3: 0 => USART2_RX,
+	D 5: 1 => USART2_TX,
+	D 6: 1 => USART2_RX,
+	B 10: 0 => USART3_TX,
+	B 11: 0 => USART3_RX,
+	C 10: 1 => USART3_TX,
+	C 11: 1 => USART3_RX,
+	D 8: 3 => USART3_TX,
+	D 9: 3 => USART3_RX,
+
+	// Timers, TIM1 has a partial remap (1) and a full remap (3)
+	A 8: 0 => TIM1_CH1,
+	A 9: 0 => TIM1_CH2,
+	A 10: 0 => TIM1_CH3,
+	A 11: 0 => TIM1_CH4,
+	A 8: 1 => TIM1_CH1,
+	A 9: 1 => TIM1_CH2,
+	A 10: 1 => TIM1_CH3,
+	A 11: 1 => TIM1_CH4,
+	E 9: 3 => TIM1_CH1,
+	E 11: 3 => TIM1_CH2,
+	E 13: 3 => TIM1_CH3,
+	E 14: 3 => TIM1_CH4,
+
+	// TIM2 has two partial remaps (1 and 2), each moving half of the channels
+	A 0: 0 => TIM2_CH1,
+	A 1: 0 => TIM2_CH2,
+	A 2: 0 => TIM2_CH3,
+	A 3: 0 => TIM2_CH4,
+	A 15: 1 => TIM2_CH1,
+	B 3: 1 => TIM2_CH2,
+	A 2: 1 => TIM2_CH3,
+	A 3: 1 => TIM2_CH4,
+	A 0: 2 => TIM2_CH1,
+	A 1: 2 => TIM2_CH2,
+	B 10: 2 => TIM2_CH3,
+	B 11: 2 => TIM2_CH4,
+	A 15: 3 => TIM2_CH1,
+	B 3: 3 => TIM2_CH2,
+	B 10: 3 => TIM2_CH3,
+	B 11: 3 => TIM2_CH4,
+
+	// TIM3 has a partial remap (2) and a full remap (3)
+	A 6: 0 => TIM3_CH1,
+	A 7: 0 => TIM3_CH2,
+	B 0: 0 => TIM3_CH3,
+	B 1: 0 => TIM3_CH4,
+	B 4: 2 => TIM3_CH1,
+	B 5: 2 => TIM3_CH2,
+	B 0: 2 => TIM3_CH3,
+	B 1: 2 => TIM3_CH4,
+	C 6: 3 => TIM3_CH1,
+	C 7: 3 => TIM3_CH2,
+	C 8: 3 => TIM3_CH3,
+	C 9: 3 => TIM3_CH4,
+
+	B 6: 0 => TIM4_CH1,
+	B 7: 0 => TIM4_CH2,
+	B 8: 0 => TIM4_CH3,
+	B 9: 0 => TIM4_CH4,
+	D 12: 1 => TIM4_CH1,
+	D 13: 1 => TIM4_CH2,
+	D 14: 1 => TIM4_CH3,
+	D 15: 1 => TIM4_CH4,
+}
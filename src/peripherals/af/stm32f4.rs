@@ -0,0 +1,267 @@
+//! Alternate functions of the STM32F4 family
+//! The entries are gated to the devices that have the pin and the peripheral,
+//! the functions only some devices route to a pin are listed per device
+
+af_table! {
+	// MCO
+	A 8: 0 => MCO1,
+	C 9: 0 => MCO2,
+
+	// I2C
+	B 6: 4 => I2C1_SCL,
+	B 7: 4 => I2C1_SDA,
+	B 8: 4 => I2C1_SCL,
+	B 9: 4 => I2C1_SDA,
+	#[cfg(has_i2c2)]
+	B 10: 4 => I2C2_SCL,
+	#[cfg(has_i2c2)]
+	B 11: 4 => I2C2_SDA,
+	#[cfg(all(has_i2c2, has_gpiof))]
+	F 0: 4 => I2C2_SDA,
+	#[cfg(all(has_i2c2, has_gpiof))]
+	F 1: 4 => I2C2_SCL,
+	#[cfg(all(has_i2c2, has_gpioh))]
+	H 4: 4 => I2C2_SCL,
+	#[cfg(all(has_i2c2, has_gpioh))]
+	H 5: 4 => I2C2_SDA,
+	#[cfg(has_i2c3)]
+	A 8: 4 => I2C3_SCL,
+	#[cfg(has_i2c3)]
+	C 9: 4 => I2C3_SDA,
+	#[cfg(all(has_i2c3, has_gpioh))]
+	H 7: 4 => I2C3_SCL,
+	#[cfg(all(has_i2c3, has_gpioh))]
+	H 8: 4 => I2C3_SDA,
+	#[cfg(any(feature = "stm32f401", feature = "stm32f411", feature = "stm32f412"))]
+	B 3: 9 => I2C2_SDA,
+	#[cfg(any(feature = "stm32f401", feature = "stm32f411", feature = "stm32f412"))]
+	B 4: 9 => I2C3_SDA,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412"))]
+	B 8: 9 => I2C3_SDA,
+	#[cfg(any(feature = "stm32f401", feature = "stm32f411", feature = "stm32f412"))]
+	B 9: 9 => I2C2_SDA,
+
+	// SPI
+	A 4: 5 => SPI1_NSS,
+	A 5: 5 => SPI1_SCK,
+	A 6: 5 => SPI1_MISO,
+	A 7: 5 => SPI1_MOSI,
+	A 15: 5 => SPI1_NSS,
+	B 3: 5 => SPI1_SCK,
+	B 4: 5 => SPI1_MISO,
+	B 5: 5 => SPI1_MOSI,
+	#[cfg(has_spi2)]
+	B 9: 5 => SPI2_NSS,
+	#[cfg(has_spi2)]
+	B 10: 5 => SPI2_SCK,
+	#[cfg(has_spi2)]
+	B 12: 5 => SPI2_NSS,
+	#[cfg(has_spi2)]
+	B 13: 5 => SPI2_SCK,
+	#[cfg(has_spi2)]
+	B 14: 5 => SPI2_MISO,
+	#[cfg(has_spi2)]
+	B 15: 5 => SPI2_MOSI,
+	#[cfg(has_spi2)]
+	C 2: 5 => SPI2_MISO,
+	#[cfg(has_spi2)]
+	C 3: 5 => SPI2_MOSI,
+	#[cfg(all(has_spi2, has_gpioi))]
+	I 0: 5 => SPI2_NSS,
+	#[cfg(all(has_spi2, has_gpioi))]
+	I 1: 5 => SPI2_SCK,
+	#[cfg(all(has_spi2, has_gpioi))]
+	I 2: 5 => SPI2_MISO,
+	#[cfg(all(has_spi2, has_gpioi))]
+	I 3: 5 => SPI2_MOSI,
+	#[cfg(has_spi3)]
+	A 4: 6 => SPI3_NSS,
+	#[cfg(has_spi3)]
+	A 15: 6 => SPI3_NSS,
+	#[cfg(has_spi3)]
+	B 3: 6 => SPI3_SCK,
+	#[cfg(has_spi3)]
+	B 4: 6 => SPI3_MISO,
+	#[cfg(has_spi3)]
+	B 5: 6 => SPI3_MOSI,
+	#[cfg(has_spi3)]
+	C 10: 6 => SPI3_SCK,
+	#[cfg(has_spi3)]
+	C 11: 6 => SPI3_MISO,
+	#[cfg(has_spi3)]
+	C 12: 6 => SPI3_MOSI,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 2: 5 => SPI4_SCK,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 4: 5 => SPI4_NSS,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 5: 5 => SPI4_MISO,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 6: 5 => SPI4_MOSI,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 11: 5 => SPI4_NSS,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 12: 5 => SPI4_SCK,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 13: 5 => SPI4_MISO,
+	#[cfg(all(has_spi4, has_gpioe))]
+	E 14: 5 => SPI4_MOSI,
+	#[cfg(feature = "stm32f4x9")]
+	F 6: 5 => SPI5_NSS,
+	#[cfg(feature = "stm32f4x9")]
+	F 7: 5 => SPI5_SCK,
+	#[cfg(feature = "stm32f4x9")]
+	F 8: 5 => SPI5_MISO,
+	#[cfg(feature = "stm32f4x9")]
+	F 9: 5 => SPI5_MOSI,
+	#[cfg(feature = "stm32f4x9")]
+	F 11: 5 => SPI5_MOSI,
+	#[cfg(feature = "stm32f4x9")]
+	H 5: 5 => SPI5_NSS,
+	#[cfg(feature = "stm32f4x9")]
+	H 6: 5 => SPI5_SCK,
+	#[cfg(feature = "stm32f4x9")]
+	H 7: 5 => SPI5_MISO,
+	#[cfg(any(feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	A 10: 6 => SPI5_MOSI,
+	#[cfg(any(feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	A 12: 6 => SPI5_MISO,
+	#[cfg(any(feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	B 0: 6 => SPI5_SCK,
+	#[cfg(any(feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	B 1: 6 => SPI5_NSS,
+	#[cfg(any(feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	B 8: 6 => SPI5_MOSI,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 2: 6 => SPI5_SCK,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 4: 6 => SPI5_NSS,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 5: 6 => SPI5_MISO,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 6: 6 => SPI5_MOSI,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 11: 6 => SPI5_NSS,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 12: 6 => SPI5_SCK,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 13: 6 => SPI5_MISO,
+	#[cfg(any(feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	E 14: 6 => SPI5_MOSI,
+
+	// USART
+	A 9: 7 => USART1_TX,
+	A 10: 7 => USART1_RX,
+	B 6: 7 => USART1_TX,
+	B 7: 7 => USART1_RX,
+	A 2: 7 => USART2_TX,
+	A 3: 7 => USART2_RX,
+	#[cfg(has_gpiod)]
+	D 5: 7 => USART2_TX,
+	#[cfg(has_gpiod)]
+	D 6: 7 => USART2_RX,
+	#[cfg(has_usart3)]
+	B 10: 7 => USART3_TX,
+	#[cfg(has_usart3)]
+	B 11: 7 => USART3_RX,
+	#[cfg(has_usart3)]
+	C 10: 7 => USART3_TX,
+	#[cfg(has_usart3)]
+	C 11: 7 => USART3_RX,
+	#[cfg(all(has_usart3, has_gpiod))]
+	D 8: 7 => USART3_TX,
+	#[cfg(all(has_usart3, has_gpiod))]
+	D 9: 7 => USART3_RX,
+	#[cfg(has_uart4)]
+	A 0: 8 => UART4_TX,
+	#[cfg(has_uart4)]
+	A 1: 8 => UART4_RX,
+	#[cfg(has_uart4)]
+	C 10: 8 => UART4_TX,
+	#[cfg(has_uart4)]
+	C 11: 8 => UART4_RX,
+	#[cfg(has_uart5)]
+	C 12: 8 => UART5_TX,
+	#[cfg(all(has_uart5, has_gpiod))]
+	D 2: 8 => UART5_RX,
+	#[cfg(has_usart6)]
+	C 6: 8 => USART6_TX,
+	#[cfg(has_usart6)]
+	C 7: 8 => USART6_RX,
+	#[cfg(all(has_usart6, has_gpiog))]
+	G 14: 8 => USART6_TX,
+	#[cfg(all(has_usart6, has_gpiog))]
+	G 9: 8 => USART6_RX,
+	#[cfg(any(feature = "stm32f401", feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	A 11: 8 => USART6_TX,
+	#[cfg(any(feature = "stm32f401", feature = "stm32f410", feature = "stm32f411", feature = "stm32f412", feature = "stm32f4x3"))]
+	A 12: 8 => USART6_RX,
+
+	// Timers
+	A 8: 1 => TIM1_CH1,
+	A 9: 1 => TIM1_CH2,
+	A 10: 1 => TIM1_CH3,
+	A 11: 1 => TIM1_CH4,
+	#[cfg(has_gpioe)]
+	E 9: 1 => TIM1_CH1,
+	#[cfg(has_gpioe)]
+	E 11: 1 => TIM1_CH2,
+	#[cfg(has_gpioe)]
+	E 13: 1 => TIM1_CH3,
+	#[cfg(has_gpioe)]
+	E 14: 1 => TIM1_CH4,
+	#[cfg(has_tim2)]
+	A 0: 1 => TIM2_CH1,
+	#[cfg(has_tim2)]
+	A 5: 1 => TIM2_CH1,
+	#[cfg(has_tim2)]
+	A 15: 1 => TIM2_CH1,
+	#[cfg(has_tim2)]
+	A 1: 1 => TIM2_CH2,
+	#[cfg(has_tim2)]
+	B 3: 1 => TIM2_CH2,
+	#[cfg(has_tim2)]
+	A 2: 1 => TIM2_CH3,
+	#[cfg(has_tim2)]
+	B 10: 1 => TIM2_CH3,
+	#[cfg(has_tim2)]
+	A 3: 1 => TIM2_CH4,
+	#[cfg(has_tim2)]
+	B 11: 1 => TIM2_CH4,
+	#[cfg(has_tim3)]
+	A 6: 2 => TIM3_CH1,
+	#[cfg(has_tim3)]
+	B 4: 2 => TIM3_CH1,
+	#[cfg(has_tim3)]
+	C 6: 2 => TIM3_CH1,
+	#[cfg(has_tim3)]
+	A 7: 2 => TIM3_CH2,
+	#[cfg(has_tim3)]
+	B 5: 2 => TIM3_CH2,
+	#[cfg(has_tim3)]
+	C 7: 2 => TIM3_CH2,
+	#[cfg(has_tim3)]
+	B 0: 2 => TIM3_CH3,
+	#[cfg(has_tim3)]
+	C 8: 2 => TIM3_CH3,
+	#[cfg(has_tim3)]
+	B 1: 2 => TIM3_CH4,
+	#[cfg(has_tim3)]
+	C 9: 2 => TIM3_CH4,
+	#[cfg(has_tim4)]
+	B 6: 2 => TIM4_CH1,
+	#[cfg(all(has_tim4, has_gpiod))]
+	D 12: 2 => TIM4_CH1,
+	#[cfg(has_tim4)]
+	B 7: 2 => TIM4_CH2,
+	#[cfg(all(has_tim4, has_gpiod))]
+	D 13: 2 => TIM4_CH2,
+	#[cfg(has_tim4)]
+	B 8: 2 => TIM4_CH3,
+	#[cfg(all(has_tim4, has_gpiod))]
+	D 14: 2 => TIM4_CH3,
+	#[cfg(has_tim4)]
+	B 9: 2 => TIM4_CH4,
+	#[cfg(all(has_tim4, has_gpiod))]
+	D 15: 2 => TIM4_CH4,
+}
@@ -34,8 +34,8 @@ impl CpuId {
 	/// `level` is masked to be between 0 and 7
 	pub fn select_cache(&mut self, level: u32, ind: CsselrCacheType) {
 		self.block[CPUIDRegs::CSSELR as usize].write(
-			(((level as u32) << 1) & (0x7 << 1)) |
-			(((ind   as u32) << 0) &  1)
+			((level << 1) & (0x7 << 1)) |
+			((ind as u32) & 1)
 		)
 	}
 
@@ -6,8 +6,8 @@ pub const ADDRESS: u32 = 0xE000_0000;
 pub const SIZE: usize = 1006;
 
 #[repr(C)]
-pub struct Dwt {
+pub struct Itm {
 	block: [Register<u32>; SIZE],
 }
 
-impl crate::common::VolatileStruct for Dwt {}
+impl crate::common::VolatileStruct for Itm {}
@@ -2,7 +2,7 @@
 
 
 
-#[cfg(fpu)]
+#[cfg(feature = "fpu")]
 pub mod fpu;
 
 #[cfg(not(armv6m))]
@@ -1,6 +1,6 @@
 //! Nested Vector Interrupt Controller
 
-use crate::common::{ Register, State };
+use crate::common::Register;
 
 pub const ADDRESS: u32 = 0xE000_E100;
 #[cfg(not(armv6m))]
@@ -81,7 +81,7 @@ impl Nvic {
 	/// On ARMv6-M, updating an interrupt priority requires a read-modify-write operation. On
 	/// ARMv7-M, the operation is performed in a single atomic write operation.
 	///
-	/// # Unsafety
+	/// # Safety
 	///
 	/// Changing priority levels can break priority-based critical sections (see
 	/// [`register::basepri`](../register/basepri/index.html)) and compromise memory safety.
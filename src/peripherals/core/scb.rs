@@ -1,6 +1,6 @@
 //! System Control Block
 
-use crate::common::{ Register, State, asm, VolatileStruct };
+use crate::common::{ Register, asm, VolatileStruct };
 
 #[cfg(not(armv6m))]
 use super::cpuid::CsselrCacheType;
@@ -9,22 +9,67 @@ pub const ADDRESS: u32 = 0xE000_ED04;
 
 pub const SIZE: usize = 34;
 
-#[derive(Copy, Clone, PartialEq)]
-pub enum SCBRegs {
-	ICSR = 0,
-	VTOR = 1,
-	AIRCR = 2,
-	SCR = 3,
-	CCR = 4,
-	SHPR = 5,
-	SHCSR = 8,
-	CFSR = 9,
-	HFSR = 10,
-	DFSR = 11,
-	MMFAR = 12,
-	BFAR = 13,
-	AFSR = 14,
-	CPACR = 33,
+registers! {
+	/// Interrupt control and state register
+	ICSR @ 0, reset = 0x0000_0000 {
+		/// PendSV set-pending
+		pendsvset: 28, 1, RW;
+		/// PendSV clear-pending
+		pendsvclr: 27, 1, WO;
+		/// SysTick set-pending
+		pendstset: 26, 1, RW;
+		/// SysTick clear-pending
+		pendstclr: 25, 1, WO;
+		/// Active exception
+		vectactive: 0, 9, RO;
+	}
+
+	/// Application interrupt and reset control register
+	AIRCR @ 2, reset = 0xFA05_0000 {
+		/// Register key, 0x05FA on writes
+		vectkey: 16, 16, RW;
+		/// Interrupt priority grouping
+		prigroup: 8, 3, RW;
+		/// System reset request
+		sysresetreq: 2, 1, WO;
+	}
+
+	/// System control register
+	SCR @ 3, reset = 0x0000_0000 {
+		/// Deep sleep as the low power mode
+		sleepdeep: 2, 1, RW;
+		/// Sleep on return to thread mode
+		sleeponexit: 1, 1, RW;
+	}
+
+	/// Configuration and control register
+	CCR @ 4, reset = 0x0000_0200 {
+		/// Instruction cache enable
+		ic: 17, 1, RW;
+		/// Data cache enable
+		dc: 16, 1, RW;
+	}
+
+	/// System handler priority register 1 (handlers 4 to 7)
+	SHPR1 @ 5, reset = 0x0000_0000 {
+		pri[4]: 0, 8, RW;
+	}
+
+	/// System handler priority register 2 (handlers 8 to 11)
+	SHPR2 @ 6, reset = 0x0000_0000 {
+		pri[4]: 0, 8, RW;
+	}
+
+	/// System handler priority register 3 (handlers 12 to 15)
+	SHPR3 @ 7, reset = 0x0000_0000 {
+		pri[4]: 0, 8, RW;
+	}
+
+	/// Coprocessor access control register
+	CPACR @ 33, reset = 0x0000_0000 {
+		/// Access to CP10 and CP11, the FPU
+		cp[2]: 20, 2, RW;
+	}
 }
 
 #[repr(C)]
@@ -47,16 +92,6 @@ pub enum FpuAccessMode {
 	Priviliged,
 }
 
-#[cfg(has_fpu)]
-mod fpu_consts {
-	pub const SCB_CPACR_FPU_MASK: u32 = 0b11_11 << 20;
-	pub const SCB_CPACR_FPU_ENABLE: u32 = 0b01_01 << 20;
-	pub const SCB_CPACR_FPU_USER: u32 = 0b10_10 << 20;
-}
-
-#[cfg(has_fpu)]
-use self::fpu_consts::*;
-
 #[cfg(has_fpu)]
 impl Scb {
 	/// Disable FPU
@@ -71,34 +106,30 @@ impl Scb {
 
 	/// Get FPU access mode
 	pub fn fpu_access_mode(&self) -> FpuAccessMode {
-		let cpacr = self.block[SCBRegs::CPACR as usize].read();
-
-		// CPACR & FPU_ENABLE
-		match cpacr & SCB_CPACR_FPU_MASK {
-			// PRIVILIGED   |  USER
-			SCB_CPACR_FPU_ENABLE | SCB_CPACR_FPU_USER => FpuAccessMode::Enabled,
-			// ONLY PRIVILIGED
-			SCB_CPACR_FPU_ENABLE => FpuAccessMode::Priviliged,
+		// CP10 and CP11 are always set together
+		match self.read_reg::<CPACR>().cp(0) {
+			0b11 => FpuAccessMode::Enabled,
+			0b01 => FpuAccessMode::Priviliged,
 			_ => FpuAccessMode::Disabled,
 		}
 	}
 
 	/// Sets FPU access mode
 	pub fn set_fpu_access(&mut self, mode: FpuAccessMode) {
-		self.block[SCBRegs::CPACR as usize] &= !(0b11_11 << 20);
+		let access = match mode {
+			FpuAccessMode::Disabled => 0b00,
+			FpuAccessMode::Priviliged => 0b01,
+			FpuAccessMode::Enabled => 0b11,
+		};
 
-		match mode {
-			FpuAccessMode::Disabled => (),
-			FpuAccessMode::Priviliged => self.block[SCBRegs::CPACR as usize] |= SCB_CPACR_FPU_ENABLE,
-			FpuAccessMode::Enabled => self.block[SCBRegs::CPACR as usize] |= SCB_CPACR_FPU_ENABLE | SCB_CPACR_FPU_USER,			
-		}
+		self.reg::<CPACR>().modify(|_, w| w.cp(0, access).cp(1, access));
 	}
 }
 
 impl Scb {
 	/// Returns the active exception number
 	pub fn vect_active(&self) -> VectActive {
-		match self.block[SCBRegs::ICSR as usize].read() as u8 {
+		match self.read_reg::<ICSR>().vectactive() as u8 {
 			0 => VectActive::ThreadMode,
 			2 => VectActive::Exception(Exception::NonMaskableInt),
 			3 => VectActive::Exception(Exception::HardFault),
@@ -232,22 +263,13 @@ impl VectActive {
 			14 => VectActive::Exception(Exception::PendSV),
 			15 => VectActive::Exception(Exception::SysTick),
 
-			irqn if (irqn >= 16) && (irqn < 240) => VectActive::Interrupt(irqn),
+			irqn if (16..240).contains(&irqn) => VectActive::Interrupt(irqn),
 
 			_ => return None,
 		})
 	}
 }
 
-#[cfg(not(armv6m))]
-mod scb_consts {
-	pub const SCB_CCR_IC_MASK: u32 = (1 << 17);
-	pub const SCB_CCR_DC_MASK: u32 = (1 << 16);
-}
-
-#[cfg(not(armv6m))]
-use self::scb_consts::*;
-
 #[cfg(not(armv6m))]
 impl Scb {
 	/// Enable I-Cache if disabled
@@ -261,11 +283,11 @@ impl Scb {
 			true => {
 				let cbp = unsafe { super::cbp::Cbp::from_addr( super::cbp::ADDRESS ) };
 				cbp.iciallu();
-				self.block[SCBRegs::CCR as usize] |= SCB_CCR_IC_MASK;
+				self.reg::<CCR>().modify(|_, w| w.ic(1));
 			},
 
 			false => {
-				self.block[SCBRegs::CCR as usize] &= !SCB_CCR_IC_MASK;
+				self.reg::<CCR>().modify(|_, w| w.ic(0));
 				let cbp = unsafe { super::cbp::Cbp::from_addr( super::cbp::ADDRESS ) };
 				cbp.iciallu();
 			},
@@ -283,7 +305,7 @@ impl Scb {
 		asm::dsb();
 		asm::isb();
 
-		self.block[SCBRegs::CCR as usize].read() & SCB_CCR_IC_MASK == SCB_CCR_IC_MASK
+		self.read_reg::<CCR>().ic() == 1
 	}
 
 	/// Invalidate I-Cache
@@ -305,13 +327,13 @@ impl Scb {
 
 			true => {
 				self.invalidate_dcache( unsafe { super::cpuid::CpuId::from_addr(super::cpuid::ADDRESS) } );
-				self.block[SCBRegs::CCR as usize] |= SCB_CCR_DC_MASK;
+				self.reg::<CCR>().modify(|_, w| w.dc(1));
 				asm::dsb();
 				asm::isb();
 			},
 
 			false => {
-				self.block[SCBRegs::CCR as usize] &= !SCB_CCR_DC_MASK;
+				self.reg::<CCR>().modify(|_, w| w.dc(0));
 				self.clean_invalidate_dcache( unsafe { super::cpuid::CpuId::from_addr(super::cpuid::ADDRESS) } );
 			},
 		}
@@ -325,7 +347,7 @@ impl Scb {
 		asm::dsb();
 		asm::isb();
 
-		self.block[SCBRegs::CCR as usize].read() & SCB_CCR_DC_MASK == SCB_CCR_DC_MASK
+		self.read_reg::<CCR>().dc() == 1
 	}
 
 	/// Invalidates D-cache
@@ -491,14 +513,14 @@ impl Scb {
 impl Scb {
 	/// Set/Reset the SLEEPDEEP bit in the SCR register
 	pub fn sleepdeep_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(SCBRegs::SCR as usize, 2) }
-		else { self.clear(SCBRegs::SCR as usize, 2) }
+		self.reg::<SCR>().modify(|_, w| w.sleepdeep(s as u32));
+		self
 	}
 
 	/// Set/Reset the SLEEPONEXIT bit in the SCR register
 	pub fn sleeponexit_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(SCBRegs::SCR as usize, 1) }
-		else { self.clear(SCBRegs::SCR as usize, 1) }
+		self.reg::<SCR>().modify(|_, w| w.sleeponexit(s as u32));
+		self
 	}
 }
 
@@ -507,8 +529,8 @@ impl Scb {
 	pub fn system_reset(&mut self) -> ! {
 		asm::dsb();
 
-		let old = self.block[SCBRegs::AIRCR as usize].read();
-		self.block[SCBRegs::AIRCR as usize].write( (0x05FA << 16) | (old & (0x5 << 8)) | (1 << 2) );
+		let prigroup = self.read_reg::<AIRCR>().prigroup();
+		self.reg::<AIRCR>().write(|w| w.vectkey(0x05FA).prigroup(prigroup).sysresetreq(1));
 
 		asm::dsb();
 
@@ -521,26 +543,33 @@ impl Scb {
 impl Scb {
 	/// Set/Reset the PENDSVSET bit in the ICSR register which will pend the PendSV interrupt
 	pub fn pendsv_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(SCBRegs::ICSR as usize, 28) }
-		else { self.set(SCBRegs::ICSR as usize, 27) }
+		// Writing 0 to the other bits of ICSR has no effect
+		match s {
+			true => self.reg::<ICSR>().write(|w| w.pendsvset(1)),
+			_ => self.reg::<ICSR>().write(|w| w.pendsvclr(1)),
+		}
+		self
 	}
 
 	/// Returns true if PENDSVSET bit in ICSR is set
 	pub fn is_pendsv_pending(&self) -> bool {
-		self.block[SCBRegs::ICSR as usize].read() & (1 << 28) == (1 << 28)
+		self.read_reg::<ICSR>().pendsvset() == 1
 	}
 
 	/// Set the PENDSTCLR bit in the ICSR register which will clear a pending SysTick interrupt
 	#[inline]
 	pub fn pendst_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(SCBRegs::ICSR as usize, 26) }
-		else { self.set(SCBRegs::ICSR as usize, 25) }
+		match s {
+			true => self.reg::<ICSR>().write(|w| w.pendstset(1)),
+			_ => self.reg::<ICSR>().write(|w| w.pendstclr(1)),
+		}
+		self
 	}
 
 	#[inline]
 	/// Returns true if PENDSTSET bit in ICSR is set
 	pub fn is_pendst_pending(&self) -> bool {
-		self.block[SCBRegs::ICSR as usize].read() & (1 << 26) == (1 << 26)
+		self.read_reg::<ICSR>().pendstset() == 1
 	}
 }
 
@@ -570,6 +599,12 @@ pub enum SystemHandler {
 }
 
 impl SystemHandler {
+	/// Returns the SHPRx register and the byte of the priority
+	fn shpr(&self) -> (u8, usize) {
+		let index = self.index();
+		(index / 4, index as usize % 4)
+	}
+
 	fn index(&self) -> u8 {
 		match *self {
 			#[cfg(not(armv6m))]
@@ -592,51 +627,59 @@ impl SystemHandler {
 impl Scb {
 	/// Returns the hardware priority of `handler`
 	pub fn get_priority(&self, handler: SystemHandler) -> u8 {
-		#[cfg(not(armv6m))]
-		{
-			return match handler {
-				SystemHandler::MemManagement => self.block[SCBRegs::SHPR as usize].read()       & 0b1111_1111,
-				SystemHandler::BusFault   => (self.block[SCBRegs::SHPR as usize ].read() >>  8) & 0b1111_1111,
-				SystemHandler::UsageFault => (self.block[SCBRegs::SHPR as usize ].read() >> 16) & 0b1111_1111,
-				SystemHandler::PendSV  => (self.block[SCBRegs::SHPR as usize + 2].read() >> 16) & 0b1111_1111,
-				SystemHandler::SysTick => (self.block[SCBRegs::SHPR as usize + 2].read() >> 24) & 0b1111_1111,
-				SystemHandler::SVCall  => (self.block[SCBRegs::SHPR as usize + 1].read() >> 24) & 0b1111_1111,
-				_ => 0,
-			} as u8;
-		}
+		let (shpr, n) = handler.shpr();
 
-		#[cfg(armv6m)]
-		{
-			return match handler {
-				SystemHandler::PendSV  => (self.block[SCBRegs::SHPR as usize + 2].read() >> 16) & 0b1111_1111,
-				SystemHandler::SysTick => (self.block[SCBRegs::SHPR as usize + 2].read() >> 24) & 0b1111_1111,
-				SystemHandler::SVCall  => (self.block[SCBRegs::SHPR as usize + 1].read() >> 24) & 0b1111_1111,
-			} as u8;
-		}
+		let priority = match shpr {
+			1 => self.read_reg::<SHPR1>().pri(n),
+			2 => self.read_reg::<SHPR2>().pri(n),
+			_ => self.read_reg::<SHPR3>().pri(n),
+		};
+
+		priority as u8
 	}
 
-	/// Returns the hardware priority of `handler`
+	/// Sets the hardware priority of `handler`
 	pub fn set_priority(&mut self, handler: SystemHandler, priority: u32) -> &mut Self {
-		#[cfg(not(armv6m))]
-		{
-			match handler {
-				SystemHandler::MemManagement => self.write_bits(SCBRegs::SHPR as usize, 0, priority, 8),
-				SystemHandler::BusFault    => self.write_bits(SCBRegs::SHPR as usize,   8, priority, 8),
-				SystemHandler::UsageFault  => self.write_bits(SCBRegs::SHPR as usize,  16, priority, 8),
-				SystemHandler::PendSV   => self.write_bits(SCBRegs::SHPR as usize + 2, 16, priority, 8),
-				SystemHandler::SysTick  => self.write_bits(SCBRegs::SHPR as usize + 2, 24, priority, 8),
-				SystemHandler::SVCall   => self.write_bits(SCBRegs::SHPR as usize + 1, 24, priority, 8),
-				_ => self,
-			}
-		}
+		let (shpr, n) = handler.shpr();
 
-		#[cfg(armv6m)]
-		{
-			match handler {
-				SystemHandler::PendSV   => self.write_bits(SCBRegs::SHPR as usize + 2, 16, priority, 8),
-				SystemHandler::SysTick  => self.write_bits(SCBRegs::SHPR as usize + 2, 24, priority, 8),
-				SystemHandler::SVCall   => self.write_bits(SCBRegs::SHPR as usize + 1, 24, priority, 8),
-			}
+		match shpr {
+			1 => self.reg::<SHPR1>().modify(|_, w| w.pri(n, priority)),
+			2 => self.reg::<SHPR2>().modify(|_, w| w.pri(n, priority)),
+			_ => self.reg::<SHPR3>().modify(|_, w| w.pri(n, priority)),
 		}
+
+		self
+	}
+}
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+
+	#[test]
+	fn priorities_land_in_their_shpr_byte() {
+		let block = SimBlock::<SIZE>::new();
+		let scb = unsafe { block.view::<Scb>() };
+
+		scb.set_priority(SystemHandler::SVCall, 0x40)
+			.set_priority(SystemHandler::PendSV, 0xF0)
+			.set_priority(SystemHandler::SysTick, 0x80);
+
+		assert_eq!(block.peek(SHPR2::INDEX), 0x4000_0000);
+		assert_eq!(block.peek(SHPR3::INDEX), 0x80F0_0000);
+		assert_eq!(scb.get_priority(SystemHandler::PendSV), 0xF0);
+		assert_eq!(scb.get_priority(SystemHandler::SysTick), 0x80);
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn pending_bits_are_written_alone() {
+		let block = SimBlock::<SIZE>::new();
+		let scb = unsafe { block.view::<Scb>() };
+		block.poke(ICSR::INDEX, 0x0000_0016);
+
+		scb.pendsv_state(true).pendst_state(false);
+
+		assert_eq!(block.writes(ICSR::INDEX), [1 << 28, 1 << 25]);
+	}
+}
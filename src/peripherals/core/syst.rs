@@ -1,10 +1,38 @@
 //! SysTick - System Timer
 
-use crate::common::{ Register, State };
+use crate::common::Register;
 
 pub const ADDRESS: u32 = 0xE000_E010;
 pub const SIZE: usize = 4;
 
+registers! {
+	/// Control and status register
+	CSR @ 0, reset = 0x0000_0000 {
+		/// Counter wrapped since last read, cleared on read
+		countflag: 16, 1, RO;
+		clksource: 2, 1, RW;
+		tickint: 1, 1, RW;
+		enable: 0, 1, RW;
+	}
+
+	/// Reload value register
+	RVR @ 1, reset = 0x0000_0000 {
+		reload: 0, 24, RW;
+	}
+
+	/// Current value register, any write clears it
+	CVR @ 2, reset = 0x0000_0000 {
+		current: 0, 24, RW;
+	}
+
+	/// Calibration value register
+	CALIB @ 3, reset = 0x0000_0000 {
+		noref: 31, 1, RO;
+		skew: 30, 1, RO;
+		tenms: 0, 24, RO;
+	}
+}
+
 #[repr(C)]
 pub struct SysTick {
 	block: [Register<u32>; SIZE],
@@ -19,7 +47,7 @@ impl SysTick {
 	/// 
 	/// After clearing, the wrapper bit turns to 0
 	pub fn clear_current(&mut self) -> &mut Self {
-		self.block[2].write(0);
+		self.reg::<CVR>().write(|w| w.current(0));
 		self
 	}
 
@@ -33,21 +61,21 @@ impl SysTick {
 	///
 	/// The sequence translates to `self.set_reload(x); self.clear_current(); self.counter_state(State::ON)`
 	pub fn counter_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(0, 0) }
-		else { self.clear(0, 0) }
+		self.reg::<CSR>().modify(|_, w| w.enable(s as u32));
+		self
 	}
 
 	/// Enables/Disables SysTick interrupt
 	pub fn interrupt_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(0, 1) }
-		else { self.clear(0, 1) }
+		self.reg::<CSR>().modify(|_, w| w.tickint(s as u32));
+		self
 	}
 
 	/// Gets clock source
 	/// 
 	/// It can clear the wrapper bit
 	pub fn get_clock_source(&mut self) -> SysTClock {
-		match self.is_set(0, 2) {
+		match self.read_reg::<CSR>().clksource() == 1 {
 			false => SysTClock::External,
 			true => SysTClock::Core,
 		}
@@ -55,43 +83,43 @@ impl SysTick {
 
 	/// Gets current counter value
 	pub fn get_current(&self) -> u32 {
-		self.block[2].read()
+		self.read_reg::<CVR>().current()
 	}
 
 	/// Gets reload value
 	pub fn get_reload(&self) -> u32 {
-		self.block[1].read()
+		self.read_reg::<RVR>().reload()
 	}
 
 	/// Returns the reload value with which the counter would wrap every 10 ms
 	pub fn get_ticks_per_10ms(&self) -> u32 {
-		self.block[3].read() & 0x00FFFFFF
+		self.read_reg::<CALIB>().tenms()
 	}
 
 	/// Checks if an external reference clock is available
 	pub fn has_ref_clock(&self) -> bool {
-		!self.is_set(3, 31)
+		self.read_reg::<CALIB>().noref() == 0
 	}
 
 	/// Checks if the counter wrapped
 	/// 
 	/// This operation clears the wrapper bit
 	pub fn has_wrapped(&self) -> bool {
-		self.is_set(0, 16)
+		self.read_reg::<CSR>().countflag() == 1
 	}
 
 	/// Checks if counter is enabled
 	/// 
 	/// This operation clears the wrapper bit
 	pub fn is_counter_enabled(&self) -> bool {
-		self.is_set(0, 0)
+		self.read_reg::<CSR>().enable() == 1
 	}
 
 	/// Checks if interrupt is enabled
 	/// 
 	/// This operation clears the wrapper bit
 	pub fn is_interrupt_enabled(&self) -> bool {
-		self.is_set(0, 1)
+		self.read_reg::<CSR>().tickint() == 1
 	}
 
 	/// Checks if the calibration is precise
@@ -100,15 +128,17 @@ impl SysTick {
 	/// `get_ticks_per_10ms()` may result in a period significantly deviating
 	/// from 10 ms.
 	pub fn is_precise(&self) -> bool {
-		!self.is_set(3, 30) 
+		self.read_reg::<CALIB>().skew() == 0
 	}
 
 	/// Sets clock source
 	pub fn set_clock_source(&mut self, src: SysTClock) -> &mut Self {
 		match src {
-			SysTClock::External => self.clear(0, 2),
-			SysTClock::Core => self.set(0, 2),
+			SysTClock::External => self.reg::<CSR>().modify(|_, w| w.clksource(0)),
+			SysTClock::Core => self.reg::<CSR>().modify(|_, w| w.clksource(1)),
 		}
+
+		self
 	}
 
 	/// Sets reload value
@@ -117,7 +147,7 @@ impl SysTick {
 	///
 	/// *NOTE* To make the timer wrap every `N` ticks set the reload value to `N - 1`
 	pub fn set_reload(&mut self, value: u32) -> &mut Self {
-		self.block[1].write(value & 0x00FFFFFF);
+		self.reg::<RVR>().write(|w| w.reload(value));
 		self
 	}
 }
@@ -6,14 +6,9 @@ use std::{ ptr };
 #[cfg(not(feature = "std"))]
 use core::{ ptr };
 
-use crate::common::{ asm, Register, Frequency, TIMChannel, TIMPolarity, ICFilter, ICPrescaler, ICChConfig, SlaveMode, TIMTrigger, TIMInterrupt };
+use crate::common::{ asm, Register, Frequency, TIMChannel, TIMPolarity, ICFilter, ICPrescaler, ICChConfig, SlaveMode, TIMTrigger, TIMInterrupt, TIMError };
 
-pub const ADDRESS_A: u32 = 0x4002_0000;
-pub const ADDRESS_B: u32 = 0x4002_0400;
-pub const ADDRESS_C: u32 = 0x4002_0800;
-pub const ADDRESS_D: u32 = 0x4002_0C00;
-pub const ADDRESS_E: u32 = 0x4002_1000;
-pub const ADDRESS_F: u32 = 0x4002_1C00;
+pub const ADDRESS: u32 = crate::peripherals::map::TIM1;
 
 pub const SIZE: usize = 10;
 
@@ -47,9 +42,9 @@ impl AdvTim {
 	/// The register CCx with x = `duty_cycle`(`TIMChannel`) is the register in which the duty cycle will be stored
 	/// To gather the data, the user must set up the corresponding PWM interrupt. In the interrupt, read the values
 	/// of the two registers and perform the next operation:
-	pub fn pwm_capture(&mut self, period: TIMChannel, pin1: ICChConfig, duty_cycle: TIMChannel, pin2: ICChConfig) -> Result<&mut Self, ()> {
+	pub fn pwm_capture(&mut self, period: TIMChannel, pin1: ICChConfig, duty_cycle: TIMChannel, pin2: ICChConfig) -> Result<&mut Self, TIMError> {
 		match pin1 == pin2 || period == duty_cycle {
-			true => return Err(()),
+			true => return Err(TIMError::SameChannel),
 			_ => (),
 		}
 		// Configure pin 1
@@ -115,7 +110,7 @@ impl AdvTim {
 	}
 
 	/// Configure one Input channel
-	pub fn ic_channel_config(&mut self, ch: TIMChannel, polarity: TIMPolarity, cfg: ICChConfig, filter: ICFilter) -> Result<&mut Self, ()> {
+	pub fn ic_channel_config(&mut self, ch: TIMChannel, polarity: TIMPolarity, cfg: ICChConfig, filter: ICFilter) -> Result<&mut Self, TIMError> {
 		// TODO : Investigate option 0b11 IC1 is mapped to TRC
 
 		// Disable Channel
@@ -140,12 +135,12 @@ impl AdvTim {
 	}
 
 	/// Configure where the channel is mapped
-	pub fn ic_channel_map(&mut self, cfg: ICChConfig) -> Result<&mut Self, ()> {
+	pub fn ic_channel_map(&mut self, cfg: ICChConfig) -> Result<&mut Self, TIMError> {
 		match cfg {
 			ICChConfig::TI1 => Ok( self.write_bits(7, 0, 0b01, 2) ),
 			ICChConfig::TI2 => Ok( self.write_bits(7, 0, 0b10, 2) ),
 			// TODO : Implement TRC mapping
-			_ => Err(())
+			_ => Err(TIMError::UnsupportedMapping)
 		}
 	}
 }
\ No newline at end of file
@@ -1,10 +1,28 @@
 //! CRC Peripheral
 
 use crate::common::{ Register };
+use crate::peripherals::map;
 
-pub const ADDRESS: u32 = 0x4002_3000;
+pub const ADDRESS: u32 = map::CRC;
 pub const SIZE: usize = 6;
 
+registers! {
+	/// Data register
+	DR @ 0, reset = 0xFFFF_FFFF {
+		dr: 0, 32, RW;
+	}
+
+	/// Independent data register
+	IDR @ 1, reset = 0x0000_0000 {
+		idr: 0, 8, RW;
+	}
+
+	/// Control register
+	CR @ 2, reset = 0x0000_0000 {
+		reset: 0, 1, WO;
+	}
+}
+
 #[repr(C)]
 pub struct Crc {
 	block: [Register<u32>; SIZE],
@@ -18,28 +36,31 @@ impl Crc {
 	/// Write the next data to process
 	/// WARNING!! This overwrites the result of the previous calculation
 	pub fn write(&mut self, data: u32) -> &mut Self {
-		self.block[0].write(data);
+		self.reg::<DR>().write(|w| w.dr(data));
 		self
 	}
 
 	/// Read the calculation result
 	pub fn read(&self) -> u32 {
-		self.block[0].read()
+		self.read_reg::<DR>().dr()
 	}
 
 	/// Write to the independent register
+	#[allow(non_snake_case)]
 	pub fn writeI(&mut self, data: u8) -> &mut Self {
-		self.block[1].write(data as u32);
+		self.reg::<IDR>().write(|w| w.idr(data as u32));
 		self
 	}
 
 	/// Read the independent register
+	#[allow(non_snake_case)]
 	pub fn readI(&self) -> u32 {
-		self.block[1].read()
+		self.read_reg::<IDR>().idr()
 	}
 
 	/// Resets the calculation
 	pub fn reset(&mut self) -> &mut Self {
-		self.set(2, 0)
+		self.reg::<CR>().write(|w| w.reset(1));
+		self
 	}
 }
@@ -1,10 +1,43 @@
 //! External Interrupt/event register
 
-use crate::common::{ Register, State, Trigger, enums::{ EXTILine } };
+use crate::common::{ Register, Trigger, enums::{ EXTILine } };
+use crate::peripherals::map;
 
-pub const ADDRESS: u32 = 0x4001_3C00;
+pub const ADDRESS: u32 = map::EXTI;
 pub const SIZE: usize = 6;
 
+registers! {
+	/// Interrupt mask register
+	IMR @ 0, reset = 0x0000_0000 {
+		mr[32]: 0, 1, RW;
+	}
+
+	/// Event mask register
+	EMR @ 1, reset = 0x0000_0000 {
+		mr[32]: 0, 1, RW;
+	}
+
+	/// Rising trigger selection register
+	RTSR @ 2, reset = 0x0000_0000 {
+		tr[32]: 0, 1, RW;
+	}
+
+	/// Falling trigger selection register
+	FTSR @ 3, reset = 0x0000_0000 {
+		tr[32]: 0, 1, RW;
+	}
+
+	/// Software interrupt event register
+	SWIER @ 4, reset = 0x0000_0000 {
+		swier[32]: 0, 1, RW;
+	}
+
+	/// Pending register
+	PR @ 5, reset = 0x0000_0000 {
+		pr[32]: 0, 1, W1C;
+	}
+}
+
 #[repr(C)]
 pub struct Exti {
 	block: [Register<u32>; SIZE],
@@ -17,59 +50,57 @@ impl_rwio!(Exti);
 impl Exti {
 	/// Enable/Disable interrupt by masking it
 	pub fn int_state(&mut self, int: EXTILine, s: bool) -> &mut Self {
-		if s { self.set(0, int as usize) }
-		else { self.clear(0, int as usize) }
+		self.reg::<IMR>().modify(|_, w| w.mr(int as usize, s as u32));
+		self
 	}
 
 	/// Enable/Disable event by masking it
 	pub fn event_state(&mut self, ev: EXTILine, s: bool) -> &mut Self {
-		if s { self.set(1, ev as usize) }
-		else { self.clear(1, ev as usize) }
+		self.reg::<EMR>().modify(|_, w| w.mr(ev as usize, s as u32));
+		self
 	}
 
 	/// Enables/Disables the type of trigger
 	pub fn set_trigger(&mut self, line: EXTILine, trg: Trigger, s: bool) -> &mut Self {
-		if s {
-			match trg {
-				Trigger::Rising => self.set(2, line as usize),
-				Trigger::Falling => self.set(3, line as usize),
-				Trigger::RiseFall => {
-					self.set(2, line as usize);
-					self.set(3, line as usize)
-				},
-			}
-		} else {
-			match trg {
-				Trigger::Rising => self.clear(2, line as usize),
-				Trigger::Falling => self.clear(3, line as usize),
-				Trigger::RiseFall => {
-					self.clear(2, line as usize);
-					self.clear(3, line as usize)
-				},
-			}
+		match trg {
+			Trigger::Rising => self.reg::<RTSR>().modify(|_, w| w.tr(line as usize, s as u32)),
+			Trigger::Falling => self.reg::<FTSR>().modify(|_, w| w.tr(line as usize, s as u32)),
+			Trigger::RiseFall => {
+				self.reg::<RTSR>().modify(|_, w| w.tr(line as usize, s as u32));
+				self.reg::<FTSR>().modify(|_, w| w.tr(line as usize, s as u32))
+			},
 		}
+
+		self
 	}
 
 	/// Requests a software interrupt if interrupts are enabled in that line
 	/// 
 	/// If the flag has already been raised, no irq is generated
 	pub fn request_swie(&mut self, line: EXTILine) -> &mut Self {
-		self.set(4, line as usize)
+		self.reg::<SWIER>().modify(|_, w| w.swier(line as usize, 1));
+		self
 	}
 
 	/// Clears a Software interrupt
 	pub fn clear_swie(&mut self, line: EXTILine) -> &mut Self {
-		self.block[5].write(1u32 << line as usize);
+		self.reg::<PR>().write(|w| w.pr(line as usize));
+		self
+	}
+
+	/// Clears the pending flag of the line
+	pub fn clear_pending(&mut self, line: EXTILine) -> &mut Self {
+		self.reg::<PR>().write(|w| w.pr(line as usize));
 		self
 	}
 
 	/// Checks if the interrupt has been raised
 	pub fn is_raised(&self, line: EXTILine) -> bool {
-		self.is_set(5, line as usize)
+		self.read_reg::<PR>().pr(line as usize) == 1
 	}
 
 	/// Returns the PR register as a u32
 	pub fn pending(&self) -> u32 {
-		self.block[5].read()
+		self.read_reg::<PR>().bits()
 	}
 }
\ No newline at end of file
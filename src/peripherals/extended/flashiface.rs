@@ -1,10 +1,65 @@
-//! I2C Peripheral
+//! Flash Interface
 
-use crate::common::{ Register };
+use crate::common::{ Register, Frequency, VoltageRange };
+use crate::common::ClockSpeeds::WAITSTATE;
+use crate::peripherals::map;
 
+pub const ADDRESS: u32 = map::FLASH;
 
 pub const SIZE: usize = 6;
 
+registers! {
+	/// Access control register
+	ACR @ 0, reset = 0x0000_0000 {
+		/// Data cache reset
+		#[cfg(not(any(feature = "stm32f1", feature = "stm32f7")))]
+		dcrst: 12, 1, RW;
+		/// Instruction cache reset
+		#[cfg(not(any(feature = "stm32f1", feature = "stm32f7")))]
+		icrst: 11, 1, RW;
+		/// Data cache enable
+		#[cfg(not(any(feature = "stm32f1", feature = "stm32f7")))]
+		dcen: 10, 1, RW;
+		/// Instruction cache enable
+		#[cfg(not(any(feature = "stm32f1", feature = "stm32f7")))]
+		icen: 9, 1, RW;
+		/// ART accelerator reset
+		#[cfg(feature = "stm32f7")]
+		artrst: 11, 1, RW;
+		/// ART accelerator enable
+		#[cfg(feature = "stm32f7")]
+		arten: 9, 1, RW;
+		/// Prefetch enable
+		#[cfg(not(feature = "stm32f1"))]
+		prften: 8, 1, RW;
+		/// Prefetch buffer enable
+		#[cfg(feature = "stm32f1")]
+		prften: 4, 1, RW;
+		/// Wait states of a read
+		latency: 0, 4, RW;
+	}
+
+	/// Key register
+	KEYR @ 1, reset = 0x0000_0000 {
+		key: 0, 32, WO;
+	}
+
+	/// Option key register
+	OPTKEYR @ 2, reset = 0x0000_0000 {
+		optkey: 0, 32, WO;
+	}
+
+	/// Status register
+	SR @ 3, reset = 0x0000_0000 {
+		/// Busy
+		#[cfg(not(feature = "stm32f1"))]
+		bsy: 16, 1, RO;
+		/// Busy
+		#[cfg(feature = "stm32f1")]
+		bsy: 0, 1, RO;
+	}
+}
+
 #[repr(C)]
 pub struct FlashIface {
 	block: [Register<u32>; SIZE],
@@ -12,52 +67,122 @@ pub struct FlashIface {
 
 impl crate::common::VolatileStruct for FlashIface {}
 
+impl_rwio!(FlashIface);
+
+
 impl FlashIface {
-	/// Sets bit at block and offset given
-	pub fn set(&mut self, b: usize, o: usize) -> &mut Self {
-		self.block[b] |= 1 << o;
+	fn wait_idle(&self) {
+		while self.read_reg::<SR>().bsy() == 1 {}
+	}
+
+	/// Unlock the Flash interface
+	pub fn unlock(&mut self) {
+		self.reg::<KEYR>().write(|w| w.key(0x45670123));
+		self.wait_idle();
+		self.reg::<KEYR>().write(|w| w.key(0xCDEF89AB));
+		self.wait_idle();
+	}
+
+	/// Unlock the Flash OTP bits
+	pub fn unlock_otp(&mut self) {
+		self.reg::<OPTKEYR>().write(|w| w.optkey(0x08192A3B));
+		self.wait_idle();
+		self.reg::<OPTKEYR>().write(|w| w.optkey(0x4C5D6E7F));
+		self.wait_idle();
+	}
+
+	/// Set latency
+	/// Waits until the new latency is read back, as it must be before changing the clocks
+	pub fn set_latency(&mut self, latency: u32) -> &mut Self {
+		self.reg::<ACR>().modify(|_, w| w.latency(latency));
+		while self.latency() != latency & 0b1111 {}
 		self
 	}
 
-	/// Clears bit at block and offset given
-	pub fn clear(&mut self, b: usize, o: usize) -> &mut Self {
-		self.block[b] &= !(1 << o);
+	/// Returns the latency
+	pub fn latency(&self) -> u32 {
+		self.read_reg::<ACR>().latency()
+	}
+
+	/// Enable/Disable the prefetch buffer
+	pub fn prefetch_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<ACR>().modify(|_, w| w.prften(s as u32));
 		self
 	}
 
-	/// Checks if bit is set
-	pub fn is_set(&self, r: usize, b: usize) -> bool {
-		(self.block[r].read() >> b) & 1 == 1
+	/// Enable/Disable the instruction and data caches
+	/// The caches are reset before enabling them
+	#[cfg(not(any(feature = "stm32f1", feature = "stm32f7")))]
+	pub fn cache_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<ACR>().modify(|_, w| w.icen(0).dcen(0));
+
+		if s {
+			self.reg::<ACR>().modify(|_, w| w.icrst(1).dcrst(1));
+			self.reg::<ACR>().modify(|_, w| w.icrst(0).dcrst(0));
+			self.reg::<ACR>().modify(|_, w| w.icen(1).dcen(1));
+		}
+
+		self
 	}
 
-	pub fn write_bits(&mut self, b: usize, o: usize, data: u32, size: usize) -> &mut Self {
-		let mask = (1u32 << size) - 1;
-		let old = self.block[b].read();
-		self.block[b].write( old & !(mask << o) | ((data & mask) << o) );
+	/// Enable/Disable the ART accelerator
+	/// The accelerator is reset before enabling it
+	#[cfg(feature = "stm32f7")]
+	pub fn art_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<ACR>().modify(|_, w| w.arten(0));
+
+		if s {
+			self.reg::<ACR>().modify(|_, w| w.artrst(1));
+			self.reg::<ACR>().modify(|_, w| w.artrst(0).arten(1));
+		}
+
 		self
 	}
 }
 
+/// Returns the wait states needed to read the flash at `hclk` with a supply in `vdd`
+pub fn wait_states(hclk: Frequency, vdd: VoltageRange) -> u32 {
+	hclk.hz().saturating_sub(1) / WAITSTATE[vdd as usize].hz()
+}
 
-impl FlashIface {
-	/// Unlock the Flash interface
-	pub fn unlock(&mut self) {
-		self.block[1].write(0x45670123);
-		while self.is_set(3, 16) {}
-		self.block[1].write(0xCDEF89AB);
-		while self.is_set(3, 16) {}
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+
+	#[test]
+	#[cfg(not(any(feature = "stm32f1", feature = "stm32f7")))]
+	fn cache_state_resets_before_enabling() {
+		let block = SimBlock::<SIZE>::new();
+		let flash = unsafe { block.view::<FlashIface>() };
+
+		flash.set_latency(5).cache_state(true);
+
+		assert_eq!(block.writes(ACR::INDEX), [0x005, 0x005, 0x1805, 0x005, 0x605]);
+		assert_eq!(flash.latency(), 5);
 	}
 
-	/// Unlock the Flash OTP bits
-	pub fn unlock_otp(&mut self) {
-		self.block[2].write(0x08192A3B);
-		while self.is_set(3, 16) {}
-		self.block[2].write(0x4C5D6E7F);
-		while self.is_set(3, 16) {}
+	#[test]
+	fn unlock_writes_the_keys() {
+		let block = SimBlock::<SIZE>::new();
+		let flash = unsafe { block.view::<FlashIface>() };
+
+		flash.unlock();
+
+		assert_eq!(block.writes(KEYR::INDEX), [0x4567_0123, 0xCDEF_89AB]);
 	}
 
-	/// Set latency
-	pub fn set_latency(&mut self, latency: u32) -> &mut Self {
-		self.write_bits(0, 0, latency, 4)
+	#[test]
+	fn prefetch_state_sets_the_prefetch_bit() {
+		let block = SimBlock::<SIZE>::new();
+		let flash = unsafe { block.view::<FlashIface>() };
+
+		flash.prefetch_state(true);
+
+		#[cfg(not(feature = "stm32f1"))]
+		assert_eq!(block.peek(ACR::INDEX), 1 << 8);
+		#[cfg(feature = "stm32f1")]
+		assert_eq!(block.peek(ACR::INDEX), 1 << 4);
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,365 @@
+//! CRL/CRH register layout (STM32F1)
+//!
+//! Each pin has a 4-bit field, CNF in the upper two bits and MODE in the lower two.
+//! MODE is 0 for an input and the output speed otherwise. The pull-up/pull-down of
+//! an input is selected by its ODR bit. The alternate functions are not selected
+//! per pin, they are remapped by the AFIO (see `Syscfg::remap`).
+
+use crate::common::{ GPIOError, GPIOPin, PortConfig, GPIOSpeed, PUPD, OutputType };
+
+use super::{ Gpio, PinConfig, reg };
+
+pub const SIZE: usize = 7;
+
+registers! {
+	/// Port configuration register low (pins 0 to 7)
+	CRL @ 0, reset = 0x4444_4444 {
+		/// CNF and MODE of the pin
+		cnfmode[8]: 0, 4, RW;
+	}
+
+	/// Port configuration register high (pins 8 to 15)
+	CRH @ 1, reset = 0x4444_4444 {
+		/// CNF and MODE of the pin
+		cnfmode[8]: 0, 4, RW;
+	}
+
+	/// Port input data register
+	IDR @ 2, reset = 0x0000_0000 {
+		idr[16]: 0, 1, RO;
+	}
+
+	/// Port output data register
+	ODR @ 3, reset = 0x0000_0000 {
+		odr[16]: 0, 1, RW;
+	}
+
+	/// Port bit set/reset register
+	BSRR @ 4, reset = 0x0000_0000 {
+		/// Set the pin
+		bs[16]: 0, 1, WO;
+		/// Reset the pin
+		br[16]: 16, 1, WO;
+	}
+
+	/// Port bit reset register
+	BRR @ 5, reset = 0x0000_0000 {
+		br[16]: 0, 1, WO;
+	}
+
+	/// Port configuration lock register
+	LCKR @ 6, reset = 0x0000_0000 {
+		lck[16]: 0, 1, RW;
+		/// Lock key
+		lckk: 16, 1, RW;
+	}
+}
+
+/// Configuration of a port
+/// Used to save and restore the port around low power modes
+/// The output data is kept, as it selects the pull-up/pull-down of the inputs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortSnapshot {
+	pub crl: u32,
+	pub crh: u32,
+	pub odr: u32,
+}
+
+/// Floating input, the reset configuration
+const INPUT_FLOATING: u32 = 0b0100;
+
+/// Input with pull-up or pull-down
+const INPUT_PULL: u32 = 0b1000;
+
+/// Output speed used when a pin becomes an output
+const DEFAULT_SPEED: u32 = 0b10;
+
+/// Returns the MODE bits of an output at `speed`
+/// Low is 2 MHz, Medium is 10 MHz, Fast and High are 50 MHz
+const fn speed_bits(speed: GPIOSpeed) -> u32 {
+	match speed {
+		GPIOSpeed::Low => 0b10,
+		GPIOSpeed::Medium => 0b01,
+		GPIOSpeed::Fast | GPIOSpeed::High => 0b11,
+	}
+}
+
+/// Reads the CNF and MODE bits of the pin `n`
+fn cnfmode(base: usize, n: usize) -> u32 {
+	match n {
+		0..=7 => reg::<CRL>(base).read().cnfmode(n),
+		_     => reg::<CRH>(base).read().cnfmode(n - 8),
+	}
+}
+
+/// Writes the CNF and MODE bits of the pin `n`
+fn set_cnfmode(base: usize, n: usize, bits: u32) {
+	match n {
+		0..=7 => reg::<CRL>(base).modify(|_, w| w.cnfmode(n, bits)),
+		_     => reg::<CRH>(base).modify(|_, w| w.cnfmode(n - 8, bits)),
+	}
+}
+
+/// Returns the speed of the pin `n` if it is an output, the default speed otherwise
+fn output_speed(base: usize, n: usize) -> u32 {
+	match cnfmode(base, n) & 0b11 {
+		0 => DEFAULT_SPEED,
+		m => m,
+	}
+}
+
+/// Sets the pull-up/pull-down of the input `n`
+/// The ODR bit is written first, so the pin does not glitch to the other pull
+fn set_pull(base: usize, n: usize, pupd: PUPD) {
+	match pupd {
+		PUPD::NONE => {},
+		PUPD::PullUp => reg::<BSRR>(base).write(|w| w.bs(n, 1)),
+		PUPD::PullDown => reg::<BSRR>(base).write(|w| w.br(n, 1)),
+	}
+
+	match pupd {
+		PUPD::NONE => set_cnfmode(base, n, INPUT_FLOATING),
+		_ => set_cnfmode(base, n, INPUT_PULL),
+	}
+}
+
+/// Configures the pin `n` of the port at `base`
+/// An output keeps its speed, a pin that was not one starts at 2 MHz
+pub(crate) fn configure(base: usize, n: u32, config: PinConfig) {
+	let n = n as usize;
+
+	match config {
+		PinConfig::Input(pupd) => set_pull(base, n, pupd),
+
+		PinConfig::Output(otype) => {
+			let speed = output_speed(base, n);
+			set_cnfmode(base, n, ((otype as u32) << 2) | speed);
+		},
+
+		PinConfig::Alternate(_, otype) => {
+			let speed = output_speed(base, n);
+			set_cnfmode(base, n, ((0b10 | otype as u32) << 2) | speed);
+		},
+
+		PinConfig::Analog => set_cnfmode(base, n, 0b0000),
+	}
+}
+
+/// Sets the output speed of the pin `n` of the port at `base`
+/// Inputs have no speed and are not changed
+pub(crate) fn set_speed(base: usize, n: u32, speed: GPIOSpeed) {
+	let n = n as usize;
+	let bits = cnfmode(base, n);
+
+	if bits & 0b11 != 0 {
+		set_cnfmode(base, n, (bits & 0b1100) | speed_bits(speed));
+	}
+}
+
+/// Sets the pins of `mask` as outputs (`true`) or floating inputs (`false`)
+/// The outputs are push-pull at 2 MHz, the input configuration is not kept
+pub(crate) fn set_direction(base: usize, mask: u16, output: bool) {
+	let bits = match output {
+		true => DEFAULT_SPEED,
+		_ => INPUT_FLOATING,
+	};
+
+	reg::<CRL>(base).modify(|_, w| {
+		for n in (0..8).filter(|n| mask & (1 << n) != 0) {
+			w.cnfmode(n, bits);
+		}
+		w
+	});
+
+	reg::<CRH>(base).modify(|_, w| {
+		for n in (0..8).filter(|n| mask & (1 << (n + 8)) != 0) {
+			w.cnfmode(n, bits);
+		}
+		w
+	});
+}
+
+/// Port level configuration
+/// Only the pins not handed out by `Gpio::pin` can be configured, the others
+/// are owned by their `Pin` or by the peripheral using them
+impl Gpio {
+	/// Set up port mode
+	/// An input becomes a push-pull output at 2 MHz, an output keeps its type and speed
+	pub fn set_mode(&mut self, pin: GPIOPin, mode: PortConfig) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		let (base, n) = (self.base, pin as usize);
+
+		let bits = cnfmode(base, n);
+		let otype = match bits & 0b11 {
+			0 => 0,
+			_ => (bits >> 2) & 1,
+		};
+
+		match mode {
+			PortConfig::Input => set_cnfmode(base, n, INPUT_FLOATING),
+			PortConfig::Output => set_cnfmode(base, n, (otype << 2) | output_speed(base, n)),
+			PortConfig::AltFunction => set_cnfmode(base, n, ((0b10 | otype) << 2) | output_speed(base, n)),
+			PortConfig::Analog => set_cnfmode(base, n, 0b0000),
+		}
+
+		Ok( self )
+	}
+
+	/// Set port output type
+	/// Only outputs have a type, set the mode first
+	pub fn set_otype(&mut self, pin: GPIOPin, otype: OutputType) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		let (base, n) = (self.base, pin as usize);
+		let bits = cnfmode(base, n);
+
+		if bits & 0b11 != 0 {
+			set_cnfmode(base, n, (bits & !0b0100) | ((otype as u32) << 2));
+		}
+
+		Ok( self )
+	}
+
+	/// Set port output speed
+	/// Only outputs have a speed, set the mode first
+	pub fn set_ospeed(&mut self, pin: GPIOPin, speed: GPIOSpeed) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		set_speed(self.base, pin as u32, speed);
+		Ok( self )
+	}
+
+	/// Set port PUPD mode
+	/// Only inputs have a pull-up/pull-down, the pin is set as an input
+	pub fn set_pupd(&mut self, pin: GPIOPin, pupd: PUPD) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		set_pull(self.base, pin as usize, pupd);
+		Ok( self )
+	}
+
+	/// Captures the configuration of the port
+	pub fn snapshot(&self) -> PortSnapshot {
+		PortSnapshot {
+			crl: self.read_reg::<CRL>().bits(),
+			crh: self.read_reg::<CRH>().bits(),
+			odr: self.read_reg::<ODR>().bits(),
+		}
+	}
+
+	/// Restores a configuration captured with `snapshot`
+	/// The output data is restored first, so the inputs get back their pulls
+	/// and the outputs their levels. Locked pins keep their configuration
+	pub fn restore(&mut self, snapshot: &PortSnapshot) -> &mut Self {
+		self.reg::<ODR>().write(|w| w.set_bits(0, 32, snapshot.odr));
+		self.reg::<CRL>().write(|w| w.set_bits(0, 32, snapshot.crl));
+		self.reg::<CRH>().write(|w| w.set_bits(0, 32, snapshot.crh));
+		self
+	}
+}
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::{ AccessKind, SimBlock }, RegisterSpec };
+
+	/// Configures the pin `n` of a port at reset and returns its CNF/MODE bits and the BSRR writes
+	fn configured(n: u32, config: PinConfig) -> (u32, Vec<u32>) {
+		let block = SimBlock::<SIZE>::with_reset([CRL::RESET, CRH::RESET, 0, 0, 0, 0, 0]);
+
+		configure(block.base(), n, config);
+
+		let (i, shift) = match n {
+			0..=7 => (CRL::INDEX, n * 4),
+			_ => (CRH::INDEX, (n - 8) * 4),
+		};
+
+		((block.peek(i) >> shift) & 0xF, block.writes(BSRR::INDEX))
+	}
+
+	#[test]
+	fn each_mode_has_its_cnf_and_mode() {
+		for n in [1, 9] {
+			assert_eq!(configured(n, PinConfig::Input(PUPD::NONE)), (0b0100, vec![]));
+			assert_eq!(configured(n, PinConfig::Input(PUPD::PullUp)), (0b1000, vec![1 << n]));
+			assert_eq!(configured(n, PinConfig::Input(PUPD::PullDown)), (0b1000, vec![1 << (n + 16)]));
+
+			// The outputs start at 2 MHz
+			assert_eq!(configured(n, PinConfig::Output(OutputType::PushPull)).0, 0b0010);
+			assert_eq!(configured(n, PinConfig::Output(OutputType::OpenDrain)).0, 0b0110);
+			assert_eq!(configured(n, PinConfig::Alternate(0, OutputType::PushPull)).0, 0b1010);
+			assert_eq!(configured(n, PinConfig::Alternate(0, OutputType::OpenDrain)).0, 0b1110);
+
+			assert_eq!(configured(n, PinConfig::Analog).0, 0b0000);
+		}
+	}
+
+	#[test]
+	fn only_outputs_have_a_speed() {
+		let block = SimBlock::<SIZE>::with_reset([CRL::RESET, CRH::RESET, 0, 0, 0, 0, 0]);
+		let base = block.base();
+
+		configure(base, 2, PinConfig::Alternate(0, OutputType::OpenDrain));
+
+		for (speed, bits) in [(GPIOSpeed::Low, 0b1110), (GPIOSpeed::Medium, 0b1101), (GPIOSpeed::Fast, 0b1111)] {
+			set_speed(base, 2, speed);
+			assert_eq!((block.peek(CRL::INDEX) >> 8) & 0xF, bits);
+		}
+
+		// An output keeps its speed when its configuration changes
+		configure(base, 2, PinConfig::Output(OutputType::PushPull));
+		assert_eq!((block.peek(CRL::INDEX) >> 8) & 0xF, 0b0011);
+
+		// The inputs are left as they are
+		set_speed(base, 3, GPIOSpeed::High);
+		assert_eq!((block.peek(CRL::INDEX) >> 12) & 0xF, INPUT_FLOATING);
+	}
+
+	#[test]
+	fn set_direction_writes_both_halves() {
+		let block = SimBlock::<SIZE>::with_reset([CRL::RESET, CRH::RESET, 0, 0, 0, 0, 0]);
+
+		set_direction(block.base(), 0x0180, true);
+		assert_eq!(block.peek(CRL::INDEX), 0x2444_4444);
+		assert_eq!(block.peek(CRH::INDEX), 0x4444_4442);
+
+		set_direction(block.base(), 0x0100, false);
+		assert_eq!(block.peek(CRH::INDEX), CRH::RESET);
+	}
+
+	#[test]
+	fn restore_writes_back_the_snapshot() {
+		let block = SimBlock::<SIZE>::with_reset([CRL::RESET, CRH::RESET, 0, 0, 0, 0, 0]);
+		let mut gpio = Gpio::from_block( unsafe { block.regs() } );
+
+		// PA1 push-pull output set high, PA9 alternate function, PA10 input with pull-up
+		block.poke(CRL::INDEX, 0x4444_4424).poke(CRH::INDEX, 0x4444_48B4).poke(ODR::INDEX, 0x0402);
+		let snapshot = gpio.snapshot();
+
+		assert_eq!((snapshot.crl, snapshot.crh, snapshot.odr), (0x4444_4424, 0x4444_48B4, 0x0402));
+
+		block.poke(CRL::INDEX, 0x3333_3333).poke(CRH::INDEX, 0x8888_8888).poke(ODR::INDEX, 0xFFFF);
+		gpio.restore(&snapshot);
+
+		assert_eq!(block.peek(CRL::INDEX), snapshot.crl);
+		assert_eq!(block.peek(CRH::INDEX), snapshot.crh);
+		assert_eq!(block.peek(ODR::INDEX), snapshot.odr);
+	}
+
+	#[test]
+	fn restore_writes_the_output_data_first() {
+		let block = SimBlock::<SIZE>::with_reset([CRL::RESET, CRH::RESET, 0, 0, 0, 0, 0]);
+		let mut gpio = Gpio::from_block( unsafe { block.regs() } );
+		let snapshot = gpio.snapshot();
+
+		gpio.restore(&snapshot);
+
+		let writes: Vec<usize> = block.accesses().into_iter()
+			.filter(|(kind, _, _)| *kind == AccessKind::Write)
+			.map(|(_, i, _)| i)
+			.collect();
+
+		assert_eq!(writes, [ODR::INDEX, CRL::INDEX, CRH::INDEX]);
+	}
+}
@@ -0,0 +1,255 @@
+//! General Purpose I/O (GPIO)
+//!
+//! The STM32F1 configures its pins with the 4-bit CNF/MODE fields of CRL/CRH,
+//! the other families with MODER, OTYPER, OSPEEDR, PUPDR and AFR. Both layouts
+//! expose the same `Gpio` and `Pin` API, the differences are noted in the methods.
+
+use crate::common::{ Register, GPIOError, GPIOPin, OutputType, PUPD };
+use crate::common::{ Pin, Unknown, masked_bsrr };
+use crate::common::register::{ Reg, RegisterSpec };
+use crate::peripherals::map;
+
+#[cfg(not(feature = "stm32f1"))]
+mod moder;
+
+#[cfg(not(feature = "stm32f1"))]
+pub use self::moder::*;
+
+#[cfg(feature = "stm32f1")]
+mod crx;
+
+#[cfg(feature = "stm32f1")]
+pub use self::crx::*;
+
+// The addresses of the ports are in the memory map (e.g. `map::GPIOA`)
+
+/// Configuration of a pin, independent of the register layout
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum PinConfig {
+	Input(PUPD),
+	Output(OutputType),
+	/// Alternate function number (ignored by the STM32F1) and output type
+	Alternate(u32, OutputType),
+	Analog,
+}
+
+/// Returns the port number (0 for port A, 1 for port B...) of the GPIO at `base`
+/// The ports are 0x400 apart in every family
+pub(crate) fn port_number(base: usize) -> u32 {
+	#[cfg(feature = "sim")]
+	let base = crate::common::register::sim::hardware_address(base);
+
+	(base as u32).wrapping_sub(map::GPIOA) / 0x400
+}
+
+/// Typed access to the register `S` of the port at `base`
+/// Used by the pins and buses, which do not own the port
+pub(crate) fn reg<S: RegisterSpec>(base: usize) -> Reg<'static, S> {
+	Reg::new( unsafe { &mut *((base + S::INDEX * 4) as *mut Register<u32>) } )
+}
+
+#[repr(C)]
+pub struct Gpio {
+	base: usize,
+	pins: u32,
+	block: &'static mut [Register<u32>; SIZE],
+}
+
+impl Gpio {
+	/// Get the GPIO at `address`
+	///
+	/// # Safety
+	///
+	/// The GPIO must not be aliased by any other reference
+	pub unsafe fn new(address: u32) -> Self {
+		Gpio {
+			base: address as usize,
+			pins: (1 << 16) - 1,
+			block: &mut *(address as *mut _),
+		}
+	}
+
+	/// Get the GPIO over the given register block
+	/// Used to build the peripheral over a simulated block on the host
+	pub fn from_block(block: &'static mut [Register<u32>; SIZE]) -> Self {
+		Gpio {
+			base: block.as_ptr() as usize,
+			pins: (1 << 16) - 1,
+			block,
+		}
+	}
+
+	/// Request access to the `n`th pin
+	/// Each pin can only be requested once, as the port is owned (see `Peripherals::take`)
+	/// The pin must be converted to a mode with one of its `into_*` methods
+	pub fn pin(&mut self, n: u32) -> Result<Pin<Unknown>, GPIOError> {
+		if n > 15 { return Err(GPIOError::InvalidPin); }
+
+		match self.pins & (1 << n) == 0 {
+			true => Err(GPIOError::PinTaken),
+			_ => {
+				self.pins &= !(1 << n);
+				Ok( Pin::new(self.base, n) )
+			},
+		}
+	}
+
+	/// Checks that `pin` has not been handed out by `Gpio::pin`
+	/// The port level configuration only touches the pins it still owns
+	fn owns(&self, pin: GPIOPin) -> Result<(), GPIOError> {
+		match self.pins & (1 << pin as u32) {
+			0 => Err(GPIOError::PinTaken),
+			_ => Ok(()),
+		}
+	}
+}
+
+impl_rwio!(Gpio);
+
+/// Port level access
+/// These also affect the pins handed out by `Gpio::pin`
+impl Gpio {
+	/// Reads input
+	pub fn read(&mut self) -> u32 {
+		self.read_reg::<IDR>().bits() & mask!(16)
+	}
+
+	/// Outputs `value`
+	/// Written through BSRR, so it does not race with other writers of the port
+	pub fn send(&mut self, value: u32) -> &mut Self {
+		self.write_masked(0xFFFF, value as u16)
+	}
+
+	/// Outputs `value` on the pins of `mask`, the other pins are not changed
+	/// The write is a single BSRR write, so it is atomic
+	pub fn write_masked(&mut self, mask: u16, value: u16) -> &mut Self {
+		self.reg::<BSRR>().write(|w| w.set_bits(0, 32, masked_bsrr(mask, value)));
+		self
+	}
+
+	/// Sets the given port
+	pub fn set_port(&mut self, pin: GPIOPin) -> &mut Self {
+		self.reg::<BSRR>().write(|w| w.bs(pin as usize, 1));
+		self
+	}
+
+	/// Resets/Clears the given port
+	pub fn reset_port(&mut self, pin: GPIOPin) -> &mut Self {
+		self.reg::<BSRR>().write(|w| w.br(pin as usize, 1));
+		self
+	}
+}
+
+impl Gpio {
+	/// Locks the configuration of the pins in `mask` until the next reset
+	/// Returns all the locked pins of the port
+	///
+	/// The mode, output type, speed, pull-up/pull-down and alternate function of
+	/// a locked pin cannot be changed, the output data can
+	pub fn lock(&mut self, mask: u16) -> Result<u16, GPIOError> {
+		let mask = mask as u32;
+
+		// Key sequence: LCKK = 1, 0, 1 with the same pins, then two reads
+		// The sequence must not be interrupted by another write to LCKR
+		crate::interrupt::free(|_| {
+			self.reg::<LCKR>().write(|w| w.set_bits(0, 16, mask).lckk(1));
+			self.reg::<LCKR>().write(|w| w.set_bits(0, 16, mask).lckk(0));
+			self.reg::<LCKR>().write(|w| w.set_bits(0, 16, mask).lckk(1));
+			self.read_reg::<LCKR>();
+		});
+
+		// Every pin of `mask` must be locked, not only the port
+		let locked = self.locked();
+
+		match locked as u32 & mask == mask {
+			true => Ok( locked ),
+			_ => Err(GPIOError::LockFailed),
+		}
+	}
+
+	/// Returns the locked pins, empty if the port has not been locked
+	pub fn locked(&self) -> u16 {
+		let lckr = self.read_reg::<LCKR>();
+
+		match lckr.lckk() == 1 {
+			true => (lckr.bits() & mask!(16)) as u16,
+			_ => 0,
+		}
+	}
+
+	/// Checks if `pin` is locked
+	pub fn is_locked(&self, pin: GPIOPin) -> bool {
+		self.locked() & (1 << pin as u32) != 0
+	}
+}
+
+
+#[cfg(all(test, feature = "sim", not(feature = "stm32f1")))]
+mod tests {
+	use super::*;
+	use crate::common::PortConfig;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+
+	fn gpio() -> (SimBlock<SIZE>, Gpio) {
+		let block = SimBlock::<SIZE>::new();
+		let gpio = Gpio::from_block( unsafe { block.regs() } );
+		(block, gpio)
+	}
+
+	#[test]
+	fn write_masked_is_a_single_bsrr_write() {
+		let (block, mut gpio) = gpio();
+
+		gpio.write_masked(0x00FF, 0xA5C3);
+		assert_eq!(block.writes(BSRR::INDEX), [0x003C_00C3]);
+
+		gpio.send(0x8001);
+		assert_eq!(block.writes(BSRR::INDEX)[1], 0x7FFE_8001);
+		assert!(block.writes(ODR::INDEX).is_empty());
+	}
+
+	#[test]
+	fn port_configuration_leaves_the_handed_out_pins() {
+		let (block, mut gpio) = gpio();
+		let _pin = gpio.pin(3).unwrap();
+
+		assert_eq!(gpio.set_mode(GPIOPin::P3, PortConfig::Output).err(), Some(GPIOError::PinTaken));
+		assert_eq!(gpio.set_otype(GPIOPin::P3, OutputType::OpenDrain).err(), Some(GPIOError::PinTaken));
+		assert!(block.accesses().is_empty());
+
+		assert!(gpio.set_mode(GPIOPin::P4, PortConfig::Output).is_ok());
+		assert_eq!(block.peek(MODER::INDEX), 0b01 << 8);
+	}
+
+	#[test]
+	fn lock_writes_the_key_sequence() {
+		let (block, mut gpio) = gpio();
+
+		assert_eq!(gpio.lock(0x00F0), Ok(0x00F0));
+		assert_eq!(block.writes(7), [0x1_00F0, 0x0_00F0, 0x1_00F0]);
+		assert!(gpio.is_locked(GPIOPin::P4));
+		assert!(!gpio.is_locked(GPIOPin::P3));
+	}
+
+	#[test]
+	fn lock_fails_if_a_pin_is_not_locked() {
+		let (block, mut gpio) = gpio();
+
+		// Locked before with only P0, LCKR cannot be written until the next reset
+		block.poke(7, 0x1_0001).read_only(7, 0xFFFF_FFFF);
+
+		assert_eq!(gpio.lock(0x0003), Err(GPIOError::LockFailed));
+		assert_eq!(gpio.lock(0x0001), Ok(0x0001));
+	}
+
+	#[test]
+	fn lock_fails_without_lckk() {
+		let (block, mut gpio) = gpio();
+
+		// Wrong key sequence, LCKK stays cleared
+		block.on_write(7, |_, new| new & !(1 << 16));
+
+		assert_eq!(gpio.lock(0x0001), Err(GPIOError::LockFailed));
+		assert_eq!(gpio.locked(), 0);
+	}
+}
@@ -0,0 +1,262 @@
+//! MODER register layout (STM32F0, STM32F4 and STM32F7)
+
+use crate::common::{ GPIOError, GPIOPin, PortConfig, AltFunction, GPIOSpeed, PUPD, OutputType };
+
+use super::{ Gpio, PinConfig, reg };
+
+pub const SIZE: usize = 10;
+
+registers! {
+	/// Port mode register
+	/// Reset value is 0xA800_0000 for port A and 0x0000_0280 for port B
+	MODER @ 0, reset = 0x0000_0000 {
+		mode[16]: 0, 2, RW;
+	}
+
+	/// Port output type register
+	OTYPER @ 1, reset = 0x0000_0000 {
+		ot[16]: 0, 1, RW;
+	}
+
+	/// Port output speed register
+	OSPEEDR @ 2, reset = 0x0000_0000 {
+		ospeed[16]: 0, 2, RW;
+	}
+
+	/// Port pull-up/pull-down register
+	PUPDR @ 3, reset = 0x0000_0000 {
+		pupd[16]: 0, 2, RW;
+	}
+
+	/// Port input data register
+	IDR @ 4, reset = 0x0000_0000 {
+		idr[16]: 0, 1, RO;
+	}
+
+	/// Port output data register
+	ODR @ 5, reset = 0x0000_0000 {
+		odr[16]: 0, 1, RW;
+	}
+
+	/// Port bit set/reset register
+	BSRR @ 6, reset = 0x0000_0000 {
+		/// Set the pin
+		bs[16]: 0, 1, WO;
+		/// Reset the pin
+		br[16]: 16, 1, WO;
+	}
+
+	/// Port configuration lock register
+	LCKR @ 7, reset = 0x0000_0000 {
+		lck[16]: 0, 1, RW;
+		/// Lock key
+		lckk: 16, 1, RW;
+	}
+
+	/// Alternate function low register (pins 0 to 7)
+	AFRL @ 8, reset = 0x0000_0000 {
+		afr[8]: 0, 4, RW;
+	}
+
+	/// Alternate function high register (pins 8 to 15)
+	AFRH @ 9, reset = 0x0000_0000 {
+		afr[8]: 0, 4, RW;
+	}
+}
+
+/// Configuration of a port
+/// Used to save and restore the port around low power modes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortSnapshot {
+	pub moder: u32,
+	pub otyper: u32,
+	pub ospeedr: u32,
+	pub pupdr: u32,
+	pub afrl: u32,
+	pub afrh: u32,
+}
+
+/// Configures the pin `n` of the port at `base`
+pub(crate) fn configure(base: usize, n: u32, config: PinConfig) {
+	let n = n as usize;
+
+	match config {
+		PinConfig::Input(pupd) => {
+			reg::<MODER>(base).modify(|_, w| w.mode(n, 0b00));
+			reg::<PUPDR>(base).modify(|_, w| w.pupd(n, pupd as u32));
+		},
+
+		PinConfig::Output(otype) => {
+			reg::<PUPDR>(base).modify(|_, w| w.pupd(n, 0b00));
+			reg::<OTYPER>(base).modify(|_, w| w.ot(n, otype as u32));
+			reg::<MODER>(base).modify(|_, w| w.mode(n, 0b01));
+		},
+
+		PinConfig::Alternate(af, otype) => {
+			reg::<OTYPER>(base).modify(|_, w| w.ot(n, otype as u32));
+
+			match n {
+				0..=7 => reg::<AFRL>(base).modify(|_, w| w.afr(n, af)),
+				_     => reg::<AFRH>(base).modify(|_, w| w.afr(n - 8, af)),
+			}
+
+			reg::<MODER>(base).modify(|_, w| w.mode(n, 0b10));
+		},
+
+		PinConfig::Analog => {
+			reg::<PUPDR>(base).modify(|_, w| w.pupd(n, 0b00));
+			reg::<MODER>(base).modify(|_, w| w.mode(n, 0b11));
+		},
+	}
+}
+
+/// Sets the output speed of the pin `n` of the port at `base`
+pub(crate) fn set_speed(base: usize, n: u32, speed: GPIOSpeed) {
+	reg::<OSPEEDR>(base).modify(|_, w| w.ospeed(n as usize, speed as u32));
+}
+
+/// Sets the pull-up/pull-down of the pin `n` of the port at `base`, in any mode
+pub(crate) fn set_pull(base: usize, n: u32, pupd: PUPD) {
+	reg::<PUPDR>(base).modify(|_, w| w.pupd(n as usize, pupd as u32));
+}
+
+/// Sets the pins of `mask` as outputs (`true`) or floating inputs (`false`)
+/// The output type, speed and pull-up/pull-down are not changed
+pub(crate) fn set_direction(base: usize, mask: u16, output: bool) {
+	let mode = output as u32;
+
+	reg::<MODER>(base).modify(|_, w| {
+		for n in (0..16).filter(|n| mask & (1 << n) != 0) {
+			w.mode(n, mode);
+		}
+		w
+	});
+}
+
+/// Port level configuration
+/// Only the pins not handed out by `Gpio::pin` can be configured, the others
+/// are owned by their `Pin` or by the peripheral using them
+impl Gpio {
+	/// Set up port mode
+	pub fn set_mode(&mut self, pin: GPIOPin, mode: PortConfig) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		self.reg::<MODER>().modify(|_, w| w.mode(pin as usize, mode as u32));
+		Ok( self )
+	}
+
+	/// Set port output type
+	pub fn set_otype(&mut self, pin: GPIOPin, otype: OutputType) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		self.reg::<OTYPER>().modify(|_, w| w.ot(pin as usize, otype as u32));
+		Ok( self )
+	}
+
+	/// Set port output speed
+	pub fn set_ospeed(&mut self, pin: GPIOPin, speed: GPIOSpeed) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		self.reg::<OSPEEDR>().modify(|_, w| w.ospeed(pin as usize, speed as u32));
+		Ok( self )
+	}
+
+	/// Set port PUPD mode
+	pub fn set_pupd(&mut self, pin: GPIOPin, pupd: PUPD) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		self.reg::<PUPDR>().modify(|_, w| w.pupd(pin as usize, pupd as u32));
+		Ok( self )
+	}
+
+	/// Sets the AltFunction for `pin`
+	pub fn set_af(&mut self, pin: GPIOPin, af: AltFunction) -> Result<&mut Self, GPIOError> {
+		self.owns(pin)?;
+
+		match pin as usize {
+			n @ 0..=7  => self.reg::<AFRL>().modify(|_, w| w.afr(n, af as u32)),
+			n @ 8..=15 => self.reg::<AFRH>().modify(|_, w| w.afr(n - 8, af as u32)),
+			_ => unreachable!(),
+		}
+
+		Ok( self )
+	}
+
+	/// Captures the configuration of the port
+	pub fn snapshot(&self) -> PortSnapshot {
+		PortSnapshot {
+			moder: self.read_reg::<MODER>().bits(),
+			otyper: self.read_reg::<OTYPER>().bits(),
+			ospeedr: self.read_reg::<OSPEEDR>().bits(),
+			pupdr: self.read_reg::<PUPDR>().bits(),
+			afrl: self.read_reg::<AFRL>().bits(),
+			afrh: self.read_reg::<AFRH>().bits(),
+		}
+	}
+
+	/// Restores a configuration captured with `snapshot`
+	/// The alternate functions are restored before the modes so no pin glitches
+	/// to a wrong function. Locked pins keep their configuration
+	pub fn restore(&mut self, snapshot: &PortSnapshot) -> &mut Self {
+		self.reg::<AFRL>().write(|w| w.set_bits(0, 32, snapshot.afrl));
+		self.reg::<AFRH>().write(|w| w.set_bits(0, 32, snapshot.afrh));
+		self.reg::<OTYPER>().write(|w| w.set_bits(0, 32, snapshot.otyper));
+		self.reg::<OSPEEDR>().write(|w| w.set_bits(0, 32, snapshot.ospeedr));
+		self.reg::<PUPDR>().write(|w| w.set_bits(0, 32, snapshot.pupdr));
+		self.reg::<MODER>().write(|w| w.set_bits(0, 32, snapshot.moder));
+		self
+	}
+}
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::{ AccessKind, SimBlock }, RegisterSpec };
+
+	/// Port A at reset, with PA5 on AF5 and PA9 on AF7
+	fn porta() -> (SimBlock<SIZE>, Gpio) {
+		let block = SimBlock::<SIZE>::with_reset([0xA800_0000, 0, 0x0C00_0000, 0x6400_0000, 0, 0, 0, 0, 0, 0]);
+		block.poke(AFRL::INDEX, 5 << 20).poke(AFRH::INDEX, 7 << 4);
+
+		let gpio = Gpio::from_block( unsafe { block.regs() } );
+		(block, gpio)
+	}
+
+	#[test]
+	fn restore_writes_back_the_snapshot() {
+		let (block, mut gpio) = porta();
+		let snapshot = gpio.snapshot();
+
+		assert_eq!((snapshot.moder, snapshot.ospeedr, snapshot.pupdr), (0xA800_0000, 0x0C00_0000, 0x6400_0000));
+		assert_eq!((snapshot.afrl, snapshot.afrh), (5 << 20, 7 << 4));
+
+		for (i, value) in [(MODER::INDEX, 0x5555_5555), (OTYPER::INDEX, 0xFFFF), (OSPEEDR::INDEX, 0xFFFF_FFFF),
+			(PUPDR::INDEX, 0xAAAA_AAAA), (AFRL::INDEX, 0x1234_5678), (AFRH::INDEX, 0x8765_4321)] {
+			block.poke(i, value);
+		}
+
+		gpio.restore(&snapshot);
+
+		assert_eq!(block.peek(MODER::INDEX), snapshot.moder);
+		assert_eq!(block.peek(OTYPER::INDEX), snapshot.otyper);
+		assert_eq!(block.peek(OSPEEDR::INDEX), snapshot.ospeedr);
+		assert_eq!(block.peek(PUPDR::INDEX), snapshot.pupdr);
+		assert_eq!(block.peek(AFRL::INDEX), snapshot.afrl);
+		assert_eq!(block.peek(AFRH::INDEX), snapshot.afrh);
+	}
+
+	#[test]
+	fn restore_writes_the_modes_last() {
+		let (block, mut gpio) = porta();
+		let snapshot = gpio.snapshot();
+
+		gpio.restore(&snapshot);
+
+		let writes: Vec<usize> = block.accesses().into_iter()
+			.filter(|(kind, _, _)| *kind == AccessKind::Write)
+			.map(|(_, i, _)| i)
+			.collect();
+
+		assert_eq!(writes, [AFRL::INDEX, AFRH::INDEX, OTYPER::INDEX, OSPEEDR::INDEX, PUPDR::INDEX, MODER::INDEX]);
+	}
+}
@@ -0,0 +1,225 @@
+//! General purpose timer
+//! Only the time base (prescaler, auto-reload and update event) is supported
+
+use crate::common::{ Register, Frequency, Clocks, PeripheralBus };
+
+#[cfg(has_tim2)]
+pub use crate::peripherals::map::TIM2;
+#[cfg(has_tim3)]
+pub use crate::peripherals::map::TIM3;
+#[cfg(has_tim4)]
+pub use crate::peripherals::map::TIM4;
+#[cfg(has_tim5)]
+pub use crate::peripherals::map::TIM5;
+
+pub const SIZE: usize = 21;
+
+registers! {
+	/// Control register 1
+	CR1 @ 0, reset = 0x0000_0000 {
+		/// Auto-reload preload enable
+		arpe: 7, 1, RW;
+		/// Direction (0: upcounter, 1: downcounter)
+		dir: 4, 1, RW;
+		/// One-pulse mode
+		opm: 3, 1, RW;
+		/// Update request source
+		urs: 2, 1, RW;
+		/// Update disable
+		udis: 1, 1, RW;
+		/// Counter enable
+		cen: 0, 1, RW;
+	}
+
+	/// DMA/Interrupt enable register
+	DIER @ 3, reset = 0x0000_0000 {
+		/// Update interrupt enable
+		uie: 0, 1, RW;
+	}
+
+	/// Status register, the flags are cleared by writing a 0
+	SR @ 4, reset = 0x0000_0000 {
+		/// Capture/Compare overcapture flags
+		ccof[4]: 9, 1, W0C;
+		/// Trigger interrupt flag
+		tif: 6, 1, W0C;
+		/// Capture/Compare interrupt flags
+		ccif[4]: 1, 1, W0C;
+		/// Update interrupt flag
+		uif: 0, 1, W0C;
+	}
+
+	/// Event generation register
+	EGR @ 5, reset = 0x0000_0000 {
+		/// Update generation
+		ug: 0, 1, WO;
+	}
+
+	/// Counter
+	CNT @ 9, reset = 0x0000_0000 {
+		cnt: 0, 32, RW;
+	}
+
+	/// Prescaler
+	PSC @ 10, reset = 0x0000_0000 {
+		psc: 0, 16, RW;
+	}
+
+	/// Auto-reload register
+	ARR @ 11, reset = 0x0000_FFFF {
+		arr: 0, 32, RW;
+	}
+}
+
+#[repr(C)]
+pub struct GpTim {
+	block: [Register<u32>; SIZE],
+}
+
+impl crate::common::VolatileStruct for GpTim {}
+
+impl_rwio!(GpTim);
+
+impl GpTim {
+	/// Returns the counter clock of a timer on `bus` before the prescaler
+	/// The timers run at twice the APB clock when the APB prescaler is not 1
+	pub fn clock(clocks: &Clocks, bus: PeripheralBus) -> Frequency {
+		let apb = match bus {
+			PeripheralBus::APB1 => clocks.apb1f,
+			PeripheralBus::APB2 => clocks.apb2f,
+			PeripheralBus::AHB => return clocks.ahb1f,
+		};
+
+		if apb == clocks.ahb1f { apb } else { apb * 2 }
+	}
+
+	/// Enables/Disables the counter
+	pub fn counter_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<CR1>().modify(|_, w| w.cen(s as u32));
+		self
+	}
+
+	/// Enables/Disables the one-pulse mode, the counter stops at the next update event
+	pub fn one_pulse_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<CR1>().modify(|_, w| w.opm(s as u32));
+		self
+	}
+
+	/// Enables/Disables the update interrupt
+	pub fn update_interrupt_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<DIER>().modify(|_, w| w.uie(s as u32));
+		self
+	}
+
+	/// Sets the prescaler, the counter clock is divided by `psc + 1`
+	/// The new value is used after the next update event
+	pub fn set_prescaler(&mut self, psc: u16) -> &mut Self {
+		self.reg::<PSC>().write(|w| w.psc(psc as u32));
+		self
+	}
+
+	/// Sets the auto-reload value, the counter wraps every `arr + 1` ticks
+	/// TIM2 and TIM5 are 32-bit, the other timers only use the lower 16 bits
+	pub fn set_autoreload(&mut self, arr: u32) -> &mut Self {
+		self.reg::<ARR>().write(|w| w.arr(arr));
+		self
+	}
+
+	/// Sets the counter value
+	pub fn set_counter(&mut self, cnt: u32) -> &mut Self {
+		self.reg::<CNT>().write(|w| w.cnt(cnt));
+		self
+	}
+
+	/// Gets the counter value
+	pub fn get_counter(&self) -> u32 {
+		self.read_reg::<CNT>().cnt()
+	}
+
+	/// Generates an update event, reloading the prescaler and the counter
+	/// Also sets the update flag unless the update request source is restricted
+	pub fn update(&mut self) -> &mut Self {
+		self.reg::<EGR>().write(|w| w.ug(1));
+		self
+	}
+
+	/// Checks if an update event happened
+	pub fn is_updated(&self) -> bool {
+		self.read_reg::<SR>().uif() == 1
+	}
+
+	/// Clears the update flag
+	pub fn clear_update(&mut self) -> &mut Self {
+		self.reg::<SR>().write(|w| w.uif());
+		self
+	}
+
+	/// Checks if the counter is enabled
+	pub fn is_counter_enabled(&self) -> bool {
+		self.read_reg::<CR1>().cen() == 1
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+pub(crate) mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+
+	/// Timer over a simulated block with the status flags cleared by writing a 0
+	pub(crate) fn tim() -> (SimBlock<SIZE>, &'static mut GpTim) {
+		let block = SimBlock::<SIZE>::new();
+		block.write_zero_to_clear(SR::INDEX, 0x1E5F);
+
+		let tim = unsafe { block.view::<GpTim>() };
+		(block, tim)
+	}
+
+	#[test]
+	fn the_clock_is_doubled_with_an_apb_prescaler() {
+		let clocks = Clocks { ahb1f: Frequency::MHz(84), apb1f: Frequency::MHz(42), apb2f: Frequency::MHz(84), ..Clocks::default() };
+
+		assert_eq!(GpTim::clock(&clocks, PeripheralBus::APB1), Frequency::MHz(84));
+		assert_eq!(GpTim::clock(&clocks, PeripheralBus::APB2), Frequency::MHz(84));
+		assert_eq!(GpTim::clock(&clocks, PeripheralBus::AHB), Frequency::MHz(84));
+	}
+
+	#[test]
+	fn time_base() {
+		let (block, tim) = tim();
+
+		tim.set_prescaler(83)
+			.set_autoreload(999)
+			.set_counter(5)
+			.one_pulse_state(true)
+			.counter_state(true)
+			.update();
+
+		assert_eq!(block.peek(PSC::INDEX), 83);
+		assert_eq!(block.peek(ARR::INDEX), 999);
+		assert_eq!(tim.get_counter(), 5);
+		assert_eq!(block.peek(CR1::INDEX), 0b1001);
+		assert_eq!(block.writes(EGR::INDEX), [1]);
+		assert!(tim.is_counter_enabled());
+	}
+
+	#[test]
+	fn clear_update_only_clears_the_update_flag() {
+		let (block, tim) = tim();
+
+		// UIF, CC1IF and CC2OF raised
+		block.poke(SR::INDEX, (1 << 10) | 0b11);
+		assert!(tim.is_updated());
+
+		tim.clear_update();
+
+		// A single write, with a 1 on the other flags so they are kept
+		assert_eq!(block.writes(SR::INDEX), [0x1E5E]);
+		assert_eq!(block.peek(SR::INDEX), (1 << 10) | 0b10);
+		assert!(!tim.is_updated());
+
+		// Nor does a read-modify-write of SR
+		tim.reg::<SR>().modify(|_, w| w);
+		assert_eq!(block.peek(SR::INDEX), (1 << 10) | 0b10);
+	}
+}
@@ -1,223 +1,340 @@
 //! I2C Peripheral
+//! Register layout of the STM32F1 and STM32F4, the STM32F0 and STM32F7 have another one
+//! Implements `embedded-hal` traits
 
-use crate::common::{ Register, Frequency, I2CInterrupt, I2CFlags, I2CBitMode, MasterMode, DutyCycle, DualAddress };
-use crate::common::enums::RCCPeripheral;
-use crate::common::structs::pins::Pin;
+use crate::common::{ asm, Register, Frequency, I2CError, I2CInterrupt, I2CFlags, I2CBitMode, MasterMode, DutyCycle, DualAddress };
+use crate::common::{ RCCPeripheral, OutputType };
+use crate::common::structs::{ Alternate, AltFn, OpenDrain, Pin, Unknown };
+use crate::peripherals::af::Signal;
+use crate::peripherals::extended::rcc::Rcc;
 
-use embedded_hal::blocking::i2c::{ Read, Write, WriteRead };
+#[cfg(feature = "stm32f1")]
+use crate::peripherals::extended::syscfg;
 
-use crate::peripherals::extended::{ gpio::Gpio, rcc::Rcc };
+use embedded_hal::blocking::i2c::{ Read, Write, WriteRead };
 
-pub const I2C1: u32 = 0x4000_5400;
-pub const I2C2: u32 = 0x4000_5800;
-pub const I2C3: u32 = 0x4000_5C00;
+pub use crate::peripherals::map::I2C1;
+#[cfg(has_i2c2)]
+pub use crate::peripherals::map::I2C2;
+#[cfg(has_i2c3)]
+pub use crate::peripherals::map::I2C3;
 
 pub const SIZE: usize = 10;
 
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum I2CError {
-	/// NACK received
-	NACK,
-	/// Bus error
-	Bus,
-	/// Arbitration loss
-	Arbitration,
-	/// Overrun - Slave mode only
-	Overrun,
-	/// PEC - SMBUS mode only
-	PEC,
-	/// Timeout - SMBUS mode only
-	Timeout,
-	/// Alert - SMBUS mode only
-	Alert,
-	Other,
+/// Cycles waited for a flag of a transfer, the SMBus timeout of 25 ms at 16 MHz
+pub const FLAG_TIMEOUT: u32 = 400_000;
+
+/// (SDA, SCL) pins of `I2c::master`
+pub type MasterPins<SDA, SCL> = (Pin<Alternate<SDA, OpenDrain>>, Pin<Alternate<SCL, OpenDrain>>);
+
+registers! {
+	/// Control register 1
+	CR1 @ 0, reset = 0x0000_0000 {
+		/// Software reset
+		swrst: 15, 1, RW;
+		/// Packet error checking of the next byte
+		pec: 12, 1, RW;
+		/// Acknowledge of the next byte
+		pos: 11, 1, RW;
+		/// Acknowledge enable
+		ack: 10, 1, RW;
+		/// Stop generation
+		stop: 9, 1, RW;
+		/// Start generation
+		start: 8, 1, RW;
+		/// Peripheral enable
+		pe: 0, 1, RW;
+	}
+
+	/// Control register 2
+	CR2 @ 1, reset = 0x0000_0000 {
+		/// DMA last transfer
+		last: 12, 1, RW;
+		/// Buffer, event and error interrupt enables
+		itbufen: 10, 1, RW;
+		itevten: 9, 1, RW;
+		iterren: 8, 1, RW;
+		/// Peripheral clock frequency, in MHz
+		freq: 0, 6, RW;
+	}
+
+	/// Own address register 1
+	OAR1 @ 2, reset = 0x0000_0000 {
+		/// Addressing mode (0: 7-bit, 1: 10-bit)
+		addmode: 15, 1, RW;
+		add10: 0, 10, RW;
+		add7: 1, 7, RW;
+	}
+
+	/// Own address register 2
+	OAR2 @ 3, reset = 0x0000_0000 {
+		add2: 1, 7, RW;
+		/// Dual addressing mode enable
+		endual: 0, 1, RW;
+	}
+
+	/// Data register
+	DR @ 4, reset = 0x0000_0000 {
+		dr: 0, 8, RW;
+	}
+
+	/// Status register 1
+	/// The error flags (bits 8 to 15) are cleared by writing 0
+	SR1 @ 5, reset = 0x0000_0000 {
+		/// Acknowledge failure
+		af: 10, 1, RO;
+		/// Data register empty
+		txe: 7, 1, RO;
+		/// Data register not empty
+		rxne: 6, 1, RO;
+		/// Byte transfer finished
+		btf: 2, 1, RO;
+		/// Address sent
+		addr: 1, 1, RO;
+		/// Start bit
+		sb: 0, 1, RO;
+	}
+
+	/// Status register 2
+	SR2 @ 6, reset = 0x0000_0000 {
+		pec: 8, 8, RO;
+		/// Dual flag, the second address matched
+		dualf: 7, 1, RO;
+		/// Transmitter
+		tra: 2, 1, RO;
+		/// Bus busy
+		busy: 1, 1, RO;
+		/// Master
+		msl: 0, 1, RO;
+	}
+
+	/// Clock control register
+	CCR @ 7, reset = 0x0000_0000 {
+		/// Fast mode
+		fs: 15, 1, RW;
+		/// Fast mode duty cycle (0: 2, 1: 16/9)
+		duty: 14, 1, RW;
+		ccr: 0, 12, RW;
+	}
+
+	/// Rise time register
+	TRISE @ 8, reset = 0x0000_0002 {
+		trise: 0, 6, RW;
+	}
+
+	/// Filter register
+	FLTR @ 9, reset = 0x0000_0000 {
+		/// Analog noise filter off
+		anoff: 4, 1, RW;
+		/// Digital noise filter
+		dnf: 0, 4, RW;
+	}
 }
 
+
 #[repr(C)]
 pub struct I2c {
-	#[repr(C)]
-	block: &'static [Register<u32>; SIZE],
-	pins: (Pin, Pin),
+	id: RCCPeripheral,
+	pins: Option<[Pin<Unknown>; 2]>,
+	block: &'static mut [Register<u32>; SIZE],
 }
 
-impl I2c {
-	/// Sets bit at block and offset given
-	pub fn set(&mut self, b: usize, o: usize) -> &mut Self {
-		self.block[b] |= 1 << o;
-		self
-	}
-
-	/// Clears bit at block and offset given
-	pub fn clear(&mut self, b: usize, o: usize) -> &mut Self {
-		self.block[b] &= !(1 << o);
-		self
-	}
-
-	/// Checks if bit is set
-	pub fn is_set(&self, r: usize, b: usize) -> bool {
-		(self.block[r].read() >> b) & 1 == 1
-	}
-
-	pub fn write_bits(&mut self, b: usize, o: usize, data: u32, size: usize) -> &mut Self {
-		let mask = (1u32 << size) - 1;
-		let old = self.block[b].read();
-		self.block[b].write( old & !(mask << o) | ((data & mask) << o) );
-		self
-	}
-}
+impl_rwio!(I2c);
 
 impl I2c {
-	/// Set up as master
-	pub fn master<'a>(address: u32, pins: (Pin, Pin), rcc: &'a Rcc, clocks: Clocks, speed: Frequency) -> Result<Self, I2CError> {
-		let i2cid = match address {
+	/// Get the I2C at `address`
+	///
+	/// # Safety
+	///
+	/// The I2C must not be aliased by any other reference
+	pub unsafe fn from_addr(address: u32) -> Result<Self, I2CError> {
+		let id = match address {
 			I2C1 => RCCPeripheral::I2C1,
+			#[cfg(has_i2c2)]
 			I2C2 => RCCPeripheral::I2C2,
+			#[cfg(has_i2c3)]
 			I2C3 => RCCPeripheral::I2C3,
-			_ => return Err(I2CError::Other),
+			_ => return Err(I2CError::InvalidAddress),
 		};
 
-		let new = I2c {
+		Ok( I2c {
+			id,
+			pins: None,
 			block: &mut *(address as *mut _),
-			pins,
-		};
+		} )
+	}
 
-		let (sda, scl) = pins;
+	/// Get the I2C `id` over the given register block
+	/// Used to build the peripheral over a simulated block on the host
+	pub fn from_block(id: RCCPeripheral, block: &'static mut [Register<u32>; SIZE]) -> Self {
+		I2c {
+			id,
+			pins: None,
+			block,
+		}
+	}
+
+	/// Returns the (SDA, SCL) signals of the peripheral
+	fn signals(&self) -> (Signal, Signal) {
+		match self.id {
+			#[cfg(has_i2c2)]
+			RCCPeripheral::I2C2 => (Signal::I2C2_SDA, Signal::I2C2_SCL),
+			#[cfg(has_i2c3)]
+			RCCPeripheral::I2C3 => (Signal::I2C3_SDA, Signal::I2C3_SCL),
+			_ => (Signal::I2C1_SDA, Signal::I2C1_SCL),
+		}
+	}
 
-		// TODO : Change to each board
-		sda.altfn(4)
-			.speed(HIGH);
-		scl.altfn(4)
-			.speed(HIGH);
+	/// Set up as master at `speed`, up to 100 kHz in standard mode and 400 kHz in fast mode
+	/// The pins are (SDA, SCL), as the open-drain alternate functions of the signals
+	/// they carry. Pins that do not carry them are rejected
+	/// The fast mode uses a duty cycle of 2
+	pub fn master<SDA: AltFn, SCL: AltFn>(&mut self, pins: MasterPins<SDA, SCL>, speed: Frequency, rcc: &mut Rcc) -> Result<&mut Self, I2CError> {
+		let (sdasig, sclsig) = self.signals();
+		let functions = [pins.0.carries(sdasig), pins.1.carries(sclsig)];
 
-		rcc.peripheral_state(true, i2cid);
-		rcc.reset_peripheral(i2cid);
+		if functions.contains(&0) {
+			return Err(I2CError::InvalidPin);
+		}
 
-		// TODO set up RCC clocks
+		// The STM32F1 routes both pins with one remap
+		#[cfg(feature = "stm32f1")]
+		let remap = syscfg::common_remap(&functions).ok_or(I2CError::InvalidPin)?;
 
-		// Disable he peripheral
-		// by clearing PE bit in CR1
-		new.clear(0, 0);
-		// Calculate settings for I2C speed modes
-		// If the user used the RCC given clocks, APB clock is legal
+		let pclk = rcc.clocks().apb1f;
+		let freq = pclk.mhz();
 
-		// Configure bus frequency into I2C peripheral
-		new.write_bits(1, 0, clocks.apb1.mhz(), 6);
+		if !(2..=50).contains(&freq) {
+			return Err(I2CError::FrequencyNotAllowed);
+		}
 
-		let trise = if speed <= Frequency::KHz(100) {
-			clocks.apb1.mhz() + 1
-		} else {
-			((clocks.apb.mhz() * 300) / 1000) + 1
+		// CCR and TRISE, the rise time is 1000 ns in standard mode and 300 ns in fast mode
+		let (fs, ccr, trise) = match speed.hz() {
+			0 => return Err(I2CError::InvalidBusSpeed),
+			1..=100_000 => (0, (pclk.hz() / (speed.hz() * 2)).max(4), freq + 1),
+			100_001..=400_000 => (1, (pclk.hz() / (speed.hz() * 3)).max(1), freq * 300 / 1000 + 1),
+			_ => return Err(I2CError::InvalidBusSpeed),
 		};
 
-		// Configure correct rise times
-		new.write_bits(8, 0, trise, 6);
-
-		// I2C clock control calculation
-		// If in slow mode
-		if speed <= Frequency::KHz(100) {
-			let ccr = match clocks.apb.hz() / (speed.hz() * 2) {
-				0...3 => 4,
-				n => n,
-			};
-
-			// Set clock to standard mode with appropiate parameters for selected speed
-			new.clear(7, 15)
-				.clear(7, 14)
-				.write_bits(7, 0, ccr, 12);
-		} else {
-			// Fast mode
-			// Defaults for now to 2:1 duty cycle
-			if true {
-				let ccr = match clocks.apb1.hz() / (speed.hz() * 3) {
-					0 => 1,
-					n => n,
-				};
-
-				new.set(7, 15)
-					.clear(7, 14)
-					.write_bits(7, 0, ccr, 12);
-			} else {
-				// 16:9 duty cycle
-				let ccr = match clocks.apb1.hz() / (speed.hz() * 25) {
-					0 => 1,
-					n => n,
-				};
-
-				new.set(7, 15)
-					.set(7, 14)
-					.write_bits(7, 0, ccr, 12);
-			}
-		}
+		rcc.peripheral_state(true, self.id)
+			.reset_peripheral(self.id);
+
+		#[cfg(feature = "stm32f1")]
+		syscfg::apply_remap(self.id, remap, rcc);
 
-		new.set(0, 0);
+		let od = OutputType::OpenDrain;
 
-		Ok( new )
+		self.pins = match (pins.0.into_signal(sdasig, od), pins.1.into_signal(sclsig, od)) {
+			(Some(sda), Some(scl)) => Some( [sda, scl] ),
+			_ => unreachable!(),
+		};
+
+		// The timing is only written with the peripheral disabled
+		self.reg::<CR1>().modify(|_, w| w.pe(0));
+		self.reg::<CR2>().modify(|_, w| w.freq(freq));
+		self.reg::<TRISE>().write(|w| w.trise(trise));
+		self.reg::<CCR>().write(|w| w.fs(fs).duty(0).ccr(ccr));
+		self.reg::<CR1>().modify(|_, w| w.pe(1));
+
+		Ok( self )
 	}
 
 	/// Stop the peripheral and release the pins
-	pub fn free(&mut self) -> (Pin, Pin) {
-		self.clear(0, 0);
-		self.pins
+	/// The pins are still in their alternate function mode
+	pub fn free(&mut self) -> [Pin<Unknown>; 2] {
+		let pins = match self.pins.take() {
+			Some(p) => p,
+			_ => panic!("Cannot free an uninitialized peripheral"),
+		};
+
+		self.reg::<CR1>().modify(|_, w| w.pe(0));
+
+		pins
 	}
 }
 
-
-/*
 impl I2c {
-	/// Scans for devices and returns all the addresses it found connected
-	pub fn scan(&mut self) -> Vec<u8> {
-		let mut addresses = Vec::new();
-		let mut void = &[0];
-
-		for i in 0..128 {
-			match self.read(i, void) {
-				Ok(()) => addresses.push(i),
-				_ => (),
+	/// Generates a START, or a repeated START, and sends the address of the slave
+	/// The address is acknowledged when this returns, ADDR is still to be cleared
+	fn address(&mut self, addr: u8, read: bool) -> Result<(), I2CError> {
+		self.start();
+		self.wait(I2CFlags::Start, false)?;
+
+		self.write_data(((addr as u32) << 1) | read as u32);
+		self.wait(I2CFlags::AddressSent, true)
+	}
+
+	/// Waits until `f` is raised, for at most `FLAG_TIMEOUT` cycles
+	/// If `acked`, an ACK failure ends the wait with a NACK. Both errors generate a STOP
+	fn wait(&mut self, f: I2CFlags, acked: bool) -> Result<(), I2CError> {
+		let mut waited = 0;
+
+		loop {
+			if acked && self.is_raised(I2CFlags::ACKFailure) {
+				self.clear_flag(I2CFlags::ACKFailure)
+					.stop();
+				return Err(I2CError::NACK);
+			}
+
+			if self.is_raised(f) { return Ok(()); }
+
+			if waited >= FLAG_TIMEOUT {
+				self.stop();
+				return Err(I2CError::Timeout);
 			}
+
+			asm::delay(100);
+			waited += 100;
+		}
+	}
+
+	/// Clears ADDR, by reading SR1 then SR2
+	fn clear_addr(&mut self) {
+		self.read_reg::<SR1>();
+		self.read_reg::<SR2>();
+	}
+
+	/// Sends `bytes` to `addr`, without a STOP
+	fn send(&mut self, addr: u8, bytes: &[u8]) -> Result<(), I2CError> {
+		self.address(addr, false)?;
+		self.clear_addr();
+
+		for b in bytes {
+			self.send_byte(*b)?;
 		}
 
-		addresses
+		Ok(())
 	}
 }
-*/
+
 impl Read for I2c {
 	type Error = I2CError;
 
-	/// Read bytes into buffer
-	/// This function is based on MASTER mode
-	/// WARNING!
-	/// `unsafe` function (but now marked as such). This function may leave the sender hanging 
-	/// if the sender sends more bytes than what the buffer can hold.
-	/// This is due to no STOP signal being sent back.
+	/// Reads bytes into `buffer` in master mode
+	/// The last byte is not acknowledged and followed by a STOP
 	fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), I2CError> {
-		let last = buffer.len() - 1;
-
-		// Send start condition and ACK bit
-		self.start()
-			.ack();
-		// Wait until START condition is generated
-		while !self.is_set(5, 0) {}
-		// Wait until all devices are listening to us (bus is free)
-		while !self.is_set(6, 0) && !self.is_set(6, 1) {}
-
-		// Set up current address to talk to
-		self.write_data(((addr as u32) << 1) + 1);
-		// wait until address was sent
-		while !self.is_set(5, 1) {}
-
-		// Clear condition by reading SR2
-		let _ = self.block[6].read();
-
-		// Store bytes
-		for i in 0..last {
-			buffer[i] = self.recv_byte()?;
+		let last = match buffer.len() {
+			0 => return Ok(()),
+			n => n - 1,
+		};
+
+		self.ack();
+		self.address(addr, true)?;
+
+		// A single byte is not acknowledged, which is set before ADDR is cleared
+		if last == 0 {
+			self.nack();
+		}
+
+		self.clear_addr();
+
+		for byte in buffer[..last].iter_mut() {
+			*byte = self.recv_byte()?;
 		}
 
 		self.nack()
 			.stop();
 
-		// Read last byte
 		buffer[last] = self.recv_byte()?;
 
 		Ok(())
@@ -227,28 +344,10 @@ impl Read for I2c {
 impl Write for I2c {
 	type Error = I2CError;
 
-	/// Send a buffer of bytes
+	/// Sends `bytes` to `addr`, followed by a STOP
 	fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), I2CError> {
-		// Send START condition
-		self.start();
-		// Wait until START condition is generated
-		while !self.is_set(5, 0) {}
-		// Wait until all devices are listening to us (bus is free)
-		while !self.is_set(6, 0) && !self.is_set(6, 1) {}
-
-		// Set up current address to talk to
-		self.write_data((addr as u32) << 1);
-		// wait until address was sent
-		while !self.is_set(5, 1) {}
-
-		// Clear condition by reading SR2
-		// let _ = ptr::read_volatile(self as u32 + 0x18);
-		let _ = self.block[6].read();
-
-		// Send the bytes
-		for b in bytes {
-			self.send_byte(*b)?;
-		}
+		self.send(addr, bytes)?;
+		self.stop();
 
 		Ok(())
 	}
@@ -257,35 +356,30 @@ impl Write for I2c {
 impl WriteRead for I2c {
 	type Error = I2CError;
 
-	/// Writes some bytes then reads some bytes
+	/// Sends `bytes` then reads `buffer`, with a repeated START in between
 	fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), I2CError> {
-		self.write(addr, bytes)?;
+		self.send(addr, bytes)?;
 		self.read(addr, buffer)
 	}
 }
 
 impl I2c {
 	/// Sends a byte
+	/// Fails with a timeout, after a STOP, if it is not sent within `FLAG_TIMEOUT`
 	pub fn send_byte(&mut self, byte: u8) -> Result<&mut Self, I2CError> {
 		// Wait until TX buffer is empty
-		while !self.is_raised(I2CFlags::TxEmpty) {}
+		self.wait(I2CFlags::TxEmpty, false)?;
 
 		self.write_data(byte as u32);
-
-		while {
-			if self.is_raised(I2CFlags::ACKFailure) {
-				return Err(I2CError::NACK);
-			}
-
-			!self.is_raised(I2CFlags::TransferComplete)
-		} {}
+		self.wait(I2CFlags::TransferComplete, true)?;
 
 		Ok( self )
 	}
 
 	/// Receive a byte
-	pub fn recv_byte(&self) -> Result<u8, I2CError> {
-		while !self.is_raised(I2CFlags::RxNotEmpty) {}
+	/// Fails with a timeout, after a STOP, if none comes within `FLAG_TIMEOUT`
+	pub fn recv_byte(&mut self) -> Result<u8, I2CError> {
+		self.wait(I2CFlags::RxNotEmpty, false)?;
 		Ok( self.read_data() )
 	}
 }
@@ -293,233 +387,477 @@ impl I2c {
 impl I2c {
 	/// Enable the sending of ACK signal after byte transfer
 	pub fn ack(&mut self) -> &mut Self {
-		self.set(0, 10)
+		self.reg::<CR1>().modify(|_, w| w.ack(1));
+		self
 	}
 
 	/// Disable the sending of ACK signal (effectively sending a NACK) after byte transfer
 	pub fn nack(&mut self) -> &mut Self {
-		self.clear(0, 10)
+		self.reg::<CR1>().modify(|_, w| w.ack(0));
+		self
 	}
 
 	/// Stop generation
-	/// 0: No stop generation
-	/// 1: Slave Mode - Release the SCL and SDA lines after current byte transfer
-	///    Master Mode - Stop generation after the current byte transfer or current Start condition is sent
+	/// Slave Mode - Release the SCL and SDA lines after current byte transfer
+	/// Master Mode - Stop generation after the current byte transfer or current Start condition is sent
 	pub fn stop(&mut self) -> &mut Self {
-		self.set(0, 9)
+		self.reg::<CR1>().modify(|_, w| w.stop(1));
+		self
 	}
 
 	/// Start generation
-	/// 0: No start generation
-	/// 1: Slave Mode - Start generation when bus id free
-	///    Master Mode - Repeated start generation
+	/// Slave Mode - Start generation when bus id free
+	/// Master Mode - Repeated start generation
 	pub fn start(&mut self) -> &mut Self {
-		self.set(0, 8)
+		self.reg::<CR1>().modify(|_, w| w.start(1));
+		self
 	}
 
 	/// Enable/Disable peripheral
 	pub fn state(&mut self, s: bool) -> &mut Self {
-		match s {
-			true => self.set(0, 0),
-			_ => self.clear(0, 0),
-		}
+		self.reg::<CR1>().modify(|_, w| w.pe(s as u32));
+		self
 	}
 
 	/// If enabled the next byte will received in shift register
 	pub fn receive_in_shift(&mut self) -> &mut Self {
-		self.set(0, 11)
+		self.reg::<CR1>().modify(|_, w| w.pos(1));
+		self
 	}
 
 	/// Starts Packet Error Checking (PEC) for the next transfer
 	pub fn start_pec(&mut self) -> &mut Self {
-		self.set(0, 12)
+		self.reg::<CR1>().modify(|_, w| w.pec(1));
+		self
 	}
 
 	/// Resets the peripheral
 	pub fn reset(&mut self) -> &mut Self {
 		// TODO : check lines are free
-		self.stop()
-			.set(0, 15)
+		self.stop();
+		self.reg::<CR1>().modify(|_, w| w.swrst(1));
+		self
 	}
 
-	/// Sets the frequency of the transfer
+	/// Sets the frequency of the peripheral clock
 	pub fn set_frequency(&mut self, f: Frequency) -> Result<&mut Self, I2CError> {
 		match f.mhz() {
-			2...50 => Ok( self.write_bits(1, 0, f.mhz() as u32, 6) ),
+			2..=50 => {
+				self.reg::<CR2>().modify(|_, w| w.freq(f.mhz()));
+				Ok( self )
+			},
 			_ => Err(I2CError::InvalidBusSpeed),
 		}
 	}
 
 	/// Indicate this is the last trasnfer
 	pub fn last_transfer(&mut self) -> &mut Self {
-		self.set(1, 12)
+		self.reg::<CR2>().modify(|_, w| w.last(1));
+		self
 	}
 
 	/// Enable/Disable interrupt
 	pub fn int_state(&mut self, s: bool, int: I2CInterrupt) -> &mut Self {
-		let offsets = int.offsets();
+		let (r, b) = int.offsets();
 
 		match s {
-			true => self.set(offsets.0, offsets.1),
-			_ => self.clear(offsets.0, offsets.1),
+			true => self.set(r, b),
+			_ => self.clear(r, b),
 		}
 	}
 
 	/// Sets the addressing mode between 7-bit and 10-bit
 	pub fn address_mode(&mut self, a: I2CBitMode) -> &mut Self {
-		match a {
-			I2CBitMode::Bit7 => self.clear(2, 15),
-			_ => self.set(2, 15),
-		}
+		let mode = match a {
+			I2CBitMode::Bit7 => 0,
+			_ => 1,
+		};
+
+		self.reg::<OAR1>().modify(|_, w| w.addmode(mode));
+		self
 	}
 
 	/// Writes the interface address 1
 	/// To be set **after** the interface bit size is set (7-bit or 10-bit)
 	pub fn set_address_1(&mut self, addr: u32) -> &mut Self {
-		match self.is_set(2, 15) {
-			true => self.write_bits(2, 0, addr, 10),
-			_ => self.write_bits(2, 1, addr, 7),
+		match self.read_reg::<OAR1>().addmode() {
+			1 => self.reg::<OAR1>().modify(|_, w| w.add10(addr)),
+			_ => self.reg::<OAR1>().modify(|_, w| w.add7(addr)),
 		}
+
+		self
 	}
 
 	/// Writes the interface address 2
 	/// Returns an error if not in 7 bit mode
 	pub fn set_address_2(&mut self, addr: u32) -> Result<&mut Self, I2CError> {
-		match self.is_set(2, 15) {
-			true => Err( I2CError::Address2NotAllowed),
-			_ => Ok( self.write_bits(3, 1, addr, 7) ),
+		match self.read_reg::<OAR1>().addmode() {
+			1 => Err(I2CError::Address2NotAllowed),
+			_ => {
+				self.reg::<OAR2>().modify(|_, w| w.add2(addr));
+				Ok( self )
+			},
 		}
 	}
 
 	/// Enable/Disable dual addressing mode
 	pub fn dual_address_state(&mut self, s: bool) -> &mut Self {
-		match s {
-			true => self.set(3, 0),
-			_ => self.clear(3, 0),
-		}
+		self.reg::<OAR2>().modify(|_, w| w.endual(s as u32));
+		self
 	}
 
 	/// Read received byte
 	pub fn read_data(&self) -> u8 {
-		self.block[4].read() as u8
+		self.read_reg::<DR>().dr() as u8
 	}
 
 	/// Write data to be transmitted
 	pub fn write_data(&mut self, data: u32) -> &mut Self {
-		self.write_bits(4, 0, data, 8)
+		self.reg::<DR>().write(|w| w.dr(data));
+		self
 	}
 
 	/// Returns true if the flag is raised
 	pub fn is_raised(&self, f: I2CFlags) -> bool {
-		let offsets = f.offsets();
-
-		self.is_set( offsets.0, offsets.1 )
+		let (r, b) = f.offsets();
+		self.is_set(r, b)
 	}
 
 	/// Returns true if the device is master
 	pub fn is_master(&self) -> bool {
-		self.is_set(6, 0)
+		self.read_reg::<SR2>().msl() == 1
 	}
 
 	/// Returns true if the bus is busy
 	pub fn is_bus_busy(&self) -> bool {
-		self.is_set(6, 1)
+		self.read_reg::<SR2>().busy() == 1
 	}
 
 	/// Returns true if the TRA bit is set
 	pub fn is_tra_set(&self) -> bool {
-		self.is_set(6, 2)
+		self.read_reg::<SR2>().tra() == 1
 	}
 
 	/// Returns which Dual Address has matched
 	pub fn which_addr(&self) -> DualAddress {
-		match self.is_set(6, 7) {
-			true => DualAddress::Addr2,
+		match self.read_reg::<SR2>().dualf() {
+			1 => DualAddress::Addr2,
 			_ => DualAddress::Addr1,
 		}
 	}
 
 	/// Returns the PEC register
 	pub fn pec(&self) -> u32 {
-		(self.block[6].read() >> 8) & 0b1111_1111
+		self.read_reg::<SR2>().pec()
 	}
 
 	/// Clear the given flag
 	/// If the flag is cleared by hardware, it does nothing
+	/// The error flags are cleared by writing 0, the others are written 1 which has no effect
 	pub fn clear_flag(&mut self, f: I2CFlags) -> &mut Self {
-		match f.offsets() {
-			(5, o) => match o {
-				8...15 => self.clear(5, o),
-				_ => self
-			},
-			_ => self
+		if let (5, o @ 8..=15) = f.offsets() {
+			self.reg::<SR1>().write(|w| w.set_bits(8, 8, !(1 << (o - 8))));
 		}
+
+		self
 	}
 
-	/// Set CCR 
+	/// Set CCR
 	/// Refer to the STM32F4 user manual
 	pub fn set_ccr(&mut self, data: u32) -> &mut Self {
-		self.write_bits(7, 0, data, 12)
+		self.reg::<CCR>().modify(|_, w| w.ccr(data));
+		self
 	}
 
-	/// Set Master Mode
+	/// Set Master Mode (Standard or Fast)
 	/// Refer to the STM32F4 user manual
 	pub fn set_master_mode(&mut self, mode: MasterMode) -> &mut Self {
-		match mode {
-			MasterMode::SM => self.clear(7, 15),
-			MasterMode::FM => self.set(7, 15),
-		}
+		let fs = match mode {
+			MasterMode::SM => 0,
+			MasterMode::FM => 1,
+		};
+
+		self.reg::<CCR>().modify(|_, w| w.fs(fs));
+		self
 	}
 
 	/// Set duty cycle
 	/// Refer to the STM32F4 user manual
 	pub fn set_duty_cycle(&mut self, d: DutyCycle) -> &mut Self {
-		match d {
-			DutyCycle::D2 => self.clear(7, 14),
-			DutyCycle::D169 => self.set(7, 14),
-		}
+		let duty = match d {
+			DutyCycle::D2 => 0,
+			DutyCycle::D169 => 1,
+		};
+
+		self.reg::<CCR>().modify(|_, w| w.duty(duty));
+		self
 	}
 
 	/// Set maximum rise time
 	pub fn max_rise_time(&mut self, data: u32) -> &mut Self {
-		self.write_bits(8, 0, data, 6)
+		self.reg::<TRISE>().modify(|_, w| w.trise(data));
+		self
 	}
 
 	/// Enable/Disable Analog Filter
 	pub fn analog_filter_state(&mut self, s: bool) -> &mut Self {
-		match s {
-			true => self.clear(9, 4),
-			_ => self.set(9, 4),
-		}
+		self.reg::<FLTR>().modify(|_, w| w.anoff(!s as u32));
+		self
 	}
 
 	/// Sets the Digital Noise Filter
 	pub fn digital_noise_filter(&mut self, d: Option<u32>) -> &mut Self {
-		match d {
-			None => self.write_bits(9, 0, 0, 4),
-			Some(a) => self.write_bits(9, 0, a, 4),
-		}
+		self.reg::<FLTR>().modify(|_, w| w.dnf(d.unwrap_or(0)));
+		self
+	}
+
+	/// Set secondary address
+	pub fn set_secondary_address(&mut self, address: u32) -> &mut Self {
+		self.reg::<OAR2>().modify(|_, w| w.add2(address));
+		self
 	}
 }
 
-impl I2c {
 
-	/// Set master mode (Standard or Fast)
-	pub fn master_mode(&mut self, mode: MasterMode) -> &mut Self {
-		match mode {
-			MasterMode::SM => self.clear(7, 15),
-			_ => self.set(7, 15),
-		}
+#[cfg(all(test, feature = "sim", any(feature = "stm32f1", feature = "stm32f4")))]
+mod tests {
+	use super::*;
+	use crate::common::{ ClockCfg, SrcClock };
+	use crate::common::structs::AF4;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::peripherals::extended::{ gpio, rcc };
+	use crate::peripherals::map;
+
+	/// RCC running from the HSI at 16 MHz
+	fn rcc() -> (SimBlock<{ rcc::SIZE }>, rcc::Rcc) {
+		rcc::tests::frozen( ClockCfg { pllcfg: None, sysclk: (SrcClock::HSI, 0), ppre: (1, 1, 1), ..rcc::tests::cfg() } )
 	}
 
-	/// Write Interface Address
-	pub fn set_address(&mut self, address: u32) -> &mut Self {
-		let cons = if self.is_set(2, 15) { (0, 10) } else { (1, 7) };
-		self.write_bits(2, cons.0, address, cons.1)
+	fn gpiob() -> (SimBlock<{ gpio::SIZE }>, gpio::Gpio) {
+		let block = SimBlock::<{ gpio::SIZE }>::new();
+		block.stand_for(map::GPIOB);
+
+		let gpio = gpio::Gpio::from_block( unsafe { block.regs() } );
+		(block, gpio)
 	}
 
-	/// Set secondary address
-	pub fn set_secondary_address(&mut self, address: u32) -> &mut Self {
-		self.write_bits(3, 1, address, 7)
+	/// I2C1 as master on PB7 (SDA) and PB6 (SCL) at `speed`
+	fn master(speed: Frequency) -> (SimBlock<SIZE>, I2c) {
+		let (_, mut rcc) = rcc();
+		let (_, mut gpiob) = gpiob();
+		#[cfg(feature = "stm32f1")]
+		let _afio = afio();
+		let block = SimBlock::<SIZE>::with_reset([0, 0, 0, 0, 0, 0, 0, 0, TRISE::RESET, 0]);
+		let mut i2c = I2c::from_block(RCCPeripheral::I2C1, unsafe { block.regs() });
+
+		i2c.master((af4(&mut gpiob, 7), af4(&mut gpiob, 6)), speed, &mut rcc).unwrap();
+		(block, i2c)
+	}
+
+	/// Pin `n` of `gpio` as the open-drain AF4, the function of I2C1, ignored on the STM32F1
+	fn af4(gpio: &mut gpio::Gpio, n: u32) -> Pin<Alternate<AF4, OpenDrain>> {
+		gpio.pin(n).unwrap().into_alternate_open_drain()
+	}
+
+	/// Bus where every step completes at once, the slave acknowledges everything
+	/// and sends `byte`
+	fn bus(block: &SimBlock<SIZE>, byte: u32) {
+		// SB, ADDR, BTF, RXNE and TXE
+		block.on_read(SR1::INDEX, |_| 0b1100_0111)
+			.on_read(DR::INDEX, move |_| byte);
+
+		// START and STOP are cleared by the hardware once generated
+		block.on_write(CR1::INDEX, |_, new| new & !(0b11 << 8));
+	}
+
+	/// Indices of the writes to CR1 that set `bit`
+	fn cr1_writes(block: &SimBlock<SIZE>, bit: u32) -> Vec<usize> {
+		block.writes(CR1::INDEX).iter()
+			.enumerate()
+			.filter(|(_, w)| *w & (1 << bit) != 0)
+			.map(|(i, _)| i)
+			.collect()
+	}
+
+	/// AFIO written by the driver to remap its pins
+	#[cfg(feature = "stm32f1")]
+	fn afio() -> SimBlock<{ syscfg::SIZE }> {
+		let block = SimBlock::<{ syscfg::SIZE }>::new();
+		block.stand_for(syscfg::ADDRESS);
+		block
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f4")]
+	fn master_in_standard_mode() {
+		let (rcc_block, mut rcc) = rcc();
+		let (gpio_block, mut gpiob) = gpiob();
+		let block = SimBlock::<SIZE>::new();
+		let mut i2c = I2c::from_block(RCCPeripheral::I2C1, unsafe { block.regs() });
+
+		let pins = (af4(&mut gpiob, 7), af4(&mut gpiob, 6));
+		assert!(i2c.master(pins, Frequency::KHz(100), &mut rcc).is_ok());
+
+		// 16 MHz, 16 MHz / (2 * 100 kHz) and 1000 ns of rise time
+		assert_eq!(block.peek(CR2::INDEX) & 0x3F, 16);
+		assert_eq!(block.peek(CCR::INDEX), 80);
+		assert_eq!(block.peek(TRISE::INDEX), 17);
+		assert_eq!(block.peek(CR1::INDEX) & 1, 1, "enabled");
+
+		// Clock enabled
+		let (bus, bit) = RCCPeripheral::I2C1.offsets();
+		assert_ne!(rcc_block.peek(bus + 8) & (1 << bit), 0);
+
+		// PB6 and PB7 open-drain on AF4
+		assert_eq!((gpio_block.peek(0) >> 12) & 0b11_11, 0b10_10);
+		assert_eq!((gpio_block.peek(1) >> 6) & 0b11, 0b11);
+		assert_eq!((gpio_block.peek(8) >> 24) & 0xFF, 0x44);
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f4")]
+	fn master_in_fast_mode() {
+		let (block, _) = master(Frequency::KHz(400));
+
+		// 16 MHz / (3 * 400 kHz) with a duty cycle of 2, 300 ns of rise time
+		assert_eq!(block.peek(CCR::INDEX), (1 << 15) | 13);
+		assert_eq!(block.peek(TRISE::INDEX), 5);
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f411")]
+	fn master_on_a_device_specific_function() {
+		let (_, mut rcc) = rcc();
+		let (gpio_block, mut gpiob) = gpiob();
+		let block = SimBlock::<SIZE>::new();
+		let mut i2c = I2c::from_block(RCCPeripheral::I2C2, unsafe { block.regs() });
+
+		// I2C2_SDA is on AF9 on PB3
+		let pins = (gpiob.pin(3).unwrap().into_alternate_open_drain::<crate::common::structs::AF9>(), af4(&mut gpiob, 10));
+		assert!(i2c.master(pins, Frequency::KHz(100), &mut rcc).is_ok());
+
+		assert_eq!((gpio_block.peek(8) >> 12) & 0xF, 9);
+		assert_eq!((gpio_block.peek(9) >> 8) & 0xF, 4);
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f1")]
+	fn master_applies_the_remap_of_its_pins() {
+		let (_, mut rcc) = rcc();
+		let (gpio_block, mut port) = gpiob();
+		let afio = afio();
+		let block = SimBlock::<SIZE>::new();
+		let mut i2c = I2c::from_block(RCCPeripheral::I2C1, unsafe { block.regs() });
+
+		// PB9 and PB8 are SDA and SCL with I2C1_REMAP
+		let pins = (af4(&mut port, 9), af4(&mut port, 8));
+		assert!(i2c.master(pins, Frequency::KHz(100), &mut rcc).is_ok());
+
+		assert_eq!(afio.peek(1) & (1 << 1), 1 << 1);
+
+		// Open-drain alternate functions at 50 MHz
+		assert_eq!(gpio_block.peek(1) & 0xFF, 0xFF);
+
+		// PB7 is SDA without the remap, PB8 SCL with it
+		let mut i2c = I2c::from_block(RCCPeripheral::I2C1, unsafe { block.regs() });
+		let (_, mut port) = gpiob();
+
+		let pins = (af4(&mut port, 7), af4(&mut port, 8));
+		assert_eq!(i2c.master(pins, Frequency::KHz(100), &mut rcc).err(), Some(I2CError::InvalidPin));
+		assert_eq!(afio.peek(1) & (1 << 1), 1 << 1);
+	}
+
+	#[test]
+	fn master_rejects_wrong_pins_and_speeds() {
+		let (_, mut rcc) = rcc();
+		let (gpio_block, mut gpiob) = gpiob();
+		let block = SimBlock::<SIZE>::new();
+		let mut i2c = I2c::from_block(RCCPeripheral::I2C1, unsafe { block.regs() });
+
+		// PB6 carries I2C1_SCL, not SDA
+		let pins = (af4(&mut gpiob, 6), af4(&mut gpiob, 7));
+		let accesses = gpio_block.accesses().len();
+		assert_eq!(i2c.master(pins, Frequency::KHz(100), &mut rcc).err(), Some(I2CError::InvalidPin));
+		assert_eq!(gpio_block.accesses().len(), accesses);
+
+		let pins = (af4(&mut gpiob, 9), af4(&mut gpiob, 8));
+		let accesses = gpio_block.accesses().len();
+		assert_eq!(i2c.master(pins, Frequency::KHz(1000), &mut rcc).err(), Some(I2CError::InvalidBusSpeed));
+
+		assert!(block.accesses().is_empty());
+		assert_eq!(gpio_block.accesses().len(), accesses);
+	}
+
+	#[test]
+	fn write_sends_the_address_then_the_bytes() {
+		let (block, mut i2c) = master(Frequency::KHz(100));
+		bus(&block, 0);
+
+		assert_eq!(i2c.write(0x50, &[0x01, 0x02]), Ok(()));
+
+		assert_eq!(block.writes(DR::INDEX), [0xA0, 0x01, 0x02]);
+		assert_eq!(cr1_writes(&block, 8).len(), 1, "START");
+		assert_eq!(cr1_writes(&block, 9).len(), 1, "STOP");
+	}
+
+	#[test]
+	fn write_stops_on_a_nack() {
+		let (block, mut i2c) = master(Frequency::KHz(100));
+
+		// SB then AF, the address is not acknowledged
+		block.on_read(SR1::INDEX, |_| (1 << 10) | 1);
+
+		assert_eq!(i2c.write(0x50, &[0x01]), Err(I2CError::NACK));
+		assert_eq!(block.writes(DR::INDEX), [0xA0]);
+		assert_ne!(block.peek(CR1::INDEX) & (1 << 9), 0, "STOP");
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn write_read_restarts_for_the_read() {
+		let (block, mut i2c) = master(Frequency::KHz(100));
+		bus(&block, 0x5A);
+
+		let mut buffer = [0; 2];
+		assert_eq!(i2c.write_read(0x50, &[0x10], &mut buffer), Ok(()));
+
+		assert_eq!(buffer, [0x5A, 0x5A]);
+		assert_eq!(block.writes(DR::INDEX), [0xA0, 0x10, 0xA1]);
+
+		// START for the write, then the repeated START, and the STOP after the read only
+		let starts = cr1_writes(&block, 8);
+		let stop = cr1_writes(&block, 9);
+		assert_eq!(starts.len(), 2);
+		assert_eq!(stop.len(), 1);
+		assert!(stop[0] > starts[1]);
+
+		let cr1 = block.writes(CR1::INDEX);
+		assert_eq!(cr1[stop[0]] & (1 << 10), 0, "last byte not acknowledged");
+	}
+
+	#[test]
+	fn a_stuck_bus_times_out() {
+		// No START, SB never rises
+		let (block, mut i2c) = master(Frequency::KHz(100));
+		block.on_read(SR1::INDEX, |_| 0);
+
+		assert_eq!(i2c.write(0x50, &[0x01]), Err(I2CError::Timeout));
+		assert!(block.writes(DR::INDEX).is_empty());
+		assert_eq!(cr1_writes(&block, 9).len(), 1, "STOP");
+
+		// The address is acknowledged but the byte is never sent, BTF never rises
+		let (block, mut i2c) = master(Frequency::KHz(100));
+		block.on_read(SR1::INDEX, |_| 0b1000_0011);
+
+		assert_eq!(i2c.write(0x50, &[0x01]), Err(I2CError::Timeout));
+		assert_eq!(block.writes(DR::INDEX), [0xA0, 0x01]);
+		assert_eq!(cr1_writes(&block, 9).len(), 1, "STOP");
+
+		// The slave never sends, RXNE never rises
+		let (block, mut i2c) = master(Frequency::KHz(100));
+		block.on_read(SR1::INDEX, |_| 0b1000_0011);
+
+		let mut buffer = [0; 2];
+		assert_eq!(i2c.read(0x50, &mut buffer), Err(I2CError::Timeout));
+		assert_eq!(cr1_writes(&block, 9).len(), 1, "STOP");
+	}
+}
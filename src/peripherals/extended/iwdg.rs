@@ -1,12 +1,38 @@
 //! Independent Watchdog
 
 use crate::common::{ Register };
+use crate::peripherals::map;
 
 use embedded_hal::watchdog::*;
 
-pub const ADDRESS: u32 = 0x4001_3C00;
+pub const ADDRESS: u32 = map::IWDG;
 pub const SIZE: usize = 6;
 
+registers! {
+	/// Key register
+	KR @ 0, reset = 0x0000_0000 {
+		key: 0, 16, WO;
+	}
+
+	/// Prescaler register
+	PR @ 1, reset = 0x0000_0000 {
+		pr: 0, 3, RW;
+	}
+
+	/// Reload register
+	RLR @ 2, reset = 0x0000_0FFF {
+		rl: 0, 12, RW;
+	}
+
+	/// Status register
+	SR @ 3, reset = 0x0000_0000 {
+		/// Reload value update in progress
+		rvu: 1, 1, RO;
+		/// Prescaler value update in progress
+		pvu: 0, 1, RO;
+	}
+}
+
 #[repr(C)]
 pub struct Iwdg {
 	block: [Register<u32>; SIZE],
@@ -27,31 +53,31 @@ impl Iwdg {
 	/// This operation blocks until the value has been registered in the
 	/// VDD voltage domain (up to 5 cycles)
 	pub fn set_countdown(&mut self, value: u32) {
-		self.block[2].write(value & mask!(11));
-		while self.is_set(3, 1) {}
+		self.reg::<RLR>().write(|w| w.rl(value));
+		while self.read_reg::<SR>().rvu() == 1 {}
 	}
 
 	/// Sets the prescaler into the register
 	/// This operation blocks until the value has been registered in the
 	/// VDD voltage domain (up to 5 cycles)
 	pub fn set_prescaler(&mut self, value: u32) {
-		self.block[2].write(value & mask!(3));
-		while self.is_set(3, 0) {}
+		self.reg::<PR>().write(|w| w.pr(value));
+		while self.read_reg::<SR>().pvu() == 1 {}
 	}
 
 	/// Resets the countdown, preventing it from reseting the MCU
 	pub fn reload(&mut self) {
-		self.block[0].write(0xAAAA)
+		self.reg::<KR>().write(|w| w.key(0xAAAA))
 	}
 
 	/// Unlocks the IWDG registers
 	/// Must be performed before a write to the value or prescaler
 	pub fn unlock(&mut self) {
-		self.block[0].write(0x5555)
+		self.reg::<KR>().write(|w| w.key(0x5555))
 	}
 
 	/// Starts the IWDG
 	pub fn start(&mut self) {
-		self.block[0].write(0xCCCC)
+		self.reg::<KR>().write(|w| w.key(0xCCCC))
 	}
 }
\ No newline at end of file
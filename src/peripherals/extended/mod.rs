@@ -1,23 +1,33 @@
 //! Extended peripherals
 
-reexport!{
-	private:
-		mod crc; // ADDR
-		mod exti;
-		mod iwdg;
-		mod wwdg;
-		mod pwr; // ADDR
-		mod rtc; // ADDR
-	public:
-		mod gpio;
-}
+pub mod crc;
+pub mod exti;
+pub mod iwdg;
+pub mod wwdg;
+pub mod pwr;
+pub mod rtc;
+pub mod gpio;
+
+pub use self::crc::Crc;
+pub use self::exti::Exti;
+pub use self::iwdg::Iwdg;
+pub use self::wwdg::Wwdg;
+pub use self::pwr::Pwr;
+pub use self::rtc::Rtc;
+pub use self::gpio::Gpio;
 
 pub mod rcc;
 
-//pub mod i2c;
+// The STM32F0 and STM32F7 have another I2C
+#[cfg(any(feature = "stm32f1", feature = "stm32f4"))]
+pub mod i2c;
 
 pub mod spi;
 
+pub mod gptim;
+
+pub mod syscfg;
+
 //pub mod advtim;
 
 //pub mod timers;
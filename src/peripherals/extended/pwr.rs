@@ -1,10 +1,76 @@
 //! Power Management Peripheral
 
-use crate::common::{ Register };
+use crate::common::Register;
+#[cfg(not(feature = "stm32f1"))]
+use crate::common::Frequency;
+#[cfg(not(feature = "stm32f1"))]
+use crate::common::ClockSpeeds::{ OVERDRIVE, VOSCALE };
+use crate::peripherals::map;
 
-pub const ADDRESS: u32 = 0x4000_7000;
+pub const ADDRESS: u32 = map::PWR;
 pub const SIZE: usize = 6;
 
+// The STM32F1 has no voltage scaling, over-drive or backup regulator
+
+registers! {
+	/// Power control register
+	CR @ 0, reset = 0x0000_8000 {
+		/// Over-drive switching enable
+		#[cfg(not(feature = "stm32f1"))]
+		odswen: 17, 1, RW;
+		/// Over-drive enable
+		#[cfg(not(feature = "stm32f1"))]
+		oden: 16, 1, RW;
+		/// Regulator voltage scaling output selection
+		#[cfg(not(any(feature = "stm32f1", feature = "stm32f4x5", feature = "stm32f4x7")))]
+		vos: 14, 2, RW;
+		/// Regulator voltage scaling output selection, bit 15 is reserved
+		#[cfg(any(feature = "stm32f4x5", feature = "stm32f4x7"))]
+		vos: 14, 1, RW;
+		/// Disable backup domain write protection
+		dbp: 8, 1, RW;
+		/// PVD level selection
+		pls: 5, 3, RW;
+		/// Power voltage detector enable
+		pvde: 4, 1, RW;
+		/// Clear standby flag
+		csbf: 3, 1, WO;
+		/// Clear wakeup flag
+		cwuf: 2, 1, WO;
+		/// Power down deepsleep
+		pdds: 1, 1, RW;
+		/// Low power deepsleep
+		lpds: 0, 1, RW;
+	}
+
+	/// Power control/status register
+	CSR @ 1, reset = 0x0000_0000 {
+		/// Over-drive mode switching ready
+		#[cfg(not(feature = "stm32f1"))]
+		odswrdy: 17, 1, RO;
+		/// Over-drive mode ready
+		#[cfg(not(feature = "stm32f1"))]
+		odrdy: 16, 1, RO;
+		/// Regulator voltage scaling output selection ready
+		#[cfg(not(feature = "stm32f1"))]
+		vosrdy: 14, 1, RO;
+		/// Backup regulator enable
+		#[cfg(not(feature = "stm32f1"))]
+		bre: 9, 1, RW;
+		/// Enable WKUP pin
+		ewup: 8, 1, RW;
+		/// Backup regulator ready
+		#[cfg(not(feature = "stm32f1"))]
+		brr: 3, 1, RO;
+		/// PVD output
+		pvdo: 2, 1, RO;
+		/// Standby flag
+		sbf: 1, 1, RO;
+		/// Wakeup flag
+		wuf: 0, 1, RO;
+	}
+}
+
 #[repr(C)]
 pub struct Pwr {
 	block: [Register<u32>; SIZE],
@@ -31,41 +97,139 @@ impl Pwr {
 			_ => return self,
 		};
 
-		self.write_bits(0, 5, value, 3)
+		self.reg::<CR>().modify(|_, w| w.pls(value));
+		self
 	}
 
 	/// Enable the PVD
 	#[inline]
 	pub fn enable_pvd(&mut self) -> &mut Self {
-		self.set(0, 4)
+		self.reg::<CR>().modify(|_, w| w.pvde(1));
+		self
 	}
 
 	/// Disable the PVD
 	#[inline]
 	pub fn disable_pvd(&mut self) -> &mut Self {
-		self.clear(0, 4)
+		self.reg::<CR>().modify(|_, w| w.pvde(0));
+		self
 	}
 
 	/// Returns `true` if the voltage is higher than the threshold
 	/// and the PVD is enabled
 	pub fn voltlevel(&self) -> bool {
-		self.is_set(1, 2)
+		self.read_reg::<CSR>().pvdo() == 1
 	}
 
 	/// Enable/Disable the Wakeup pin
 	pub fn wakeup_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(1, 8) }
-		else { self.clear(1, 8) }
+		self.reg::<CSR>().modify(|_, w| w.ewup(s as u32));
+		self
 	}
 
 	/// Enable/Disable the Backup Regulator
+	#[cfg(not(feature = "stm32f1"))]
 	pub fn bckpreg_state(&mut self, s: bool) -> &mut Self {
-		if s { self.set(1, 9) }
-		else { self.clear(1, 9) }
+		self.reg::<CSR>().modify(|_, w| w.bre(s as u32));
+		self
 	}
 
 	/// Call this function once after reset to enable the RTC
 	pub fn unlockrtc(&mut self) -> &mut Self {
-		self.set(0, 8)
+		self.reg::<CR>().modify(|_, w| w.dbp(1));
+		self
+	}
+
+	/// Sets the regulator voltage scale, as the VOS value
+	/// Only written while the PLL is off, it takes effect when the PLL is turned on
+	#[cfg(not(feature = "stm32f1"))]
+	pub fn voltage_scale(&mut self, vos: u32) -> &mut Self {
+		self.reg::<CR>().modify(|_, w| w.vos(vos));
+		self
+	}
+
+	/// Enable/Disable the over-drive, on the parts that have it
+	/// Switched with the PLL on and the HSI or HSE as SYSCLK
+	/// Does nothing on the parts without over-drive
+	#[cfg(not(feature = "stm32f1"))]
+	pub fn overdrive_state(&mut self, s: bool) -> &mut Self {
+		if OVERDRIVE.is_none() {
+			return self;
+		}
+
+		if s {
+			self.reg::<CR>().modify(|_, w| w.oden(1));
+			while self.read_reg::<CSR>().odrdy() == 0 {}
+
+			self.reg::<CR>().modify(|_, w| w.odswen(1));
+			while self.read_reg::<CSR>().odswrdy() == 0 {}
+		} else {
+			self.reg::<CR>().modify(|_, w| w.odswen(0).oden(0));
+			while self.read_reg::<CSR>().odswrdy() == 1 {}
+		}
+
+		self
+	}
+
+	/// Returns `true` if the over-drive is switched on
+	#[cfg(not(feature = "stm32f1"))]
+	pub fn is_overdrive(&self) -> bool {
+		self.read_reg::<CSR>().odswrdy() == 1
+	}
+}
+
+/// Returns the VOS value of the lowest power voltage scale that runs `hclk`,
+/// and if it needs the over-drive
+/// `None` on the parts without voltage scaling
+#[cfg(not(feature = "stm32f1"))]
+pub fn scale_for(hclk: Frequency) -> Option<(u32, bool)> {
+	let overdrive = match OVERDRIVE {
+		Some(f) => hclk > f,
+		None => false,
+	};
+
+	VOSCALE.iter()
+		.find(|(max, _)| hclk <= *max)
+		.or(VOSCALE.last())
+		.map(|&(_, vos)| (vos, overdrive))
+}
+
+#[cfg(all(test, feature = "sim", not(feature = "stm32f1")))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::common::ClockSpeeds::SYSCLKMAX;
+
+	#[test]
+	fn voltage_scale_stays_in_the_vos_field() {
+		let block = SimBlock::<SIZE>::new();
+		let pwr = unsafe { block.view::<Pwr>() };
+
+		for &(_, vos) in VOSCALE {
+			pwr.voltage_scale(vos);
+			assert_eq!(block.peek(CR::INDEX), vos << 14);
+		}
+	}
+
+	#[test]
+	fn the_fastest_scale_runs_the_sysclk_max() {
+		let scale = scale_for(SYSCLKMAX);
+
+		assert_eq!(scale.map(|(vos, _)| vos), VOSCALE.last().map(|&(_, vos)| vos));
+		assert_eq!(scale.map(|(_, od)| od), VOSCALE.last().map(|_| OVERDRIVE.is_some_and(|f| SYSCLKMAX > f)));
+	}
+
+	#[test]
+	fn overdrive_only_where_the_part_has_it() {
+		let block = SimBlock::<SIZE>::new();
+		let pwr = unsafe { block.view::<Pwr>() };
+
+		// The ready flags follow the enables
+		block.follow(CR::INDEX, 1 << 16, CSR::INDEX, 1 << 16)
+			.follow(CR::INDEX, 1 << 17, CSR::INDEX, 1 << 17);
+
+		pwr.overdrive_state(true);
+
+		assert_eq!(pwr.is_overdrive(), OVERDRIVE.is_some());
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,591 @@
+//! CFGR register layout (STM32F1)
+//!
+//! The PLL is configured in CFGR: its input is the HSI divided by 2 or the HSE,
+//! optionally divided by 2 (PLLXTPRE), and it is multiplied by 2 to 16 (PLLMUL).
+//! There is no separate VCO, PLLCFGR or voltage scaling.
+
+use crate::common::{ asm, Register, Frequency };
+use crate::peripherals::map;
+
+use crate::common::enums::{ RCCInterrupt, DeviceClock, RCCPeripheral, RCCError, ClockLimit, HSEMode };
+
+use crate::common::{ SrcClock, Clocks, ClockCfg };
+
+use crate::peripherals::extended::flashiface::{ self, FlashIface };
+use crate::peripherals::extended::pwr::Pwr;
+
+use super::{ hpre_bits, ppre_bits, within };
+
+pub const ADDRESS: u32 = map::RCC;
+pub const SIZE: usize = 10;
+
+pub const HSIF: Frequency = Frequency::MHz(8);
+pub const LSIF: Frequency = Frequency::KHz(40);
+pub const LSEF: Frequency = Frequency::Hz(32768);
+
+/// Polls of HSERDY (in cycles) before giving up on the HSE
+pub const HSE_TIMEOUT: u32 = 1_600_000;
+
+/// Index of the enable register of the AHB peripherals, which have no reset register
+const AHBENR: usize = 5;
+
+registers! {
+	/// Clock control register
+	CR @ 0, reset = 0x0000_0083 {
+		/// PLL clock ready flag
+		pllrdy: 25, 1, RO;
+		/// PLL enable
+		pllon: 24, 1, RW;
+		/// Clock security system enable
+		csson: 19, 1, RW;
+		/// HSE clock bypass
+		hsebyp: 18, 1, RW;
+		/// HSE clock ready flag
+		hserdy: 17, 1, RO;
+		/// HSE clock enable
+		hseon: 16, 1, RW;
+		/// HSI clock calibration
+		hsical: 8, 8, RO;
+		/// HSI clock trimming
+		hsitrim: 3, 5, RW;
+		/// HSI clock ready flag
+		hsirdy: 1, 1, RO;
+		/// HSI clock enable
+		hsion: 0, 1, RW;
+	}
+
+	/// Clock configuration register
+	CFGR @ 1, reset = 0x0000_0000 {
+		/// Microcontroller clock output
+		mco: 24, 3, RW;
+		/// USB prescaler, the PLL is not divided if set, divided by 1.5 otherwise
+		#[cfg(not(feature = "stm32f100"))]
+		usbpre: 22, 1, RW;
+		/// PLL multiplication factor, 2 to 16 as 0 to 14
+		pllmul: 18, 4, RW;
+		/// HSE divided by 2 for the PLL entry
+		pllxtpre: 17, 1, RW;
+		/// PLL entry clock source, HSE if set, HSI / 2 otherwise
+		pllsrc: 16, 1, RW;
+		/// ADC prescaler
+		adcpre: 14, 2, RW;
+		/// APB high-speed prescaler
+		ppre2: 11, 3, RW;
+		/// APB low-speed prescaler
+		ppre1: 8, 3, RW;
+		/// AHB prescaler
+		hpre: 4, 4, RW;
+		/// System clock switch status
+		sws: 2, 2, RO;
+		/// System clock switch
+		sw: 0, 2, RW;
+	}
+
+	/// Clock interrupt register
+	CIR @ 2, reset = 0x0000_0000 {
+		/// Clock security system interrupt clear
+		cssc: 23, 1, WO;
+		/// Ready interrupt clears of PLL, HSE, HSI, LSE and LSI
+		pllrdyc: 20, 1, WO;
+		hserdyc: 19, 1, WO;
+		hsirdyc: 18, 1, WO;
+		lserdyc: 17, 1, WO;
+		lsirdyc: 16, 1, WO;
+		/// Ready interrupt enables of PLL, HSE, HSI, LSE and LSI
+		pllrdyie: 12, 1, RW;
+		hserdyie: 11, 1, RW;
+		hsirdyie: 10, 1, RW;
+		lserdyie: 9, 1, RW;
+		lsirdyie: 8, 1, RW;
+		/// Clock security system interrupt flag
+		cssf: 7, 1, RO;
+		/// Ready interrupt flags of PLL, HSE, HSI, LSE and LSI
+		pllrdyf: 4, 1, RO;
+		hserdyf: 3, 1, RO;
+		hsirdyf: 2, 1, RO;
+		lserdyf: 1, 1, RO;
+		lsirdyf: 0, 1, RO;
+	}
+
+	/// Backup domain control register
+	BDCR @ 8, reset = 0x0000_0000 {
+		/// Backup domain software reset
+		bdrst: 16, 1, RW;
+		/// RTC clock enable
+		rtcen: 15, 1, RW;
+		/// RTC clock source selection
+		rtcsel: 8, 2, RW;
+		/// LSE oscillator bypass
+		lsebyp: 2, 1, RW;
+		/// LSE oscillator ready
+		lserdy: 1, 1, RO;
+		/// LSE oscillator enable
+		lseon: 0, 1, RW;
+	}
+
+	/// Control/status register
+	CSR @ 9, reset = 0x0C00_0000 {
+		/// Reset flags: low-power, window watchdog, independent watchdog, software, POR/PDR and pin
+		lpwrrstf: 31, 1, RO;
+		wwdgrstf: 30, 1, RO;
+		iwdgrstf: 29, 1, RO;
+		sftrstf: 28, 1, RO;
+		porrstf: 27, 1, RO;
+		pinrstf: 26, 1, RO;
+		/// Remove the reset flags
+		rmvf: 24, 1, RW;
+		/// LSI oscillator ready
+		lsirdy: 1, 1, RO;
+		/// LSI oscillator enable
+		lsion: 0, 1, RW;
+	}
+}
+
+
+#[repr(C)]
+pub struct Rcc {
+	clocks: Clocks,
+	block: &'static mut [Register<u32>; SIZE],
+}
+
+impl crate::common::VolatileStruct for Rcc {}
+
+impl Rcc {
+	pub fn clocks(&self) -> Clocks {
+		self.clocks
+	}
+
+	/// Get the RCC from the given address
+	pub fn from_addr(address: u32) -> Self {
+		Rcc {
+			clocks: Clocks::default(),
+			block: unsafe{ &mut *(address as *mut _) },
+		}
+	}
+
+	/// Get the RCC over the given register block
+	/// Used to build the peripheral over a simulated block on the host
+	pub fn from_block(block: &'static mut [Register<u32>; SIZE]) -> Self {
+		Rcc {
+			clocks: Clocks::default(),
+			block,
+		}
+	}
+}
+
+impl_rwio!(Rcc);
+
+// PLLINPUT = HSI / 2 or HSE / PLLXTPRE
+// PLLOUT = PLLINPUT * PLLMUL
+
+impl Rcc {
+	/// Freezes the clocks, making it effective
+	/// Fails without touching the RCC if `cfg` breaks a limit of the device, see `validate`,
+	/// or if the HSE is needed and does not start within `HSE_TIMEOUT`
+	///
+	/// The PLL factors of `cfg.pllcfg` are `(PLLMUL, predivider, _, _)`. The predivider is
+	/// 1 or 2 for the HSE and 2 for the HSI, which is always halved.
+	/// The flash latency is raised before speeding up and lowered after slowing down,
+	/// and the prefetch buffer is enabled. `pwr` is not used, there is no voltage scaling.
+	pub fn freeze(&mut self, cfg: ClockCfg, flash: &mut FlashIface, _pwr: &mut Pwr) -> Result<(), RCCError> {
+		let target = validate(&cfg)?;
+
+		self.start_hse(&cfg)?;
+
+		let latency = flashiface::wait_states(target.ahb1f, cfg.vdd);
+
+		// The prefetch buffer is switched only while the SYSCLK has no prescaler
+		flash.prefetch_state(true);
+
+		if latency > flash.latency() {
+			flash.set_latency(latency);
+		}
+
+		// Run from the HSI while reconfiguring, the PLL cannot be changed while it runs the SYSCLK
+		self.reg::<CR>().modify(|_, w| w.hsion(1));
+		while self.read_reg::<CR>().hsirdy() == 0 {}
+		self.set_sysclk_source(0b00);
+
+		self.reg::<CR>().modify(|_, w| w.pllon(0));
+		while self.read_reg::<CR>().pllrdy() == 1 {}
+
+		if let Some(((mul, prediv, _, _), src)) = cfg.pllcfg {
+			let hse = (src == SrcClock::HSE) as u32;
+			let xtpre = (hse == 1 && prediv == 2) as u32;
+
+			self.reg::<CFGR>().modify(|_, w| w.pllmul(mul - 2).pllxtpre(xtpre).pllsrc(hse));
+			self.reg::<CR>().modify(|_, w| w.pllon(1));
+
+			while self.read_reg::<CR>().pllrdy() == 0 {}
+		}
+
+		// Set the bus prescalers before speeding up, so the buses never run too fast
+		let (_, hpre) = hpre_bits(cfg.hpre.0);
+		let (_, ppre1) = ppre_bits(cfg.ppre.0);
+		let (_, ppre2) = ppre_bits(cfg.ppre.1);
+
+		self.reg::<CFGR>().modify(|_, w| w.hpre(hpre).ppre1(ppre1).ppre2(ppre2));
+
+		match cfg.sysclk.0 {
+			SrcClock::HSI => (),
+			SrcClock::HSE => { self.set_sysclk_source(0b01); },
+			SrcClock::PLL => { self.set_sysclk_source(0b10); },
+		}
+
+		if latency < flash.latency() {
+			flash.set_latency(latency);
+		}
+
+		self.clocks = target;
+
+		Ok(())
+	}
+
+	/// Starts the HSE of `cfg` if the PLL or the SYSCLK use it, and waits until it is ready
+	/// If it does not start within `HSE_TIMEOUT` it is turned back off, and the clocks
+	/// keep running as they were
+	fn start_hse(&mut self, cfg: &ClockCfg) -> Result<(), RCCError> {
+		let needed = matches!((cfg.sysclk.0, cfg.pllcfg), (SrcClock::HSE, _) | (_, Some((_, SrcClock::HSE))));
+
+		// Already running, the bypass cannot change while it is on
+		if !needed || self.read_reg::<CR>().hserdy() == 1 { return Ok(()); }
+
+		let bypass = matches!(cfg.hse, Some((_, HSEMode::Bypass)));
+
+		self.reg::<CR>().modify(|_, w| w.hsebyp(bypass as u32));
+		self.reg::<CR>().modify(|_, w| w.hseon(1));
+
+		let mut waited = 0;
+
+		while self.read_reg::<CR>().hserdy() == 0 {
+			if waited >= HSE_TIMEOUT {
+				self.reg::<CR>().modify(|_, w| w.hseon(0));
+				return Err(RCCError::HSETimeout);
+			}
+
+			asm::delay(100);
+			waited += 100;
+		}
+
+		Ok(())
+	}
+
+	#[inline]
+	fn set_sysclk_source(&mut self, value: u32) -> &mut Self {
+		self.reg::<CFGR>().modify(|_, w| w.sw(value));
+		while self.read_reg::<CFGR>().sws() != value {}
+		self
+	}
+}
+
+/// Checks `cfg` against the limits of the device and returns the clocks it gives
+/// Does not touch the RCC, the prescalers are rounded down as in `freeze`
+/// The first broken limit is returned with the offending value
+pub fn validate(cfg: &ClockCfg) -> Result<Clocks, RCCError> {
+	use crate::common::ClockSpeeds::*;
+
+	let mut clocks = Clocks::default();
+
+	#[cfg(not(feature = "stm32f100"))]
+	let crystal = Frequency::MHz(16);
+
+	#[cfg(feature = "stm32f100")]
+	let crystal = Frequency::MHz(24);
+
+	if let Some((f, mode)) = cfg.hse {
+		match mode {
+			HSEMode::Crystal => within(ClockLimit::HSE, f, Frequency::MHz(4), crystal)?,
+			HSEMode::Bypass => within(ClockLimit::HSE, f, Frequency::MHz(1), Frequency::MHz(25).min(SYSCLKMAX))?,
+		}
+	}
+
+	let hse = match cfg.hse {
+		Some((f, _)) => Ok(f),
+		None => Err(RCCError::HSENotConfigured),
+	};
+
+	if let Some(((mul, prediv, _, _), src)) = cfg.pllcfg {
+		if !(2..=16).contains(&mul) { return Err(RCCError::OutOfRange(ClockLimit::PLLN, mul)); }
+
+		let input = match (src, prediv) {
+			(SrcClock::HSE, 1 | 2) => hse? / prediv,
+			(SrcClock::HSE, _) => return Err(RCCError::OutOfRange(ClockLimit::PLLM, prediv)),
+			(_, 2) => HSIF / 2,
+			_ => return Err(RCCError::OutOfRange(ClockLimit::PLLM, prediv)),
+		};
+
+		within(ClockLimit::PLLInput, input, PLLINMIN, PLLINMAX)?;
+		within(ClockLimit::VCO, input * mul, VCOMIN, VCOMAX)?;
+
+		clocks.pllout = input * mul;
+	}
+
+	clocks.sysf = match cfg.sysclk.0 {
+		SrcClock::HSI => HSIF,
+		SrcClock::HSE => hse?,
+		SrcClock::PLL => match cfg.pllcfg {
+			Some(_) => clocks.pllout,
+			None => return Err(RCCError::PLLNotConfigured),
+		},
+	};
+	within(ClockLimit::SYSCLK, clocks.sysf, Frequency::Hz(0), SYSCLKMAX)?;
+
+	clocks.ahb1f = clocks.sysf / hpre_bits(cfg.hpre.0).0;
+	within(ClockLimit::AHB, clocks.ahb1f, Frequency::Hz(0), AHBMAX)?;
+
+	clocks.apb1f = clocks.ahb1f / ppre_bits(cfg.ppre.0).0;
+	within(ClockLimit::APB1, clocks.apb1f, Frequency::Hz(0), APB1MAX)?;
+
+	clocks.apb2f = clocks.ahb1f / ppre_bits(cfg.ppre.1).0;
+	within(ClockLimit::APB2, clocks.apb2f, Frequency::Hz(0), APB2MAX)?;
+
+	Ok(clocks)
+}
+
+/// Clock Interrupt Register (CIR)
+impl Rcc {
+	/// Clears `flag`
+	pub fn clear_flag(&mut self, flag: RCCInterrupt) -> &mut Self {
+		self.reg::<CIR>().modify(|_, w| match flag {
+			RCCInterrupt::CSSC => w.cssc(1),
+			RCCInterrupt::PLLRDY => w.pllrdyc(1),
+			RCCInterrupt::HSERDY => w.hserdyc(1),
+			RCCInterrupt::HSIRDY => w.hsirdyc(1),
+			RCCInterrupt::LSERDY => w.lserdyc(1),
+			RCCInterrupt::LSIRDY => w.lsirdyc(1),
+			_ => w,
+		});
+		self
+	}
+
+	/// Enable/Disable `int`
+	pub fn int_state(&mut self, s: bool, int: RCCInterrupt) -> &mut Self {
+		let s = s as u32;
+
+		self.reg::<CIR>().modify(|_, w| match int {
+			RCCInterrupt::PLLRDY => w.pllrdyie(s),
+			RCCInterrupt::HSERDY => w.hserdyie(s),
+			RCCInterrupt::HSIRDY => w.hsirdyie(s),
+			RCCInterrupt::LSERDY => w.lserdyie(s),
+			RCCInterrupt::LSIRDY => w.lsirdyie(s),
+			_ => w,
+		});
+		self
+	}
+
+	/// Returns `true` if the flag is raised
+	/// The STM32F1 has no PLLI2S and no brownout reset flag
+	pub fn is_raised(&mut self, flag: RCCInterrupt) -> bool {
+		let cir = self.read_reg::<CIR>();
+		let csr = self.read_reg::<CSR>();
+
+		let raised = match flag {
+			RCCInterrupt::CSSC => cir.cssf(),
+			RCCInterrupt::PLLRDY => cir.pllrdyf(),
+			RCCInterrupt::HSERDY => cir.hserdyf(),
+			RCCInterrupt::HSIRDY => cir.hsirdyf(),
+			RCCInterrupt::LSERDY => cir.lserdyf(),
+			RCCInterrupt::LSIRDY => cir.lsirdyf(),
+
+			RCCInterrupt::LPWRRST => csr.lpwrrstf(),
+			RCCInterrupt::WWDGRST => csr.wwdgrstf(),
+			RCCInterrupt::IWDGRST => csr.iwdgrstf(),
+			RCCInterrupt::SFTRST  => csr.sftrstf(),
+			RCCInterrupt::PORRST  => csr.porrstf(),
+			RCCInterrupt::PINRST  => csr.pinrstf(),
+			RCCInterrupt::PLLI2SRDY | RCCInterrupt::BORRST => 0,
+		};
+
+		raised == 1
+	}
+}
+
+/// APB2 Peripheral Reset Register APB2RSTR
+/// APB1 Peripheral Reset Register APB1RSTR
+/// AHB, APB2 and APB1 Peripheral Clock Enable Registers AHBENR, APB2ENR and APB1ENR
+impl Rcc {
+	/// Resets the given `peripheral`
+	/// Does nothing for the AHB peripherals, they have no reset
+	pub fn reset_peripheral(&mut self, peripheral: RCCPeripheral) -> &mut Self {
+		let (rstr, bit) = peripheral.offsets();
+
+		if rstr == AHBENR { return self; }
+
+		self.set(rstr, bit);
+		// 10 cycles delay
+		asm::delay(9);
+		self.clear(rstr, bit)
+	}
+
+	/// Enables/Disables the given peripheral
+	pub fn peripheral_state(&mut self, s: bool, peripheral: RCCPeripheral) -> &mut Self {
+		let (reg, bit) = peripheral.offsets();
+
+		// APB2ENR and APB1ENR are 3 registers after their reset register
+		let enr = if reg == AHBENR { AHBENR } else { reg + 3 };
+
+		if s { self  .set(enr, bit) }
+		else { self.clear(enr, bit) }
+	}
+}
+
+/// Backup Domain Control Register (BDCR)
+impl Rcc {
+	/// Resets the Backup Domain
+	pub fn reset_bck_domain(&mut self) -> &mut Self {
+		self.reg::<BDCR>().modify(|_, w| w.bdrst(1));
+		self
+	}
+
+	/// Enable/Disable RTC clock
+	pub fn rtc_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<BDCR>().modify(|_, w| w.rtcen(s as u32));
+		self
+	}
+
+	/// Sets the RTC clock source
+	/// Defaults to the LSI, the HSE is divided by 128
+	pub fn rtc_src(&mut self, src: Option<DeviceClock>) -> &mut Self {
+		let sel = match src {
+			Some(DeviceClock::LSE) => 1,
+			Some(DeviceClock::HSE) => 3,
+			Some(_) => 2,
+			None => 0,
+		};
+
+		self.reg::<BDCR>().modify(|_, w| w.rtcsel(sel));
+		self
+	}
+
+	/// Enable/Disable LSE Bypass with an external clock
+	pub fn lse_bypass_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<BDCR>().modify(|_, w| w.lsebyp(s as u32));
+		self
+	}
+
+	/// Clears **ALL** the Reset Flags
+	pub fn clear_rst_flags(&mut self) -> &mut Self {
+		self.reg::<CSR>().modify(|_, w| w.rmvf(1));
+		self
+	}
+}
+
+#[cfg(all(test, feature = "sim"))]
+pub(crate) mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::common::enums::VoltageRange;
+	use crate::common::ClockSpeeds::*;
+	use crate::peripherals::extended::{ flashiface, pwr };
+
+	/// RCC block whose ready flags follow their enables and SWS follows SW
+	pub(crate) fn rcc() -> (SimBlock<SIZE>, Rcc) {
+		let block = SimBlock::<SIZE>::with_reset([CR::RESET, 0, 0, 0, 0, 0x14, 0, 0, 0, CSR::RESET]);
+
+		block.follow(CR::INDEX, 1 << 16, CR::INDEX, 1 << 17)
+			.follow(CR::INDEX, 1 << 24, CR::INDEX, 1 << 25)
+			.on_write(CFGR::INDEX, |_, new| (new & !0b1100) | ((new & 0b11) << 2));
+
+		let rcc = Rcc::from_block( unsafe { block.regs() } );
+		(block, rcc)
+	}
+
+	/// RCC frozen to `cfg`
+	pub(crate) fn frozen(cfg: ClockCfg) -> (SimBlock<SIZE>, Rcc) {
+		let (block, mut rcc) = rcc();
+		let flash_block = SimBlock::<{ flashiface::SIZE }>::new();
+		let pwr_block = SimBlock::<{ pwr::SIZE }>::new();
+
+		rcc.freeze(cfg, unsafe { flash_block.view() }, unsafe { pwr_block.view() }).unwrap();
+		(block, rcc)
+	}
+
+	/// HSI, no PLL and no prescaler
+	pub(crate) fn cfg() -> ClockCfg {
+		ClockCfg {
+			hse: None,
+			pllcfg: None,
+			sysclk: (SrcClock::HSI, 0),
+			hpre: (1, 1, 1),
+			ppre: (1, 1, 1),
+			i2scfg: (0, 0, 0),
+			vdd: VoltageRange::V2_7,
+		}
+	}
+
+	/// HSE crystal at 8 MHz through the PLL
+	fn hse_pll(mul: u32, prediv: u32) -> ClockCfg {
+		ClockCfg {
+			hse: Some((Frequency::MHz(8), HSEMode::Crystal)),
+			pllcfg: Some(((mul, prediv, 0, 0), SrcClock::HSE)),
+			sysclk: (SrcClock::PLL, 0),
+			..cfg()
+		}
+	}
+
+	#[test]
+	fn freeze_writes_the_pll_in_cfgr() {
+		let (block, rcc) = frozen( ClockCfg { ppre: (2, 1, 1), ..hse_pll(3, 1) } );
+
+		let cfgr = block.peek(CFGR::INDEX);
+		assert_eq!((cfgr >> 18) & 0xF, 1, "PLLMUL x3");
+		assert_eq!((cfgr >> 16) & 0b11, 0b01, "HSE not divided");
+		assert_eq!((cfgr >> 8) & 0b111, 0b100, "APB1 / 2");
+		assert_eq!(cfgr & 0b11, 0b10, "PLL as SYSCLK");
+
+		let clocks = rcc.clocks();
+		assert_eq!(clocks.sysf, Frequency::MHz(24));
+		assert_eq!(clocks.apb1f, Frequency::MHz(12));
+		assert_eq!(clocks.apb2f, Frequency::MHz(24));
+	}
+
+	#[test]
+	fn the_hsi_is_halved_for_the_pll() {
+		let cfg = ClockCfg { pllcfg: Some(((4, 2, 0, 0), SrcClock::HSI)), sysclk: (SrcClock::PLL, 0), ..cfg() };
+		assert_eq!(validate(&cfg).map(|c| c.sysf), Ok(Frequency::MHz(16)));
+
+		let cfg = ClockCfg { pllcfg: Some(((4, 1, 0, 0), SrcClock::HSI)), ..cfg };
+		assert_eq!(validate(&cfg).err(), Some(RCCError::OutOfRange(ClockLimit::PLLM, 1)));
+	}
+
+	#[test]
+	fn validate_limits() {
+		assert_eq!(validate(&hse_pll(1, 1)).err(), Some(RCCError::OutOfRange(ClockLimit::PLLN, 1)));
+		assert_eq!(validate(&hse_pll(17, 1)).err(), Some(RCCError::OutOfRange(ClockLimit::PLLN, 17)));
+		assert_eq!(validate(&hse_pll(3, 3)).err(), Some(RCCError::OutOfRange(ClockLimit::PLLM, 3)));
+
+		// The fastest PLL output is the SYSCLK max, with APB1 halved
+		let mul = SYSCLKMAX.hz() / Frequency::MHz(8).hz();
+		let fastest = ClockCfg { ppre: (2, 1, 1), ..hse_pll(mul, 1) };
+		assert_eq!(validate(&fastest).map(|c| c.sysf), Ok(SYSCLKMAX));
+		assert_eq!(validate(&hse_pll(mul + 1, 1)).err(), Some(RCCError::OutOfRange(ClockLimit::VCO, 8_000_000 * (mul + 1))));
+
+		// APB1 is the slowest bus
+		if APB1MAX < SYSCLKMAX {
+			let cfg = hse_pll(mul, 1);
+			assert_eq!(validate(&cfg).err(), Some(RCCError::OutOfRange(ClockLimit::APB1, SYSCLKMAX.hz())));
+		}
+	}
+
+	#[test]
+	fn peripherals_are_enabled_and_reset_in_their_bus() {
+		let (block, mut rcc) = rcc();
+
+		rcc.peripheral_state(true, RCCPeripheral::SPI1)
+			.reset_peripheral(RCCPeripheral::SPI1)
+			.peripheral_state(true, RCCPeripheral::I2C1)
+			.reset_peripheral(RCCPeripheral::I2C1)
+			.peripheral_state(true, RCCPeripheral::DMA1)
+			.reset_peripheral(RCCPeripheral::DMA1);
+
+		// APB2ENR, APB1ENR and AHBENR
+		assert_eq!(block.peek(6), 1 << 12);
+		assert_eq!(block.peek(7), 1 << 21);
+		assert_eq!(block.peek(5), 0x14 | 1);
+
+		// APB2RSTR and APB1RSTR pulsed, CIR not touched by the AHB reset
+		assert_eq!(block.writes(3), [1 << 12, 0]);
+		assert_eq!(block.writes(4), [1 << 21, 0]);
+		assert!(block.writes(CIR::INDEX).is_empty());
+	}
+}
@@ -1,635 +1,662 @@
-//! Helper module with compile time calculations
+//! Compile time solver of the clock tree
+//!
+//! `clock_calculation` searches the PLL factors and the bus prescalers that get
+//! closest to the requested frequencies without going over the limits of the device
+//! (`ClockSpeeds`). It is a `const fn`, so the configuration can be solved into a
+//! constant and costs nothing at runtime.
+//!
+//! ```ignore
+//! const TREE: ClockTree = clock_calculation(RccClocks {
+//! 	hse: Some((Frequency::MHz(8), HSEMode::Crystal)),
+//! 	sysclk: Some(Frequency::MHz(96)),
+//! 	usb: true,
+//! 	..RccClocks::new()
+//! }, false);
+//!
+//! rcc.freeze(TREE.cfg(VoltageRange::V2_7), p.flash, p.pwr)?;
+//! ```
 
-#[cfg(feature = "std")]
-use std::{cmp, ops, convert, f32};
+use crate::common::ClockSpeeds::*;
+use crate::common::{ Clocks, ClockCfg, Frequency, HSEMode, SrcClock, VoltageRange };
 
-#[cfg(not(feature = "std"))]
-use core::{cmp, ops, convert, f32};
+/// Range of the PLLM, PLLN and PLLQ factors
+const PLLM: (u32, u32) = (2, 63);
+const PLLN: (u32, u32) = (50, 432);
+const PLLQ: (u32, u32) = (2, 15);
 
+/// Values of the PLLP divider
+const PLLP: [u32; 4] = [2, 4, 6, 8];
 
-use crate::common::ClockSpeeds::*;
-use crate::common::ClockSpeeds::APB1MAX;
-use crate::common::frequency::Frequency;
+/// Values of the AHB and APB prescalers
+const HPRE: [u32; 9] = [1, 2, 4, 8, 16, 64, 128, 256, 512];
+const PPRE: [u32; 5] = [1, 2, 4, 8, 16];
 
-pub struct Clocks {
-	pub sysclk: Frequency,
-	pub ahb: Frequency,
-	pub apb1: Frequency,
-	#[cfg(feature = "apb2")]
-	pub apb2: Frequency,
-	#[cfg(feature = "apb3")]
-	pub apb3: Frequency,
-}
+/// Clock of the USB OTG FS, SDIO and RNG
+const PLL48: u32 = 48_000_000;
 
-impl Clocks {
-	pub const fn new() -> Clocks {
-		Clocks {
-			sysclk: Frequency::Hz(0),
-			ahb: Frequency::Hz(0),
-			apb1: Frequency::Hz(0),
-			#[cfg(feature = "apb2")]
-			apb2: Frequency::Hz(0),
-			#[cfg(feature = "apb3")]
-			apb3: Frequency::Hz(0),
-		}
-	}
-}
+/// Range of the HSE with a crystal, and in bypass
+const HSECRYSTAL: (u32, u32) = (4_000_000, 26_000_000);
+const HSEBYPASS: (u32, u32) = (1_000_000, 50_000_000);
 
+/// Requested frequencies, `None` leaves the solver free
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct RccClocks {
+	/// HSE of the board, `None` if there is none
+	pub hse: Option<(Frequency, HSEMode)>,
 	pub sysclk: Option<Frequency>,
 	pub ahb: Option<Frequency>,
 	pub apb1: Option<Frequency>,
 	#[cfg(feature = "apb2")]
 	pub apb2: Option<Frequency>,
-	#[cfg(feature = "apb3")]
-	pub apb3: Option<Frequency>,
+	/// Requires the PLL48 clock as close to 48 MHz as possible
+	pub usb: bool,
 }
 
-/// This is a compile-time calculation of a RCC Clock configuration
-/// Using this allows for very optimized clock configurations 
-/// with almost no cost in runtime.
-pub const fn clock_calculation(cfg: RccClocks, lp: bool) -> ([u32; 12], Clocks) {
-	// Array
-	//  0: SYSCLK source -> 0 HSI 1 HSE 2 PLL
-	//  1: AHB prescaler
-	//  2: APB1 prescaler
-	//  3: APB2 prescaler
-	//  4: APB3 prescaler
-	//  8: PLLP
-	//  9: PLLN
-	// 10: PLLM
-	let mut array = [0; 6];
-
-	let mut clocks = Clocks::new();
-
-	match cfg.sysclk {
-		// If there is a predefined SYSCLK frequency, try to achieve it
-		Some(sys) => defined_sysclk(cfg, lp),
-		// If not, continue with the AHB
-		None => match cfg.ahb {
-			// There is a predefined AHBCLK
-			Some(ahb) => defined_ahb(cfg, lp),
-
-			// SYSCLK undefined and AHBCLK undefined
-			// Basically, no info
-			None => {
-				let mut defined = false;
-
-				match cfg.apb1 {
-					Some(_) => defined = true,
-					_ => (),
-				}
+impl Default for RccClocks {
+	fn default() -> Self {
+		Self::new()
+	}
+}
 
-				#[cfg(feature = "apb2")]
-				match cfg.apb2 {
-					Some(_) => defined = true,
-					_ => (),
-				}
+impl RccClocks {
+	/// Nothing requested, solves to the HSI
+	pub const fn new() -> RccClocks {
+		RccClocks {
+			hse: None,
+			sysclk: None,
+			ahb: None,
+			apb1: None,
+			#[cfg(feature = "apb2")]
+			apb2: None,
+			usb: false,
+		}
+	}
+}
 
-				#[cfg(feature = "apb3")]
-				match cfg.apb3 {
-					Some(_) => defined = true,
-					_ => (),
-				}
+/// Solved clock tree
+/// The fields hold the values of the register fields, not the division factors
+#[derive(Debug, Copy, Clone)]
+pub struct ClockTree {
+	/// SW field of RCC_CFGR: 0 HSI, 1 HSE, 2 PLL
+	pub sw: u32,
+	/// PLLSRC bit of RCC_PLLCFGR: 0 HSI, 1 HSE
+	pub pllsrc: u32,
+	pub pllm: u32,
+	pub plln: u32,
+	/// PLLP field of RCC_PLLCFGR, 0b00 divides by 2
+	pub pllp: u32,
+	pub pllq: u32,
+	/// HPRE, PPRE1 and PPRE2 fields of RCC_CFGR
+	pub hpre: u32,
+	pub ppre1: u32,
+	pub ppre2: u32,
+
+	/// HSE of the request
+	pub hse: Option<(Frequency, HSEMode)>,
+
+	/// Achieved frequencies
+	pub clocks: Clocks,
+	/// Achieved PLL48 clock, 0 if the PLL is not used
+	pub pll48: Frequency,
+}
 
-				match defined {
-					true => defined_apb(cfg, lp),
-					_ => no_defines(cfg, lp),
-				}
+impl ClockTree {
+	/// Returns the value of RCC_PLLCFGR
+	pub const fn pllcfgr(&self) -> u32 {
+		self.pllm | (self.plln << 6) | (self.pllp << 16) | (self.pllsrc << 22) | (self.pllq << 24)
+	}
 
+	/// Returns the SW, HPRE, PPRE1 and PPRE2 fields of RCC_CFGR
+	pub const fn cfgr(&self) -> u32 {
+		self.sw | (self.hpre << 4) | (self.ppre1 << 10) | (self.ppre2 << 13)
+	}
+
+	/// Returns the configuration for `Rcc::freeze`
+	/// PLLI2S runs at 192 times its input divided by M, divided by 2, with M the smallest
+	/// that keeps the divided input under `PLLINMAX`
+	pub const fn cfg(&self, vdd: VoltageRange) -> ClockCfg {
+		let src = match self.pllsrc {
+			1 => SrcClock::HSE,
+			_ => SrcClock::HSI,
+		};
+
+		// The PLLI2S shares the input of the PLL, the HSI when there is no PLL
+		let input = match (self.sw, src, self.hse) {
+			(2, SrcClock::HSE, Some((f, _))) => f.hz(),
+			_ => HSI.hz(),
+		};
+
+		let i2sm = input.div_ceil(PLLINMAX.hz());
+
+		ClockCfg {
+			hse: self.hse,
+			pllcfg: match self.sw {
+				2 => Some(((self.plln, self.pllm, (self.pllp + 1) * 2, self.pllq), src)),
+				_ => None,
 			},
-		},
+			sysclk: (match self.sw { 0 => SrcClock::HSI, 1 => SrcClock::HSE, _ => SrcClock::PLL }, 0),
+			hpre: (hpre_div(self.hpre), 1, 1),
+			ppre: (ppre_div(self.ppre1), ppre_div(self.ppre2), 1),
+			i2scfg: (192, if i2sm < PLLM.0 { PLLM.0 } else { i2sm }, 2),
+			vdd,
+		}
 	}
 }
 
-const fn pllerror(vco: Frequency, ahb: Frequency) -> (u32, u32, f32) {
-	// Get the least error configuration
-	let mut previous = (0, 0, 1000.0);
-	let mut i = 1;
-	let mut j = 50;
-
-	// WTF no ranged loops in const fn????
-	'outer: loop {
-		'inner: loop {
-			let ahb_real = (vco * j / (i * 2)).hz() as f32;
-
-			// WTF no .abs() in core?????
-			let error = match ((ahb_real / (ahb.hz() as f32)) - 1.0) {
-				a if a < 0.0 => -a,
-				b => b,
-			};
+/// Requested frequencies in Hz, capped at the limits of the device
+#[derive(Copy, Clone)]
+struct Targets {
+	sysclk: Option<u32>,
+	ahb: Option<u32>,
+	apb1: Option<u32>,
+	apb2: Option<u32>,
+	usb: bool,
+	lp: bool,
+	hse: bool,
+}
 
-			if error < previous.2 {
-				previous = (i*2, j, error);
-			}
+/// Ranking of a solution, each field compared only if the previous ones are equal
+/// Lower is better
+#[derive(Copy, Clone)]
+struct Score {
+	/// Distance of PLL48 to 48 MHz, if required
+	usb: u32,
+	/// Sum of the distances to the requested frequencies
+	error: u32,
+	/// 1 if the PLL is used
+	pll: u32,
+	/// 1 if running from the HSI while there is a HSE, which is more accurate
+	hsi: u32,
+	/// VCO frequency in low power, else the inverse of the VCO input (less jitter)
+	power: u32,
+}
 
-			j += 1;
+impl Score {
+	const fn better(&self, other: &Score) -> bool {
+		if self.usb != other.usb { return self.usb < other.usb; }
+		if self.error != other.error { return self.error < other.error; }
+		if self.pll != other.pll { return self.pll < other.pll; }
+		if self.hsi != other.hsi { return self.hsi < other.hsi; }
+		self.power < other.power
+	}
+}
 
-			if j == 433 {
-				break 'inner;
-			}
-		}
+/// Solves the clock tree for the requested frequencies
+///
+/// The SYSCLK is searched close to the requested one, or else to the requested AHB
+/// clock, or else to the fastest requested APB clock. The AHB and APB prescalers
+/// are then the closest to their requests, or the fastest allowed if not requested.
+/// With nothing requested the tree runs from the HSE if there is one, else the HSI.
+/// A HSE out of its range (4 to 26 MHz with a crystal, 1 to 50 MHz in bypass) is
+/// left off and the tree runs from the HSI.
+///
+/// Ties go to the tree without the PLL, then to the HSE over the HSI, then to the
+/// one with the highest VCO input, or with `lp` to the one with the slowest VCO.
+pub const fn clock_calculation(cfg: RccClocks, lp: bool) -> ClockTree {
+	let hse = match cfg.hse {
+		Some((f, mode)) if hse_valid(f, mode) => Some((f, mode)),
+		_ => None,
+	};
 
-		i += 1;
+	#[cfg(feature = "apb2")]
+	let apb2 = target(cfg.apb2, APB2MAX);
+	#[cfg(not(feature = "apb2"))]
+	let apb2 = None;
+
+	let targets = Targets {
+		sysclk: target(cfg.sysclk, SYSCLKMAX),
+		ahb: target(cfg.ahb, AHBMAX),
+		apb1: target(cfg.apb1, APB1MAX),
+		apb2,
+		usb: cfg.usb,
+		lp,
+		hse: hse.is_some(),
+	};
+
+	// Frequency the PLL aims at
+	let aim = match (targets.sysclk, targets.ahb) {
+		(Some(f), _) => Some(f),
+		(None, Some(f)) => Some(f),
+		_ => match (targets.apb1, targets.apb2) {
+			(Some(a), Some(b)) => Some(if a > b { a } else { b }),
+			(Some(f), None) => Some(f),
+			(None, Some(f)) => Some(f),
+			_ if cfg.usb => Some(SYSCLKMAX.hz()),
+			_ => None,
+		},
+	};
+
+	// HSI, always available
+	let (mut best, mut score) = evaluate(0, 0, HSI.hz(), 0, 0, 0, 0, &targets);
 
-		if i == 5 {
-			break 'outer;
+	// HSE
+	if let Some((hse, _)) = hse {
+		if hse.hz() <= SYSCLKMAX.hz() {
+			let (tree, s) = evaluate(1, 1, hse.hz(), 0, 0, 0, 0, &targets);
+			if s.better(&score) { best = tree; score = s; }
 		}
 	}
 
+	// PLL from the HSI, then from the HSE
+	if let Some(aim) = aim {
+		let mut src = 0;
 
-	previous
-}
+		while src < 2 {
+			let input = match (src, hse) {
+				(0, _) => Some(HSI.hz()),
+				(_, Some((hse, _))) => Some(hse.hz()),
+				_ => None,
+			};
 
+			if let Some(input) = input {
+				let mut m = PLLM.0;
 
-const fn defined_sysclk(cfg: RccClocks, _: bool) -> ([u32; 12], Clocks) {
-	let mut clocks = Clocks::new();
-	let mut array = [0u32; 12];
-
-	match cfg.sysclk {
-		// Already checked
-		None => unreachable!(),
-		// Unwraping
-		Some(sys) => {
-			// CSet up SYSCLK
-			match sys {
-				// Easy and energy performant
-				HSI => {
-					array[0] = 0;
-					clocks.sysclk = HSI;
-				},
-				// Anything else will go through PLL
-				_ => {
-					// Get VCO
-					let pllm = match ((HSI.mhz() / 2), (HSI.hz() % 2)) {
-						(m, 0) => m,
-						(m, _) => m + 1,
-					};
-
-					let vco = HSI / pllm;
-
-					// Get best PLL approximation
-					let (pllp, plln, _) = pllerror(vco, sys);
-
-					// Set SYSCLK source as PLL
-					array[0] = 2;
-					clocks.sysclk = vco * plln / pllp;
-				}
-			}
+				while m <= PLLM.1 {
+					let vin = input / m;
 
-			// Set up AHB
-			match cfg.ahb {
-				// Predefined AHB
-				Some(ahb) => {
-					let (ratio, bytes): (u32, u32) = match u32::from(clocks.sysclk / cmp::min(ahb, AHBMAX)) {
-						0..=1     => (  1, 0b0000),
-						2..=3     => (  2, 0b1000),
-						4..=7     => (  4, 0b1001),
-						8..=15    => (  8, 0b1010),
-						16..=63   => ( 16, 0b1011),
-						64..=127  => ( 64, 0b1100),
-						128..=255 => (128, 0b1101),
-						256..=511 => (256, 0b1110),
-						_         => (512, 0b1111),
-					};
-
-					// Set up the AHB prescaler
-					array[1] = bytes;
-					clocks.ahb = clocks.sysclk / ratio;
-				},
-				// Undefined AHB
-				None => {
-					// Set the same as SYSCLK
-					array[1] = 0;
-					clocks.ahb = clocks.sysclk;
-				},
-			}
+					if vin >= PLLINMIN.hz() && vin <= PLLINMAX.hz() {
+						let mut i = 0;
 
-			// Set up APB1 and APB2 and APB3 if they exist
-			{
-				let (ratio, bytes): (u32, u32) = match cfg.apb1 {
-					Some(apb) => match u32::from(clocks.ahb / cmp::min(apb, APB1MAX)) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					},
-					None => match u32::from(clocks.ahb / APB1MAX) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					}
-				};
+						while i < PLLP.len() {
+							let p = PLLP[i];
 
-				// Set the values
-				array[2] = bytes;
-				clocks.apb1 = clocks.ahb / ratio;
-			}
+							// PLLN around the aimed frequency
+							let n = (aim as u64 * (m * p) as u64 / input as u64) as u32;
+							let mut k = 0;
 
-			#[cfg(feature = "apb2")]
-			{
-				let (ratio, bytes): (u32, u32) = match cfg.apb2 {
-					Some(apb) => match u32::from(clocks.ahb / cmp::min(apb, APB2MAX)) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					},
-					None => match u32::from(clocks.ahb / APB2MAX) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					}
-				};
+							while k < 2 {
+								let (tree, s) = evaluate(2, src, input, m, n + k, p, 0, &targets);
+								if s.better(&score) { best = tree; score = s; }
+								k += 1;
+							}
 
-				// Set the values
-				array[3] = bytes;
-				clocks.apb2 = clocks.ahb / ratio;
-			}
+							// PLLN giving a VCO of exactly 48 MHz times PLLQ
+							if cfg.usb {
+								let mut q = PLLQ.0;
 
-			#[cfg(feature = "apb3")]
-			{
-				let (ratio, bytes): (u32, u32) = match cfg.apb3 {
-					Some(apb) => match u32::from(clocks.ahb / cmp::min(apb, APB3MAX)) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					},
-					None => match u32::from(clocks.ahb / APB3MAX) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
+								while q <= PLLQ.1 {
+									let n = (PLL48 as u64 * (q * m) as u64 / input as u64) as u32;
+									let (tree, s) = evaluate(2, src, input, m, n, p, q, &targets);
+									if s.better(&score) { best = tree; score = s; }
+									q += 1;
+								}
+							}
+
+							i += 1;
+						}
 					}
-				};
 
-				// Set the values
-				array[4] = bytes;
-				clocks.apb3 = clocks.ahb / ratio;
+					m += 1;
+				}
 			}
-		},
+
+			src += 1;
+		}
 	}
 
-	(array, clocks)
+	best.hse = hse;
+	best
 }
 
-const fn defined_ahb(cfg: RccClocks, lp: bool) -> ([u32; 12], Clocks) {
-	let mut clocks = Clocks::new();
-	let mut array = [0u32; 12];
-
-	const HSIHZ: u32 = HSI.hz();
-
-	match cfg.ahb {
-		None => unreachable!(),
-		Some(ahb) => {
-			match cmp::min(ahb.hz(), AHBMAX.hz()) {
-				// If AHBCLK <= HSI -> SYSCLK = HSI
-				0..=HSIHZ => {
-					let (ratio, bytes): (u32, u32) = match u32::from(HSI / ahb) {
-						0..=1     => (  1, 0b0000),
-						2..=3     => (  2, 0b1000),
-						4..=7     => (  4, 0b1001),
-						8..=15    => (  8, 0b1010),
-						16..=63   => ( 16, 0b1011),
-						64..=127  => ( 64, 0b1100),
-						128..=255 => (128, 0b1101),
-						256..=511 => (256, 0b1110),
-						_         => (512, 0b1111),
-					};
-
-					array[0] = 0;
-					array[1] = bytes;
-
-					clocks.sysclk = HSI;
-					clocks.ahb = HSI / ratio;
-				},
-				_ => {
-					let pllm = match (HSI.mhz() / 2, HSI.hz() % 2) {
-						(m, 0) => m,
-						(m, _) => m + 1,
-					};
-
-					let vco = HSI / pllm;
-
-					// If it's low power mode and 1.5 * AHBCLK < SYSCLKMAX 
-					// it defaults to SYSCLK = AHBCLK to save power
-					match lp && u32::from(SYSCLKMAX / cmp::min(ahb, AHBMAX)) < 2
-					         && (SYSCLKMAX.hz() % cmp::min(ahb.hz(), AHBMAX.hz()) < (cmp::min(ahb.hz(), AHBMAX.hz()) / 2)) {
-						true => {
-							let (pllp, plln, _) = pllerror(vco, ahb);
-
-							array[0] = 2;
-							array[1] = 0;
-							array[8] = match pllp {
-								2 => 0b00,
-								4 => 0b01,
-								6 => 0b10,
-								8 => 0b11,
-								_ => unreachable!(),
-							};
-							array[9] = plln;
-							array[10] = pllm;
-
-							clocks.sysclk = vco * plln / pllp;
-							clocks.ahb = clocks.sysclk;
-						},
-
-						false => {
-							let (ahbpre, bytes): (u32, u32) = match u32::from(SYSCLKMAX / ahb) {
-								0..=1     => (  1, 0b0000),
-								2..=3     => (  2, 0b1000),
-								4..=7     => (  4, 0b1001),
-								8..=15    => (  8, 0b1010),
-								16..=63   => ( 16, 0b1011),
-								64..=127  => ( 64, 0b1100),
-								128..=255 => (128, 0b1101),
-								256..=511 => (256, 0b1110),
-								_         => (512, 0b1111),
-							};
-
-							let (pllp, plln, _) = pllerror(vco, ahb * ahbpre);
-							array[0] = 2;
-							array[1] = bytes;
-							array[8] = match pllp {
-								2 => 0b00,
-								4 => 0b01,
-								6 => 0b10,
-								8 => 0b11,
-								_ => unreachable!(),
-							};
-							array[9] = plln;
-							array[10] = pllm;
-
-							clocks.sysclk = vco * plln / plln;
-							clocks.ahb = clocks.sysclk / ahbpre;
-						},
-					}
-				},
-			}
+/// Returns `true` if `f` is in the range of the HSE in `mode`
+const fn hse_valid(f: Frequency, mode: HSEMode) -> bool {
+	let (min, max) = match mode {
+		HSEMode::Crystal => HSECRYSTAL,
+		HSEMode::Bypass => HSEBYPASS,
+	};
 
-			// Set up APB1 and APB2 and APB3 if they exist
-			{
-				let (ratio, bytes): (u32, u32) = match cfg.apb1 {
-					Some(apb) => match u32::from(clocks.ahb / cmp::min(apb, APB1MAX)) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					},
-					None => match u32::from(clocks.ahb / APB1MAX) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					}
-				};
+	f.hz() >= min && f.hz() <= max
+}
 
-				// Set the values
-				array[2] = bytes;
-				clocks.apb1 = clocks.ahb / ratio;
-			}
+/// Builds and ranks the tree with SYSCLK source `sw`
+/// For the PLL, `q` 0 picks the PLLQ that gets closest to 48 MHz without going over
+/// Invalid PLL factors rank last
+#[allow(clippy::too_many_arguments)]
+const fn evaluate(sw: u32, src: u32, input: u32, m: u32, n: u32, p: u32, q: u32, t: &Targets) -> (ClockTree, Score) {
+	const WORST: Score = Score { usb: u32::MAX, error: u32::MAX, pll: u32::MAX, hsi: u32::MAX, power: u32::MAX };
+
+	let mut tree = ClockTree {
+		sw,
+		pllsrc: src,
+		pllm: 0,
+		plln: 0,
+		pllp: 0,
+		pllq: 0,
+		hpre: 0,
+		ppre1: 0,
+		ppre2: 0,
+		hse: None,
+		clocks: Clocks {
+			sysf: Frequency::Hz(input),
+			ahb1f: Frequency::Hz(0),
+			apb1f: Frequency::Hz(0),
+			apb2f: Frequency::Hz(0),
+			apb3f: Frequency::Hz(0),
+			pllout: Frequency::Hz(0),
+			i2sf: Frequency::Hz(0),
+		},
+		pll48: Frequency::Hz(0),
+	};
 
-			#[cfg(feature = "apb2")]
-			{
-				let (ratio, bytes): (u32, u32) = match cfg.apb2 {
-					Some(apb) => match u32::from(clocks.ahb / cmp::min(apb, APB2MAX)) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					},
-					None => match u32::from(clocks.ahb / APB2MAX) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					}
-				};
+	let mut score = Score { usb: 0, error: 0, pll: 0, hsi: 0, power: 0 };
 
-				// Set the values
-				array[3] = bytes;
-				clocks.apb2 = clocks.ahb / ratio;
-			}
+	if sw == 2 {
+		if n < PLLN.0 || n > PLLN.1 { return (tree, WORST); }
 
-			#[cfg(feature = "apb3")]
-			{
-				let (ratio, bytes): (u32, u32) = match cfg.apb3 {
-					Some(apb) => match u32::from(clocks.ahb / cmp::min(apb, APB3MAX)) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					},
-					None => match u32::from(clocks.ahb / APB3MAX) {
-						0..=1  => ( 1, 0b000),
-						2..=3  => ( 2, 0b100),
-						4..=8  => ( 4, 0b101),
-						8..=15 => ( 8, 0b110),
-						_      => (16, 0b111),
-					}
-				};
+		let vco = Frequency::Hz(input).ratio(n, m).hz();
+		if vco < VCOMIN.hz() || vco > VCOMAX.hz() { return (tree, WORST); }
 
-				// Set the values
-				array[4] = bytes;
-				clocks.apb3 = clocks.ahb / ratio;
-			}
-		}
+		let sysclk = vco / p;
+		if sysclk > SYSCLKMAX.hz() { return (tree, WORST); }
+
+		let q = match q {
+			0 => {
+				let q = vco.div_ceil(PLL48);
+				if q < PLLQ.0 { PLLQ.0 } else if q > PLLQ.1 { PLLQ.1 } else { q }
+			},
+			q => q,
+		};
+
+		let pll48 = vco / q;
+
+		tree.pllm = m;
+		tree.plln = n;
+		tree.pllp = p / 2 - 1;
+		tree.pllq = q;
+		tree.clocks.sysf = Frequency::Hz(sysclk);
+		tree.clocks.pllout = Frequency::Hz(sysclk);
+		tree.pll48 = Frequency::Hz(pll48);
+
+		score.pll = 1;
+		score.power = if t.lp { vco } else { u32::MAX - input / m };
+	}
+
+	score.hsi = (t.hse && src == 0) as u32;
+
+	if t.usb {
+		score.usb = if sw == 2 { distance(tree.pll48.hz(), PLL48) } else { u32::MAX };
+	}
+
+	let sysclk = tree.clocks.sysf.hz();
+
+	if let Some(f) = t.sysclk {
+		score.error += distance(sysclk, f);
 	}
 
-	(array, clocks)
+	// AHB, at SYSCLK if not requested
+	let (hpre, ahb, error) = prescaler(sysclk, t.ahb, AHBMAX.hz(), &HPRE);
+	tree.hpre = hpre_bits(hpre);
+	tree.clocks.ahb1f = Frequency::Hz(ahb);
+	score.error += error;
+
+	let (ppre, apb, error) = prescaler(ahb, t.apb1, APB1MAX.hz(), &PPRE);
+	tree.ppre1 = ppre_bits(ppre);
+	tree.clocks.apb1f = Frequency::Hz(apb);
+	score.error += error;
+
+	#[cfg(feature = "apb2")]
+	{
+		let (ppre, apb, error) = prescaler(ahb, t.apb2, APB2MAX.hz(), &PPRE);
+		tree.ppre2 = ppre_bits(ppre);
+		tree.clocks.apb2f = Frequency::Hz(apb);
+		score.error += error;
+	}
+
+	(tree, score)
 }
 
-const fn defined_apb(cfg: RccClocks, lp: bool) -> ([u32; 12], Clocks) {
-	let mut clocks = Clocks::new();
-	let mut array = [0u32; 12];
+/// Finds the divider of `table` that gets `input` closest to `target`, under `max`
+/// Without a target, the smallest divider under `max`
+/// Returns the divider, the divided frequency and its distance to the target
+const fn prescaler(input: u32, target: Option<u32>, max: u32, table: &[u32]) -> (u32, u32, u32) {
+	let mut best = (table[table.len() - 1], input / table[table.len() - 1], u32::MAX);
+	let mut i = 0;
 
+	while i < table.len() {
+		let f = input / table[i];
 
-	// Get max APB speed
-	const APBC: [Frequency; 3] = [Frequency::Hz(0), Frequency::Hz(0), Frequency::Hz(0)];
-	let mut apb = APBC;
+		if f <= max {
+			let error = match target {
+				Some(t) => distance(f, t),
+				None => 0,
+			};
 
-	match cfg.apb1 {
-		Some(a) if a <= APB1MAX => apb[0] = a,
-		_ => apb[0] = APB1MAX,
+			// Dividers are increasing, ties keep the fastest
+			if error < best.2 {
+				best = (table[i], f, error);
+			}
+		}
+
+		i += 1;
 	}
 
-	#[cfg(feature = "apb2")]
-	match cfg.apb2 {
-		Some(a) if a <= APB1MAX => apb[1] = a,
-		_ => apb[1] = APB1MAX,
+	// Nothing under `max`, keep the largest divider
+	if best.2 == u32::MAX {
+		best.2 = match target {
+			Some(t) => distance(best.1, t),
+			None => 0,
+		};
 	}
 
-	#[cfg(feature = "apb3")]
-	match cfg.apb3 {
-		Some(a) if a <= APB3MAX => apb[2] = a,
-		_ => apb[2] = APB3MAX,
+	best
+}
+
+/// Request in Hz, capped at `max`
+const fn target(f: Option<Frequency>, max: Frequency) -> Option<u32> {
+	match f {
+		Some(f) if f.hz() > max.hz() => Some(max.hz()),
+		Some(f) => Some(f.hz()),
+		None => None,
 	}
+}
 
-	let max = apb.iter().max();
+const fn distance(a: u32, b: u32) -> u32 {
+	a.abs_diff(b)
+}
 
+const fn hpre_bits(div: u32) -> u32 {
+	match div {
+		1   => 0b0000,
+		2   => 0b1000,
+		4   => 0b1001,
+		8   => 0b1010,
+		16  => 0b1011,
+		64  => 0b1100,
+		128 => 0b1101,
+		256 => 0b1110,
+		_   => 0b1111,
+	}
+}
 
-	match max {
-		// Everything defaults to HSI
-		Some( Frequency::Hz(0) ) | None => {
-			array[0] = 0;
-			array[1] = 0;
-			array[2] = 0;
-			array[3] = 0;
-			array[4] = 0;
+const fn hpre_div(bits: u32) -> u32 {
+	match bits {
+		0b1000 => 2,
+		0b1001 => 4,
+		0b1010 => 8,
+		0b1011 => 16,
+		0b1100 => 64,
+		0b1101 => 128,
+		0b1110 => 256,
+		0b1111 => 512,
+		_      => 1,
+	}
+}
 
-			clocks.sysclk = HSI;
-			clocks.ahb = HSI;
-			clocks.apb1 = HSI;
-			#[cfg(feature = "apb2")]
-			{
-				clocks.apb2 = HSI;
-			}
+const fn ppre_bits(div: u32) -> u32 {
+	match div {
+		1 => 0b000,
+		2 => 0b100,
+		4 => 0b101,
+		8 => 0b110,
+		_ => 0b111,
+	}
+}
 
-			#[cfg(feature = "apb3")]
-			{
-				clocks.apb3 = HSI;
-			}
-		},
+const fn ppre_div(bits: u32) -> u32 {
+	match bits {
+		0b100 => 2,
+		0b101 => 4,
+		0b110 => 8,
+		0b111 => 16,
+		_     => 1,
+	}
+}
 
-		// If it's not default -> max SYSCLK speed & max AHB speed
-		Some( a ) => {
-			// Get VCO
-			let pllm = match (u32::from(HSI / 2u32), (HSI.hz() % 2u32)) {
-				(m, 0) => m,
-				(m, _) => m + 1,
-			};
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::peripherals::extended::rcc::validate;
+
+	/// HSE of common boards
+	const BOARDS: [Option<(Frequency, HSEMode)>; 5] = [
+		None,
+		Some((Frequency::MHz(8), HSEMode::Crystal)),
+		Some((Frequency::MHz(25), HSEMode::Crystal)),
+		Some((Frequency::MHz(12), HSEMode::Bypass)),
+		Some((Frequency::Hz(14_745_600), HSEMode::Crystal)),
+	];
+
+	/// Checks that `tree` passes `validate` and gives the clocks it reports
+	fn check(tree: &ClockTree) {
+		let clocks = validate(&tree.cfg(VoltageRange::V2_7)).unwrap();
+
+		assert_eq!(clocks.sysf, tree.clocks.sysf);
+		assert_eq!(clocks.ahb1f, tree.clocks.ahb1f);
+		assert_eq!(clocks.apb1f, tree.clocks.apb1f);
+		assert_eq!(clocks.apb2f, tree.clocks.apb2f);
+		assert_eq!(clocks.pllout, tree.clocks.pllout);
+	}
 
-			let vco = HSI / pllm;
+	#[test]
+	fn sysclk_sweep() {
+		// From VCOMIN / 8 up, some PLLP puts the VCO in range for every whole MHz
+		let exact = VCOMIN.hz().div_ceil(8);
 
-			let (pllp, plln, error) = pllerror(vco, SYSCLKMAX);
+		for hse in BOARDS {
+			for mhz in 1..=SYSCLKMAX.hz() / 1_000_000 {
+				let f = mhz * 1_000_000;
+				let tree = clock_calculation(RccClocks { hse, sysclk: Some(Frequency::Hz(f)), ..RccClocks::new() }, false);
 
-			array[0] = 2;
-			array[8] = match pllp {
-				2 => 0b00,
-				4 => 0b01,
-				6 => 0b10,
-				8 => 0b11,
-				_ => unreachable!(),
-			};
-			array[9] = plln;
-			array[10] = pllm;
-
-			clocks.sysclk = vco * plln / pllp;
-
-			let mut best = (0, 0, 1000.0);
-
-			for i in &[1u32, 2, 4, 8, 16, 64, 128, 256, 512] {
-				for j in &[1u32, 2, 4, 8, 16] {
-					let mut error = 0.0;
-
-					error += match (u32::from((clocks.sysclk / *i) / *j) as f32 / (apb[0].hz() as f32) - 1.0) {
-						a if a < 0.0 => -a,
-						b => b,
-					};
-					#[cfg(feature = "apb2")]
-					{
-						error += match (u32::from((clocks.sysclk / *i) / *j) as f32 / (apb[1].hz() as f32) - 1.0) {
-							a if a < 0.0 => -a,
-							b => b,
-						};
-					}
-					#[cfg(feature = "apb3")]
-					{
-						error += match (u32::from((clocks.sysclk / *i) / *j) as f32 / (apb[2].hz() as f32) - 1.0) {
-							a if a < 0.0 => -a,
-							b => b,
-						};
-					}
+				check(&tree);
 
-					if error < best.2 {
-						best = (*i, *j, error);
-					}
+				if f >= exact {
+					assert_eq!(tree.clocks.sysf.hz(), f, "{:?} from {:?}", f, hse);
 				}
 			}
+		}
+	}
 
-			array[1] = match best.0 {
-				1   => 0b0000,
-				2   => 0b1000,
-				4   => 0b1001,
-				8   => 0b1010,
-				16  => 0b1011,
-				64  => 0b1100,
-				128 => 0b1101,
-				256 => 0b1110,
-				_   => 0b1111,
-			};
+	#[test]
+	fn sysclk_sweep_with_usb() {
+		for hse in BOARDS {
+			for mhz in (VCOMIN.hz() / 8_000_000..=SYSCLKMAX.hz() / 1_000_000).step_by(3) {
+				let cfg = RccClocks { hse, sysclk: Some(Frequency::MHz(mhz)), usb: true, ..RccClocks::new() };
+				let tree = clock_calculation(cfg, true);
 
-			clocks.sysclk = HSI;
-			clocks.ahb = HSI / best.0;
+				check(&tree);
+				assert!(tree.pll48.hz() <= PLL48);
+			}
+		}
+	}
 
-			match u32::from(((HSI / best.0) / best.1) / apb[0] ) {
-				0..=1  => { array[2] = 0b000; clocks.apb1 = (HSI / best.0) /  1u32;},
-				2..=3  => { array[2] = 0b100; clocks.apb1 = (HSI / best.0) /  2u32;},
-				4..=7  => { array[2] = 0b101; clocks.apb1 = (HSI / best.0) /  4u32;},
-				8..=15 => { array[2] = 0b110; clocks.apb1 = (HSI / best.0) /  8u32;},
-				_      => { array[2] = 0b111; clocks.apb1 = (HSI / best.0) / 16u32;},
+	#[test]
+	fn buses_sweep() {
+		for mhz in 1..=AHBMAX.hz() / 1_000_000 {
+			let cfg = RccClocks {
+				sysclk: Some(SYSCLKMAX),
+				ahb: Some(Frequency::MHz(mhz)),
+				apb1: Some(Frequency::MHz(mhz / 2)),
+				..RccClocks::new()
 			};
 
-			#[cfg(feature = "apb2")]
-			{
-				match u32::from(((HSI / best.0) / best.1) / apb[1] ) {
-					0..=1  => { array[3] = 0b000; clocks.apb2 = (HSI / best.0) /  1u32;},
-					2..=3  => { array[3] = 0b100; clocks.apb2 = (HSI / best.0) /  2u32;},
-					4..=7  => { array[3] = 0b101; clocks.apb2 = (HSI / best.0) /  4u32;},
-					8..=15 => { array[3] = 0b110; clocks.apb2 = (HSI / best.0) /  8u32;},
-					_      => { array[3] = 0b111; clocks.apb2 = (HSI / best.0) / 16u32;},
-				};
-			}
+			check(&clock_calculation(cfg, false));
+		}
+	}
 
-			#[cfg(feature = "apb3")]
-			{
-				match u32::from(((HSI / best.0) / best.1) / apb[2] ) {
-					0..=1  => { array[4] = 0b000; clocks.apb3 = (HSI / best.0) /  1u32;},
-					2..=3  => { array[4] = 0b100; clocks.apb3 = (HSI / best.0) /  2u32;},
-					4..=7  => { array[4] = 0b101; clocks.apb3 = (HSI / best.0) /  4u32;},
-					8..=15 => { array[4] = 0b110; clocks.apb3 = (HSI / best.0) /  8u32;},
-					_      => { array[4] = 0b111; clocks.apb3 = (HSI / best.0) / 16u32;},
-				};
+	#[test]
+	fn hse_sweep() {
+		for mode in [HSEMode::Crystal, HSEMode::Bypass] {
+			for hz in (0..=52).map(|mhz| mhz * 1_000_000).chain([3_999_999, 26_000_001, 50_000_001]) {
+				let hse = Some((Frequency::Hz(hz), mode));
+				let tree = clock_calculation(RccClocks { hse, sysclk: Some(SYSCLKMAX), ..RccClocks::new() }, false);
+
+				check(&tree);
+
+				if hse_valid(Frequency::Hz(hz), mode) {
+					assert_eq!(tree.hse, hse);
+				} else {
+					assert_eq!(tree.hse, None);
+					assert_eq!(tree.pllsrc, 0, "{} Hz", hz);
+					assert_ne!(tree.sw, 1, "{} Hz", hz);
+				}
 			}
-
-		},
+		}
 	}
 
-	(array, clocks)
-}
+	#[test]
+	fn hse_is_preferred_to_the_hsi() {
+		let hse = Some((Frequency::MHz(8), HSEMode::Crystal));
 
-// Everything defaults to HSI
-const fn no_defines(cfg: RccClocks, lp: bool) -> ([u32; 12], Clocks) {
-	let mut clocks = Clocks::new();
-	let mut array = [0u32; 12];
+		let tree = clock_calculation(RccClocks { hse, ..RccClocks::new() }, false);
+		assert_eq!((tree.sw, tree.clocks.sysf), (1, Frequency::MHz(8)));
 
-	clocks.sysclk = HSI;
-	clocks.ahb = HSI;
-	clocks.apb1 = HSI;
+		let tree = clock_calculation(RccClocks { hse, sysclk: Some(Frequency::MHz(64)), ..RccClocks::new() }, false);
+		assert_eq!((tree.sw, tree.pllsrc), (2, 1));
+	}
 
-	#[cfg(feature = "apb2")]
-	{
-		clocks.apb2 = HSI;
+	#[test]
+	fn plli2s_input_is_in_range() {
+		for hse in BOARDS {
+			for sysclk in [None, Some(Frequency::MHz(8)), Some(SYSCLKMAX)] {
+				let tree = clock_calculation(RccClocks { hse, sysclk, ..RccClocks::new() }, false);
+				let (n, m, r) = tree.cfg(VoltageRange::V2_7).i2scfg;
+
+				let input = match (tree.sw, tree.pllsrc, tree.hse) {
+					(2, 1, Some((f, _))) => f,
+					_ => HSI,
+				};
+
+				assert!((PLLM.0..=PLLM.1).contains(&m), "M {} from {:?}", m, hse);
+				assert!(input / m >= PLLINMIN && input / m <= PLLINMAX, "M {} from {:?}", m, hse);
+				assert!(input.ratio(n, m) <= VCOMAX, "M {} from {:?}", m, hse);
+				assert_eq!(r, 2);
+			}
+		}
 	}
 
-	#[cfg(feature = "apb3")]
-	{
-		clocks.apb3 = HSI;
+	#[test]
+	fn solves_in_a_const_item() {
+		const TREE: ClockTree = clock_calculation(RccClocks {
+			hse: Some((Frequency::MHz(25), HSEMode::Crystal)),
+			sysclk: Some(Frequency::MHz(48)),
+			usb: true,
+			..RccClocks::new()
+		}, false);
+		const CFG: ClockCfg = TREE.cfg(VoltageRange::V2_7);
+
+		check(&TREE);
+		assert_eq!(CFG, TREE.cfg(VoltageRange::V2_7));
+		assert_eq!(TREE.clocks.sysf, Frequency::MHz(48));
+		assert_eq!(CFG.i2scfg, (192, 13, 2));
 	}
 
-	(array, clocks)
+	#[test]
+	fn register_values() {
+		// HSE 8 MHz / 8 * 192 / 4 = 48 MHz, PLL48 on PLLQ 4
+		let cfg = RccClocks {
+			hse: Some((Frequency::MHz(8), HSEMode::Crystal)),
+			sysclk: Some(Frequency::MHz(48)),
+			usb: true,
+			..RccClocks::new()
+		};
+		let tree = clock_calculation(cfg, false);
+
+		check(&tree);
+		assert_eq!(tree.pll48, Frequency::MHz(48));
+		assert_eq!(tree.clocks.sysf, Frequency::MHz(48));
+		assert_eq!(tree.cfgr() & 0b11, 0b10);
+		assert_eq!(tree.pllcfgr() & (1 << 22), 1 << 22);
+		assert_eq!(tree.pllcfgr() & 0x3F, tree.pllm);
+		assert_eq!((tree.pllcfgr() >> 6) & 0x1FF, tree.plln);
+	}
 }
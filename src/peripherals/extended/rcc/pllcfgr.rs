@@ -0,0 +1,1153 @@
+//! PLLCFGR register layout (STM32F4, STM32F7)
+//!
+//! The main PLL is configured in PLLCFGR with the M, N, P and Q factors.
+
+use crate::common::{ asm, Register, Frequency };
+use crate::common::register::W;
+use crate::peripherals::map;
+
+use crate::common::enums::{ RCCInterrupt, DeviceClock, RCCPeripheral, RCCError, ClockLimit, HSEMode };
+
+use crate::common::{ SrcClock, Clocks, ClockCfg };
+
+use crate::peripherals::extended::flashiface::{ self, FlashIface };
+use crate::peripherals::extended::pwr::{ self, Pwr };
+
+use super::{ hpre_bits, ppre_bits, within };
+
+pub const ADDRESS: u32 = map::RCC;
+pub const SIZE: usize = 38;
+
+// TODO : Set up HSI speed depending on chip
+pub const HSIF: Frequency = Frequency::MHz(16);
+pub const LSIF: Frequency = Frequency::KHz(32);
+pub const LSEF: Frequency = Frequency::Hz(32768);
+
+/// Cycles waited for the HSE to start, 100 ms at the HSI
+pub const HSE_TIMEOUT: u32 = 1_600_000;
+
+registers! {
+	/// Clock control register
+	CR @ 0, reset = 0x0000_0083 {
+		/// PLLSAI clock ready flag
+		pllsairdy: 29, 1, RO;
+		/// PLLSAI enable
+		pllsaion: 28, 1, RW;
+		/// PLLI2S clock ready flag
+		plli2srdy: 27, 1, RO;
+		/// PLLI2S enable
+		plli2son: 26, 1, RW;
+		/// Main PLL clock ready flag
+		pllrdy: 25, 1, RO;
+		/// Main PLL enable
+		pllon: 24, 1, RW;
+		/// Clock security system enable
+		csson: 19, 1, RW;
+		/// HSE clock bypass
+		hsebyp: 18, 1, RW;
+		/// HSE clock ready flag
+		hserdy: 17, 1, RO;
+		/// HSE clock enable
+		hseon: 16, 1, RW;
+		/// HSI clock calibration
+		hsical: 8, 8, RO;
+		/// HSI clock trimming
+		hsitrim: 3, 5, RW;
+		/// HSI clock ready flag
+		hsirdy: 1, 1, RO;
+		/// HSI clock enable
+		hsion: 0, 1, RW;
+	}
+
+	/// PLL configuration register
+	PLLCFGR @ 1, reset = 0x2400_3010 {
+		/// Main PLL division factor for the USB OTG FS, SDIO and RNG clocks
+		pllq: 24, 4, RW;
+		/// Main PLL and PLLI2S entry clock source, HSE if set
+		pllsrc: 22, 1, RW;
+		/// Main PLL division factor for the main system clock
+		pllp: 16, 2, RW;
+		/// Main PLL multiplication factor for the VCO
+		plln: 6, 9, RW;
+		/// Division factor for the main PLL input clock
+		pllm: 0, 6, RW;
+	}
+
+	/// Clock configuration register
+	CFGR @ 2, reset = 0x0000_0000 {
+		/// Microcontroller clock output 2
+		mco2: 30, 2, RW;
+		/// MCO2 prescaler
+		mco2pre: 27, 3, RW;
+		/// MCO1 prescaler
+		mco1pre: 24, 3, RW;
+		/// I2S clock selection
+		i2ssrc: 23, 1, RW;
+		/// Microcontroller clock output 1
+		mco1: 21, 2, RW;
+		/// HSE division factor for the RTC clock
+		rtcpre: 16, 5, RW;
+		/// APB high-speed prescaler
+		ppre2: 13, 3, RW;
+		/// APB low-speed prescaler
+		ppre1: 10, 3, RW;
+		/// AHB prescaler
+		hpre: 4, 4, RW;
+		/// System clock switch status
+		sws: 2, 2, RO;
+		/// System clock switch
+		sw: 0, 2, RW;
+	}
+
+	/// Clock interrupt register
+	CIR @ 3, reset = 0x0000_0000 {
+		/// Clock security system interrupt clear
+		cssc: 23, 1, WO;
+		/// Ready interrupt clears of PLLI2S, PLL, HSE, HSI, LSE and LSI
+		plli2srdyc: 21, 1, WO;
+		pllrdyc: 20, 1, WO;
+		hserdyc: 19, 1, WO;
+		hsirdyc: 18, 1, WO;
+		lserdyc: 17, 1, WO;
+		lsirdyc: 16, 1, WO;
+		/// Ready interrupt enables of PLLI2S, PLL, HSE, HSI, LSE and LSI
+		plli2srdyie: 13, 1, RW;
+		pllrdyie: 12, 1, RW;
+		hserdyie: 11, 1, RW;
+		hsirdyie: 10, 1, RW;
+		lserdyie: 9, 1, RW;
+		lsirdyie: 8, 1, RW;
+		/// Clock security system interrupt flag
+		cssf: 7, 1, RO;
+		/// Ready interrupt flags of PLLI2S, PLL, HSE, HSI, LSE and LSI
+		plli2srdyf: 5, 1, RO;
+		pllrdyf: 4, 1, RO;
+		hserdyf: 3, 1, RO;
+		hsirdyf: 2, 1, RO;
+		lserdyf: 1, 1, RO;
+		lsirdyf: 0, 1, RO;
+	}
+
+	/// Backup domain control register
+	BDCR @ 28, reset = 0x0000_0000 {
+		/// Backup domain software reset
+		bdrst: 16, 1, RW;
+		/// RTC clock enable
+		rtcen: 15, 1, RW;
+		/// RTC clock source selection
+		rtcsel: 8, 2, RW;
+		/// LSE oscillator mode, high drive if set
+		lsemod: 3, 1, RW;
+		/// LSE oscillator bypass
+		lsebyp: 2, 1, RW;
+		/// LSE oscillator ready
+		lserdy: 1, 1, RO;
+		/// LSE oscillator enable
+		lseon: 0, 1, RW;
+	}
+
+	/// Clock control and status register
+	CSR @ 29, reset = 0x0E00_0000 {
+		/// Reset flags: low-power, window watchdog, independent watchdog, software, POR/PDR, pin and BOR
+		lpwrrstf: 31, 1, RO;
+		wwdgrstf: 30, 1, RO;
+		iwdgrstf: 29, 1, RO;
+		sftrstf: 28, 1, RO;
+		porrstf: 27, 1, RO;
+		padrstf: 26, 1, RO;
+		borrstf: 25, 1, RO;
+		/// Remove the reset flags
+		rmvf: 24, 1, RW;
+		/// LSI oscillator ready
+		lsirdy: 1, 1, RO;
+		/// LSI oscillator enable
+		lsion: 0, 1, RW;
+	}
+
+	/// Spread spectrum clock generation register
+	SSCGR @ 32, reset = 0x0000_0000 {
+		/// Spread spectrum modulation enable
+		sscgen: 31, 1, RW;
+		/// Spread select, down spread if set
+		spreadsel: 30, 1, RW;
+		/// Incrementation step
+		incstep: 13, 15, RW;
+		/// Modulation period
+		modper: 0, 13, RW;
+	}
+
+	/// PLLI2S configuration register
+	PLLI2SCFGR @ 33, reset = 0x2000_3000 {
+		/// PLLI2S division factor for the I2S clocks
+		plli2sr: 28, 3, RW;
+		/// PLLI2S multiplication factor for the VCO
+		plli2sn: 6, 9, RW;
+		/// Division factor for the PLLI2S input clock
+		plli2sm: 0, 6, RW;
+	}
+
+	/// Dedicated clocks configuration register
+	DCKCFGR @ 35, reset = 0x0000_0000 {
+		/// Timers clocks prescalers selection
+		timpre: 24, 1, RW;
+	}
+}
+
+
+#[repr(C)]
+pub struct Rcc {
+	clocks: Clocks,
+	block: &'static mut [Register<u32>; SIZE],
+}
+
+impl crate::common::VolatileStruct for Rcc {}
+
+impl Rcc {
+	pub fn clocks(&self) -> Clocks {
+		self.clocks
+	}
+	/// Get the RCC from the given address
+	pub fn from_addr(address: u32) -> Self {
+		Rcc {
+			clocks: Clocks::default(),
+			block: unsafe{ &mut *(address as *mut _) },
+		}
+	}
+
+	/// Get the RCC over the given register block
+	/// Used to build the peripheral over a simulated block on the host
+	pub fn from_block(block: &'static mut [Register<u32>; SIZE]) -> Self {
+		Rcc {
+			clocks: Clocks::default(),
+			block,
+		}
+	}
+}
+
+impl_rwio!(Rcc);
+
+// VCO = PLLINPUT / PLLM
+// PLLOUT = VCO *PLLN / PLLP
+// USB/SDIO = VCO * PLLN / PLLQ
+
+impl Rcc {
+	/// Freezes the clocks, making it effective
+	/// Fails without touching the RCC if `cfg` breaks a limit of the device, see `validate`,
+	/// or if the HSE is needed and does not start within `HSE_TIMEOUT`
+	///
+	/// The flash latency is raised before speeding up and lowered after slowing down.
+	/// The regulator voltage scale and over-drive follow the new AHB clock, and the
+	/// flash prefetch and caches (the ART accelerator on the F7) are enabled.
+	pub fn freeze(&mut self, cfg: ClockCfg, flash: &mut FlashIface, pwr: &mut Pwr) -> Result<(), RCCError> {
+		let target = validate(&cfg)?;
+
+		self.start_hse(&cfg)?;
+
+		// Checked by `validate`
+		let hsef = match cfg.hse {
+			Some((f, _)) => f,
+			None => Frequency::Hz(0),
+		};
+
+		let latency = flashiface::wait_states(target.ahb1f, cfg.vdd);
+		let scale = pwr::scale_for(target.ahb1f);
+
+		if latency > flash.latency() {
+			flash.set_latency(latency);
+		}
+
+		// Run from the HSI while reconfiguring, the PLL cannot be changed while it
+		// runs the SYSCLK and the voltage scale only while it is off
+		self.reg::<CR>().modify(|_, w| w.hsion(1));
+		while self.read_reg::<CR>().hsirdy() == 0 {}
+		self.set_sysclk_source(0b00);
+
+		self.peripheral_state(true, RCCPeripheral::PWR);
+
+		if pwr.is_overdrive() {
+			pwr.overdrive_state(false);
+		}
+
+		self.reg::<CR>().modify(|_, w| w.pllon(0));
+		while self.read_reg::<CR>().pllrdy() == 1 {}
+
+		if let Some((vos, _)) = scale {
+			pwr.voltage_scale(vos);
+		}
+
+		// Configure if needed the PLL
+		let (haspll, pllout) = if let Some(pll) = cfg.pllcfg {
+			// pllcfg has a layout of
+			// ( (u32, u32, u32, u32?), SrcClock )
+			// Last u32 depends on hardware
+			let (n, m, p, _) = self.set_pll_cfg((pll.0).0, (pll.0).1, (pll.0).2, (pll.0).3);
+
+			// If the clock selected is not the HSE, default to HSI
+			let src = match pll.1 {
+				SrcClock::HSE => hsef,
+				_ => HSIF,
+			};
+
+			self.reg::<PLLCFGR>().modify(|_, w| w.pllsrc((pll.1 == SrcClock::HSE) as u32));
+			self.reg::<CR>().modify(|_, w| w.pllon(1));
+
+			while self.read_reg::<CR>().pllrdy() == 0 {}
+
+			#[cfg(feature = "debug")]
+			{
+				use cortex_m_semihosting::hprintln;
+				hprintln!("Has PLL. PLL Frequency = {:?}. VCO Frequency = {:?}", src.ratio(n, m * p), src / m);
+			}
+
+			(true, src.ratio(n, m * p))
+		} else {
+			(false, Frequency::Hz(0))
+		};
+
+		// The over-drive is switched on with the PLL running, before it runs the SYSCLK
+		if let Some((_, true)) = scale {
+			pwr.overdrive_state(true);
+		}
+
+		// Set the bus prescalers before speeding up, so the buses never run too fast
+		// First AHB1
+		let hpre = self.set_hpre1(cfg.hpre.0);
+
+		// Now start setting all APB buses
+		let ppre1 = self.set_ppre1(cfg.ppre.0);
+
+		#[cfg(feature = "apb2")]
+		let ppre2 = self.set_ppre2(cfg.ppre.1);
+
+		// With PLL configured, set the correct SYSCLK
+		let sysf = match cfg.sysclk.0 {
+			SrcClock::HSI => HSIF,
+			SrcClock::HSE => {
+				self.set_sysclk_source(0b01);
+				hsef
+			},
+			SrcClock::PLL if haspll => {
+				self.set_sysclk_source(0b10);
+				pllout
+			},
+			_ => return Err(RCCError::PLLNotConfigured),
+		};
+
+		if latency < flash.latency() {
+			flash.set_latency(latency);
+		}
+
+		flash.prefetch_state(true);
+
+		#[cfg(feature = "stm32f7")]
+		flash.art_state(true);
+
+		#[cfg(not(feature = "stm32f7"))]
+		flash.cache_state(true);
+
+		let ahb1f = sysf / hpre;
+		let apb1f = ahb1f / ppre1;
+
+		#[cfg(feature = "apb2")]
+		let apb2f = ahb1f / ppre2;
+
+		#[cfg(not(feature = "apb2"))]
+		let apb2f = Frequency::Hz(0);
+
+		#[cfg(feature = "debug")]
+		{
+			use cortex_m_semihosting::hprintln;
+			hprintln!("System Frequency = {:?}, source: {:b}", sysf, self.read_reg::<CFGR>().sws());
+			hprintln!("AHB1 frequency = {:?}, APB1 frequency = {:?}", ahb1f, apb1f);
+		}
+
+		// TODO : Check documentation for the rest of the APB buses
+
+		#[cfg(feature = "plli2s")]
+		let i2sf = self.start_plli2s(cfg.i2scfg, hsef);
+
+		#[cfg(not(feature = "plli2s"))]
+		let i2sf = Frequency::Hz(0);
+
+		// All clocks and buses enabled
+
+		self.clocks = Clocks {
+			sysf,
+			ahb1f,
+			apb1f,
+			apb2f,
+			apb3f: Frequency::Hz(0),
+			pllout,
+			i2sf,
+		};
+
+		Ok(())
+
+	}
+}
+
+impl Rcc {
+	/// Starts the HSE of `cfg` if the PLL or the SYSCLK use it, and waits until it is ready
+	/// If it does not start within `HSE_TIMEOUT` it is turned back off, and the clocks
+	/// keep running as they were
+	fn start_hse(&mut self, cfg: &ClockCfg) -> Result<(), RCCError> {
+		let needed = matches!((cfg.sysclk.0, cfg.pllcfg), (SrcClock::HSE, _) | (_, Some((_, SrcClock::HSE))));
+
+		// Already running, the bypass cannot change while it is on
+		if !needed || self.read_reg::<CR>().hserdy() == 1 { return Ok(()); }
+
+		let bypass = matches!(cfg.hse, Some((_, HSEMode::Bypass)));
+
+		self.reg::<CR>().modify(|_, w| w.hsebyp(bypass as u32));
+		self.reg::<CR>().modify(|_, w| w.hseon(1));
+
+		let mut waited = 0;
+
+		while self.read_reg::<CR>().hserdy() == 0 {
+			if waited >= HSE_TIMEOUT {
+				self.reg::<CR>().modify(|_, w| w.hseon(0));
+				return Err(RCCError::HSETimeout);
+			}
+
+			asm::delay(100);
+			waited += 100;
+		}
+
+		Ok(())
+	}
+
+	/// Starts the PLLI2S from the PLL source with the factors `(n, m, r)`, checked by `validate`,
+	/// and returns the I2S clock
+	#[cfg(feature = "plli2s")]
+	fn start_plli2s(&mut self, (n, m, r): (u32, u32, u32), hsef: Frequency) -> Frequency {
+		let src = match self.read_reg::<PLLCFGR>().pllsrc() {
+			1 => hsef,
+			_ => HSIF,
+		};
+
+		// Disable, set, enable
+		self.reg::<CR>().modify(|_, w| w.plli2son(0));
+		self.reg::<PLLI2SCFGR>().modify(|_, w| w.plli2sm(m).plli2sn(n).plli2sr(r));
+		self.reg::<CR>().modify(|_, w| w.plli2son(1));
+
+		while self.read_reg::<CR>().plli2srdy() == 0 {}
+
+		src.ratio(n, m * r)
+	}
+
+	/// Writes the PLL factors, P is rounded up to an even value
+	/// Returns the factors written, Q is 0 on devices without the 48 MHz clock
+	#[inline]
+	fn set_pll_cfg(&mut self, plln: u32, pllm: u32, pllp: u32, _pllq: u32) -> (u32, u32, u32, u32) {
+		let (p, bits) = match pllp {
+			0..=2 => (2, 0b00),
+			3 | 4 => (4, 0b01),
+			5 | 6 => (6, 0b10),
+			_ => (8, 0b11),
+		};
+
+		#[cfg(any(feature = "sdio", feature = "usbotg"))]
+		let pllq = {
+			self.reg::<PLLCFGR>().modify(|_, w| w.pllq(_pllq));
+			_pllq
+		};
+
+		#[cfg(not(any(feature = "sdio", feature = "usbotg")))]
+		let pllq = 0;
+
+		self.reg::<PLLCFGR>().modify(|_, w| w.pllm(pllm).plln(plln).pllp(bits));
+
+		#[cfg(feature = "debug")]
+		{
+			use cortex_m_semihosting::hprintln;
+			hprintln!("PLLCFGR = {:b}", self.read_reg::<PLLCFGR>().bits());
+		}
+
+		(plln, pllm, p, pllq)
+	}
+
+	#[inline]
+	fn set_ppre1(&mut self, ppre: u32) -> u32 {
+		let (div, bits) = ppre_bits(ppre);
+		self.reg::<CFGR>().modify(|_, w| w.ppre1(bits));
+		div
+	}
+
+	#[cfg(feature = "apb2")]
+	#[inline]
+	fn set_ppre2(&mut self, ppre: u32) -> u32 {
+		let (div, bits) = ppre_bits(ppre);
+		self.reg::<CFGR>().modify(|_, w| w.ppre2(bits));
+		div
+	}
+
+	#[inline]
+	fn set_hpre1(&mut self, hpre: u32) -> u32 {
+		let (div, bits) = hpre_bits(hpre);
+		self.reg::<CFGR>().modify(|_, w| w.hpre(bits));
+		div
+	}
+
+	#[inline]
+	fn set_sysclk_source(&mut self, value: u32) -> &mut Self {
+		self.reg::<CFGR>().modify(|_, w| w.sw(value));
+		while self.read_reg::<CFGR>().sws() != value {}
+		self
+	}
+}
+
+
+/// Checks `cfg` against the limits of the device and returns the clocks it gives
+/// Does not touch the RCC, the prescalers are rounded down as in `freeze`
+/// The first broken limit is returned with the offending value
+pub fn validate(cfg: &ClockCfg) -> Result<Clocks, RCCError> {
+	use crate::common::ClockSpeeds::*;
+
+	let mut clocks = Clocks::default();
+
+	if let Some((f, mode)) = cfg.hse {
+		match mode {
+			HSEMode::Crystal => within(ClockLimit::HSE, f, Frequency::MHz(4), Frequency::MHz(26))?,
+			HSEMode::Bypass => within(ClockLimit::HSE, f, Frequency::MHz(1), Frequency::MHz(50))?,
+		}
+	}
+
+	let hse = match cfg.hse {
+		Some((f, _)) => Ok(f),
+		None => Err(RCCError::HSENotConfigured),
+	};
+
+	if let Some(((n, m, p, _q), src)) = cfg.pllcfg {
+		if !(2..=63).contains(&m) { return Err(RCCError::OutOfRange(ClockLimit::PLLM, m)); }
+		if !(50..=432).contains(&n) { return Err(RCCError::OutOfRange(ClockLimit::PLLN, n)); }
+
+		match p {
+			2 | 4 | 6 | 8 => (),
+			_ => return Err(RCCError::OutOfRange(ClockLimit::PLLP, p)),
+		}
+
+		let input = match src {
+			SrcClock::HSE => hse?,
+			_ => HSIF,
+		};
+
+		within(ClockLimit::PLLInput, input / m, PLLINMIN, PLLINMAX)?;
+		within(ClockLimit::VCO, input.ratio(n, m), VCOMIN, VCOMAX)?;
+
+		#[cfg(any(feature = "sdio", feature = "usbotg"))]
+		{
+			if !(2..=15).contains(&_q) { return Err(RCCError::OutOfRange(ClockLimit::PLLQ, _q)); }
+
+			within(ClockLimit::PLL48, input.ratio(n, m * _q), Frequency::Hz(0), Frequency::MHz(48))?;
+		}
+
+		clocks.pllout = input.ratio(n, m * p);
+	}
+
+	// The PLLI2S shares the input of the PLL, the HSI when there is no PLL
+	#[cfg(feature = "plli2s")]
+	{
+		let (n, m, r) = cfg.i2scfg;
+
+		if !(2..=63).contains(&m) { return Err(RCCError::OutOfRange(ClockLimit::PLLI2SM, m)); }
+		if !(50..=432).contains(&n) { return Err(RCCError::OutOfRange(ClockLimit::PLLI2SN, n)); }
+		if !(2..=7).contains(&r) { return Err(RCCError::OutOfRange(ClockLimit::PLLI2SR, r)); }
+
+		let input = match cfg.pllcfg {
+			Some((_, SrcClock::HSE)) => hse?,
+			_ => HSIF,
+		};
+
+		within(ClockLimit::PLLInput, input / m, PLLINMIN, PLLINMAX)?;
+		within(ClockLimit::VCO, input.ratio(n, m), VCOMIN, VCOMAX)?;
+
+		clocks.i2sf = input.ratio(n, m * r);
+	}
+
+	clocks.sysf = match cfg.sysclk.0 {
+		SrcClock::HSI => HSIF,
+		SrcClock::HSE => hse?,
+		SrcClock::PLL => match cfg.pllcfg {
+			Some(_) => clocks.pllout,
+			None => return Err(RCCError::PLLNotConfigured),
+		},
+	};
+	within(ClockLimit::SYSCLK, clocks.sysf, Frequency::Hz(0), SYSCLKMAX)?;
+
+	clocks.ahb1f = clocks.sysf / hpre_bits(cfg.hpre.0).0;
+	within(ClockLimit::AHB, clocks.ahb1f, Frequency::Hz(0), AHBMAX)?;
+
+	clocks.apb1f = clocks.ahb1f / ppre_bits(cfg.ppre.0).0;
+	within(ClockLimit::APB1, clocks.apb1f, Frequency::Hz(0), APB1MAX)?;
+
+	#[cfg(feature = "apb2")]
+	{
+		clocks.apb2f = clocks.ahb1f / ppre_bits(cfg.ppre.1).0;
+		within(ClockLimit::APB2, clocks.apb2f, Frequency::Hz(0), APB2MAX)?;
+	}
+
+	Ok(clocks)
+}
+
+/// Clock Interrupt Register (CIR)
+impl Rcc {
+	/// Clears `flag`
+	pub fn clear_flag(&mut self, flag: RCCInterrupt) -> &mut Self {
+		self.reg::<CIR>().modify(|_, w| match flag {
+			RCCInterrupt::CSSC => w.cssc(1),
+			RCCInterrupt::PLLI2SRDY => w.plli2srdyc(1),
+			RCCInterrupt::PLLRDY => w.pllrdyc(1),
+			RCCInterrupt::HSERDY => w.hserdyc(1),
+			RCCInterrupt::HSIRDY => w.hsirdyc(1),
+			RCCInterrupt::LSERDY => w.lserdyc(1),
+			RCCInterrupt::LSIRDY => w.lsirdyc(1),
+			_ => w,
+		});
+		self
+	}
+
+	/// Enable/Disable `int`
+	pub fn int_state(&mut self, s: bool, int: RCCInterrupt) -> &mut Self {
+		let s = s as u32;
+
+		self.reg::<CIR>().modify(|_, w| match int {
+			RCCInterrupt::PLLI2SRDY => w.plli2srdyie(s),
+			RCCInterrupt::PLLRDY => w.pllrdyie(s),
+			RCCInterrupt::HSERDY => w.hserdyie(s),
+			RCCInterrupt::HSIRDY => w.hsirdyie(s),
+			RCCInterrupt::LSERDY => w.lserdyie(s),
+			RCCInterrupt::LSIRDY => w.lsirdyie(s),
+			_ => w,
+		});
+		self
+	}
+
+	/// Returns `true` if the flag is raised
+	pub fn is_raised(&mut self, flag: RCCInterrupt) -> bool {
+		let cir = self.read_reg::<CIR>();
+		let csr = self.read_reg::<CSR>();
+
+		let raised = match flag {
+			RCCInterrupt::CSSC => cir.cssf(),
+			RCCInterrupt::PLLI2SRDY => cir.plli2srdyf(),
+			RCCInterrupt::PLLRDY => cir.pllrdyf(),
+			RCCInterrupt::HSERDY => cir.hserdyf(),
+			RCCInterrupt::HSIRDY => cir.hsirdyf(),
+			RCCInterrupt::LSERDY => cir.lserdyf(),
+			RCCInterrupt::LSIRDY => cir.lsirdyf(),
+
+			RCCInterrupt::LPWRRST => csr.lpwrrstf(),
+			RCCInterrupt::WWDGRST => csr.wwdgrstf(),
+			RCCInterrupt::IWDGRST => csr.iwdgrstf(),
+			RCCInterrupt::SFTRST  => csr.sftrstf(),
+			RCCInterrupt::PORRST  => csr.porrstf(),
+			RCCInterrupt::PINRST  => csr.padrstf(),
+			RCCInterrupt::BORRST  => csr.borrstf(),
+		};
+
+		raised == 1
+	}
+}
+
+/// AHB1 Peripheral Reset Register AHB1RSTR
+/// AHB2 Peripheral Reset Register AHB2RSTR
+/// AHB3 Peripheral Reset Register AHB3RSTR
+/// APB1 Peripheral Reset Register APB1RSTR
+/// APB2 Peripheral Reset Register APB2RSTR
+/// APB3 Peripheral Reset Register APB3RSTR
+impl Rcc {
+	/// Resets the given `peripheral`
+	pub fn reset_peripheral(&mut self, peripheral: RCCPeripheral) -> &mut Self {
+		let offsets = peripheral.offsets();
+
+		self.set(offsets.0, offsets.1);
+		// 10 cycles delay
+		asm::delay(9);
+		self.clear(offsets.0, offsets.1)
+	}
+
+	/// Enables/Disables the given peripheral
+	pub fn peripheral_state(&mut self, s: bool, peripheral: RCCPeripheral) -> &mut Self {
+		let offsets = peripheral.offsets();
+
+		if s { self  .set(offsets.0 + 8, offsets.1) }
+		else { self.clear(offsets.0 + 8, offsets.1) }
+	}
+
+	/// Enables/Disables the given peripheral when in Low Power mode
+	pub fn lp_peripheral_state(&mut self, s: bool, peripheral: RCCPeripheral) -> &mut Self {
+		let offsets = peripheral.offsets();
+
+		if s { self  .set(offsets.0 + 16, offsets.1) }
+		else { self.clear(offsets.0 + 16, offsets.1) }
+	}
+}
+
+/// Backup Domain Control Register (BDCR)
+impl Rcc {
+	/// Resets the Backup Domain
+	pub fn reset_bck_domain(&mut self) -> &mut Self {
+		self.reg::<BDCR>().modify(|_, w| w.bdrst(1));
+		self
+	}
+
+	/// Enable/Disable RTC clock
+	pub fn rtc_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<BDCR>().modify(|_, w| w.rtcen(s as u32));
+		self
+	}
+
+	/// Sets the RTC clock source
+	/// Defaults to the LSI
+	pub fn rtc_src(&mut self, src: Option<DeviceClock>) -> &mut Self {
+		let sel = match src {
+			Some(DeviceClock::LSE) => 1,
+			Some(DeviceClock::HSE) => 3,
+			Some(_) => 2,
+			None => 0,
+		};
+
+		self.reg::<BDCR>().modify(|_, w| w.rtcsel(sel));
+		self
+	}
+
+	/// Enables/Disables the LSE oscillator "high drive" mode
+	pub fn lse_high_drive_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<BDCR>().modify(|_, w| w.lsemod(s as u32));
+		self
+	}
+
+	/// Enable/Disable LSE Bypass with an external clock
+	pub fn lse_bypass_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<BDCR>().modify(|_, w| w.lsebyp(s as u32));
+		self
+	}
+
+	/// Clears **ALL** the Reset Flags
+	pub fn clear_rst_flags(&mut self) -> &mut Self {
+		self.reg::<CSR>().modify(|_, w| w.rmvf(1));
+		self
+	}
+}
+
+/// Spread Spectrum Clock Generator Register (SSCGR)
+impl Rcc {
+	fn pll_on(&self) -> bool {
+		self.read_reg::<CR>().pllon() == 1
+	}
+
+	/// Enables/Disables the SSCG
+	/// Fails if it's disabled before the PLL or enabled after the PLL
+	pub fn sscg_state(&mut self, s: bool) -> Result<&mut Self, RCCError> {
+		match (s, self.pll_on()) {
+			(true, false) => self.reg::<SSCGR>().modify(|_, w| w.sscgen(1)),
+			(false, true) => self.reg::<SSCGR>().modify(|_, w| w.sscgen(0)),
+			(true, true) => return Err(RCCError::PLLEnabled),
+			(false, false) => return Err(RCCError::PLLDisabled),
+		}
+
+		Ok(self)
+	}
+
+	/// Sets a SSCG field, only while the PLL is off
+	fn sscg_modify<F>(&mut self, f: F) -> Result<&mut Self, RCCError>
+		where F: for<'w> FnOnce(&'w mut W<SSCGR>) -> &'w mut W<SSCGR>
+	{
+		if self.pll_on() { return Err(RCCError::PLLEnabled); }
+
+		self.reg::<SSCGR>().modify(|_, w| f(w));
+		Ok(self)
+	}
+
+	/// Enable SSCG Centre spread
+	pub fn sscg_center_spread(&mut self) -> Result<&mut Self, RCCError> {
+		self.sscg_modify(|w| w.spreadsel(0))
+	}
+
+	/// Enable SSCG Down spread
+	pub fn sscg_down_spread(&mut self) -> Result<&mut Self, RCCError> {
+		self.sscg_modify(|w| w.spreadsel(1))
+	}
+
+	/// Sets the Modulation Amplitude of the SSCG
+	pub fn sscg_amplitude(&mut self, amplitude: u32) -> Result<&mut Self, RCCError> {
+		self.sscg_modify(|w| w.incstep(amplitude))
+	}
+
+	/// Sets the Modulation Period of the SSCG
+	pub fn sscg_period(&mut self, period: u32) -> Result<&mut Self, RCCError> {
+		self.sscg_modify(|w| w.modper(period))
+	}
+}
+
+/// PLLI2S Configuration Register (PLLI2SCFGR)
+impl Rcc {
+	/// Sets the PLLI2S R division factor
+	pub fn plli2sr(&mut self, r: u32) -> &mut Self {
+		self.reg::<PLLI2SCFGR>().modify(|_, w| w.plli2sr(r));
+		self
+	}
+
+	/// Sets the PLLI2S N multiplication factor
+	pub fn plli2sn(&mut self, n: u32) -> &mut Self {
+		self.reg::<PLLI2SCFGR>().modify(|_, w| w.plli2sn(n));
+		self
+	}
+
+	/// Sets the PLLI2S M division factor
+	pub fn plli2sm(&mut self, m: u32) -> &mut Self {
+		self.reg::<PLLI2SCFGR>().modify(|_, w| w.plli2sm(m));
+		self
+	}
+}
+
+/// Dedicated Clocks Configuration Register (DCKCFGR)
+impl Rcc {
+	/// Sets/Clears the TIMPRE bit
+	/// Refer to Reference Manual for the behaviour
+	pub fn timpre_state(&mut self, s: bool) -> &mut Self {
+		self.reg::<DCKCFGR>().modify(|_, w| w.timpre(s as u32));
+		self
+	}
+}
+
+#[cfg(all(test, feature = "sim", feature = "stm32f4"))]
+pub(crate) mod tests {
+	use super::*;
+	use crate::common::register::{ sim::{ self, SimBlock }, RegisterSpec, R };
+	use crate::common::enums::VoltageRange;
+	use crate::common::ClockSpeeds::*;
+
+	/// RCC with the HSI running, where the ready flags and SWS follow the driver
+	pub(crate) fn rcc() -> (SimBlock<SIZE>, Rcc) {
+		sim::reset();
+
+		let mut reset = [0; SIZE];
+		reset[CR::INDEX] = CR::RESET;
+		reset[PLLCFGR::INDEX] = PLLCFGR::RESET;
+		reset[PLLI2SCFGR::INDEX] = PLLI2SCFGR::RESET;
+
+		let block = SimBlock::<SIZE>::with_reset(reset);
+		block.follow(CR::INDEX, 1 << 0, CR::INDEX, 1 << 1)
+			.follow(CR::INDEX, 1 << 16, CR::INDEX, 1 << 17)
+			.follow(CR::INDEX, 1 << 24, CR::INDEX, 1 << 25)
+			.follow(CR::INDEX, 1 << 26, CR::INDEX, 1 << 27)
+			.on_write(CFGR::INDEX, |_, new| (new & !0b1100) | ((new & 0b11) << 2));
+
+		let rcc = Rcc::from_block( unsafe { block.regs() } );
+		(block, rcc)
+	}
+
+	/// RCC frozen to `cfg`, for the tests of the drivers that use the clocks
+	pub(crate) fn frozen(cfg: ClockCfg) -> (SimBlock<SIZE>, Rcc) {
+		let (block, mut rcc) = rcc();
+		let flash = SimBlock::<{ flashiface::SIZE }>::new();
+		let pwr = SimBlock::<{ pwr::SIZE }>::new();
+
+		unsafe { rcc.freeze(cfg, flash.view(), pwr.view()).unwrap(); }
+
+		sim::clear_log();
+		(block, rcc)
+	}
+
+	pub(crate) fn cfg() -> ClockCfg {
+		// HSI / 16 * 336 / 4 = 84 MHz, 48 MHz on PLLQ
+		ClockCfg {
+			hse: None,
+			pllcfg: Some( ((336, 16, 4, 7), SrcClock::HSI) ),
+			sysclk: (SrcClock::PLL, 0),
+			hpre: (1, 1, 1),
+			ppre: (2, 1, 1),
+			i2scfg: (192, 16, 2),
+			vdd: VoltageRange::V2_7,
+		}
+	}
+
+	#[test]
+	fn freeze_runs_the_pll() {
+		let (block, mut rcc) = rcc();
+		let flash_block = SimBlock::<{ flashiface::SIZE }>::new();
+		let pwr_block = SimBlock::<{ pwr::SIZE }>::new();
+		let (flash, pwr) = unsafe { (flash_block.view::<FlashIface>(), pwr_block.view::<Pwr>()) };
+
+		assert_eq!(rcc.freeze(cfg(), flash, pwr), Ok(()));
+
+		let pllcfgr = block.peek(PLLCFGR::INDEX);
+		assert_eq!(pllcfgr & 0x3F, 16);
+		assert_eq!((pllcfgr >> 6) & 0x1FF, 336);
+		assert_eq!((pllcfgr >> 16) & 0b11, 0b01);
+		assert_eq!(pllcfgr & (1 << 22), 0);
+		#[cfg(any(feature = "sdio", feature = "usbotg"))]
+		assert_eq!((pllcfgr >> 24) & 0b1111, 7);
+
+		let cfgr = block.peek(CFGR::INDEX);
+		assert_eq!(cfgr & 0b1111, 0b1010, "PLL selected and running the SYSCLK");
+		assert_eq!((cfgr >> 4) & 0b1111, 0b0000);
+		assert_eq!((cfgr >> 10) & 0b111, 0b100);
+		assert_eq!((cfgr >> 13) & 0b111, 0b000);
+
+		// PWR clock enabled before touching the regulator
+		let (rst, bit) = RCCPeripheral::PWR.offsets();
+		assert_ne!(block.peek(rst + 8) & (1 << bit), 0);
+
+		let clocks = rcc.clocks();
+		assert_eq!(clocks.sysf, Frequency::MHz(84));
+		assert_eq!(clocks.ahb1f, Frequency::MHz(84));
+		assert_eq!(clocks.apb1f, Frequency::MHz(42));
+		assert_eq!(clocks.apb2f, Frequency::MHz(84));
+		assert_eq!(Ok(clocks.sysf), validate(&cfg()).map(|c| c.sysf));
+
+		assert_eq!(flash_block.peek(0) & 0b1111, flashiface::wait_states(Frequency::MHz(84), VoltageRange::V2_7));
+		assert_ne!(flash_block.peek(0) & (1 << 8), 0, "prefetch enabled");
+
+		if let Some((vos, _)) = pwr::scale_for(Frequency::MHz(84)) {
+			assert_eq!(R::<pwr::CR>::new(pwr_block.peek(pwr::CR::INDEX)).vos(), vos);
+		}
+	}
+
+	#[test]
+	fn freeze_switches_to_the_pll_last() {
+		let (block, mut rcc) = rcc();
+		let flash_block = SimBlock::<{ flashiface::SIZE }>::new();
+		let pwr_block = SimBlock::<{ pwr::SIZE }>::new();
+		let (flash, pwr) = unsafe { (flash_block.view::<FlashIface>(), pwr_block.view::<Pwr>()) };
+
+		rcc.freeze(cfg(), flash, pwr).unwrap();
+
+		// The prescalers are set before the SYSCLK switches to the PLL
+		let cfgr = block.writes(CFGR::INDEX);
+		let switch = cfgr.iter().position(|w| w & 0b11 == 0b10).unwrap();
+		assert_eq!(cfgr[switch] & !0b1111, cfgr[switch - 1] & !0b1111);
+		assert!(cfgr[..switch].iter().any(|w| (w >> 10) & 0b111 == 0b100));
+	}
+
+	#[test]
+	fn freeze_rejects_without_touching_the_rcc() {
+		let (block, mut rcc) = rcc();
+		let flash_block = SimBlock::<{ flashiface::SIZE }>::new();
+		let pwr_block = SimBlock::<{ pwr::SIZE }>::new();
+		let (flash, pwr) = unsafe { (flash_block.view::<FlashIface>(), pwr_block.view::<Pwr>()) };
+
+		let cfg = ClockCfg { pllcfg: Some( ((336, 1, 4, 7), SrcClock::HSI) ), ..cfg() };
+
+		assert_eq!(rcc.freeze(cfg, flash, pwr), Err(RCCError::OutOfRange(ClockLimit::PLLM, 1)));
+		assert!(block.accesses().is_empty());
+		assert!(flash_block.accesses().is_empty());
+	}
+
+	#[test]
+	fn freeze_times_out_on_a_dead_hse() {
+		let (block, mut rcc) = rcc();
+		let flash_block = SimBlock::<{ flashiface::SIZE }>::new();
+		let pwr_block = SimBlock::<{ pwr::SIZE }>::new();
+		let (flash, pwr) = unsafe { (flash_block.view::<FlashIface>(), pwr_block.view::<Pwr>()) };
+
+		// HSERDY never rises
+		block.on_read(CR::INDEX, |v| v & !(1 << 17));
+
+		let cfg = ClockCfg {
+			hse: Some( (Frequency::MHz(8), HSEMode::Crystal) ),
+			pllcfg: Some( ((336, 8, 4, 7), SrcClock::HSE) ),
+			i2scfg: (192, 8, 2),
+			..cfg()
+		};
+
+		assert_eq!(rcc.freeze(cfg, flash, pwr), Err(RCCError::HSETimeout));
+		assert_eq!(block.peek(CR::INDEX) & (1 << 16), 0, "HSEON turned back off");
+		assert_eq!(block.peek(CFGR::INDEX) & 0b11, 0b00, "still running from the HSI");
+	}
+
+	#[cfg(feature = "plli2s")]
+	#[test]
+	fn freeze_runs_the_plli2s_from_the_pll_source() {
+		let (block, rcc) = frozen(cfg());
+
+		// HSI / 16 * 192 / 2
+		let plli2scfgr = block.peek(PLLI2SCFGR::INDEX);
+		assert_eq!(plli2scfgr & 0x3F, 16);
+		assert_eq!((plli2scfgr >> 6) & 0x1FF, 192);
+		assert_eq!((plli2scfgr >> 28) & 0b111, 2);
+		assert_ne!(block.peek(CR::INDEX) & (1 << 26), 0, "PLLI2S on");
+		assert_eq!(rcc.clocks().i2sf, Frequency::MHz(96));
+	}
+
+	/// PLL from the HSI, with the buses slow enough for any PLL output
+	fn pll(n: u32, m: u32, p: u32, q: u32) -> ClockCfg {
+		ClockCfg { pllcfg: Some( ((n, m, p, q), SrcClock::HSI) ), ppre: (8, 8, 1), ..cfg() }
+	}
+
+	/// PLL from the 1 MHz HSI / 16 giving `f`, with the lowest PLLP keeping the VCO in range
+	fn pll_for(f: Frequency) -> ClockCfg {
+		let mhz = f.hz() / 1_000_000;
+		let p = [2, 4, 6, 8].iter().cloned()
+			.find(|p| mhz * p >= VCOMIN.hz() / 1_000_000)
+			.unwrap();
+
+		ClockCfg { ppre: (1, 1, 1), ..pll(mhz * p, 16, p, (mhz * p).div_ceil(48)) }
+	}
+
+	/// Limit broken by `cfg`, if any
+	fn broken(cfg: ClockCfg) -> Option<ClockLimit> {
+		match validate(&cfg) {
+			Err(RCCError::OutOfRange(limit, _)) => Some(limit),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn validate_hse_range() {
+		let hse = |f, mode| ClockCfg { hse: Some((f, mode)), sysclk: (SrcClock::HSE, 0), pllcfg: None, ..cfg() };
+
+		assert!(validate(&hse(Frequency::MHz(4), HSEMode::Crystal)).is_ok());
+		assert!(validate(&hse(Frequency::MHz(26), HSEMode::Crystal)).is_ok());
+		assert_eq!(validate(&hse(Frequency::Hz(3_999_999), HSEMode::Crystal)), Err(RCCError::OutOfRange(ClockLimit::HSE, 3_999_999)));
+		assert_eq!(validate(&hse(Frequency::Hz(26_000_001), HSEMode::Crystal)), Err(RCCError::OutOfRange(ClockLimit::HSE, 26_000_001)));
+
+		assert_eq!(broken(hse(Frequency::MHz(1), HSEMode::Bypass)), None);
+		assert_ne!(broken(hse(Frequency::MHz(50), HSEMode::Bypass)), Some(ClockLimit::HSE));
+		assert_eq!(validate(&hse(Frequency::Hz(999_999), HSEMode::Bypass)), Err(RCCError::OutOfRange(ClockLimit::HSE, 999_999)));
+		assert_eq!(validate(&hse(Frequency::Hz(50_000_001), HSEMode::Bypass)), Err(RCCError::OutOfRange(ClockLimit::HSE, 50_000_001)));
+	}
+
+	#[test]
+	fn validate_sysclk_max() {
+		let clocks = validate(&ClockCfg { ppre: (8, 8, 1), ..pll_for(SYSCLKMAX) }).unwrap();
+		assert_eq!(clocks.sysf, SYSCLKMAX);
+
+		let over = SYSCLKMAX.hz() + 1_000_000;
+		assert_eq!(
+			validate(&ClockCfg { ppre: (8, 8, 1), ..pll_for(Frequency::Hz(over)) }),
+			Err(RCCError::OutOfRange(ClockLimit::SYSCLK, over))
+		);
+	}
+
+	#[test]
+	fn validate_ahb_max() {
+		let cfg = ClockCfg { ppre: (8, 8, 1), ..pll_for(SYSCLKMAX) };
+		assert_eq!(validate(&cfg).map(|c| c.ahb1f), Ok(SYSCLKMAX));
+
+		// The AHB only runs slower than the SYSCLK where their limits differ
+		if AHBMAX < SYSCLKMAX {
+			let ratio = SYSCLKMAX.hz() / AHBMAX.hz();
+			assert!(validate(&ClockCfg { hpre: (ratio, 1, 1), ..cfg }).is_ok());
+			assert_eq!(
+				validate(&ClockCfg { hpre: (ratio / 2, 1, 1), ..cfg }),
+				Err(RCCError::OutOfRange(ClockLimit::AHB, SYSCLKMAX.hz() / (ratio / 2)))
+			);
+		}
+	}
+
+	#[test]
+	fn validate_apb1_max() {
+		let ratio = SYSCLKMAX.hz() / APB1MAX.hz();
+		let cfg = |ppre1| ClockCfg { ppre: (ppre1, 8, 1), ..pll_for(SYSCLKMAX) };
+
+		assert_eq!(validate(&cfg(ratio)).map(|c| c.apb1f), Ok(APB1MAX));
+		assert_eq!(
+			validate(&cfg(ratio / 2)),
+			Err(RCCError::OutOfRange(ClockLimit::APB1, SYSCLKMAX.hz() / (ratio / 2)))
+		);
+	}
+
+	#[cfg(feature = "apb2")]
+	#[test]
+	fn validate_apb2_max() {
+		let ratio = SYSCLKMAX.hz() / APB2MAX.hz();
+		let cfg = |ppre2| ClockCfg { ppre: (8, ppre2, 1), ..pll_for(SYSCLKMAX) };
+
+		assert_eq!(validate(&cfg(ratio)).map(|c| c.apb2f), Ok(APB2MAX));
+
+		if ratio > 1 {
+			assert_eq!(
+				validate(&cfg(ratio / 2)),
+				Err(RCCError::OutOfRange(ClockLimit::APB2, SYSCLKMAX.hz() / (ratio / 2)))
+			);
+		}
+	}
+
+	#[test]
+	fn validate_pll_input_range() {
+		// HSI / 8 = 2 MHz, HSI / 16 = 1 MHz
+		assert_ne!(broken(pll(216, 8, 8, 9)), Some(ClockLimit::PLLInput));
+		assert_ne!(broken(pll(432, 16, 8, 9)), Some(ClockLimit::PLLInput));
+		assert_eq!(validate(&pll(216, 7, 8, 9)), Err(RCCError::OutOfRange(ClockLimit::PLLInput, 16_000_000 / 7)));
+		assert_eq!(validate(&pll(432, 17, 8, 9)), Err(RCCError::OutOfRange(ClockLimit::PLLInput, 16_000_000 / 17)));
+	}
+
+	#[test]
+	fn validate_vco_range() {
+		let min = VCOMIN.hz() / 1_000_000;
+
+		assert_eq!(validate(&pll(216, 8, 8, 9)).map(|c| c.pllout), Ok(Frequency::MHz(54)));
+		assert_eq!(validate(&pll(217, 8, 8, 9)), Err(RCCError::OutOfRange(ClockLimit::VCO, 434_000_000)));
+
+		assert!(validate(&pll(min, 16, 8, 4)).is_ok());
+		assert_eq!(validate(&pll(min - 1, 16, 8, 4)), Err(RCCError::OutOfRange(ClockLimit::VCO, (min - 1) * 1_000_000)));
+	}
+
+	#[cfg(any(feature = "sdio", feature = "usbotg"))]
+	#[test]
+	fn validate_pll48_max() {
+		assert!(validate(&pll(336, 16, 4, 7)).is_ok());
+		assert_eq!(validate(&pll(336, 16, 4, 6)), Err(RCCError::OutOfRange(ClockLimit::PLL48, 56_000_000)));
+	}
+
+	#[test]
+	fn validate_pll_factors() {
+		assert_eq!(validate(&pll(336, 1, 4, 7)), Err(RCCError::OutOfRange(ClockLimit::PLLM, 1)));
+		assert_eq!(validate(&pll(336, 64, 4, 7)), Err(RCCError::OutOfRange(ClockLimit::PLLM, 64)));
+		assert_ne!(broken(pll(336, 2, 4, 7)), Some(ClockLimit::PLLM));
+		assert_ne!(broken(pll(336, 63, 4, 7)), Some(ClockLimit::PLLM));
+
+		assert_eq!(validate(&pll(49, 16, 4, 7)), Err(RCCError::OutOfRange(ClockLimit::PLLN, 49)));
+		assert_eq!(validate(&pll(433, 16, 8, 9)), Err(RCCError::OutOfRange(ClockLimit::PLLN, 433)));
+		assert_ne!(broken(pll(50, 16, 2, 2)), Some(ClockLimit::PLLN));
+		assert!(validate(&pll(432, 16, 8, 9)).is_ok());
+
+		assert_eq!(validate(&pll(336, 16, 3, 7)), Err(RCCError::OutOfRange(ClockLimit::PLLP, 3)));
+		assert_eq!(validate(&pll(336, 16, 10, 7)), Err(RCCError::OutOfRange(ClockLimit::PLLP, 10)));
+		assert!(validate(&pll(336, 16, 8, 7)).is_ok());
+	}
+
+	#[cfg(feature = "plli2s")]
+	#[test]
+	fn validate_plli2s_range() {
+		let i2s = |n, m, r| ClockCfg { i2scfg: (n, m, r), ..cfg() };
+
+		assert_eq!(validate(&i2s(192, 16, 2)).map(|c| c.i2sf), Ok(Frequency::MHz(96)));
+		assert_eq!(validate(&i2s(192, 1, 2)), Err(RCCError::OutOfRange(ClockLimit::PLLI2SM, 1)));
+		assert_eq!(validate(&i2s(192, 64, 2)), Err(RCCError::OutOfRange(ClockLimit::PLLI2SM, 64)));
+		assert_eq!(validate(&i2s(49, 16, 2)), Err(RCCError::OutOfRange(ClockLimit::PLLI2SN, 49)));
+		assert_eq!(validate(&i2s(433, 16, 2)), Err(RCCError::OutOfRange(ClockLimit::PLLI2SN, 433)));
+		assert_eq!(validate(&i2s(192, 16, 1)), Err(RCCError::OutOfRange(ClockLimit::PLLI2SR, 1)));
+		assert_eq!(validate(&i2s(192, 16, 8)), Err(RCCError::OutOfRange(ClockLimit::PLLI2SR, 8)));
+
+		// HSI / 2 = 8 MHz in, HSI / 8 * 432 = 864 MHz out
+		assert_eq!(validate(&i2s(192, 2, 2)), Err(RCCError::OutOfRange(ClockLimit::PLLInput, 8_000_000)));
+		assert_eq!(validate(&i2s(432, 8, 2)), Err(RCCError::OutOfRange(ClockLimit::VCO, 864_000_000)));
+	}
+
+	#[cfg(any(feature = "sdio", feature = "usbotg"))]
+	#[test]
+	fn validate_pllq_range() {
+		assert_eq!(validate(&pll(336, 16, 4, 1)), Err(RCCError::OutOfRange(ClockLimit::PLLQ, 1)));
+		assert_eq!(validate(&pll(336, 16, 4, 16)), Err(RCCError::OutOfRange(ClockLimit::PLLQ, 16)));
+		assert!(validate(&pll(336, 16, 4, 15)).is_ok());
+	}
+
+	#[test]
+	fn sscg_is_set_only_with_the_pll_off() {
+		let (block, mut rcc) = rcc();
+
+		assert!(rcc.sscg_down_spread().is_ok());
+		assert!(rcc.sscg_period(0x1ABC).is_ok());
+		assert_eq!(block.peek(SSCGR::INDEX), (1 << 30) | 0x1ABC);
+
+		block.poke(CR::INDEX, block.peek(CR::INDEX) | (1 << 24));
+		assert_eq!(rcc.sscg_amplitude(1).err(), Some(RCCError::PLLEnabled));
+		assert_eq!(block.peek(SSCGR::INDEX), (1 << 30) | 0x1ABC);
+
+		assert_eq!(rcc.sscg_state(true).err(), Some(RCCError::PLLEnabled));
+		assert_eq!(block.peek(SSCGR::INDEX), (1 << 30) | 0x1ABC);
+	}
+}
@@ -1,10 +1,74 @@
 //! Real Time Clock Peripheral
 
 use crate::common::{ Timestamp, Weekday, Register, Clocks };
+use crate::peripherals::map;
 
-pub const ADDRESS: u32 = 0x4000_2800;
+pub const ADDRESS: u32 = map::RTC;
 pub const SIZE: usize = 20;
 
+registers! {
+	/// Time register, in BCD
+	TR @ 0, reset = 0x0000_0000 {
+		/// PM in the 12 hour format
+		pm: 22, 1, RW;
+		/// Hour tens and units
+		ht: 20, 2, RW;
+		hu: 16, 4, RW;
+		/// Minute tens and units
+		mnt: 12, 3, RW;
+		mnu: 8, 4, RW;
+		/// Second tens and units
+		st: 4, 3, RW;
+		su: 0, 4, RW;
+	}
+
+	/// Date register, in BCD
+	DR @ 1, reset = 0x0000_2101 {
+		/// Year tens and units
+		yt: 20, 4, RW;
+		yu: 16, 4, RW;
+		/// Week day, 1 is Monday
+		wdu: 13, 3, RW;
+		/// Month tens and units
+		mt: 12, 1, RW;
+		mu: 8, 4, RW;
+		/// Day tens and units
+		dt: 4, 2, RW;
+		du: 0, 4, RW;
+	}
+
+	/// Initialization and status register
+	ISR @ 3, reset = 0x0000_0007 {
+		/// Initialization mode
+		init: 7, 1, RW;
+		/// Initialization flag, the calendar can be written
+		initf: 6, 1, RO;
+		/// Shift operation pending
+		shpf: 3, 1, RO;
+	}
+
+	/// Prescaler register
+	PRER @ 4, reset = 0x007F_00FF {
+		/// Asynchronous prescaler factor
+		prediv_a: 16, 7, RW;
+		/// Synchronous prescaler factor
+		prediv_s: 0, 15, RW;
+	}
+
+	/// Write protection register
+	WPR @ 9, reset = 0x0000_0000 {
+		key: 0, 8, WO;
+	}
+
+	/// Shift control register
+	SHIFTR @ 11, reset = 0x0000_0000 {
+		/// Add one second
+		add1s: 31, 1, WO;
+		/// Subtract a fraction of a second
+		subfs: 0, 15, WO;
+	}
+}
+
 #[repr(C)]
 pub struct Rtc {
 	block: [Register<u32>; SIZE],
@@ -18,45 +82,44 @@ impl_rwio!(Rtc);
 impl Rtc {
 	/// Unlock the RTC
 	pub fn unlock(&mut self) -> &mut Self {
-		self.write_bits(9, 0, 0xCA, 8)
-			.write_bits(9, 0, 0x53, 8)
+		self.reg::<WPR>().write(|w| w.key(0xCA));
+		self.reg::<WPR>().write(|w| w.key(0x53));
+		self
 	}
 
 	/// Initialize the RTC with the given Timestamp and Clocks
-	pub fn init(&mut self, stamp: Timestamp, clocks: Clocks) -> &mut Self {
-		// Set INIT in ISR
-		self.set(3, 7);
+	pub fn init(&mut self, stamp: Timestamp, _clocks: Clocks) -> &mut Self {
+		self.reg::<ISR>().modify(|_, w| w.init(1));
+		while self.read_reg::<ISR>().initf() == 0 {}
 
-		// Poll INITF until set
-		while !self.is_set(3, 6) {}
-
-		// ?? Write PPER sync and async (both )
 		// Check which frequency we can give and try to get as close to 1 second as possible
 		// Default for now to the LSE and get 1Hz clock
-		const PRESCALERS: u32 = (mask!(7) << 16) | mask!(15);
-		self.block[4].write(PRESCALERS);
+		self.reg::<PRER>().write(|w| w.prediv_a(127).prediv_s(255));
+
+		// Load the initial values to TR and DR, the shadow registers, in the 24 hour format
+		let (hour, minute, second) = (stamp.hour as u32, stamp.minute as u32, stamp.second as u32);
+		let (year, month, day) = (stamp.year as u32, stamp.month as u32, stamp.day as u32);
 
-		// Load the initial values to TR and DR, the shadow registers
-		// Set 24 or 12 hour mode in CR, `time()` method applies the same
-		// format as the given Timestamp
-		self.block[0].write(u32::from( stamp.time() ));
-		self.block[1].write(u32::from( stamp.date() ));
-		// Clear the INIT
+		self.reg::<TR>().write(|w| w.ht(hour / 10).hu(hour % 10)
+			.mnt(minute / 10).mnu(minute % 10)
+			.st(second / 10).su(second % 10));
 
-		self.clear(3, 7)
+		self.reg::<DR>().write(|w| w.yt(year / 10).yu(year % 10)
+			.wdu(u32::from(stamp.weekday))
+			.mt(month / 10).mu(month % 10)
+			.dt(day / 10).du(day % 10));
+
+		self.reg::<ISR>().modify(|_, w| w.init(0));
+		self
 	}
 
 	/// Gets the current time, date
 	/// TODO : Check if it is necessary to do second reads
 	pub fn timestamp(&self) -> Timestamp {
-		let date = self.block[0].read();
-		let time = self.block[1].read();
+		let time = self.read_reg::<TR>();
+		let date = self.read_reg::<DR>();
 
-		let year: u16  = read_partial!(date, 4, 20, u16) * 10 + read_partial!(date, 4, 16, u16);
-		let month: u16 = read_partial!(date, 1, 12, u16) * 10 + read_partial!(date, 4,  8, u16);
-		let day: u16   = read_partial!(date, 2,  4, u16) * 10 + read_partial!(date, 4,  0, u16);
-
-		let weekday = match read_partial!(date, 3, 13, u32) {
+		let weekday = match date.wdu() {
 			1 => Weekday::Monday,
 			2 => Weekday::Tuesday,
 			3 => Weekday::Wednesday,
@@ -69,22 +132,16 @@ impl Rtc {
 			_ => return Timestamp::empty(),
 		};
 
-		let mut hour: u8  = read_partial!(time, 2, 20,  u8) * 10 + read_partial!(time, 4, 16,  u8);
-		let minute: u8    = read_partial!(time, 3, 12,  u8) * 10 + read_partial!(time, 4,  8,  u8);
-		let second: u16   = read_partial!(time, 3,  4, u16) * 10 + read_partial!(time, 4,  0, u16);
-
-		if time & (1 << 22) != 0 {
-			hour += 12;
-		}
+		let hour = time.ht() * 10 + time.hu() + if time.pm() == 1 { 12 } else { 0 };
 
 		Timestamp {
-			year,
-			month,
-			day,
+			year: (date.yt() * 10 + date.yu()) as u16,
+			month: (date.mt() * 10 + date.mu()) as u16,
+			day: (date.dt() * 10 + date.du()) as u16,
 			weekday,
-			hour,
-			minute,
-			second,
+			hour: hour as u8,
+			minute: (time.mnt() * 10 + time.mnu()) as u8,
+			second: (time.st() * 10 + time.su()) as u16,
 			subsecond: 0,
 		}
 	}
@@ -92,34 +149,123 @@ impl Rtc {
 	/// Advances the RTC for the given amount of miliseconds
 	/// Margin of error varies around 4-10 ms delays, though this is not garanteed
 	pub fn advance(&mut self, m: u32) -> &mut Self {
-		const DELAY1SEC: u32 = 1 << 31;
-		if m > 1000 {
-			for _ in 0..m/1000 {
-				// Delay for 1 second
-				self.block[11].write(DELAY1SEC);
-
-				while self.is_set(3, 3) {}
-			}
+		for _ in 0..m / 1000 {
+			// Delay for 1 second
+			self.reg::<SHIFTR>().write(|w| w.add1s(1));
+			while self.read_reg::<ISR>().shpf() == 1 {}
 		}
 
-		let prediv = read_partial!(self.block[4].read(), 15, 0, u32);
-
-		let rest = m - ((m/1000)*1000);
+		let rest = m % 1000;
 
-		let subfs = (1-rest)*(prediv + 1);
+		if rest != 0 {
+			// One second forward and the rest of the second back
+			let prediv = self.read_reg::<PRER>().prediv_s();
+			let subfs = (1000 - rest) * (prediv + 1) / 1000;
 
-		// Build the register
-		let reg = (1 << 31) | (subfs & mask!(15));
-
-		self.block[11].write(reg);
+			self.reg::<SHIFTR>().write(|w| w.add1s(1).subfs(subfs));
+		}
 
 		self
 	}
 
 	/// Delays the RTC for the given amount of miliseconds
 	/// TODO : Implementation
-	pub fn delay(&mut self, m: u32) -> &mut Self {
+	pub fn delay(&mut self, _m: u32) -> &mut Self {
 
 		self
 	}
-}
\ No newline at end of file
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+
+	/// RTC where INITF follows INIT
+	fn rtc() -> (SimBlock<SIZE>, &'static mut Rtc) {
+		let block = SimBlock::<SIZE>::new();
+		block.follow(ISR::INDEX, 1 << 7, ISR::INDEX, 1 << 6);
+
+		let rtc = unsafe { block.view::<Rtc>() };
+		(block, rtc)
+	}
+
+	fn stamp() -> Timestamp {
+		Timestamp {
+			year: 19,
+			month: 12,
+			day: 31,
+			weekday: Weekday::Tuesday,
+			hour: 23,
+			minute: 59,
+			second: 58,
+			subsecond: 0,
+		}
+	}
+
+	#[test]
+	fn init_writes_bcd() {
+		let (block, rtc) = rtc();
+
+		rtc.init(stamp(), Clocks::default());
+
+		assert_eq!(block.peek(TR::INDEX), 0x0023_5958);
+		// Weekday 2 in bits 13..15 shares a nibble with the month tens
+		assert_eq!(block.peek(DR::INDEX), 0x0019_5231);
+		assert_eq!(block.peek(ISR::INDEX) & (1 << 7), 0, "INIT cleared");
+	}
+
+	#[test]
+	fn init_encodes_single_digits() {
+		let (block, rtc) = rtc();
+
+		let stamp = Timestamp { year: 5, month: 1, day: 2, weekday: Weekday::Sunday, hour: 0, minute: 7, second: 9, ..stamp() };
+		rtc.init(stamp, Clocks::default());
+
+		assert_eq!(block.peek(TR::INDEX), 0x0000_0709);
+		assert_eq!(block.peek(DR::INDEX), 0x0005_E102);
+	}
+
+	#[test]
+	fn timestamp_reads_back_init() {
+		let (_, rtc) = rtc();
+
+		rtc.init(stamp(), Clocks::default());
+
+		assert_eq!(rtc.timestamp(), stamp());
+	}
+
+	#[test]
+	fn timestamp_rejects_a_corrupted_weekday() {
+		let (block, rtc) = rtc();
+
+		block.poke(DR::INDEX, 0x0019_1231);
+
+		assert_eq!(rtc.timestamp(), Timestamp::empty());
+	}
+
+	#[test]
+	fn init_sets_a_1_hz_calendar_from_the_lse() {
+		let (block, rtc) = rtc();
+
+		rtc.init(stamp(), Clocks::default());
+
+		// 32768 Hz / 128 / 256
+		assert_eq!(block.peek(PRER::INDEX), 0x007F_00FF);
+	}
+
+	#[test]
+	fn advance_shifts_whole_seconds_then_the_rest() {
+		let (block, rtc) = rtc();
+		block.poke(PRER::INDEX, PRER::RESET);
+
+		rtc.advance(2250);
+
+		// 2 s, then 1 s forward and 750 ms of the 256 sub-second steps back
+		assert_eq!(block.writes(SHIFTR::INDEX), [1 << 31, 1 << 31, (1 << 31) | 192]);
+
+		rtc.advance(1000);
+		assert_eq!(block.writes(SHIFTR::INDEX).len(), 4);
+	}
+}
@@ -1,19 +1,111 @@
 //! SPI interface peripheral
 //! Implements `embedded-hal` traits
 
-use crate::common::{ Register, config::SPIConfig, SPIInterrupt, PeripheralBus, SPIFlag, SPIError, DFFormat, FrameFormat };
-use crate::common::structs::Pin;
-use crate::common::Frequency;
-use crate::common::{ RCCPeripheral, DeviceClock };
-
-pub const SPI1: u32 = 0x4001_3000;
-pub const SPI2: u32 = 0x4000_3800;
-pub const SPI3: u32 = 0x4000_3C00;
-pub const SPI4: u32 = 0x4001_3400;
-pub const SPI5: u32 = 0x4001_5000;
+use crate::common::{ Register, SPIInterrupt, SPIFlag, SPIError, DFFormat, FrameFormat };
+use crate::common::structs::{ Alternate, AltFn, Pin, Unknown };
+use crate::peripherals::af::Signal;
+use crate::common::{ Clocks, Frequency };
+use crate::common::{ RCCPeripheral, OutputType };
+
+#[cfg(feature = "stm32f1")]
+use crate::peripherals::extended::syscfg;
+
+pub use crate::peripherals::map::SPI1;
+#[cfg(has_spi2)]
+pub use crate::peripherals::map::SPI2;
+#[cfg(has_spi3)]
+pub use crate::peripherals::map::SPI3;
+#[cfg(has_spi4)]
+pub use crate::peripherals::map::SPI4;
+#[cfg(has_spi5)]
+pub use crate::peripherals::map::SPI5;
 
 pub const SIZE: usize = 9;
 
+/// (SCK, MISO, MOSI) pins of `Spi::init_master`
+pub type MasterPins<SCK, MISO, MOSI> = (Pin<Alternate<SCK>>, Pin<Alternate<MISO>>, Pin<Alternate<MOSI>>);
+
+registers! {
+	/// Control register 1
+	CR1 @ 0, reset = 0x0000_0000 {
+		/// Bidirectional data mode enable
+		bidimode: 15, 1, RW;
+		/// Output enable in bidirectional mode
+		bidioe: 14, 1, RW;
+		/// Hardware CRC calculation enable
+		crcen: 13, 1, RW;
+		/// CRC transfer next
+		crcnext: 12, 1, RW;
+		/// Data frame format (0: 8-bit, 1: 16-bit)
+		dff: 11, 1, RW;
+		/// Receive only
+		rxonly: 10, 1, RW;
+		/// Software slave management
+		ssm: 9, 1, RW;
+		/// Internal slave select
+		ssi: 8, 1, RW;
+		/// Frame format (0: MSB first, 1: LSB first)
+		lsbfirst: 7, 1, RW;
+		/// SPI enable
+		spe: 6, 1, RW;
+		/// Baud rate control
+		br: 3, 3, RW;
+		/// Master selection
+		mstr: 2, 1, RW;
+		/// Clock polarity
+		cpol: 1, 1, RW;
+		/// Clock phase
+		cpha: 0, 1, RW;
+	}
+
+	/// Control register 2
+	CR2 @ 1, reset = 0x0000_0000 {
+		txeie: 7, 1, RW;
+		rxneie: 6, 1, RW;
+		errie: 5, 1, RW;
+		/// Frame format (0: Motorola, 1: TI)
+		frf: 4, 1, RW;
+		/// SS output enable
+		ssoe: 2, 1, RW;
+		txdmaen: 1, 1, RW;
+		rxdmaen: 0, 1, RW;
+	}
+
+	/// Status register
+	SR @ 2, reset = 0x0000_0002 {
+		fre: 8, 1, RO;
+		bsy: 7, 1, RO;
+		ovr: 6, 1, RO;
+		modf: 5, 1, RO;
+		/// CRC error flag, cleared by writing 0
+		crcerr: 4, 1, RW;
+		udr: 3, 1, RO;
+		chside: 2, 1, RO;
+		txe: 1, 1, RO;
+		rxne: 0, 1, RO;
+	}
+
+	/// Data register
+	DR @ 3, reset = 0x0000_0000 {
+		dr: 0, 16, RW;
+	}
+
+	/// CRC polynomial register
+	CRCPR @ 4, reset = 0x0000_0007 {
+		crcpoly: 0, 16, RW;
+	}
+
+	/// RX CRC register
+	RXCRCR @ 5, reset = 0x0000_0000 {
+		rxcrc: 0, 16, RO;
+	}
+
+	/// TX CRC register
+	TXCRCR @ 6, reset = 0x0000_0000 {
+		txcrc: 0, 16, RO;
+	}
+}
+
 
 /// This struct is not a direct abstraction over the hardware peripheral
 /// This is due to communication protocal constraints and checks that must not 
@@ -21,7 +113,7 @@ pub const SIZE: usize = 9;
 #[repr(C)]
 pub struct Spi {
 	id: RCCPeripheral,
-	pins: Option<[Pin; 3]>,
+	pins: Option<[Pin<Unknown>; 3]>,
 	block: &'static mut [Register<u32>; SIZE],
 }
 
@@ -29,12 +121,12 @@ impl_rwio!(Spi);
 
 impl Spi {
 	pub fn read(&mut self) -> Result<u8, SPIError> {
-		let sr = self.block[2].read();
+		let sr = self.read_reg::<SR>();
 
-		if sr & 0b111_0000 != 0 {
+		if sr.ovr() | sr.modf() | sr.crcerr() != 0 {
 			Err( SPIError::ReadErr )
 		} else {
-			if self.is_set(2, 0) {
+			if sr.rxne() == 1 {
 				Ok( self.read_data() )
 			} else {
 				Err( SPIError::ReadErr )
@@ -43,12 +135,12 @@ impl Spi {
 	}
 
 	pub fn send(&mut self, byte: u8) -> Result<&mut Self, SPIError> {
-		let sr = self.block[2].read();
+		let sr = self.read_reg::<SR>();
 
-		if sr & 0b111_0000 != 0 {
+		if sr.ovr() | sr.modf() | sr.crcerr() != 0 {
 			Err( SPIError::SendErr )
 		} else {
-			if self.is_set(2, 1) {
+			if sr.txe() == 1 {
 				Ok( self.write_data(byte) )
 			} else {
 				Err( SPIError::SendErr )
@@ -59,14 +151,22 @@ impl Spi {
 
 impl Spi {
 	/// Get the SPI at `address`
-	pub unsafe fn from_addr(address: u32) -> Result<Self, ()> {
+	///
+	/// # Safety
+	///
+	/// The SPI must not be aliased by any other reference
+	pub unsafe fn from_addr(address: u32) -> Result<Self, SPIError> {
 		let id = match address {
 			SPI1 => RCCPeripheral::SPI1,
+			#[cfg(has_spi2)]
 			SPI2 => RCCPeripheral::SPI2,
+			#[cfg(has_spi3)]
 			SPI3 => RCCPeripheral::SPI3,
+			#[cfg(has_spi4)]
 			SPI4 => RCCPeripheral::SPI4,
+			#[cfg(has_spi5)]
 			SPI5 => RCCPeripheral::SPI5,
-			_ => return Err(()),
+			_ => return Err(SPIError::InvalidAddress),
 		};
 
 		Ok( Spi {
@@ -76,93 +176,133 @@ impl Spi {
 		} )
 	}
 
+	/// Get the SPI `id` over the given register block
+	/// Used to build the peripheral over a simulated block on the host
+	pub fn from_block(id: RCCPeripheral, block: &'static mut [Register<u32>; SIZE]) -> Self {
+		Spi {
+			id,
+			pins: None,
+			block,
+		}
+	}
+
+	/// Returns the (SCK, MISO, MOSI) signals of the peripheral
+	fn signals(&self) -> (Signal, Signal, Signal) {
+		match self.id {
+			#[cfg(has_spi2)]
+			RCCPeripheral::SPI2 => (Signal::SPI2_SCK, Signal::SPI2_MISO, Signal::SPI2_MOSI),
+			#[cfg(has_spi3)]
+			RCCPeripheral::SPI3 => (Signal::SPI3_SCK, Signal::SPI3_MISO, Signal::SPI3_MOSI),
+			#[cfg(has_spi4)]
+			RCCPeripheral::SPI4 => (Signal::SPI4_SCK, Signal::SPI4_MISO, Signal::SPI4_MOSI),
+			#[cfg(has_spi5)]
+			RCCPeripheral::SPI5 => (Signal::SPI5_SCK, Signal::SPI5_MISO, Signal::SPI5_MOSI),
+			_ => (Signal::SPI1_SCK, Signal::SPI1_MISO, Signal::SPI1_MOSI),
+		}
+	}
+
+	/// Returns the clock of the bus of the peripheral, APB2 for SPI1, SPI4 and SPI5
+	fn bus(&self, clocks: &Clocks) -> Frequency {
+		match self.id {
+			#[cfg(has_spi4)]
+			RCCPeripheral::SPI4 => clocks.apb2f,
+			#[cfg(has_spi5)]
+			RCCPeripheral::SPI5 => clocks.apb2f,
+			RCCPeripheral::SPI1 => clocks.apb2f,
+			_ => clocks.apb1f,
+		}
+	}
+
+	/// Returns the BR field of the smallest divider of `bus` (2 to 256) not faster than `freq`
+	fn baud_rate(bus: Frequency, freq: Frequency) -> Result<u32, SPIError> {
+		if freq.hz() == 0 || freq > bus {
+			return Err(SPIError::InvalidBus);
+		}
+
+		// BR 0b000 divides by 2, each step doubles the divider
+		match bus.hz().div_ceil(freq.hz()).next_power_of_two().trailing_zeros() {
+			0 | 1 => Ok(0b000),
+			n @ 2..=8 => Ok(n - 1),
+			_ => Err(SPIError::InvalidBus),
+		}
+	}
+
 	/// Initialize the SPI interface in master mode
-	pub fn init_master(&mut self, pins: [Pin; 3], freq: Frequency, rcc: &mut super::rcc::Rcc, lsb: bool) -> Result<&mut Self, SPIError> {
+	/// The pins are (SCK, MISO, MOSI), as the push-pull alternate functions of the signals
+	/// they carry. Pins that do not carry them are rejected. They are set at high speed,
+	/// and MISO as a floating input on the STM32F1
+	pub fn init_master<SCK: AltFn, MISO: AltFn, MOSI: AltFn>(&mut self, pins: MasterPins<SCK, MISO, MOSI>, freq: Frequency, rcc: &mut super::rcc::Rcc, lsb: bool) -> Result<&mut Self, SPIError> {
+		let (sck, miso, mosi) = self.signals();
+		let functions = [pins.0.carries(sck), pins.1.carries(miso), pins.2.carries(mosi)];
+
+		if functions.contains(&0) {
+			return Err(SPIError::InvalidPin);
+		}
+
+		// The STM32F1 routes all the pins with one remap
+		#[cfg(feature = "stm32f1")]
+		let remap = syscfg::common_remap(&functions).ok_or(SPIError::InvalidPin)?;
+
+		let br = Self::baud_rate(self.bus(&rcc.clocks()), freq)?;
+
 		// Disable, enable and reset, get CLOCK
 		rcc.peripheral_state(true, self.id)
 			.reset_peripheral(self.id);
 
-		// Activate 8-bit transfer size
-		// Disable Slave Select
-		self.clear(1, 2)
-			.clear(1, 4);
-
-		// Get rate
-		let br = match u32::from(rcc.clocks().apb1f / freq ) {
-			0 => return Err(SPIError::InvalidBus),
-			1..=2    => 0b000,
-			3..=5    => 0b001,
-			6..=11   => 0b010,
-			12..=23  => 0b011,
-			24..=39  => 0b100,
-			40..=95  => 0b101,
-			96..=191 => 0b110,
-			_        => 0b111,
-		};
-
-		// Create lambda for modification
-		// Creates less read/writes, less latency, less time wasted in IO
-		let setup = move |mut reg| {
-			// Set Motorola or 8-bit mode
-			reg &= !(1 << 11);
-			// Set master mode
-			reg |= 1 << 2;
-			// Set Baud rate
-			reg &= !(mask!(3) << 3);
-			reg |= (br & mask!(3)) << 3;
-			// Enable preripheral
-			reg |= 1 << 6;
-			// Set lsb or msb
-			if lsb { reg |= 1 << 7 }
-			else   { reg &= !(1 << 7) }
-			// Set SSM and SSI (software managed slave select)
-			reg |= 0b11 << 8;
-			// Disable CRC and FUll-duplex mode
-			reg &= !(0b101 << 13);
-			reg
-		};
+		#[cfg(feature = "stm32f1")]
+		syscfg::apply_remap(self.id, remap, rcc);
 
-		self.modify(0, &setup);
+		// Motorola frame format
+		// Disable Slave Select output
+		self.reg::<CR2>().modify(|_, w| w.frf(0).ssoe(0));
 
-			// Set Motorola or 8-bit mode
-		/*self.clear(0, 11)
-			// Seet master mode
-			.set(0, 2)
-			// Set Baud rate
-			.write_bits(0, 3, br, 3)
-			// Enable peripheral
-			.set(0, 6)
+		// Single read-modify-write
+		// Creates less read/writes, less latency, less time wasted in IO
+		self.reg::<CR1>().modify(|_, w| {
+			// 8-bit mode
+			w.dff(0)
+			// Master mode
+				.mstr(1)
+				.br(br)
 			// Set LSB or MSB
-			.lsb(lsb)
-			// Set SSM and SSI (software managed Slave Select)
-			.set(0, 9)
-			.set(0, 8)
-			// Disable CRC
-			.clear(0, 13)
-			// Full-duplex mode
-			.clear(0, 15);*/
+				.lsbfirst(lsb as u32)
+			// Software managed slave select
+				.ssm(1)
+				.ssi(1)
+			// Disable CRC, 2-line unidirectional mode
+				.crcen(0)
+				.bidimode(0)
+			// Enable peripheral
+				.spe(1)
+		});
 
-		self.pins = Some( pins );
+		// The pins have been checked above
+		let pp = OutputType::PushPull;
+
+		self.pins = match (pins.0.into_signal(sck, pp), pins.1.into_signal(miso, pp), pins.2.into_signal(mosi, pp)) {
+			(Some(a), Some(b), Some(c)) => Some( [a, b, c] ),
+			_ => unreachable!(),
+		};
 
 		Ok( self )
 	}
 
-	/// Stop execution of the SPI interface
-	pub fn deinit(&mut self) -> [Pin; 3] {
-		let tmp = match &self.pins {
-			Some(p) => [p[0], p[1], p[2]],
+	/// Stop execution of the SPI interface and release the pins
+	/// The pins are still in their alternate function mode
+	pub fn deinit(&mut self) -> [Pin<Unknown>; 3] {
+		let pins = match self.pins.take() {
+			Some(p) => p,
 			_ => panic!("Cannot deinit an uninitialized peripheral"),
 		};
 
-		self.clear(0, 6);
+		self.reg::<CR1>().modify(|_, w| w.spe(0));
 
-		self.pins = None;
-		[tmp[0], tmp[1], tmp[2]]
+		pins
 	}
 
 	fn lsb(&mut self, s: bool) -> &mut Self {
-		if s { self  .set(0, 7) }
-		else { self.clear(0, 7) }
+		self.reg::<CR1>().modify(|_, w| w.lsbfirst(s as u32));
+		self
 	}
 
 	/*
@@ -213,39 +353,35 @@ impl Spi {
 impl Spi {
 	/// Enable/Disable bidirectional mode
 	pub fn bidi_state(&mut self, s: bool) -> &mut Self {
-		match s {
-			true => self.clear(0, 15),
-			_ => self.set(0, 15),
-		}
+		self.reg::<CR1>().modify(|_, w| w.bidimode(!s as u32));
+		self
 	}
 
 	/// Enable/Disable full-duplex mode
 	pub fn full_duplex_state(&mut self, s: bool) -> &mut Self {
-		match s {
-			true => self.clear(0, 10),
-			_ => self.set(0, 10),
-		}
+		self.reg::<CR1>().modify(|_, w| w.rxonly(!s as u32));
+		self
 	}
 
 	/// Set transmit mode
 	pub fn transmit(&mut self) -> &mut Self {
-		self.set(0, 14)
+		self.reg::<CR1>().modify(|_, w| w.bidioe(1));
+		self
 	}
 
 	/// Set receive mode
 	pub fn receive(&mut self) -> &mut Self {
-		self.clear(0, 14)
+		self.reg::<CR1>().modify(|_, w| w.bidioe(0));
+		self
 	}
 
 	/// Enable/Disable CRC calculation
-	pub fn crc_state(&mut self, s: bool) -> Result<&mut Self, ()> {
-		if self.is_set(0, 6) {
-			Err(())
+	pub fn crc_state(&mut self, s: bool) -> Result<&mut Self, SPIError> {
+		if self.read_reg::<CR1>().spe() == 1 {
+			Err(SPIError::Enabled)
 		} else {
-			match s {
-				true => Ok( self.set(0, 13) ),
-				_ => Ok( self.clear(0, 13) ),
-			}
+			self.reg::<CR1>().modify(|_, w| w.crcen(s as u32));
+			Ok( self )
 		}
 	}
 
@@ -256,33 +392,37 @@ impl Spi {
 	/// the second last data reception.
 	/// This bit should be kept cleared when the transfers are managed by DMA
 	pub fn crc_transfer(&mut self) -> &mut Self {
-		self.set(0, 12)
+		self.reg::<CR1>().modify(|_, w| w.crcnext(1));
+		self
 	}
 
 	pub fn disable_crc_transfer(&mut self) -> &mut Self {
-		self.clear(0, 12)
+		self.reg::<CR1>().modify(|_, w| w.crcnext(0));
+		self
 	}
 
 	/// Set the Data Frame Format
-	pub fn set_dff(&mut self, dff: DFFormat) -> Result<&mut Self, ()> {
-		if self.is_set(0, 6) {
-			Err(())
+	pub fn set_dff(&mut self, dff: DFFormat) -> Result<&mut Self, SPIError> {
+		if self.read_reg::<CR1>().spe() == 1 {
+			Err(SPIError::Enabled)
 		} else {
 			match dff {
-				DFFormat::Bit8 => Ok( self.clear(0, 11) ),
-				_ => Ok( self.set(0, 11) ),
+				DFFormat::Bit8 => self.reg::<CR1>().modify(|_, w| w.dff(0)),
+				_ => self.reg::<CR1>().modify(|_, w| w.dff(1)),
 			}
+
+			Ok( self )
 		}
 	}
 
 	/// Set the frame format
-	pub fn set_ff(&mut self, ff: FrameFormat) -> Result<&mut Self, ()> {
-		if self.is_set(2, 7) {
-			Err(())
+	pub fn set_ff(&mut self, ff: FrameFormat) -> Result<&mut Self, SPIError> {
+		if self.read_reg::<SR>().bsy() == 1 {
+			Err(SPIError::Busy)
 		} else {
 			match ff {
-				FrameFormat::MSB => Ok( self.clear(0, 7) ),
-				_ => Ok( self.set(0, 7) ),
+				FrameFormat::MSB => Ok( self.lsb(false) ),
+				_ => Ok( self.lsb(true) ),
 			}
 		}
 	}
@@ -293,32 +433,244 @@ impl Spi {
 impl Spi {
 	/// Enable/Disable interrupt
 	pub fn int_state(&mut self, s: bool, int: SPIInterrupt) -> &mut Self {
-		let offset = match int {
-			SPIInterrupt::RXDMA => 0,
-			SPIInterrupt::TXDMA => 1,
-			SPIInterrupt::ERR => 5,
-			SPIInterrupt::RXNE => 6,
-			SPIInterrupt::TXE => 7,
-		};
-
-		match s {
-			true => self.set(1, offset),
-			_ => self.clear(1, offset),
-		}
+		self.reg::<CR2>().modify(|_, w| match int {
+			SPIInterrupt::RXDMA => w.rxdmaen(s as u32),
+			SPIInterrupt::TXDMA => w.txdmaen(s as u32),
+			SPIInterrupt::ERR => w.errie(s as u32),
+			SPIInterrupt::RXNE => w.rxneie(s as u32),
+			SPIInterrupt::TXE => w.txeie(s as u32),
+		});
+
+		self
 	}
 
 	/// Returns tru eif the flag is raised
 	pub fn is_flag_raised(&self, flag: SPIFlag) -> bool {
-		self.is_set(2, flag as usize)
+		(self.read_reg::<SR>().bits() >> flag as u32) & 1 == 1
 	}
 
 	/// Reads the data in the RX buffer
 	pub fn read_data(&self) -> u8 {
-		self.block[3].read() as u8
+		self.read_reg::<DR>().dr() as u8
 	}
 
 	/// Writes to the TX buffer
 	pub fn write_data(&mut self, data: u8) -> &mut Self {
-		self.write_bits(3, 0, data as u32, 8)
+		self.reg::<DR>().write(|w| w.dr(data as u32));
+		self
+	}
+}
+
+
+#[cfg(all(test, feature = "sim", any(feature = "stm32f1", feature = "stm32f4")))]
+mod tests {
+	use super::*;
+	use crate::common::{ ClockCfg, SrcClock };
+	use crate::common::structs::AF5;
+	use crate::common::register::sim::SimBlock;
+	use crate::peripherals::extended::{ gpio, rcc };
+	use crate::peripherals::map;
+
+	/// RCC running from the HSI at 16 MHz
+	fn rcc() -> (SimBlock<{ rcc::SIZE }>, rcc::Rcc) {
+		rcc::tests::frozen( ClockCfg { pllcfg: None, sysclk: (SrcClock::HSI, 0), ppre: (1, 1, 1), ..rcc::tests::cfg() } )
+	}
+
+	fn gpioa() -> (SimBlock<{ gpio::SIZE }>, gpio::Gpio) {
+		let block = SimBlock::<{ gpio::SIZE }>::new();
+		block.stand_for(map::GPIOA);
+
+		let gpio = gpio::Gpio::from_block( unsafe { block.regs() } );
+		(block, gpio)
+	}
+
+	fn gpiob() -> (SimBlock<{ gpio::SIZE }>, gpio::Gpio) {
+		let block = SimBlock::<{ gpio::SIZE }>::new();
+		block.stand_for(map::GPIOB);
+
+		let gpio = gpio::Gpio::from_block( unsafe { block.regs() } );
+		(block, gpio)
+	}
+
+	/// Pin `n` of `gpio` on AF5, the function of SPI1 and SPI2, ignored on the STM32F1
+	fn af5(gpio: &mut gpio::Gpio, n: u32) -> Pin<Alternate<AF5>> {
+		gpio.pin(n).unwrap().into_alternate()
+	}
+
+	/// AFIO written by the driver to remap its pins
+	#[cfg(feature = "stm32f1")]
+	fn afio() -> SimBlock<{ syscfg::SIZE }> {
+		let block = SimBlock::<{ syscfg::SIZE }>::new();
+		block.stand_for(syscfg::ADDRESS);
+		block
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f4")]
+	fn init_master() {
+		let (rcc_block, mut rcc) = rcc();
+		let (gpio_block, mut gpioa) = gpioa();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+
+		let pins = (af5(&mut gpioa, 5), af5(&mut gpioa, 6), af5(&mut gpioa, 7));
+		assert!(spi.init_master(pins, Frequency::MHz(1), &mut rcc, true).is_ok());
+
+		// 16 MHz / 1 MHz, a prescaler of 16
+		let cr1 = block.peek(0);
+		assert_eq!((cr1 >> 3) & 0b111, 0b011);
+		assert_eq!(cr1 & (1 << 2), 1 << 2, "master");
+		assert_eq!(cr1 & (1 << 6), 1 << 6, "enabled");
+		assert_eq!(cr1 & (1 << 7), 1 << 7, "LSB first");
+		assert_eq!(cr1 & (0b11 << 8), 0b11 << 8, "software slave select");
+		assert_eq!(cr1 & ((1 << 15) | (1 << 13) | (1 << 11)), 0, "2-line, no CRC, 8-bit");
+		assert_eq!(block.peek(1) & ((1 << 4) | (1 << 2)), 0, "Motorola, no SS output");
+
+		// Clock enabled and the peripheral reset
+		let (bus, bit) = RCCPeripheral::SPI1.offsets();
+		assert_ne!(rcc_block.peek(bus + 8) & (1 << bit), 0);
+		assert_eq!(rcc_block.writes(bus).iter().filter(|w| *w & (1 << bit) != 0).count(), 1);
+		assert_eq!(rcc_block.peek(bus) & (1 << bit), 0);
+
+		// PA5..7 on AF5 at high speed
+		assert_eq!((gpio_block.peek(0) >> 10) & 0b11_11_11, 0b10_10_10);
+		assert_eq!((gpio_block.peek(8) >> 20) & 0xFFF, 0x555);
+		assert_eq!((gpio_block.peek(2) >> 10) & 0b11_11_11, 0b11_11_11);
+	}
+
+	#[test]
+	fn init_master_rejects_wrong_pins() {
+		let (_, mut rcc) = rcc();
+		let (gpio_block, mut gpioa) = gpioa();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+
+		// PA0 does not carry SPI1_SCK
+		let pins = (af5(&mut gpioa, 0), af5(&mut gpioa, 6), af5(&mut gpioa, 7));
+		let accesses = gpio_block.accesses().len();
+		assert_eq!(spi.init_master(pins, Frequency::MHz(1), &mut rcc, false).err(), Some(SPIError::InvalidPin));
+
+		assert!(block.accesses().is_empty());
+		assert_eq!(gpio_block.accesses().len(), accesses);
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f4")]
+	fn init_master_rejects_pins_on_another_function() {
+		let (_, mut rcc) = rcc();
+		let (_, mut gpioa) = gpioa();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+
+		// PA5 carries SPI1_SCK on AF5, not AF6
+		let pins = (gpioa.pin(5).unwrap().into_alternate::<crate::common::structs::AF6>(), af5(&mut gpioa, 6), af5(&mut gpioa, 7));
+		assert_eq!(spi.init_master(pins, Frequency::MHz(1), &mut rcc, false).err(), Some(SPIError::InvalidPin));
+		assert!(block.accesses().is_empty());
+	}
+
+	#[test]
+	fn init_master_rejects_a_frequency_above_the_bus() {
+		let (rcc_block, mut rcc) = rcc();
+		let (_, mut gpioa) = gpioa();
+		#[cfg(feature = "stm32f1")]
+		let _afio = afio();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+
+		let pins = (af5(&mut gpioa, 5), af5(&mut gpioa, 6), af5(&mut gpioa, 7));
+		let accesses = rcc_block.accesses().len();
+		assert_eq!(spi.init_master(pins, Frequency::MHz(32), &mut rcc, false).err(), Some(SPIError::InvalidBus));
+
+		// Checked before the clock is enabled
+		assert_eq!(rcc_block.accesses().len(), accesses);
+		assert!(block.accesses().is_empty());
+	}
+
+	#[test]
+	fn baud_rate_rounds_the_divider_up() {
+		let bus = Frequency::MHz(16);
+
+		assert_eq!(Spi::baud_rate(bus, Frequency::MHz(16)), Ok(0b000));
+		assert_eq!(Spi::baud_rate(bus, Frequency::MHz(8)), Ok(0b000));
+		assert_eq!(Spi::baud_rate(bus, Frequency::MHz(5)), Ok(0b001));
+		assert_eq!(Spi::baud_rate(bus, Frequency::MHz(3)), Ok(0b010));
+		assert_eq!(Spi::baud_rate(bus, Frequency::MHz(1)), Ok(0b011));
+		assert_eq!(Spi::baud_rate(bus, Frequency::KHz(999)), Ok(0b100));
+		assert_eq!(Spi::baud_rate(bus, Frequency::Hz(62_500)), Ok(0b111));
+
+		assert_eq!(Spi::baud_rate(bus, Frequency::Hz(62_499)), Err(SPIError::InvalidBus));
+		assert_eq!(Spi::baud_rate(bus, Frequency::Hz(0)), Err(SPIError::InvalidBus));
+	}
+
+	#[test]
+	#[cfg(has_spi2)]
+	fn init_master_divides_the_clock_of_its_bus() {
+		// APB1 at half the APB2
+		let (_, mut rcc) = rcc::tests::frozen( ClockCfg { pllcfg: None, sysclk: (SrcClock::HSI, 0), ppre: (2, 1, 1), ..rcc::tests::cfg() } );
+		let freq = rcc.clocks().apb2f / 4;
+		#[cfg(feature = "stm32f1")]
+		let _afio = afio();
+
+		let (_, mut gpioa) = gpioa();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+
+		let pins = (af5(&mut gpioa, 5), af5(&mut gpioa, 6), af5(&mut gpioa, 7));
+		assert!(spi.init_master(pins, freq, &mut rcc, false).is_ok());
+		assert_eq!((block.peek(0) >> 3) & 0b111, 0b001);
+
+		let (_, mut gpiob) = gpiob();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI2, unsafe { block.regs() });
+
+		let pins = (af5(&mut gpiob, 13), af5(&mut gpiob, 14), af5(&mut gpiob, 15));
+		assert!(spi.init_master(pins, freq, &mut rcc, false).is_ok());
+		assert_eq!((block.peek(0) >> 3) & 0b111, 0b000);
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f1")]
+	fn init_master_applies_the_remap_of_its_pins() {
+		let (rcc_block, mut rcc) = rcc();
+		let (gpio_block, mut gpiob) = gpiob();
+		let afio = afio();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+
+		let pins = (af5(&mut gpiob, 3), af5(&mut gpiob, 4), af5(&mut gpiob, 5));
+		assert!(spi.init_master(pins, Frequency::MHz(1), &mut rcc, false).is_ok());
+
+		// SPI1_REMAP set, with the AFIO clock enabled
+		assert_eq!(afio.peek(1) & 1, 1);
+		assert_ne!(rcc_block.peek(6) & 1, 0);
+
+		// SCK and MOSI are push-pull alternate functions at 50 MHz, MISO a floating input
+		assert_eq!((gpio_block.peek(0) >> 12) & 0xFFF, 0xB4B);
+
+		// Back on PA5..7 without the remap
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+		let (_, mut gpioa) = gpioa();
+
+		let pins = (af5(&mut gpioa, 5), af5(&mut gpioa, 6), af5(&mut gpioa, 7));
+		assert!(spi.init_master(pins, Frequency::MHz(1), &mut rcc, false).is_ok());
+		assert_eq!(afio.peek(1) & 1, 0);
+	}
+
+	#[test]
+	#[cfg(feature = "stm32f1")]
+	fn init_master_rejects_pins_of_different_remaps() {
+		let (_, mut rcc) = rcc();
+		let (_, mut gpioa) = gpioa();
+		let (_, mut gpiob) = gpiob();
+		let afio = afio();
+		let block = SimBlock::<SIZE>::new();
+		let mut spi = Spi::from_block(RCCPeripheral::SPI1, unsafe { block.regs() });
+
+		// PA5 is SCK without the remap, PB4 and PB5 are MISO and MOSI with it
+		let pins = (af5(&mut gpioa, 5), af5(&mut gpiob, 4), af5(&mut gpiob, 5));
+		assert_eq!(spi.init_master(pins, Frequency::MHz(1), &mut rcc, false).err(), Some(SPIError::InvalidPin));
+
+		assert!(block.accesses().is_empty());
+		assert!(afio.accesses().is_empty());
 	}
 }
@@ -0,0 +1,346 @@
+//! System configuration controller
+//! Only the EXTI port selection is supported. On the STM32F1 it is done by the AFIO,
+//! which also remaps the peripherals to other pins
+
+use crate::common::{ Register, GPIOError, RCCPeripheral };
+use crate::peripherals::map;
+use crate::peripherals::extended::rcc::Rcc;
+
+#[cfg(feature = "stm32f1")]
+use crate::common::register::field_get;
+
+#[cfg(feature = "stm32f1")]
+use crate::interrupt::{ self, Mutex };
+
+#[cfg(all(feature = "stm32f1", feature = "std"))]
+use std::cell::Cell;
+
+#[cfg(all(feature = "stm32f1", not(feature = "std")))]
+use core::cell::Cell;
+
+#[cfg(not(feature = "stm32f1"))]
+pub const ADDRESS: u32 = map::SYSCFG;
+
+#[cfg(feature = "stm32f1")]
+pub const ADDRESS: u32 = map::AFIO;
+
+pub const SIZE: usize = 6;
+
+registers! {
+	/// External interrupt configuration register 1 (lines 0 to 3)
+	EXTICR1 @ 2, reset = 0x0000_0000 {
+		exti[4]: 0, 4, RW;
+	}
+
+	/// External interrupt configuration register 2 (lines 4 to 7)
+	EXTICR2 @ 3, reset = 0x0000_0000 {
+		exti[4]: 0, 4, RW;
+	}
+
+	/// External interrupt configuration register 3 (lines 8 to 11)
+	EXTICR3 @ 4, reset = 0x0000_0000 {
+		exti[4]: 0, 4, RW;
+	}
+
+	/// External interrupt configuration register 4 (lines 12 to 15)
+	EXTICR4 @ 5, reset = 0x0000_0000 {
+		exti[4]: 0, 4, RW;
+	}
+}
+
+#[cfg(feature = "stm32f1")]
+registers! {
+	/// AF remap and debug I/O configuration register
+	MAPR @ 1, reset = 0x0000_0000 {
+		/// Serial wire JTAG configuration, write only
+		swj_cfg: 24, 3, WO;
+	}
+}
+
+/// Peripherals that the AFIO can move to other pins
+/// The pins of each remap are in the alternate function table (`af::TABLE`)
+#[cfg(feature = "stm32f1")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Remap {
+	SPI1,
+	I2C1,
+	USART1,
+	USART2,
+	USART3,
+	TIM1,
+	TIM2,
+	TIM3,
+	TIM4,
+	CAN,
+	PD01,
+}
+
+#[cfg(feature = "stm32f1")]
+impl Remap {
+	/// Returns the offset and width of the remap field in MAPR
+	const fn field(self) -> (usize, usize) {
+		match self {
+			Remap::SPI1   => ( 0, 1),
+			Remap::I2C1   => ( 1, 1),
+			Remap::USART1 => ( 2, 1),
+			Remap::USART2 => ( 3, 1),
+			Remap::USART3 => ( 4, 2),
+			Remap::TIM1   => ( 6, 2),
+			Remap::TIM2   => ( 8, 2),
+			Remap::TIM3   => (10, 2),
+			Remap::TIM4   => (12, 1),
+			Remap::CAN    => (13, 2),
+			Remap::PD01   => (15, 1),
+		}
+	}
+
+	/// Returns the remap of the peripheral `id`, `None` if it cannot be remapped
+	pub const fn of(id: RCCPeripheral) -> Option<Remap> {
+		match id {
+			RCCPeripheral::SPI1   => Some(Remap::SPI1),
+			RCCPeripheral::I2C1   => Some(Remap::I2C1),
+			RCCPeripheral::USART1 => Some(Remap::USART1),
+			RCCPeripheral::USART2 => Some(Remap::USART2),
+			RCCPeripheral::USART3 => Some(Remap::USART3),
+			RCCPeripheral::TIM1   => Some(Remap::TIM1),
+			RCCPeripheral::TIM2   => Some(Remap::TIM2),
+			RCCPeripheral::TIM3   => Some(Remap::TIM3),
+			RCCPeripheral::TIM4   => Some(Remap::TIM4),
+			#[cfg(not(feature = "stm32f100"))]
+			RCCPeripheral::CAN    => Some(Remap::CAN),
+			_ => None,
+		}
+	}
+}
+
+/// Returns the remap that routes every signal of a peripheral to its pin, the lowest
+/// if several do. `functions` are the masks of the pins (see `Pin::functions`)
+/// `None` if no remap routes all the pins, the peripheral cannot use them together
+#[cfg(feature = "stm32f1")]
+pub(crate) fn common_remap(functions: &[u32]) -> Option<u32> {
+	match functions.iter().fold(!0, |all, f| all & f) {
+		0 => None,
+		mask => Some(mask.trailing_zeros()),
+	}
+}
+
+/// Enables the AFIO and moves the peripheral `id` to the pins of the remap `value`
+/// Used by the drivers once they checked their pins with `common_remap`
+/// Does nothing for the peripherals that cannot be remapped
+#[cfg(feature = "stm32f1")]
+pub(crate) fn apply_remap(id: RCCPeripheral, value: u32, rcc: &mut Rcc) {
+	let remap = match Remap::of(id) {
+		Some(remap) => remap,
+		None => return,
+	};
+
+	// On the host a simulated block must stand for the AFIO (see `SimBlock::stand_for`)
+	#[cfg(feature = "sim")]
+	let address = crate::common::register::sim::simulated_address(ADDRESS as usize);
+
+	#[cfg(not(feature = "sim"))]
+	let address = ADDRESS as usize;
+
+	// Aliases the `Syscfg` of `Peripherals`, `remap` writes MAPR in a critical section
+	let mut afio = Syscfg::from_block( unsafe { &mut *(address as *mut _) } );
+
+	afio.enable(rcc).remap(remap, value);
+}
+
+/// Last value written to SWJ_CFG, which reads back undefined
+#[cfg(feature = "stm32f1")]
+static SWJ_CFG: Mutex<Cell<u32>> = Mutex::new( Cell::new(0) );
+
+/// SYSCFG (AFIO on the STM32F1) and the EXTI lines routed with it
+pub struct Syscfg {
+	/// Lines 0 to 15 routed to a port, the EXTICR reset value does not tell them apart
+	claimed: u16,
+	block: &'static mut [Register<u32>; SIZE],
+}
+
+impl_rwio!(Syscfg);
+
+impl Syscfg {
+	pub fn from_addr(address: u32) -> Self {
+		Syscfg {
+			claimed: 0,
+			block: unsafe{ &mut *(address as *mut _) },
+		}
+	}
+
+	/// Builds the controller over the given registers (e.g. a simulated block)
+	pub fn from_block(block: &'static mut [Register<u32>; SIZE]) -> Self {
+		Syscfg {
+			claimed: 0,
+			block,
+		}
+	}
+
+	/// Enables the clock of the controller
+	pub fn enable(&mut self, rcc: &mut Rcc) -> &mut Self {
+		#[cfg(not(feature = "stm32f1"))]
+		rcc.peripheral_state(true, RCCPeripheral::SYSCFG);
+
+		#[cfg(feature = "stm32f1")]
+		rcc.peripheral_state(true, RCCPeripheral::AFIO);
+
+		self
+	}
+
+	/// Returns the port (0 for port A, 1 for port B...) routed to the EXTI `line`
+	pub fn exti_port(&self, line: u32) -> u32 {
+		let n = (line % 4) as usize;
+
+		match line / 4 {
+			0 => self.read_reg::<EXTICR1>().exti(n),
+			1 => self.read_reg::<EXTICR2>().exti(n),
+			2 => self.read_reg::<EXTICR3>().exti(n),
+			_ => self.read_reg::<EXTICR4>().exti(n),
+		}
+	}
+
+	/// Routes `port` (0 for port A, 1 for port B...) to the EXTI `line`
+	/// Fails if the line is already routed to another port with this method
+	pub fn route_exti(&mut self, line: u32, port: u32) -> Result<&mut Self, GPIOError> {
+		if line > 15 { return Err(GPIOError::InvalidPin); }
+
+		if self.claimed & (1 << line) != 0 && self.exti_port(line) != port {
+			return Err(GPIOError::LineTaken);
+		}
+
+		self.claimed |= 1 << line;
+
+		let n = (line % 4) as usize;
+
+		match line / 4 {
+			0 => self.reg::<EXTICR1>().modify(|_, w| w.exti(n, port)),
+			1 => self.reg::<EXTICR2>().modify(|_, w| w.exti(n, port)),
+			2 => self.reg::<EXTICR3>().modify(|_, w| w.exti(n, port)),
+			_ => self.reg::<EXTICR4>().modify(|_, w| w.exti(n, port)),
+		}
+
+		Ok( self )
+	}
+
+	/// Frees the EXTI `line` so another port can be routed to it
+	pub fn release_exti(&mut self, line: u32) -> &mut Self {
+		if line > 15 { return self; }
+
+		self.claimed &= !(1 << line);
+		self
+	}
+
+	/// Checks if the EXTI `line` is routed with `route_exti`
+	pub fn is_exti_claimed(&self, line: u32) -> bool {
+		line < 16 && self.claimed & (1 << line) != 0
+	}
+}
+
+#[cfg(feature = "stm32f1")]
+impl Syscfg {
+	/// Moves `remap` to the pins selected by `value`
+	/// Enable the AFIO first (see `enable`)
+	pub fn remap(&mut self, remap: Remap, value: u32) -> &mut Self {
+		let (offset, width) = remap.field();
+
+		interrupt::free(|cs| {
+			let swj = SWJ_CFG.borrow(cs).get();
+			self.reg::<MAPR>().modify(|_, w| w.set_bits(offset, width, value).swj_cfg(swj));
+		});
+
+		self
+	}
+
+	/// Returns the remap value of `remap`
+	pub fn remapped(&self, remap: Remap) -> u32 {
+		let (offset, width) = remap.field();
+		field_get(self.read_reg::<MAPR>().bits(), offset, width)
+	}
+
+	/// Enables/Disables the JTAG port, the SW port stays enabled
+	/// Disabling it frees PA15, PB3 and PB4
+	pub fn jtag_state(&mut self, s: bool) -> &mut Self {
+		let swj = match s {
+			true => 0b000,
+			_ => 0b010,
+		};
+
+		interrupt::free(|cs| {
+			SWJ_CFG.borrow(cs).set(swj);
+			self.reg::<MAPR>().modify(|_, w| w.swj_cfg(swj));
+		});
+
+		self
+	}
+}
+
+
+#[cfg(all(test, feature = "sim", any(feature = "stm32f1", feature = "stm32f4")))]
+pub(crate) mod tests {
+	use super::*;
+	use crate::common::register::{ sim::SimBlock, RegisterSpec };
+	use crate::peripherals::extended::rcc;
+
+	/// APB2ENR index and SYSCFGEN bit
+	#[cfg(not(feature = "stm32f1"))]
+	pub(crate) const ENABLE: (usize, u32) = (17, 1 << 14);
+
+	/// APB2ENR index and AFIOEN bit
+	#[cfg(feature = "stm32f1")]
+	pub(crate) const ENABLE: (usize, u32) = (6, 1 << 0);
+
+	pub(crate) fn syscfg() -> (SimBlock<SIZE>, Syscfg) {
+		let block = SimBlock::<SIZE>::new();
+		let syscfg = Syscfg::from_block( unsafe { block.regs() } );
+		(block, syscfg)
+	}
+
+	#[test]
+	fn enable_clocks_the_controller() {
+		let (rcc_block, mut rcc) = rcc::tests::rcc();
+		let (_, mut syscfg) = syscfg();
+
+		syscfg.enable(&mut rcc);
+		assert_eq!(rcc_block.peek(ENABLE.0) & ENABLE.1, ENABLE.1);
+	}
+
+	#[test]
+	fn each_line_has_its_nibble() {
+		let (block, mut syscfg) = syscfg();
+
+		for line in 0..16 {
+			assert!(syscfg.route_exti(line, line % 7).is_ok());
+			assert_eq!(syscfg.exti_port(line), line % 7);
+		}
+
+		assert_eq!(block.peek(EXTICR1::INDEX), 0x3210);
+		assert_eq!(block.peek(EXTICR2::INDEX), 0x0654);
+		assert_eq!(block.peek(EXTICR3::INDEX), 0x4321);
+		assert_eq!(block.peek(EXTICR4::INDEX), 0x1065);
+
+		assert_eq!(syscfg.route_exti(16, 0).err(), Some(GPIOError::InvalidPin));
+	}
+
+	#[test]
+	fn a_line_is_routed_to_one_port_until_released() {
+		let (block, mut syscfg) = syscfg();
+
+		assert!(syscfg.route_exti(5, 2).is_ok());
+		assert!(syscfg.is_exti_claimed(5));
+
+		// The same port can route it again, another port cannot
+		assert!(syscfg.route_exti(5, 2).is_ok());
+		assert_eq!(syscfg.route_exti(5, 0).err(), Some(GPIOError::LineTaken));
+		assert_eq!(syscfg.exti_port(5), 2);
+
+		syscfg.release_exti(5);
+		assert!(!syscfg.is_exti_claimed(5));
+
+		assert!(syscfg.route_exti(5, 0).is_ok());
+		assert_eq!(block.peek(EXTICR2::INDEX), 0);
+
+		// The claims belong to the controller they were made with
+		let (_, other) = self::syscfg();
+		assert!(!other.is_exti_claimed(5));
+	}
+}
@@ -1,12 +1,37 @@
 //! Window Watchdog
 
 use crate::common::{ Register };
+use crate::peripherals::map;
 
-use embedded_hal::watchdog::*;
-
-pub const ADDRESS: u32 = 0x4001_3C00;
+pub const ADDRESS: u32 = map::WWDG;
 pub const SIZE: usize = 6;
 
+registers! {
+	/// Control register
+	CR @ 0, reset = 0x0000_007F {
+		/// Activation bit
+		wdga: 7, 1, RW;
+		/// Counter
+		t: 0, 7, RW;
+	}
+
+	/// Configuration register
+	CFR @ 1, reset = 0x0000_007F {
+		/// Early wakeup interrupt
+		ewi: 9, 1, RW;
+		/// Timer base
+		wdgtb: 7, 2, RW;
+		/// Window value
+		w: 0, 7, RW;
+	}
+
+	/// Status register
+	SR @ 2, reset = 0x0000_0000 {
+		/// Early wakeup interrupt flag, cleared by writing 0
+		ewif: 0, 1, RW;
+	}
+}
+
 #[repr(C)]
 pub struct Wwdg {
 	block: [Register<u32>; SIZE],
@@ -19,12 +44,13 @@ impl_rwio!(Wwdg);
 impl Wwdg {
 	/// The user must give a reset value
 	pub fn reset(&mut self, value: u32) -> &mut Self {
-		self.write_bits(0, 0, value, 7)
+		self.reg::<CR>().modify(|_, w| w.t(value));
+		self
 	}
 
 	/// Start. The user must give an initial value.
 	pub fn start(&mut self, value: u32) -> &mut Self {
-		self.block[0].write( (1 << 7) | (value & mask!(7)) );
+		self.reg::<CR>().write(|w| w.wdga(1).t(value));
 		self
 	}
 
@@ -32,8 +58,8 @@ impl Wwdg {
 	/// Enables/Disables early wakeup
 	/// Early wakeup creates an interrupt one tick away from reset
 	pub fn ewi_enabled(&mut self, state: bool) -> &mut Self {
-		if state { self.set(1, 9) }
-		else     { self.clear(1, 9) }
+		self.reg::<CFR>().modify(|_, w| w.ewi(state as u32));
+		self
 	}
 
 	/// Sets a divisor for the PCLK1
@@ -43,7 +69,8 @@ impl Wwdg {
 	/// 10: div = 4
 	/// 11: div = 8
 	pub fn set_divisor(&mut self, div: u32) -> &mut Self {
-		self.write_bits(1, 7, div, 2)
+		self.reg::<CFR>().modify(|_, w| w.wdgtb(div));
+		self
 	}
 
 	/// Sets the upper bound
@@ -52,11 +79,12 @@ impl Wwdg {
 	/// meaning if upper_bound > downcounter(when reset is sent) > 0x3F everything is good
 	/// Else a reset is/has been sent
 	pub fn set_bound(&mut self, bound: u32) -> &mut Self {
-		self.write_bits(1, 0, bound, 7)
+		self.reg::<CFR>().modify(|_, w| w.w(bound));
+		self
 	}
 
 	/// Clears EWI flag
 	pub fn clear_ewi(&mut self) {
-		self.block[2].write(0)
+		self.reg::<SR>().write(|w| w.ewif(0))
 	}
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+// Peripherals that are not on every device
+//
+// Included by the build script, which sets the cfg `has_<name>` (e.g. `has_spi4`)
+// when one of the listed features is enabled. The memory map, `Peripherals` and the
+// drivers gate these peripherals on that cfg only, so a device is added here once.
+
+/// (Peripheral, features of the devices that have it)
+pub const AVAILABLE: &[(&str, &[&str])] = &[
+	("gpiod", &[
+		"stm32f0", "stm32f1", "stm32f401", "stm32f411", "stm32f412", "stm32f4x3",
+		"stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("gpioe", &[
+		"stm32f1", "stm32f401", "stm32f411", "stm32f412", "stm32f4x3",
+		"stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("gpiof", &[
+		"stm32f0", "stm32f412", "stm32f4x3", "stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("gpiog", &[
+		"stm32f412", "stm32f4x3", "stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("gpioh", &["stm32f4", "stm32f7"]),
+	("gpioi", &["stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f7"]),
+	("gpioj", &["stm32f4x9"]),
+	("gpiok", &["stm32f4x9"]),
+
+	("spi2", &["stm32f1", "stm32f4", "stm32f7"]),
+	("spi3", &[
+		"stm32f401", "stm32f411", "stm32f412", "stm32f4x3",
+		"stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("spi4", &[
+		"stm32f401", "stm32f411", "stm32f412", "stm32f4x3", "stm32f446", "stm32f4x9", "stm32f7",
+	]),
+	("spi5", &[
+		"stm32f410", "stm32f411", "stm32f412", "stm32f4x3", "stm32f4x9", "stm32f7",
+	]),
+
+	("i2c2", &["stm32f1", "stm32f4", "stm32f7"]),
+	("i2c3", &[
+		"stm32f401", "stm32f411", "stm32f412", "stm32f4x3",
+		"stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+
+	("usart3", &[
+		"stm32f1", "stm32f412", "stm32f4x3", "stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("uart4", &["stm32f4x3", "stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7"]),
+	("uart5", &["stm32f4x3", "stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7"]),
+	("usart6", &["stm32f4", "stm32f7"]),
+
+	// The STM32F410 only has the TIM5 of the general purpose timers
+	("tim2", &[
+		"stm32f1", "stm32f401", "stm32f411", "stm32f412", "stm32f4x3",
+		"stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("tim3", &[
+		"stm32f0", "stm32f1", "stm32f401", "stm32f411", "stm32f412", "stm32f4x3",
+		"stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("tim4", &[
+		"stm32f1", "stm32f401", "stm32f411", "stm32f412", "stm32f4x3",
+		"stm32f4x5", "stm32f4x7", "stm32f4x9", "stm32f446", "stm32f7",
+	]),
+	("tim5", &["stm32f4", "stm32f7"]),
+
+	// The AFIO on the STM32F1
+	("syscfg", &["stm32f0", "stm32f1", "stm32f4", "stm32f7"]),
+];
@@ -0,0 +1,36 @@
+//! Memory map of the extended peripherals
+//!
+//! The base addresses depend on the selected device feature.
+//! Peripherals that are not present in the device are not defined,
+//! so using them is a compile error. Those missing on some devices are
+//! listed once in `available.rs`, the rest of the crate gates them on
+//! the `has_<name>` cfg it sets (e.g. `#[cfg(has_spi4)]`)
+
+#[cfg(feature = "stm32f0")]
+mod stm32f0;
+
+#[cfg(feature = "stm32f1")]
+mod stm32f1;
+
+#[cfg(feature = "stm32f4")]
+mod stm32f4;
+
+#[cfg(feature = "stm32f7")]
+mod stm32f7;
+
+
+#[cfg(feature = "stm32f0")]
+pub use self::stm32f0::*;
+
+#[cfg(feature = "stm32f1")]
+pub use self::stm32f1::*;
+
+#[cfg(feature = "stm32f4")]
+pub use self::stm32f4::*;
+
+#[cfg(feature = "stm32f7")]
+pub use self::stm32f7::*;
+
+
+#[cfg(not(any(feature = "stm32f0", feature = "stm32f1", feature = "stm32f4", feature = "stm32f7")))]
+compile_error!("No device selected, enable the feature of the target device (e.g. `stm32f411`)");
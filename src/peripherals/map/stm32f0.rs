@@ -0,0 +1,35 @@
+//! STM32F0 memory map
+
+// APB
+#[cfg(has_tim3)]
+pub const TIM3: u32 = 0x4000_0400;
+pub const TIM14: u32 = 0x4000_2000;
+pub const RTC: u32 = 0x4000_2800;
+pub const WWDG: u32 = 0x4000_2C00;
+pub const IWDG: u32 = 0x4000_3000;
+pub const USART2: u32 = 0x4000_4400;
+pub const I2C1: u32 = 0x4000_5400;
+pub const PWR: u32 = 0x4000_7000;
+
+#[cfg(has_syscfg)]
+pub const SYSCFG: u32 = 0x4001_0000;
+pub const EXTI: u32 = 0x4001_0400;
+pub const ADC: u32 = 0x4001_2400;
+pub const TIM1: u32 = 0x4001_2C00;
+pub const SPI1: u32 = 0x4001_3000;
+pub const USART1: u32 = 0x4001_3800;
+
+// AHB1
+pub const DMA1: u32 = 0x4002_0000;
+pub const RCC: u32 = 0x4002_1000;
+pub const FLASH: u32 = 0x4002_2000;
+pub const CRC: u32 = 0x4002_3000;
+
+// AHB2
+pub const GPIOA: u32 = 0x4800_0000;
+pub const GPIOB: u32 = 0x4800_0400;
+pub const GPIOC: u32 = 0x4800_0800;
+#[cfg(has_gpiod)]
+pub const GPIOD: u32 = 0x4800_0C00;
+#[cfg(has_gpiof)]
+pub const GPIOF: u32 = 0x4800_1400;
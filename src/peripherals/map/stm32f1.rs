@@ -0,0 +1,43 @@
+//! STM32F1 memory map (low and medium density)
+
+// APB1
+#[cfg(has_tim2)]
+pub const TIM2: u32 = 0x4000_0000;
+#[cfg(has_tim3)]
+pub const TIM3: u32 = 0x4000_0400;
+#[cfg(has_tim4)]
+pub const TIM4: u32 = 0x4000_0800;
+pub const RTC: u32 = 0x4000_2800;
+pub const WWDG: u32 = 0x4000_2C00;
+pub const IWDG: u32 = 0x4000_3000;
+#[cfg(has_spi2)]
+pub const SPI2: u32 = 0x4000_3800;
+pub const USART2: u32 = 0x4000_4400;
+pub const USART3: u32 = 0x4000_4800;
+pub const I2C1: u32 = 0x4000_5400;
+#[cfg(has_i2c2)]
+pub const I2C2: u32 = 0x4000_5800;
+pub const BKP: u32 = 0x4000_6C00;
+pub const PWR: u32 = 0x4000_7000;
+
+// APB2
+#[cfg(has_syscfg)]
+pub const AFIO: u32 = 0x4001_0000;
+pub const EXTI: u32 = 0x4001_0400;
+pub const GPIOA: u32 = 0x4001_0800;
+pub const GPIOB: u32 = 0x4001_0C00;
+pub const GPIOC: u32 = 0x4001_1000;
+#[cfg(has_gpiod)]
+pub const GPIOD: u32 = 0x4001_1400;
+#[cfg(has_gpioe)]
+pub const GPIOE: u32 = 0x4001_1800;
+pub const ADC1: u32 = 0x4001_2400;
+pub const TIM1: u32 = 0x4001_2C00;
+pub const SPI1: u32 = 0x4001_3000;
+pub const USART1: u32 = 0x4001_3800;
+
+// AHB
+pub const DMA1: u32 = 0x4002_0000;
+pub const RCC: u32 = 0x4002_1000;
+pub const FLASH: u32 = 0x4002_2000;
+pub const CRC: u32 = 0x4002_3000;
@@ -0,0 +1,67 @@
+//! STM32F4 memory map
+
+// APB1
+#[cfg(has_tim2)]
+pub const TIM2: u32 = 0x4000_0000;
+#[cfg(has_tim3)]
+pub const TIM3: u32 = 0x4000_0400;
+#[cfg(has_tim4)]
+pub const TIM4: u32 = 0x4000_0800;
+#[cfg(has_tim5)]
+pub const TIM5: u32 = 0x4000_0C00;
+pub const RTC: u32 = 0x4000_2800;
+pub const WWDG: u32 = 0x4000_2C00;
+pub const IWDG: u32 = 0x4000_3000;
+#[cfg(has_spi2)]
+pub const SPI2: u32 = 0x4000_3800;
+#[cfg(has_spi3)]
+pub const SPI3: u32 = 0x4000_3C00;
+pub const USART2: u32 = 0x4000_4400;
+pub const I2C1: u32 = 0x4000_5400;
+#[cfg(has_i2c2)]
+pub const I2C2: u32 = 0x4000_5800;
+#[cfg(has_i2c3)]
+pub const I2C3: u32 = 0x4000_5C00;
+pub const PWR: u32 = 0x4000_7000;
+
+// APB2
+pub const TIM1: u32 = 0x4001_0000;
+pub const USART1: u32 = 0x4001_1000;
+pub const USART6: u32 = 0x4001_1400;
+pub const ADC1: u32 = 0x4001_2000;
+pub const SPI1: u32 = 0x4001_3000;
+#[cfg(has_spi4)]
+pub const SPI4: u32 = 0x4001_3400;
+#[cfg(has_syscfg)]
+pub const SYSCFG: u32 = 0x4001_3800;
+pub const EXTI: u32 = 0x4001_3C00;
+pub const TIM9: u32 = 0x4001_4000;
+pub const TIM11: u32 = 0x4001_4800;
+#[cfg(has_spi5)]
+pub const SPI5: u32 = 0x4001_5000;
+
+// AHB1
+pub const GPIOA: u32 = 0x4002_0000;
+pub const GPIOB: u32 = 0x4002_0400;
+pub const GPIOC: u32 = 0x4002_0800;
+#[cfg(has_gpiod)]
+pub const GPIOD: u32 = 0x4002_0C00;
+#[cfg(has_gpioe)]
+pub const GPIOE: u32 = 0x4002_1000;
+#[cfg(has_gpiof)]
+pub const GPIOF: u32 = 0x4002_1400;
+#[cfg(has_gpiog)]
+pub const GPIOG: u32 = 0x4002_1800;
+#[cfg(has_gpioh)]
+pub const GPIOH: u32 = 0x4002_1C00;
+#[cfg(has_gpioi)]
+pub const GPIOI: u32 = 0x4002_2000;
+#[cfg(has_gpioj)]
+pub const GPIOJ: u32 = 0x4002_2400;
+#[cfg(has_gpiok)]
+pub const GPIOK: u32 = 0x4002_2800;
+pub const CRC: u32 = 0x4002_3000;
+pub const RCC: u32 = 0x4002_3800;
+pub const FLASH: u32 = 0x4002_3C00;
+pub const DMA1: u32 = 0x4002_6000;
+pub const DMA2: u32 = 0x4002_6400;
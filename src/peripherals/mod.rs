@@ -1,4 +1,223 @@
 //! STM32 peripherals
+//!
+//! Every peripheral is handed out only once through `Peripherals::take`,
+//! later calls return `None`
+//!
+//! ```ignore
+//! let p = Peripherals::take().unwrap();
+//! ```
 
+pub mod af;
 pub mod core;
-pub mod extended;
\ No newline at end of file
+pub mod extended;
+pub mod map;
+
+use crate::interrupt;
+
+use self::core::{ cpuid, dcb, dwt, mpu, nvic, scb, syst };
+#[cfg(not(armv6m))]
+use self::core::{ cbp, fpb, itm, tpiu };
+#[cfg(feature = "fpu")]
+use self::core::fpu;
+
+#[cfg(any(feature = "stm32f1", feature = "stm32f4"))]
+use self::extended::i2c;
+use self::extended::{ rcc, spi, gptim, syscfg, flashiface, crc, exti, iwdg, wwdg, pwr, rtc, Gpio };
+use crate::common::VolatileStruct;
+
+
+/// Set when the peripherals have been taken
+static mut TAKEN: bool = false;
+
+/// All the peripherals of the device
+pub struct Peripherals {
+	// Core peripherals
+	#[cfg(not(armv6m))]
+	pub cbp: &'static mut cbp::Cbp,
+	pub cpuid: &'static mut cpuid::CpuId,
+	pub dcb: &'static mut dcb::Dcb,
+	pub dwt: &'static mut dwt::Dwt,
+	#[cfg(not(armv6m))]
+	pub fpb: &'static mut fpb::Fpb,
+	#[cfg(feature = "fpu")]
+	pub fpu: &'static mut fpu::Fpu,
+	#[cfg(not(armv6m))]
+	pub itm: &'static mut itm::Itm,
+	pub mpu: &'static mut mpu::Mpu,
+	pub nvic: &'static mut nvic::Nvic,
+	pub scb: &'static mut scb::Scb,
+	pub syst: &'static mut syst::SysTick,
+	#[cfg(not(armv6m))]
+	pub tpiu: &'static mut tpiu::Tpiu,
+
+	// Extended peripherals
+	pub rcc: rcc::Rcc,
+	pub flash: &'static mut flashiface::FlashIface,
+	pub pwr: &'static mut pwr::Pwr,
+	pub crc: &'static mut crc::Crc,
+	pub exti: &'static mut exti::Exti,
+	pub iwdg: &'static mut iwdg::Iwdg,
+	pub wwdg: &'static mut wwdg::Wwdg,
+	pub rtc: &'static mut rtc::Rtc,
+	#[cfg(has_syscfg)]
+	pub syscfg: syscfg::Syscfg,
+
+	pub gpioa: Gpio,
+	pub gpiob: Gpio,
+	pub gpioc: Gpio,
+	#[cfg(has_gpiod)]
+	pub gpiod: Gpio,
+	#[cfg(has_gpioe)]
+	pub gpioe: Gpio,
+	#[cfg(has_gpiof)]
+	pub gpiof: Gpio,
+	#[cfg(has_gpiog)]
+	pub gpiog: Gpio,
+	#[cfg(has_gpioh)]
+	pub gpioh: Gpio,
+	#[cfg(has_gpioi)]
+	pub gpioi: Gpio,
+	#[cfg(has_gpioj)]
+	pub gpioj: Gpio,
+	#[cfg(has_gpiok)]
+	pub gpiok: Gpio,
+
+	pub spi1: spi::Spi,
+	#[cfg(has_spi2)]
+	pub spi2: spi::Spi,
+	#[cfg(has_spi3)]
+	pub spi3: spi::Spi,
+	#[cfg(has_spi4)]
+	pub spi4: spi::Spi,
+	#[cfg(has_spi5)]
+	pub spi5: spi::Spi,
+
+	#[cfg(any(feature = "stm32f1", feature = "stm32f4"))]
+	pub i2c1: i2c::I2c,
+	#[cfg(all(has_i2c2, any(feature = "stm32f1", feature = "stm32f4")))]
+	pub i2c2: i2c::I2c,
+	#[cfg(all(has_i2c3, any(feature = "stm32f1", feature = "stm32f4")))]
+	pub i2c3: i2c::I2c,
+
+	#[cfg(has_tim2)]
+	pub tim2: &'static mut gptim::GpTim,
+	#[cfg(has_tim3)]
+	pub tim3: &'static mut gptim::GpTim,
+	#[cfg(has_tim4)]
+	pub tim4: &'static mut gptim::GpTim,
+	#[cfg(has_tim5)]
+	pub tim5: &'static mut gptim::GpTim,
+}
+
+impl Peripherals {
+	/// Returns all the peripherals the first time it's called, `None` afterwards
+	#[inline]
+	pub fn take() -> Option<Self> {
+		interrupt::free(|_| unsafe {
+			if TAKEN { None }
+			else { Some( Peripherals::steal() ) }
+		})
+	}
+
+	/// Returns all the peripherals, even if they have already been taken
+	///
+	/// # Safety
+	///
+	/// Creates aliases of peripherals that may already be in use
+	pub unsafe fn steal() -> Self {
+		TAKEN = true;
+
+		Peripherals {
+			#[cfg(not(armv6m))]
+			cbp: cbp::Cbp::from_addr(cbp::ADDRESS),
+			cpuid: cpuid::CpuId::from_addr(cpuid::ADDRESS),
+			dcb: dcb::Dcb::from_addr(dcb::ADDRESS),
+			dwt: dwt::Dwt::from_addr(dwt::ADDRESS),
+			#[cfg(not(armv6m))]
+			fpb: fpb::Fpb::from_addr(fpb::ADDRESS),
+			#[cfg(feature = "fpu")]
+			fpu: fpu::Fpu::from_addr(fpu::ADDRESS),
+			#[cfg(not(armv6m))]
+			itm: itm::Itm::from_addr(itm::ADDRESS),
+			mpu: mpu::Mpu::from_addr(mpu::ADDRESS),
+			nvic: nvic::Nvic::from_addr(nvic::ADDRESS),
+			scb: scb::Scb::from_addr(scb::ADDRESS),
+			syst: syst::SysTick::from_addr(syst::ADDRESS),
+			#[cfg(not(armv6m))]
+			tpiu: tpiu::Tpiu::from_addr(tpiu::ADDRESS),
+
+			rcc: rcc::Rcc::from_addr(rcc::ADDRESS),
+			flash: flashiface::FlashIface::from_addr(flashiface::ADDRESS),
+			pwr: pwr::Pwr::from_addr(pwr::ADDRESS),
+			crc: crc::Crc::from_addr(crc::ADDRESS),
+			exti: exti::Exti::from_addr(exti::ADDRESS),
+			iwdg: iwdg::Iwdg::from_addr(iwdg::ADDRESS),
+			wwdg: wwdg::Wwdg::from_addr(wwdg::ADDRESS),
+			rtc: rtc::Rtc::from_addr(rtc::ADDRESS),
+			#[cfg(has_syscfg)]
+			syscfg: syscfg::Syscfg::from_addr(syscfg::ADDRESS),
+
+			gpioa: Gpio::new(map::GPIOA),
+			gpiob: Gpio::new(map::GPIOB),
+			gpioc: Gpio::new(map::GPIOC),
+			#[cfg(has_gpiod)]
+			gpiod: Gpio::new(map::GPIOD),
+			#[cfg(has_gpioe)]
+			gpioe: Gpio::new(map::GPIOE),
+			#[cfg(has_gpiof)]
+			gpiof: Gpio::new(map::GPIOF),
+			#[cfg(has_gpiog)]
+			gpiog: Gpio::new(map::GPIOG),
+			#[cfg(has_gpioh)]
+			gpioh: Gpio::new(map::GPIOH),
+			#[cfg(has_gpioi)]
+			gpioi: Gpio::new(map::GPIOI),
+			#[cfg(has_gpioj)]
+			gpioj: Gpio::new(map::GPIOJ),
+			#[cfg(has_gpiok)]
+			gpiok: Gpio::new(map::GPIOK),
+
+			spi1: spi::Spi::from_addr(map::SPI1).unwrap(),
+			#[cfg(has_spi2)]
+			spi2: spi::Spi::from_addr(map::SPI2).unwrap(),
+			#[cfg(has_spi3)]
+			spi3: spi::Spi::from_addr(map::SPI3).unwrap(),
+			#[cfg(has_spi4)]
+			spi4: spi::Spi::from_addr(map::SPI4).unwrap(),
+			#[cfg(has_spi5)]
+			spi5: spi::Spi::from_addr(map::SPI5).unwrap(),
+
+			#[cfg(any(feature = "stm32f1", feature = "stm32f4"))]
+			i2c1: i2c::I2c::from_addr(map::I2C1).unwrap(),
+			#[cfg(all(has_i2c2, any(feature = "stm32f1", feature = "stm32f4")))]
+			i2c2: i2c::I2c::from_addr(map::I2C2).unwrap(),
+			#[cfg(all(has_i2c3, any(feature = "stm32f1", feature = "stm32f4")))]
+			i2c3: i2c::I2c::from_addr(map::I2C3).unwrap(),
+
+			#[cfg(has_tim2)]
+			tim2: gptim::GpTim::from_addr(map::TIM2),
+			#[cfg(has_tim3)]
+			tim3: gptim::GpTim::from_addr(map::TIM3),
+			#[cfg(has_tim4)]
+			tim4: gptim::GpTim::from_addr(map::TIM4),
+			#[cfg(has_tim5)]
+			tim5: gptim::GpTim::from_addr(map::TIM5),
+		}
+	}
+}
+
+
+#[cfg(all(test, feature = "sim"))]
+mod tests {
+	use super::Peripherals;
+
+	// The only test taking the peripherals, `TAKEN` is shared by the whole test binary
+	#[test]
+	fn take_once() {
+		let p = Peripherals::take();
+		assert!(p.is_some());
+
+		assert!(Peripherals::take().is_none());
+		assert!(Peripherals::take().is_none());
+	}
+}
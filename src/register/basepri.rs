@@ -22,7 +22,11 @@ pub fn read() -> u8 {
             __basepri_r()
         },
 
-        #[cfg(not(cortex_m))]
+        // The simulated backend has no interrupts, nothing is masked
+        #[cfg(all(not(cortex_m), feature = "sim"))]
+        () => 0,
+
+        #[cfg(all(not(cortex_m), not(feature = "sim")))]
         () => unimplemented!(),
     }
 }
@@ -31,6 +35,10 @@ pub fn read() -> u8 {
 ///
 /// **IMPORTANT** If you are using a Cortex-M7 device with revision r0p1 you MUST enable the
 /// `cm7-r0p1` Cargo feature or this function WILL misbehave.
+///
+/// # Safety
+///
+/// Changing the priority mask can break priority-based critical sections
 #[inline]
 pub unsafe fn write(_basepri: u8) {
     match () {
@@ -39,7 +47,7 @@ pub unsafe fn write(_basepri: u8) {
             #[cfg(not(feature = "cm7-r0p1"))]
             () => asm!("msr BASEPRI, $0" :: "r"(_basepri) : "memory" : "volatile"),
             #[cfg(feature = "cm7-r0p1")]
-            () => ::interrupt::free(
+            () => crate::interrupt::free(
                 |_| asm!("msr BASEPRI, $0" :: "r"(_basepri) : "memory" : "volatile"),
             ),
         },
@@ -64,7 +72,10 @@ pub unsafe fn write(_basepri: u8) {
             }
         },
 
-        #[cfg(not(cortex_m))]
+        #[cfg(all(not(cortex_m), feature = "sim"))]
+        () => (),
+
+        #[cfg(all(not(cortex_m), not(feature = "sim")))]
         () => unimplemented!(),
     }
 }
@@ -21,6 +21,10 @@ pub fn read() -> u32 {
 /// Writes `bits` to the CPU register
 ///
 /// **NOTE** This function is available if `cortex-m` is built with the `"inline-asm"` feature.
+///
+/// # Safety
+///
+/// Changing the return address breaks the code that relies on its current value
 #[inline]
 pub unsafe fn write(_bits: u32) {
     match () {
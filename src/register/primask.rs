@@ -52,7 +52,11 @@ pub fn read() -> Primask {
             }
         }
 
-        #[cfg(not(cortex_m))]
+        // The simulated backend has no interrupts, they are always active
+        #[cfg(all(not(cortex_m), feature = "sim"))]
+        () => Primask::Active,
+
+        #[cfg(all(not(cortex_m), not(feature = "sim")))]
         () => unimplemented!(),
     }
 }
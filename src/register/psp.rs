@@ -26,6 +26,10 @@ pub fn read() -> u32 {
 }
 
 /// Writes `bits` to the CPU register
+///
+/// # Safety
+///
+/// Changing the process stack pointer breaks the code that relies on its current value
 #[inline]
 pub unsafe fn write(_bits: u32) {
     match () {
@@ -0,0 +1,304 @@
+//! Code emitters
+//! Every function returns the contents of one generated file
+
+use std::fmt::Write;
+
+use crate::svd::{ Access, Device, Field, Register };
+
+const KEYWORDS: &[&str] = &[
+	"as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+	"if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+	"self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+	"async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro", "override",
+	"priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Turns a SVD name into a valid identifier
+fn ident(name: &str) -> String {
+	let mut s: String = name.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+		.collect();
+
+	if s.starts_with(|c: char| c.is_ascii_digit()) { s.insert(0, '_'); }
+	if KEYWORDS.contains(&s.as_str()) { s.push('_'); }
+
+	s
+}
+
+/// Formats a 32-bit value as `0xXXXX_XXXX`
+fn hex(v: u32) -> String {
+	format!("0x{:04X}_{:04X}", v >> 16, v & 0xFFFF)
+}
+
+fn access(a: Access) -> &'static str {
+	match a {
+		Access::RO => "RO",
+		Access::WO => "WO",
+		Access::RW => "RW",
+		Access::W1C => "W1C",
+	}
+}
+
+fn header(device: &Device, what: &str) -> String {
+	format!("//! {} of the {}\n//! Generated by `svdgen`, do not edit\n\n", what, device.name)
+}
+
+/// Splits `MODER12` into ("MODER", 12)
+fn indexed(name: &str) -> Option<(&str, u32)> {
+	let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+
+	if prefix.len() == name.len() || prefix.is_empty() { return None; }
+
+	name[prefix.len()..].parse().ok().map(|n| (prefix, n))
+}
+
+/// A field or an array of fields as written in `registers!`
+struct Item<'a> {
+	field: &'a Field,
+	name: String,
+	description: &'a str,
+	count: Option<u32>,
+}
+
+/// Collapses the fields `X0`, `X1`... `Xn` with a constant stride into a field array
+fn items(fields: &[Field]) -> Vec<Item<'_>> {
+	let mut items = Vec::new();
+	let mut used = vec![false; fields.len()];
+
+	for (i, f) in fields.iter().enumerate() {
+		if used[i] { continue; }
+
+		let array = indexed(&f.name).and_then(|(prefix, _)| {
+			let mut members: Vec<(u32, usize)> = fields.iter().enumerate()
+				.filter_map(|(j, g)| match indexed(&g.name) {
+					Some((p, n)) if p == prefix => Some((n, j)),
+					_ => None,
+				})
+				.collect();
+
+			members.sort();
+
+			let first = &fields[members[0].1];
+			let valid = members.len() > 1 && members.iter().enumerate().all(|(k, &(n, j))| {
+				let g = &fields[j];
+				n == k as u32 && g.width == first.width && g.access == first.access
+					&& g.offset == first.offset + k as u32 * first.width
+			});
+
+			if valid { Some((prefix, members)) } else { None }
+		});
+
+		match array {
+			Some((prefix, members)) => {
+				for &(_, j) in &members { used[j] = true; }
+
+				// The description is usually only on some of the members
+				let description = members.iter()
+					.map(|&(_, j)| fields[j].description.as_str())
+					.find(|d| !d.is_empty())
+					.unwrap_or("");
+
+				items.push( Item {
+					field: &fields[members[0].1],
+					name: ident(&prefix.to_lowercase()),
+					description,
+					count: Some(members.len() as u32),
+				} );
+			},
+			None => {
+				used[i] = true;
+
+				items.push( Item {
+					field: f,
+					name: ident(&f.name.to_lowercase()),
+					description: &f.description,
+					count: None,
+				} );
+			},
+		}
+	}
+
+	items
+}
+
+fn register(out: &mut String, r: &Register) {
+	if !r.description.is_empty() {
+		writeln!(out, "\t\t/// {}", r.description).unwrap();
+	}
+
+	writeln!(out, "\t\t{} @ {}, reset = {} {{", ident(&r.name), r.offset / 4, hex(r.reset)).unwrap();
+
+	for item in items(&r.fields) {
+		let f = item.field;
+
+		if !item.description.is_empty() {
+			writeln!(out, "\t\t\t/// {}", item.description).unwrap();
+		}
+
+		match item.count {
+			Some(n) => writeln!(out, "\t\t\t{}[{}]: {}, {}, {};", item.name, n, f.offset, f.width, access(f.access)),
+			None => writeln!(out, "\t\t\t{}: {}, {}, {};", item.name, f.offset, f.width, access(f.access)),
+		}.unwrap();
+	}
+
+	writeln!(out, "\t\t}}").unwrap();
+}
+
+/// Base addresses of the peripherals
+pub fn map(device: &Device) -> String {
+	let mut out = header(device, "Memory map");
+
+	let mut ps: Vec<_> = device.peripherals.iter().collect();
+	ps.sort_by_key(|p| p.base);
+
+	for p in ps {
+		writeln!(out, "pub const {}: u32 = {};", ident(&p.name), hex(p.base)).unwrap();
+	}
+
+	out
+}
+
+/// Register definitions of every peripheral, one module per peripheral
+pub fn registers(device: &Device) -> String {
+	let mut out = header(device, "Registers");
+
+	for p in &device.peripherals {
+		// Peripherals without registers (e.g. only listing interrupts) are only in the map
+		if p.derived.is_none() && p.registers.is_empty() { continue; }
+
+		writeln!(out, "pub mod {} {{", ident(&p.name.to_lowercase())).unwrap();
+
+		match &p.derived {
+			Some(base) if p.registers.is_empty() => {
+				writeln!(out, "\tpub use super::{}::*;", ident(&base.to_lowercase())).unwrap();
+			},
+			_ => {
+				writeln!(out, "\tregisters! {{").unwrap();
+
+				let mut first = true;
+
+				for r in &p.registers {
+					if r.size != 32 || r.offset % 4 != 0 {
+						writeln!(out, "\t\t// {} is not an aligned 32-bit register", r.name).unwrap();
+						continue;
+					}
+
+					if !first { writeln!(out).unwrap(); }
+					first = false;
+
+					register(&mut out, r);
+				}
+
+				writeln!(out, "\t}}").unwrap();
+			},
+		}
+
+		writeln!(out, "}}\n").unwrap();
+	}
+
+	out
+}
+
+/// Writes the single bit fields of `r` ending in `suffix`, highest bit first
+fn bus(out: &mut String, r: &Register, bus: &str, suffix: &str) {
+	let index = r.offset / 4;
+
+	for f in r.fields.iter().rev().filter(|f| f.width == 1 && f.name.ends_with(suffix)) {
+		let name = ident(f.name.trim_end_matches(suffix));
+
+		writeln!(out, "\t{:<7} = {},\t// {:<7} @ {} {:>2}", name, (index << 8) | f.offset, name, bus, f.offset).unwrap();
+	}
+}
+
+/// `RCCPeripheral` with the (register index, bit) encoding used by `Rcc`
+///
+/// The index is the one of the reset register of the bus, or the one of the
+/// enable register for the buses without reset register (the STM32F1 AHB)
+pub fn rcc(device: &Device) -> String {
+	let mut out = header(device, "RCC controlled peripherals");
+
+	writeln!(out, "/// Peripherals").unwrap();
+	writeln!(out, "/// Encoded as `(register index << 8) | bit`, the register is the reset register").unwrap();
+	writeln!(out, "/// of the bus, or the enable register if the bus has no reset register").unwrap();
+	writeln!(out, "#[derive(Debug, Copy, Clone)]\npub enum RCCPeripheral {{").unwrap();
+
+	if let Some(rcc) = device.peripheral("RCC") {
+		let resets: Vec<&Register> = rcc.registers.iter().filter(|r| r.name.ends_with("RSTR")).collect();
+
+		// The low power (`LPENR`) and sleep mode (`SMENR`) enables are not buses
+		let enables = rcc.registers.iter().filter(|r| {
+			let name = r.name.trim_end_matches("ENR");
+			r.name.ends_with("ENR") && !name.ends_with("LP") && !name.ends_with("SM")
+				&& !resets.iter().any(|rst| rst.name.trim_end_matches("RSTR") == name)
+		});
+
+		let buses = resets.iter().map(|r| (*r, r.name.trim_end_matches("RSTR"), "RST"))
+			.chain( enables.map(|r| (r, r.name.trim_end_matches("ENR"), "EN")) );
+
+		for (i, (r, name, suffix)) in buses.enumerate() {
+			if i != 0 { writeln!(out).unwrap(); }
+
+			bus(&mut out, r, name, suffix);
+		}
+	}
+
+	writeln!(out, "}}").unwrap();
+
+	out
+}
+
+/// `EXTILine` with the lines of the EXTI, one per bit of `IMR`
+pub fn extilines(device: &Device) -> String {
+	let mut out = header(device, "EXTI lines");
+
+	let lines: Vec<u32> = device.peripheral("EXTI")
+		.and_then(|e| e.registers.iter().find(|r| r.name == "IMR"))
+		.map(|r| r.fields.iter().filter(|f| f.width == 1).map(|f| f.offset).collect())
+		.unwrap_or_default();
+
+	writeln!(out, "/// EXTI lines\n#[derive(Debug, Copy, Clone)]\npub enum EXTILine {{").unwrap();
+
+	for i in lines {
+		writeln!(out, "\t{:<6} = {:>2},", format!("Line{}", i), i).unwrap();
+	}
+
+	writeln!(out, "}}").unwrap();
+
+	out
+}
+
+/// Device interrupts
+pub fn interrupts(device: &Device) -> String {
+	let mut out = header(device, "Interrupts");
+
+	writeln!(out, "/// Device interrupts").unwrap();
+	writeln!(out, "#[allow(non_camel_case_types)]").unwrap();
+	writeln!(out, "#[derive(Debug, Copy, Clone, PartialEq, Eq)]\npub enum Interrupt {{").unwrap();
+
+	for i in device.interrupts() {
+		if !i.description.is_empty() {
+			writeln!(out, "\t/// {}", i.description).unwrap();
+		}
+
+		writeln!(out, "\t{} = {},", ident(&i.name), i.value).unwrap();
+	}
+
+	writeln!(out, "}}\n").unwrap();
+
+	writeln!(out, "unsafe impl crate::interrupt::Nr for Interrupt {{").unwrap();
+	writeln!(out, "\t#[inline]\n\tfn nr(&self) -> u8 {{\n\t\t*self as u8\n\t}}").unwrap();
+	writeln!(out, "}}").unwrap();
+
+	out
+}
+
+/// Module file tying the generated files together
+pub fn module(device: &Device) -> String {
+	let mut out = header(device, "Generated definitions");
+
+	out.push_str("pub mod map;\npub mod registers;\n\n");
+	out.push_str("mod extilines;\nmod interrupts;\nmod rcc;\n\n");
+	out.push_str("pub use self::extilines::*;\npub use self::interrupts::*;\npub use self::rcc::*;\n");
+
+	out
+}
@@ -0,0 +1,124 @@
+//! SVD code generator
+//!
+//! Reads the CMSIS-SVD file of a device and writes the definitions that are
+//! otherwise hand-written per device:
+//!
+//! - `map.rs`: base address of every peripheral
+//! - `registers.rs`: `registers!` definitions of every peripheral
+//! - `rcc.rs`: `RCCPeripheral` with the reset/enable bit encoding used by `Rcc`
+//! - `extilines.rs`: `EXTILine` with the lines of the device
+//! - `interrupts.rs`: `Interrupt` with the device interrupt numbers
+//! - `mod.rs`: re-exports the above
+//!
+//! ```text
+//! cargo run --manifest-path tools/svdgen/Cargo.toml -- tools/svdgen/devices/stm32f411.svd src/devices/stm32f411
+//! ```
+//!
+//! The output is checked in under `src/devices`, one `#[cfg(feature = "...")]` module per
+//! device, so the crate itself does not need the SVD files to build. `devices/` holds the
+//! descriptions the crate is generated from, named after the device feature, and the tests
+//! check that the output under `src/devices` is up to date with them.
+
+mod gen;
+mod svd;
+mod xml;
+
+use std::{ env, fs, process };
+use std::path::Path;
+
+fn main() {
+	let args: Vec<String> = env::args().collect();
+
+	if args.len() != 3 {
+		eprintln!("Usage: {} <device.svd> <output directory>", args[0]);
+		process::exit(1);
+	}
+
+	if let Err(e) = run(Path::new(&args[1]), Path::new(&args[2])) {
+		eprintln!("svdgen: {}", e);
+		process::exit(1);
+	}
+}
+
+fn run(input: &Path, output: &Path) -> Result<(), String> {
+	let device = read(input)?;
+
+	fs::create_dir_all(output).map_err(|e| format!("{}: {}", output.display(), e))?;
+
+	for (name, contents) in generate(&device) {
+		let path = output.join(name);
+		fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+	}
+
+	Ok(())
+}
+
+/// Reads the device description in `input`
+fn read(input: &Path) -> Result<svd::Device, String> {
+	let src = fs::read_to_string(input).map_err(|e| format!("{}: {}", input.display(), e))?;
+	let root = xml::parse(&src).map_err(|e| format!("{}: {}", input.display(), e))?;
+
+	if root.name != "device" {
+		return Err( format!("{}: not a SVD file", input.display()) );
+	}
+
+	Ok( svd::device(&root) )
+}
+
+/// Returns the (file name, contents) of every generated file
+fn generate(device: &svd::Device) -> [(&'static str, String); 6] {
+	[
+		("map.rs", gen::map(device)),
+		("registers.rs", gen::registers(device)),
+		("rcc.rs", gen::rcc(device)),
+		("extilines.rs", gen::extilines(device)),
+		("interrupts.rs", gen::interrupts(device)),
+		("mod.rs", gen::module(device)),
+	]
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	fn root() -> PathBuf {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+	}
+
+	/// Compares the output for `input` with the files in `expected`
+	fn check(input: &Path, expected: &Path) {
+		let device = read(input).unwrap();
+
+		for (name, contents) in generate(&device).iter() {
+			let path = expected.join(name);
+			let file = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+			assert!(file == *contents, "{} is not the output for {}", path.display(), input.display());
+		}
+	}
+
+	#[test]
+	fn fixture() {
+		check(&root().join("tests/fixture.svd"), &root().join("tests/fixture"));
+	}
+
+	// The devices of the crate are generated from `devices/`, run
+	// `svdgen devices/<device>.svd ../../src/devices/<device>` after changing one
+	#[test]
+	fn devices_are_up_to_date() {
+		let mut inputs: Vec<PathBuf> = fs::read_dir(root().join("devices")).unwrap()
+			.map(|e| e.unwrap().path())
+			.filter(|p| p.extension() == Some("svd".as_ref()))
+			.collect();
+
+		inputs.sort();
+		assert!(!inputs.is_empty());
+
+		for input in inputs {
+			let device = input.file_stem().unwrap();
+			check(&input, &root().join("../../src/devices").join(device));
+		}
+	}
+}
@@ -0,0 +1,278 @@
+//! CMSIS-SVD device description
+
+use crate::xml::Element;
+
+/// Field access, in the terms of the `registers!` macro
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Access {
+	RO,
+	WO,
+	RW,
+	W1C,
+}
+
+impl Access {
+	fn parse(access: Option<&str>, modified: Option<&str>, default: Access) -> Access {
+		if modified == Some("oneToClear") { return Access::W1C; }
+
+		match access {
+			Some("read-only") => Access::RO,
+			Some("write-only") | Some("writeOnce") => Access::WO,
+			Some("read-write") | Some("read-writeOnce") => Access::RW,
+			_ => default,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+	pub name: String,
+	pub description: String,
+	pub offset: u32,
+	pub width: u32,
+	pub access: Access,
+}
+
+#[derive(Debug, Clone)]
+pub struct Register {
+	pub name: String,
+	pub description: String,
+	pub offset: u32,
+	pub size: u32,
+	pub reset: u32,
+	pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Interrupt {
+	pub name: String,
+	pub description: String,
+	pub value: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Peripheral {
+	pub name: String,
+	pub derived: Option<String>,
+	pub base: u32,
+	pub registers: Vec<Register>,
+	pub interrupts: Vec<Interrupt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Device {
+	pub name: String,
+	pub peripherals: Vec<Peripheral>,
+}
+
+impl Device {
+	/// Returns the peripheral `name`
+	pub fn peripheral(&self, name: &str) -> Option<&Peripheral> {
+		self.peripherals.iter().find(|p| p.name == name)
+	}
+
+	/// Returns all the interrupts of the device, sorted by number and without duplicates
+	pub fn interrupts(&self) -> Vec<Interrupt> {
+		let mut ints: Vec<Interrupt> = self.peripherals.iter()
+			.flat_map(|p| p.interrupts.iter().cloned())
+			.collect();
+
+		ints.sort_by_key(|i| i.value);
+		ints.dedup_by_key(|i| i.value);
+		ints
+	}
+}
+
+/// Register properties inherited from the parent elements
+#[derive(Debug, Copy, Clone)]
+struct Props {
+	size: u32,
+	reset: u32,
+	access: Access,
+}
+
+impl Props {
+	fn inherit(self, e: &Element) -> Props {
+		Props {
+			size: e.get("size").and_then(number).unwrap_or(self.size),
+			reset: e.get("resetValue").and_then(number).unwrap_or(self.reset),
+			access: Access::parse(e.get("access"), None, self.access),
+		}
+	}
+}
+
+/// Parses a SVD number (decimal, `0x` hexadecimal or `#` binary)
+pub fn number(s: &str) -> Option<u32> {
+	let s = s.trim();
+
+	if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+		u32::from_str_radix(hex, 16).ok()
+	} else if let Some(bin) = s.strip_prefix('#') {
+		u32::from_str_radix(bin, 2).ok()
+	} else {
+		s.parse().ok()
+	}
+}
+
+/// Cleans up a description so it fits in a single line doc comment
+fn description(e: &Element) -> String {
+	e.get("description")
+		.unwrap_or("")
+		.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/// Expands a `dim` element into (index, offset increment) pairs
+fn dim(e: &Element) -> Vec<(String, u32)> {
+	let n = match e.get("dim").and_then(number) {
+		Some(n) => n,
+		None => return vec![ (String::new(), 0) ],
+	};
+
+	let inc = e.get("dimIncrement").and_then(number).unwrap_or(0);
+
+	let indices: Vec<String> = match e.get("dimIndex") {
+		Some(idx) if idx.contains('-') => {
+			let mut range = idx.split('-').filter_map(number);
+			match (range.next(), range.next()) {
+				(Some(a), Some(b)) => (a..=b).map(|i| i.to_string()).collect(),
+				_ => (0..n).map(|i| i.to_string()).collect(),
+			}
+		},
+		Some(idx) => idx.split(',').map(|i| i.trim().to_string()).collect(),
+		None => (0..n).map(|i| i.to_string()).collect(),
+	};
+
+	indices.into_iter().enumerate().map(|(i, idx)| (idx, i as u32 * inc)).collect()
+}
+
+fn dim_name(name: &str, idx: &str) -> String {
+	name.replace("[%s]", idx).replace("%s", idx)
+}
+
+fn field(e: &Element, default: Access) -> Option<(u32, u32, Access)> {
+	let (offset, width) = if let (Some(o), Some(w)) = (e.get("bitOffset").and_then(number), e.get("bitWidth").and_then(number)) {
+		(o, w)
+	} else if let (Some(l), Some(m)) = (e.get("lsb").and_then(number), e.get("msb").and_then(number)) {
+		(l, m - l + 1)
+	} else {
+		let range = e.get("bitRange")?.trim_matches(|c| c == '[' || c == ']');
+		let mut it = range.split(':').filter_map(number);
+		let (m, l) = (it.next()?, it.next()?);
+		(l, m - l + 1)
+	};
+
+	Some( (offset, width, Access::parse(e.get("access"), e.get("modifiedWriteValues"), default)) )
+}
+
+fn register(e: &Element, props: Props, base: u32, out: &mut Vec<Register>) {
+	let props = props.inherit(e);
+	let offset = base + e.get("addressOffset").and_then(number).unwrap_or(0);
+
+	let mut fields = Vec::new();
+
+	if let Some(fs) = e.child("fields") {
+		for f in fs.children("field") {
+			let (foffset, width, access) = match field(f, props.access) {
+				Some(f) => f,
+				None => continue,
+			};
+
+			for (idx, inc) in dim(f) {
+				fields.push( Field {
+					name: dim_name(f.get("name").unwrap_or(""), &idx),
+					description: description(f),
+					offset: foffset + inc,
+					width,
+					access,
+				} );
+			}
+		}
+	}
+
+	fields.sort_by_key(|f| f.offset);
+
+	for (idx, inc) in dim(e) {
+		out.push( Register {
+			name: dim_name(e.get("name").unwrap_or(""), &idx),
+			description: description(e),
+			offset: offset + inc,
+			size: props.size,
+			reset: props.reset,
+			fields: fields.clone(),
+		} );
+	}
+}
+
+/// Reads the registers of a `registers` or `cluster` element
+fn registers(e: &Element, props: Props, base: u32, out: &mut Vec<Register>) {
+	for c in &e.children {
+		match c.name.as_str() {
+			"register" => register(c, props, base, out),
+			"cluster" => {
+				let props = props.inherit(c);
+				let offset = base + c.get("addressOffset").and_then(number).unwrap_or(0);
+
+				for (_, inc) in dim(c) {
+					registers(c, props, offset + inc, out);
+				}
+			},
+			_ => (),
+		}
+	}
+}
+
+/// Reads the device from the root `device` element
+pub fn device(root: &Element) -> Device {
+	let props = Props { size: 32, reset: 0, access: Access::RW }.inherit(root);
+
+	let mut peripherals = Vec::new();
+
+	if let Some(ps) = root.child("peripherals") {
+		for p in ps.children("peripheral") {
+			let props = props.inherit(p);
+			let mut regs = Vec::new();
+
+			if let Some(r) = p.child("registers") {
+				registers(r, props, 0, &mut regs);
+			}
+
+			regs.sort_by_key(|r| r.offset);
+
+			peripherals.push( Peripheral {
+				name: p.get("name").unwrap_or("").to_string(),
+				derived: p.attr("derivedFrom").map(|s| s.to_string()),
+				base: p.get("baseAddress").and_then(number).unwrap_or(0),
+				registers: regs,
+				interrupts: p.children("interrupt").map(|i| Interrupt {
+					name: i.get("name").unwrap_or("").to_string(),
+					description: description(i),
+					value: i.get("value").and_then(number).unwrap_or(0),
+				}).collect(),
+			} );
+		}
+	}
+
+	Device {
+		name: root.get("name").unwrap_or("").to_string(),
+		peripherals,
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::number;
+
+	#[test]
+	fn numbers() {
+		assert_eq!(number("42"), Some(42));
+		assert_eq!(number(" 12 "), Some(12));
+		assert_eq!(number("0x40023800"), Some(0x4002_3800));
+		assert_eq!(number("0XFF"), Some(0xFF));
+		assert_eq!(number("#1010"), Some(0b1010));
+		assert_eq!(number("0x"), None);
+		assert_eq!(number("abc"), None);
+	}
+}
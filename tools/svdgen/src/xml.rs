@@ -0,0 +1,190 @@
+//! Minimal XML reader
+//! Only supports what is found in CMSIS-SVD files: elements, attributes and text
+
+use std::fmt;
+
+/// XML element
+#[derive(Debug, Clone, Default)]
+pub struct Element {
+	pub name: String,
+	pub attrs: Vec<(String, String)>,
+	pub children: Vec<Element>,
+	pub text: String,
+}
+
+impl Element {
+	/// Returns the first child named `name`
+	pub fn child(&self, name: &str) -> Option<&Element> {
+		self.children.iter().find(|c| c.name == name)
+	}
+
+	/// Returns all children named `name`
+	pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a Element> + 'a {
+		self.children.iter().filter(move |c| c.name == name)
+	}
+
+	/// Returns the trimmed text of the first child named `name`
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.child(name).map(|c| c.text.trim())
+	}
+
+	/// Returns the attribute `name`
+	pub fn attr(&self, name: &str) -> Option<&str> {
+		self.attrs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+	}
+}
+
+/// Parsing error with the byte position where it happened
+#[derive(Debug)]
+pub struct Error {
+	pub pos: usize,
+	pub msg: &'static str,
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "XML error at byte {}: {}", self.pos, self.msg)
+	}
+}
+
+/// Parses the document and returns its root element
+pub fn parse(src: &str) -> Result<Element, Error> {
+	let mut p = Parser { src, pos: 0 };
+
+	p.misc()?;
+	let root = p.element()?;
+	p.misc()?;
+
+	Ok(root)
+}
+
+struct Parser<'a> {
+	src: &'a str,
+	pos: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn rest(&self) -> &'a str {
+		&self.src[self.pos..]
+	}
+
+	fn err<T>(&self, msg: &'static str) -> Result<T, Error> {
+		Err( Error { pos: self.pos, msg } )
+	}
+
+	fn skip_ws(&mut self) {
+		let rest = self.rest();
+		self.pos += rest.len() - rest.trim_start().len();
+	}
+
+	/// Skips everything up to and including `end`
+	fn skip_past(&mut self, end: &str) -> Result<(), Error> {
+		match self.rest().find(end) {
+			Some(i) => { self.pos += i + end.len(); Ok(()) },
+			None => self.err("Unterminated markup"),
+		}
+	}
+
+	/// Skips declarations, comments and whitespace
+	fn misc(&mut self) -> Result<(), Error> {
+		loop {
+			self.skip_ws();
+
+			if self.rest().starts_with("<?") { self.skip_past("?>")?; }
+			else if self.rest().starts_with("<!--") { self.skip_past("-->")?; }
+			else if self.rest().starts_with("<!") { self.skip_past(">")?; }
+			else { return Ok(()); }
+		}
+	}
+
+	fn name(&mut self) -> Result<String, Error> {
+		let rest = self.rest();
+		let len = rest.find(|c: char| c.is_whitespace() || c == '>' || c == '/' || c == '=').unwrap_or(rest.len());
+
+		if len == 0 { return self.err("Expected a name"); }
+
+		self.pos += len;
+		Ok( rest[..len].to_string() )
+	}
+
+	fn element(&mut self) -> Result<Element, Error> {
+		if !self.rest().starts_with('<') { return self.err("Expected an element"); }
+		self.pos += 1;
+
+		let mut e = Element { name: self.name()?, ..Element::default() };
+
+		// Attributes
+		loop {
+			self.skip_ws();
+
+			if self.rest().starts_with("/>") {
+				self.pos += 2;
+				return Ok(e);
+			}
+
+			if self.rest().starts_with('>') {
+				self.pos += 1;
+				break;
+			}
+
+			let key = self.name()?;
+			self.skip_ws();
+			if !self.rest().starts_with('=') { return self.err("Expected '='"); }
+			self.pos += 1;
+			self.skip_ws();
+
+			let quote = match self.rest().chars().next() {
+				Some(q @ '"') | Some(q @ '\'') => q,
+				_ => return self.err("Expected a quoted value"),
+			};
+			self.pos += 1;
+
+			let len = match self.rest().find(quote) {
+				Some(l) => l,
+				None => return self.err("Unterminated attribute"),
+			};
+
+			e.attrs.push( (key, unescape(&self.rest()[..len])) );
+			self.pos += len + 1;
+		}
+
+		// Content
+		loop {
+			let rest = self.rest();
+
+			if rest.starts_with("</") {
+				self.pos += 2;
+				if self.name()? != e.name { return self.err("Mismatched closing tag"); }
+				self.skip_past(">")?;
+				return Ok(e);
+			} else if rest.starts_with("<!--") {
+				self.skip_past("-->")?;
+			} else if rest.starts_with("<![CDATA[") {
+				self.pos += 9;
+				let len = match self.rest().find("]]>") {
+					Some(l) => l,
+					None => return self.err("Unterminated CDATA"),
+				};
+				e.text.push_str(&self.rest()[..len]);
+				self.pos += len + 3;
+			} else if rest.starts_with('<') {
+				let child = self.element()?;
+				e.children.push(child);
+			} else if rest.is_empty() {
+				return self.err("Unexpected end of file");
+			} else {
+				let len = rest.find('<').unwrap_or(rest.len());
+				e.text.push_str(&unescape(&rest[..len]));
+				self.pos += len;
+			}
+		}
+	}
+}
+
+fn unescape(s: &str) -> String {
+	s.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&amp;", "&")
+}
@@ -0,0 +1,10 @@
+//! EXTI lines of the FIXTURE
+//! Generated by `svdgen`, do not edit
+
+/// EXTI lines
+#[derive(Debug, Copy, Clone)]
+pub enum EXTILine {
+	Line0  =  0,
+	Line1  =  1,
+	Line22 = 22,
+}
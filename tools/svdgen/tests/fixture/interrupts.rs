@@ -0,0 +1,22 @@
+//! Interrupts of the FIXTURE
+//! Generated by `svdgen`, do not edit
+
+/// Device interrupts
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Interrupt {
+	WWDG = 0,
+	RCC = 5,
+	/// EXTI Line0 interrupt
+	EXTI0 = 6,
+	/// Timer global interrupt
+	TIM = 28,
+	RTC_Alarm = 41,
+}
+
+unsafe impl crate::interrupt::Nr for Interrupt {
+	#[inline]
+	fn nr(&self) -> u8 {
+		*self as u8
+	}
+}
@@ -0,0 +1,9 @@
+//! Memory map of the FIXTURE
+//! Generated by `svdgen`, do not edit
+
+pub const TIM: u32 = 0x4000_0000;
+pub const EXTI: u32 = 0x4001_3C00;
+pub const GPIOA: u32 = 0x4002_0000;
+pub const GPIOB: u32 = 0x4002_0400;
+pub const RCC: u32 = 0x4002_3800;
+pub const NVIC: u32 = 0xE000_E100;
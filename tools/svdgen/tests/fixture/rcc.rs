@@ -0,0 +1,14 @@
+//! RCC controlled peripherals of the FIXTURE
+//! Generated by `svdgen`, do not edit
+
+/// Peripherals
+/// Encoded as `(register index << 8) | bit`, the register is the reset register
+/// of the bus, or the enable register if the bus has no reset register
+#[derive(Debug, Copy, Clone)]
+pub enum RCCPeripheral {
+	PWR     = 1052,	// PWR     @ APB1 28
+	TIM     = 1024,	// TIM     @ APB1  0
+
+	SRAM    = 1282,	// SRAM    @ AHB  2
+	DMA1    = 1280,	// DMA1    @ AHB  0
+}
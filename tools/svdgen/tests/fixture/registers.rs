@@ -0,0 +1,81 @@
+//! Registers of the FIXTURE
+//! Generated by `svdgen`, do not edit
+
+pub mod gpioa {
+	registers! {
+		/// GPIO port mode register
+		MODER @ 0, reset = 0xA800_0000 {
+			/// Port x configuration bits & more
+			moder[2]: 0, 2, RW;
+		}
+
+		IDR @ 4, reset = 0x0000_0000 {
+			idr[4]: 0, 1, RO;
+		}
+
+		LCKR @ 7, reset = 0x0000_0000 {
+			lck0: 0, 1, RW;
+			lck2: 2, 1, RW;
+			lckk: 16, 1, RW;
+		}
+	}
+}
+
+pub mod gpiob {
+	pub use super::gpioa::*;
+}
+
+pub mod tim {
+	registers! {
+		CCR0 @ 13, reset = 0x0000_0000 {
+			ccr: 0, 16, RW;
+		}
+
+		CCR1 @ 14, reset = 0x0000_0000 {
+			ccr: 0, 16, RW;
+		}
+
+		TYPE @ 16, reset = 0x0000_000A {
+			mod_: 0, 4, WO;
+		}
+		// DMAR is not an aligned 32-bit register
+	}
+}
+
+pub mod exti {
+	registers! {
+		IMR @ 0, reset = 0x0000_0000 {
+			mr0: 0, 1, RW;
+			mr1: 1, 1, RW;
+			mr22: 22, 1, RW;
+		}
+
+		PR @ 5, reset = 0x0000_0000 {
+			pr[2]: 0, 1, W1C;
+		}
+	}
+}
+
+pub mod rcc {
+	registers! {
+		APB1RSTR @ 4, reset = 0x0000_0000 {
+			timrst: 0, 1, RW;
+			pwrrst: 28, 1, RW;
+		}
+
+		AHBENR @ 5, reset = 0x0000_0014 {
+			dma1en: 0, 1, RW;
+			sramen: 2, 1, RW;
+		}
+
+		APB1ENR @ 7, reset = 0x0000_0000 {
+			timen: 0, 1, RW;
+			pwren: 28, 1, RW;
+		}
+
+		APB1LPENR @ 8, reset = 0x0000_0000 {
+			pwrlpen: 28, 1, RW;
+		}
+	}
+}
+